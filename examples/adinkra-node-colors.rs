@@ -0,0 +1,41 @@
+/*
+Solves an Adinkra where the boson/fermion assignment is unknown.
+*/
+
+use graph_solver::*;
+
+const RED: Color = 2;
+const GREEN: Color = 3;
+
+const BOSON: Color = 0;
+const FERMION: Color = 1;
+
+fn main() {
+    let mut g = NodeColorGraph::new();
+    let boson = Node {
+        color: BOSON,
+        self_connected: false,
+        edges: vec![
+            Constraint {edge: RED, node: FERMION},
+            Constraint {edge: GREEN, node: FERMION},
+        ]
+    };
+    let fermion = Node {
+        color: FERMION,
+        self_connected: false,
+        edges: vec![
+            Constraint {edge: RED, node: BOSON},
+            Constraint {edge: GREEN, node: BOSON},
+        ]
+    };
+    for _ in 0..4 {g.push_domain(vec![boson.clone(), fermion.clone()])}
+
+    let solve_settings = SolveSettings::new();
+    if let Some(solution) = g.solve(solve_settings) {
+        let nodes = &["black", "white"];
+        let edges = &["red", "green"];
+        println!("{}", solution.puzzle.graph.graphviz("sfdp", nodes, edges));
+    } else {
+        eprintln!("<no solution>");
+    }
+}
@@ -0,0 +1,211 @@
+/*
+=== HTTP SERVICE EXAMPLE ===
+
+Runs the solver as a small shared service.
+
+    cargo run --example server --features server
+
+Endpoints:
+
+    POST   /jobs               Starts solving a spec, returns `{"id":<id>}`
+    GET    /jobs/<id>          Returns the job status, with the solution when solved
+    GET    /jobs/<id>/dot      Returns the solution in GraphViz DOT format
+    DELETE /jobs/<id>          Cancels the job and forgets it
+    POST   /solutions?limit=N  Streams up to N solutions, one JSON object per line
+
+A spec uses the JSON format of `Graph::from_json`.
+Request bodies larger than 1 MiB are rejected.
+Jobs are kept until they are deleted.
+
+For example, solving a square:
+
+    curl -d '{"nodes":[
+        {"color":0,"edges":[{"edge":2,"node":0},{"edge":2,"node":0}]},
+        {"color":0,"edges":[{"edge":2,"node":0},{"edge":2,"node":0}]},
+        {"color":0,"edges":[{"edge":2,"node":0},{"edge":2,"node":0}]},
+        {"color":0,"edges":[{"edge":2,"node":0},{"edge":2,"node":0}]}
+    ],"no_triangles":true}' localhost:8080/jobs
+
+*/
+
+use graph_solver::*;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+
+const NODE_COLORS: &[&str] = &["black", "white", "gray"];
+const EDGE_COLORS: &[&str] = &["black", "red", "blue", "green", "orange"];
+/// The largest accepted request body, in bytes.
+const MAX_BODY: usize = 1 << 20;
+
+/// Wakes nothing, since finished jobs are polled without waiting.
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+enum Job {
+    Running(SolveFuture),
+    Finished(Option<Box<Solution<Graph>>>),
+}
+
+impl Job {
+    /// Moves the result out of the future when the search is finished.
+    fn update(&mut self) {
+        if let Job::Running(future) = self {
+            if !future.is_finished() {return};
+            let waker = Waker::from(Arc::new(NoopWaker));
+            let mut cx = Context::from_waker(&waker);
+            if let Poll::Ready(result) = Pin::new(future).poll(&mut cx) {
+                *self = Job::Finished(result.map(Box::new));
+            }
+        }
+    }
+}
+
+type Jobs = Arc<Mutex<HashMap<u64, Job>>>;
+
+fn main() {
+    let addr = "127.0.0.1:8080";
+    let listener = TcpListener::bind(addr).unwrap();
+    eprintln!("Listening on http://{}", addr);
+    let jobs: Jobs = Arc::new(Mutex::new(HashMap::new()));
+    let next_id = Arc::new(Mutex::new(0));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {eprintln!("{}", err); continue}
+        };
+        let jobs = jobs.clone();
+        let next_id = next_id.clone();
+        thread::spawn(move || {
+            if let Err(err) = handle(stream, &jobs, &next_id) {eprintln!("{}", err)}
+        });
+    }
+}
+
+fn handle(mut stream: TcpStream, jobs: &Jobs, next_id: &Mutex<u64>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {break};
+        if let Some((key, value)) = line.split_once(':') {
+            if key.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        return respond(&mut stream, "413 Payload Too Large", "text/plain", "Request body is too large");
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body);
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (method, &segments[..]) {
+        ("POST", ["jobs"]) => {
+            let g = match Graph::from_json(&body) {
+                Ok(g) => g,
+                Err(err) => {
+                    return respond(&mut stream, "400 Bad Request", "text/plain", &err.to_string());
+                }
+            };
+            let id = {
+                let mut next_id = next_id.lock().unwrap();
+                *next_id += 1;
+                *next_id
+            };
+            let future = g.solve_async(SearchSettings::new());
+            jobs.lock().unwrap().insert(id, Job::Running(future));
+            respond(&mut stream, "201 Created", "application/json", &format!("{{\"id\":{}}}", id))
+        }
+        ("GET", ["jobs", id]) => {
+            let mut jobs = jobs.lock().unwrap();
+            let job = match id.parse().ok().and_then(|id: u64| jobs.get_mut(&id)) {
+                Some(job) => job,
+                None => return respond(&mut stream, "404 Not Found", "text/plain", "Unknown job"),
+            };
+            job.update();
+            let status = match job {
+                Job::Running(future) => format!("{{\"status\":\"running\",\"iterations\":{}}}",
+                    future.iterations()),
+                Job::Finished(Some(solution)) => format!("{{\"status\":\"solved\",\
+                    \"iterations\":{},\"solution\":{}}}", solution.iterations, to_json(&solution.puzzle)),
+                Job::Finished(None) => "{\"status\":\"no solution\"}".into(),
+            };
+            respond(&mut stream, "200 OK", "application/json", &status)
+        }
+        ("GET", ["jobs", id, "dot"]) => {
+            let mut jobs = jobs.lock().unwrap();
+            let job = match id.parse().ok().and_then(|id: u64| jobs.get_mut(&id)) {
+                Some(job) => job,
+                None => return respond(&mut stream, "404 Not Found", "text/plain", "Unknown job"),
+            };
+            job.update();
+            match job {
+                Job::Finished(Some(solution)) => {
+                    let dot = solution.puzzle.graphviz("sfdp", NODE_COLORS, EDGE_COLORS);
+                    respond(&mut stream, "200 OK", "text/vnd.graphviz", &dot)
+                }
+                _ => respond(&mut stream, "409 Conflict", "text/plain", "No solution yet"),
+            }
+        }
+        ("DELETE", ["jobs", id]) => {
+            let mut jobs = jobs.lock().unwrap();
+            match id.parse().ok().and_then(|id: u64| jobs.remove(&id)) {
+                Some(Job::Running(future)) => future.cancel(),
+                Some(Job::Finished(_)) => {}
+                None => return respond(&mut stream, "404 Not Found", "text/plain", "Unknown job"),
+            }
+            respond(&mut stream, "202 Accepted", "text/plain", "Cancelled")
+        }
+        ("POST", ["solutions"]) => {
+            let g = match Graph::from_json(&body) {
+                Ok(g) => g,
+                Err(err) => {
+                    return respond(&mut stream, "400 Bad Request", "text/plain", &err.to_string());
+                }
+            };
+            let limit = query.split('&')
+                .find_map(|kv| kv.strip_prefix("limit="))
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100);
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\n\
+                            Connection: close\r\n\r\n")?;
+            // Solutions are written as soon as they are found.
+            // Closing the connection stops the enumeration.
+            let solutions = g.solve_all(SearchSettings::new());
+            solutions.take(limit).try_for_each(|solution| {
+                JsonlSink::new(&mut stream).write(&solution.puzzle)
+            })
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "Unknown endpoint"),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> std::io::Result<()> {
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+                    Connection: close\r\n\r\n{}", status, content_type, body.len(), body)
+}
+
+fn to_json(g: &Graph) -> String {
+    let mut buf = vec![];
+    JsonlSink::new(&mut buf).write(g).unwrap();
+    String::from_utf8(buf).unwrap().trim_end().into()
+}
@@ -0,0 +1,111 @@
+//! Specifications of Adinkras.
+//!
+//! An Adinkra is a bipartite graph of black and white nodes,
+//! where every node has one edge of every edge color,
+//! every edge is either solid or dashed,
+//! and every quad of two edge colors has an odd number of dashed edges.
+
+use super::*;
+
+/// Solves a system of linear equations over GF(2).
+///
+/// Each equation is a list of variables whose sum equals the right hand side.
+/// Returns `None` if the system is inconsistent, otherwise a solution with free variables set to `false`.
+fn solve_gf2(vars: usize, equations: &[(Vec<usize>, bool)]) -> Option<Vec<bool>> {
+    let words = (vars + 1 + 63) / 64;
+    let bit = |row: &[u64], k: usize| row[k / 64] >> (k % 64) & 1 == 1;
+    let mut rows: Vec<Vec<u64>> = equations.iter().map(|(terms, rhs)| {
+        let mut row = vec![0; words];
+        for &k in terms {row[k / 64] ^= 1 << (k % 64)}
+        if *rhs {row[vars / 64] ^= 1 << (vars % 64)}
+        row
+    }).collect();
+    let mut pivots = vec![];
+    for k in 0..vars {
+        let r = pivots.len();
+        let p = if let Some(p) = (r..rows.len()).find(|&p| bit(&rows[p], k)) {p} else {continue};
+        rows.swap(r, p);
+        for q in 0..rows.len() {
+            if q != r && bit(&rows[q], k) {
+                let pivot = rows[r].clone();
+                for (a, b) in rows[q].iter_mut().zip(pivot) {*a ^= b}
+            }
+        }
+        pivots.push(k);
+    }
+    if rows[pivots.len()..].iter().any(|row| bit(row, vars)) {return None};
+    let mut res = vec![false; vars];
+    for (r, &k) in pivots.iter().enumerate() {res[k] = bit(&rows[r], vars)}
+    Some(res)
+}
+
+impl Graph {
+    /// Creates a puzzle for an Adinkra with `colors` edge colors and `2^dim` nodes.
+    ///
+    /// Node color `0` is black and `1` is white.
+    /// Edge color `c` is solid as `2 + 2c` and dashed as `3 + 2c`, see `Palette::adinkra`.
+    ///
+    /// The node patterns are derived from an Adinkra that is built in code:
+    /// Nodes are bitstrings of length `dim`, where the first `dim` edge colors flip one bit each.
+    /// When there are more colors than bits, the remaining colors flip several bits,
+    /// folding the `colors`-dimensional cube by a doubly-even code.
+    /// Dashed edges are then chosen such that every quad has an odd number of dashed edges.
+    ///
+    /// Sets the bipartition of black and white nodes.
+    /// Sets `commute_quad` to `Some(false)` when the cube is not folded,
+    /// since folded Adinkras have quads of four edge colors that `commute_quad` rejects.
+    ///
+    /// Returns `None` when there are more bits than colors,
+    /// or when no doubly-even code folds the cube to `2^dim` nodes.
+    pub fn adinkra(colors: usize, dim: u32) -> Option<Graph> {
+        let k = dim as usize;
+        if k > colors || k >= 64 {return None};
+        let n = 1_usize << k;
+        // The bits of nodes that each edge color flips.
+        let mut flips: Vec<usize> = (0..k).map(|c| 1 << c).collect();
+        // The code is doubly-even when every folded color flips 3 mod 4 bits
+        // and any two folded colors share an even number of bits.
+        let mut v = 0;
+        while flips.len() < colors {
+            v += 1;
+            if v >= n {return None};
+            if v.count_ones() % 4 == 3 &&
+               flips[k..].iter().all(|&w| (v & w).count_ones() % 2 == 0)
+            {
+                flips.push(v);
+            }
+        }
+
+        // Each edge is a variable, indexed by its black node and its color,
+        // which is `true` when the edge is dashed.
+        let black = |x: usize| x.count_ones() % 2 == 0;
+        let var = |x: usize, c: usize| x * colors + c;
+        let mut equations = vec![];
+        for x in (0..n).filter(|&x| black(x)) {
+            for a in 0..colors {
+                for b in a + 1..colors {
+                    let z = x ^ flips[a] ^ flips[b];
+                    equations.push((vec![var(x, a), var(x, b), var(z, a), var(z, b)], true));
+                }
+            }
+        }
+        let dashed = solve_gf2(n * colors, &equations)?;
+
+        let mut g = Graph::new();
+        for x in 0..n {
+            let color = if black(x) {0} else {1};
+            g.push(Node {
+                color,
+                self_connected: false,
+                edges: (0..colors).map(|c| {
+                    let y = if black(x) {x} else {x ^ flips[c]};
+                    let edge = 2 + 2 * c as Color + dashed[var(y, c)] as Color;
+                    Constraint {edge, node: 1 - color}
+                }).collect(),
+            });
+        }
+        if k == colors {g.commute_quad = Some(false)};
+        g.bipartition_by_color(0, 1);
+        Some(g)
+    }
+}
@@ -0,0 +1,44 @@
+//! Bitset adjacency of colored edges and incremental triangle counting.
+
+use super::*;
+
+impl Graph {
+    /// Returns the neighbors of a node along colored edges, as a bitset.
+    ///
+    /// Bit `j % 64` of word `j / 64` is set when node `j` is a neighbor.
+    /// Self-loops are not included.
+    pub fn neighbors(&self, i: usize) -> &[u64] {
+        &self.adjacency[i]
+    }
+
+    /// Returns the number of triangles along colored edges.
+    ///
+    /// Self-loops are ignored.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles
+    }
+
+    /// Updates adjacency bitsets and the triangle count after setting an edge.
+    pub(crate) fn update_adjacency(&mut self, (i, j): (usize, usize), old: Color, val: Color) {
+        if i == j || (old >= 2) == (val >= 2) {return};
+        // The edge is part of one triangle per common neighbor.
+        let common: usize = self.adjacency[i].iter().zip(&self.adjacency[j])
+            .map(|(a, b)| (a & b).count_ones() as usize).sum();
+        if val >= 2 {
+            self.adjacency[i][j / 64] |= 1 << (j % 64);
+            self.adjacency[j][i / 64] |= 1 << (i % 64);
+            self.triangles += common;
+        } else {
+            self.adjacency[i][j / 64] &= !(1 << (j % 64));
+            self.adjacency[j][i / 64] &= !(1 << (i % 64));
+            self.triangles -= common;
+        }
+    }
+
+    /// Adds an empty adjacency bitset for a new node.
+    pub(crate) fn push_adjacency(&mut self) {
+        let words = (self.nodes.len() + 63) / 64;
+        for row in &mut self.adjacency {row.resize(words, 0)}
+        self.adjacency.push(vec![0; words]);
+    }
+}
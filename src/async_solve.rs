@@ -0,0 +1,152 @@
+//! Solving without blocking an async executor.
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use super::*;
+
+/// The number of iterations between progress updates.
+const PROGRESS_EVERY: u64 = 1000;
+
+/// The number of progress updates that are kept until they are received.
+const PROGRESS_CAPACITY: usize = 64;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Sends jobs to the pool of threads for blocking searches, started on first use.
+static POOL: Mutex<Option<Sender<Job>>> = Mutex::new(None);
+
+/// Runs a job on the pool of threads for blocking searches.
+///
+/// The pool has one thread per available core, such that concurrent searches
+/// do not oversubscribe the machine. Jobs wait in a queue while all threads are busy.
+fn spawn_blocking(job: Job) {
+    let mut pool = POOL.lock().unwrap();
+    let sender = pool.get_or_insert_with(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        for _ in 0..threads {
+            let receiver = receiver.clone();
+            thread::spawn(move || loop {
+                let job = receiver.lock().unwrap().recv();
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => break,
+                }
+            });
+        }
+        sender
+    });
+    sender.send(job).unwrap();
+}
+
+/// The progress of a search running on another thread.
+///
+/// For more information, see `SolveFuture::take_progress`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SolveProgress {
+    /// The number of iterations so far.
+    pub iterations: u64,
+    /// The progress of the current partial assignment, see `Graph::progress`.
+    pub progress: f64,
+}
+
+struct Shared {
+    result: Option<Option<Solution<Graph>>>,
+    waker: Option<Waker>,
+}
+
+/// A future that resolves to the solution of a search running on another thread.
+///
+/// Dropping the future cancels the search.
+/// For more information, see `Graph::solve_async`.
+pub struct SolveFuture {
+    shared: Arc<Mutex<Shared>>,
+    cancel: CancelToken,
+    iterations: Arc<AtomicU64>,
+    progress: Option<Receiver<SolveProgress>>,
+}
+
+impl SolveFuture {
+    /// Cancels the search, such that the future resolves to `None`.
+    pub fn cancel(&self) {self.cancel.cancel()}
+
+    /// Returns the token for cancelling the search.
+    ///
+    /// This is not the cancellation token in the settings,
+    /// such that cancelling one search does not cancel others that share a token.
+    pub fn cancel_token(&self) -> CancelToken {self.cancel.clone()}
+
+    /// Returns the number of iterations so far.
+    pub fn iterations(&self) -> u64 {self.iterations.load(Ordering::SeqCst)}
+
+    /// Takes the receiver of progress updates, if not taken before.
+    ///
+    /// An update is sent every 1000 iterations, and the channel is closed when the search is finished.
+    /// Updates are skipped while 64 updates are waiting to be received.
+    pub fn take_progress(&mut self) -> Option<Receiver<SolveProgress>> {self.progress.take()}
+
+    /// Returns `true` if the search is finished.
+    pub fn is_finished(&self) -> bool {
+        self.shared.lock().unwrap().result.is_some()
+    }
+}
+
+impl Future for SolveFuture {
+    type Output = Option<Solution<Graph>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for SolveFuture {
+    fn drop(&mut self) {self.cancel()}
+}
+
+impl Graph {
+    /// Solves the graph puzzle on a pool of threads for blocking searches, using default strategy.
+    ///
+    /// Returns a future that resolves to the solution.
+    /// The search is cancelled by `SolveFuture::cancel`, by dropping the future,
+    /// or by the cancellation token in the settings.
+    /// Progress is reported by `SolveFuture::iterations` and `SolveFuture::take_progress`.
+    /// When the search panics, the future resolves to `None`.
+    ///
+    /// This does not depend on a specific async runtime.
+    pub fn solve_async(self, solve_settings: SearchSettings) -> SolveFuture {
+        let cancel = CancelToken::new();
+        let shared = Arc::new(Mutex::new(Shared {result: None, waker: None}));
+        let iterations = Arc::new(AtomicU64::new(0));
+        let (sender, progress) = mpsc::sync_channel(PROGRESS_CAPACITY);
+        let mut solutions = self.solve_all(solve_settings).on_progress(PROGRESS_EVERY, move |g, iterations| {
+            let _ = sender.try_send(SolveProgress {iterations, progress: g.progress()});
+        });
+        solutions.cancel = Some(cancel.clone());
+        solutions.counter = Some(iterations.clone());
+        let job_shared = shared.clone();
+        spawn_blocking(Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| solutions.next())).unwrap_or(None);
+            // Closes the progress channel.
+            drop(solutions);
+            let mut shared = job_shared.lock().unwrap();
+            shared.result = Some(result);
+            if let Some(waker) = shared.waker.take() {waker.wake()};
+        }));
+        SolveFuture {shared, cancel, iterations, progress: Some(progress)}
+    }
+}
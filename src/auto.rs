@@ -0,0 +1,136 @@
+//! Automatic selection of search configurations by probing.
+
+use std::time::Instant;
+
+use super::*;
+
+/// A configuration of the search, probed by `Graph::solve_auto`.
+#[derive(Copy, Clone)]
+pub struct AutoConfig {
+    /// The name of the configuration.
+    pub name: &'static str,
+    /// The variables that the solver branches on.
+    pub encoding: Encoding,
+    /// The strategy that overrides the branching variables of the encoding, if any.
+    pub strategy: Option<Strategy>,
+    /// Whether to record nogoods, see `SearchSettings::learn`.
+    pub learn: bool,
+}
+
+impl AutoConfig {
+    /// Returns the default configurations that are probed.
+    ///
+    /// - `edge-colors`: `Encoding::EdgeColors`
+    /// - `slot-matching`: `Encoding::SlotMatching`
+    /// - `mixed`: `Encoding::Mixed`
+    /// - `no-edge-first`: `Strategy::min_colors_no_edge_first`
+    /// - `learn`: `Encoding::EdgeColors` with learning of nogoods
+    pub fn defaults() -> Vec<AutoConfig> {
+        let config = |name, encoding| AutoConfig {name, encoding, strategy: None, learn: false};
+        vec![
+            config("edge-colors", Encoding::EdgeColors),
+            config("slot-matching", Encoding::SlotMatching),
+            config("mixed", Encoding::Mixed),
+            AutoConfig {strategy: Some(Strategy::min_colors_no_edge_first()),
+                        ..config("no-edge-first", Encoding::EdgeColors)},
+            AutoConfig {learn: true, ..config("learn", Encoding::EdgeColors)},
+        ]
+    }
+
+    /// Returns the solutions of a puzzle using this configuration.
+    ///
+    /// The encoding and learning of the settings are replaced by those of the configuration.
+    pub fn solutions(&self, graph: Graph, solve_settings: SearchSettings) -> Solutions {
+        let solve_settings = solve_settings.encoding(self.encoding).learn(self.learn);
+        match self.strategy {
+            Some(strategy) => Solutions::with_strategy(graph, solve_settings, strategy),
+            None => Solutions::new(graph, solve_settings),
+        }
+    }
+}
+
+/// The result of solving with automatic selection of the configuration.
+///
+/// For more information, see `Graph::solve_auto`.
+pub struct AutoReport {
+    /// The name of the selected configuration.
+    pub config: &'static str,
+    /// The statistics of every probe, by configuration name.
+    pub probes: Vec<(&'static str, Stats)>,
+    /// The solution, or why there is no solution.
+    pub outcome: SolveOutcome,
+    /// Statistics of the full search with the selected configuration.
+    ///
+    /// When a probe completes the search, these are the statistics of the probe.
+    pub stats: Stats,
+}
+
+impl Graph {
+    /// Solves the graph puzzle, selecting the configuration by short probes.
+    ///
+    /// Uses `AutoConfig::defaults` with probes of 1000 iterations.
+    /// For more information, see `Graph::solve_auto_with`.
+    pub fn solve_auto(self, solve_settings: SearchSettings) -> AutoReport {
+        self.solve_auto_with(solve_settings, &AutoConfig::defaults(), 1000)
+    }
+
+    /// Solves the graph puzzle, selecting the configuration by short probes.
+    ///
+    /// Every configuration is run for at most `probe_iterations` iterations, in order.
+    /// When a probe finds a solution or completes the search, its result is returned.
+    /// Otherwise, the configuration whose probe reached the deepest partial assignment
+    /// with fewest dead ends is used for the full search with the settings.
+    ///
+    /// Probes run one after another, so the selection does not depend on timing.
+    /// The maximum number of iterations and the timeout of the settings
+    /// are shared by the probes and the full search.
+    /// The encoding and learning of the settings are replaced by those of the configuration,
+    /// see `AutoConfig::solutions`.
+    pub fn solve_auto_with(
+        self,
+        solve_settings: SearchSettings,
+        configs: &[AutoConfig],
+        probe_iterations: u64
+    ) -> AutoReport {
+        let configs = if configs.is_empty() {AutoConfig::defaults()} else {configs.to_vec()};
+        let start = Instant::now();
+        // Returns the settings with the remaining iterations and time.
+        let remaining = |iterations: u64| {
+            let mut settings = solve_settings.clone();
+            settings.max_iterations = settings.max_iterations.map(|n| n.saturating_sub(iterations));
+            settings.timeout_ms = settings.timeout_ms
+                .map(|ms| ms.saturating_sub(start.elapsed().as_millis() as u64));
+            settings
+        };
+        let mut iterations = 0;
+        let mut probes = vec![];
+        let mut best: Option<(usize, Stats)> = None;
+        for (k, config) in configs.iter().enumerate() {
+            let probe_settings = remaining(iterations);
+            let probe_iterations = probe_settings.max_iterations.map(|n| n.min(probe_iterations))
+                .unwrap_or(probe_iterations);
+            let probe_settings = probe_settings.max_iterations(probe_iterations);
+            let mut solutions = config.solutions(self.clone(), probe_settings);
+            let outcome = solutions.next_outcome();
+            let stats = solutions.stats();
+            probes.push((config.name, stats));
+            iterations += stats.iterations;
+            let spent = solve_settings.max_iterations.map(|n| iterations >= n).unwrap_or(false);
+            // Timeouts and cancellation would stop the full search in the same way.
+            if !matches!(outcome, SolveOutcome::Exhausted) || spent {
+                return AutoReport {config: config.name, probes, outcome, stats};
+            }
+            let better = match best {
+                None => true,
+                Some((_, b)) => (stats.max_depth, std::cmp::Reverse(stats.backtracks)) >
+                                (b.max_depth, std::cmp::Reverse(b.backtracks)),
+            };
+            if better {best = Some((k, stats))};
+        }
+
+        let config = configs[best.map(|(k, _)| k).unwrap_or(0)];
+        let mut solutions = config.solutions(self, remaining(iterations));
+        let outcome = solutions.next_outcome();
+        AutoReport {config: config.name, probes, outcome, stats: solutions.stats()}
+    }
+}
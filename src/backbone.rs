@@ -0,0 +1,71 @@
+//! Analysis of edge assignments common to all solutions.
+
+use super::*;
+
+/// Stores the result of forced edge analysis.
+///
+/// For more information, see `Graph::forced_edges`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backbone {
+    /// Edges with the same value in all solutions.
+    pub forced: Vec<((usize, usize), Color)>,
+    /// Edges with different values in some solutions.
+    pub free: Vec<(usize, usize)>,
+    /// Edges where probing was stopped before it completed,
+    /// e.g. by the maximum number of iterations, the timeout or cancellation.
+    pub unknown: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    /// Determines which edge assignments are common to all solutions.
+    ///
+    /// Finds a solution and probes every edge with the other possible values.
+    /// Each probe is bounded by the maximum number of iterations and the timeout in the settings.
+    /// Solutions found while probing are used to skip probes of other edges.
+    ///
+    /// Edges that can not be colored are not included.
+    /// Returns `None` if there is no solution.
+    pub fn forced_edges(&self, solve_settings: SearchSettings) -> Option<Backbone> {
+        let first = self.clone().solve_all(solve_settings.clone()).next()?.puzzle;
+        let mut solutions = vec![first];
+        let n = self.nodes.len();
+        let mut backbone = Backbone {forced: vec![], free: vec![], unknown: vec![]};
+        for i in 0..n {
+            for j in i..n {
+                if i == j && !self.nodes[i].self_connected {continue};
+                let old = solutions[0].get((i, j)).max(1);
+                if self.get((i, j)) != 0 {
+                    backbone.forced.push(((i, j), old));
+                    continue;
+                }
+                if solutions.iter().any(|s| s.get((i, j)).max(1) != old) {
+                    backbone.free.push((i, j));
+                    continue;
+                }
+                let candidates = solutions[0].without_edge((i, j)).candidates();
+                if old == 1 && candidates.is_empty() {continue};
+                let mut unknown = false;
+                let mut found = None;
+                for c in candidates {
+                    let mut g = self.clone();
+                    g.set((i, j), c);
+                    let mut probe = g.solve_all(solve_settings.clone());
+                    if let Some(solution) = probe.next() {
+                        found = Some(solution.puzzle);
+                        break;
+                    }
+                    if probe.exhausted() || probe.timed_out() || probe.cancelled() {unknown = true}
+                }
+                if let Some(solution) = found {
+                    solutions.push(solution);
+                    backbone.free.push((i, j));
+                } else if unknown {
+                    backbone.unknown.push((i, j));
+                } else {
+                    backbone.forced.push(((i, j), old));
+                }
+            }
+        }
+        Some(backbone)
+    }
+}
@@ -0,0 +1,181 @@
+//! Timed solving of reproducible instances.
+//!
+//! This is used to measure the impact of settings and strategies on solving,
+//! on the machine of the user:
+//!
+//! ```
+//! use graph_solver::*;
+//!
+//! let settings = SearchSettings::new().max_iterations(100_000);
+//! let instances = [bench::instance("triangle").unwrap(), bench::instance("cube").unwrap()];
+//! for result in bench::run(&instances, &settings, &[0, 1, 2]) {
+//!     println!("{}", result);
+//! }
+//! ```
+//!
+//! Every instance is built in code, so results are comparable across versions of the crate.
+//! Each run uses a fixed seed, see `SearchSettings::seed`.
+
+use std::fmt;
+
+use super::*;
+
+/// A puzzle with a known answer.
+#[derive(Clone, Debug)]
+pub struct Instance {
+    /// The name of the instance.
+    pub name: &'static str,
+    /// The puzzle.
+    pub graph: Graph,
+    /// Whether the puzzle has a solution.
+    pub satisfiable: bool,
+}
+
+/// The result of solving an instance with a seed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BenchResult {
+    /// The name of the instance.
+    pub instance: &'static str,
+    /// The seed of the run.
+    pub seed: u64,
+    /// Whether a solution was found, or `None` if the search was stopped.
+    pub solved: Option<bool>,
+    /// Whether the answer matches the instance.
+    ///
+    /// Runs where the search was stopped are not correct.
+    pub correct: bool,
+    /// Statistics of the search.
+    pub stats: Stats,
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        let answer = match self.solved {
+            Some(true) => "solved",
+            Some(false) => "no solution",
+            None => "stopped",
+        };
+        write!(w, "{} (seed {}): {}{} in {} iterations, {} backtracks, {:.3} ms",
+               self.instance, self.seed, answer, if self.correct {""} else {" (wrong)"},
+               self.stats.iterations, self.stats.backtracks,
+               self.stats.elapsed.as_secs_f64() * 1000.0)
+    }
+}
+
+/// Returns a puzzle of regular nodes with a single edge color.
+fn regular(n: usize, degree: usize) -> Graph {
+    let mut g = Graph::new();
+    let a = Node {
+        color: 0,
+        self_connected: false,
+        edges: vec![Constraint {edge: 2, node: 0}; degree],
+    };
+    for _ in 0..n {g.push(a.clone())}
+    g
+}
+
+/// Returns the library of instances, ordered from easy to hard.
+///
+/// - `triangle`: 3 nodes of degree 2
+/// - `square`: 4 nodes with a horizontal and a vertical edge
+/// - `pentagon`: 5 nodes of degree 2
+/// - `hexagon`: 6 nodes of degree 2 with a pair constraint
+/// - `k4-no-triangles`: 4 nodes of degree 3 without triangles, no solution
+/// - `bipartite`: 3 + 3 nodes of two colors, each with 3 edges to the other color
+/// - `seven-bridges`: the seven bridges of Königsberg, see the example
+/// - `cube`: 8 nodes of degree 3 without triangles
+/// - `4cube`: 16 nodes of degree 4 without triangles, connected
+pub fn instances() -> Vec<Instance> {
+    let mut res = vec![];
+
+    res.push(Instance {name: "triangle", graph: regular(3, 2), satisfiable: true});
+
+    let mut g = Graph::new();
+    let a = Node {
+        color: 0,
+        self_connected: false,
+        edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+    };
+    for _ in 0..4 {g.push(a.clone())}
+    res.push(Instance {name: "square", graph: g, satisfiable: true});
+
+    res.push(Instance {name: "pentagon", graph: regular(5, 2), satisfiable: true});
+
+    let mut g = regular(6, 2);
+    g.push_pair((2, 3));
+    res.push(Instance {name: "hexagon", graph: g, satisfiable: true});
+
+    let mut g = regular(4, 3);
+    g.no_triangles = true;
+    res.push(Instance {name: "k4-no-triangles", graph: g, satisfiable: false});
+
+    let mut g = Graph::new();
+    for color in 0..2 {
+        let a = Node {
+            color,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1 - color}; 3],
+        };
+        for _ in 0..3 {g.push(a.clone())}
+    }
+    res.push(Instance {name: "bipartite", graph: g, satisfiable: true});
+
+    let mut g = Graph::new();
+    let f = |black, red| {
+        let mut edges = vec![Constraint {edge: 2, node: 0}; black];
+        edges.extend(vec![Constraint {edge: 3, node: 0}; red]);
+        Node {color: 0, self_connected: false, edges}
+    };
+    for &(black, red) in &[(1, 1), (2, 1), (1, 1), (1, 2), (1, 3), (0, 3), (1, 1), (2, 1), (1, 1)] {
+        g.push(f(black, red));
+    }
+    for &(pos, color) in &[
+        ((0, 1), 2), ((1, 2), 2), ((0, 2), 1), ((1, 4), 3), ((2, 3), 1),
+        ((2, 4), 1), ((3, 5), 1), ((3, 7), 1), ((3, 4), 2),
+    ] {
+        g.set(pos, color);
+    }
+    g.connected = true;
+    res.push(Instance {name: "seven-bridges", graph: g, satisfiable: true});
+
+    let mut g = regular(8, 3);
+    g.no_triangles = true;
+    res.push(Instance {name: "cube", graph: g, satisfiable: true});
+
+    let mut g = regular(16, 4);
+    g.no_triangles = true;
+    g.connected = true;
+    res.push(Instance {name: "4cube", graph: g, satisfiable: true});
+
+    res
+}
+
+/// Returns an instance from the library by name.
+pub fn instance(name: &str) -> Option<Instance> {
+    instances().into_iter().find(|instance| instance.name == name)
+}
+
+/// Solves every instance once per seed, in order.
+///
+/// The seed of each run overrides `SearchSettings::seed`.
+pub fn run(instances: &[Instance], solve_settings: &SearchSettings, seeds: &[u64]) -> Vec<BenchResult> {
+    let mut res = vec![];
+    for instance in instances {
+        for &seed in seeds {
+            let report = instance.graph.clone().solve_report(solve_settings.clone().seed(seed));
+            let solved = match report.outcome {
+                SolveOutcome::Solved(_) => Some(true),
+                SolveOutcome::NoSolution => Some(false),
+                _ => None,
+            };
+            res.push(BenchResult {
+                instance: instance.name,
+                seed,
+                solved,
+                correct: solved == Some(instance.satisfiable),
+                stats: report.stats,
+            });
+        }
+    }
+    res
+}
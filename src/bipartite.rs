@@ -0,0 +1,35 @@
+//! Bipartiteness constraint.
+
+use super::*;
+
+impl Graph {
+    fn with_parity<T, F: FnOnce(&mut Parity) -> T>(&self, f: F) -> T {
+        let mut cache = self.cache_parity.borrow_mut();
+        let parity = cache.get_or_insert_with(|| {
+            let n = self.nodes.len();
+            let mut parity = Parity::new(n);
+            for i in 0..n {
+                for j in i..n {
+                    if self.get((i, j)) >= 2 {parity.union(i, j)}
+                }
+            }
+            parity
+        });
+        f(parity)
+    }
+
+    /// Returns `true` if the graph of colored edges contains an odd cycle.
+    ///
+    /// A colored self-loop is an odd cycle.
+    pub fn has_odd_cycle(&self) -> bool {
+        self.with_parity(|parity| parity.odd_cycle)
+    }
+
+    /// Returns `true` if two nodes must be on the same side of a bipartition.
+    ///
+    /// This is the case when they are connected by a path of even length.
+    /// A node is always on the same side as itself.
+    pub fn same_side(&self, i: usize, j: usize) -> bool {
+        self.with_parity(|parity| parity.same_side(i, j))
+    }
+}
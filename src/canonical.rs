@@ -0,0 +1,226 @@
+//! Canonical forms of colored graphs.
+
+use std::collections::HashSet;
+
+use super::*;
+
+/// The refinement signature of a node: cell, self-loop and neighbors.
+type Signature = (usize, Color, Vec<(Color, usize)>);
+
+/// A leaf of the search tree for a canonical labeling.
+#[derive(Clone)]
+struct Leaf {
+    form: Vec<Color>,
+    labeling: Vec<usize>,
+    /// The individualized nodes from the root to the leaf.
+    path: Vec<usize>,
+}
+
+/// The state of the search for a canonical labeling.
+#[derive(Default)]
+struct CanonicalSearch {
+    /// The first leaf found.
+    first: Option<Leaf>,
+    /// The leaf with the smallest form so far.
+    best: Option<Leaf>,
+    /// Automorphisms found at leaves with equal forms,
+    /// mapping every node to its image.
+    automorphisms: Vec<Vec<usize>>,
+}
+
+impl CanonicalSearch {
+    /// Returns `true` if an automorphism that fixes the path maps a node to an explored node.
+    fn in_explored_orbit(&self, n: usize, path: &[usize], explored: &[usize], i: usize) -> bool {
+        let mut parent: Vec<usize> = (0..n).collect();
+        fn find(parent: &mut [usize], mut a: usize) -> usize {
+            while parent[a] != a {
+                parent[a] = parent[parent[a]];
+                a = parent[a];
+            }
+            a
+        }
+        for gamma in &self.automorphisms {
+            if path.iter().any(|&p| gamma[p] != p) {continue};
+            for (a, &b) in gamma.iter().enumerate() {
+                let (a, b) = (find(&mut parent, a), find(&mut parent, b));
+                parent[a] = b;
+            }
+        }
+        let orbit = find(&mut parent, i);
+        explored.iter().any(|&j| find(&mut parent, j) == orbit)
+    }
+}
+
+impl Graph {
+    /// Returns a canonical form of the colored graph.
+    ///
+    /// Two graphs have the same canonical form if and only if
+    /// they are isomorphic, preserving node and edge colors.
+    ///
+    /// The form is the number of nodes, followed by node colors
+    /// and the upper triangle of the edge matrix in canonical order.
+    /// Edge values `0` and `1` are both treated as no edge.
+    pub fn canonical_form(&self) -> Vec<Color> {
+        let labeling = self.canonical_labeling();
+        self.form(&labeling)
+    }
+
+    /// Returns a canonical labeling of nodes.
+    ///
+    /// The node at index `k` in canonical order is `labeling[k]`.
+    /// Uses color refinement and individualization of nodes.
+    /// Branches that are mapped to explored branches by automorphisms of the graph are pruned,
+    /// such that graphs with many symmetries are labeled quickly.
+    pub fn canonical_labeling(&self) -> Vec<usize> {
+        let n = self.nodes.len();
+        let mut node_colors: Vec<Color> = self.nodes.iter().map(|node| node.color).collect();
+        node_colors.sort();
+        node_colors.dedup();
+        let cells: Vec<usize> = self.nodes.iter()
+            .map(|node| node_colors.binary_search(&node.color).unwrap()).collect();
+        let mut search = CanonicalSearch::default();
+        self.canonical_search(cells, &mut vec![], &mut search);
+        search.best.map(|leaf| leaf.labeling).unwrap_or_else(|| (0..n).collect())
+    }
+
+    /// Returns `true` if two graphs are isomorphic, preserving node and edge colors.
+    pub fn is_isomorphic(&self, other: &Graph) -> bool {
+        self.nodes.len() == other.nodes.len() &&
+        self.canonical_form() == other.canonical_form()
+    }
+
+    fn edge_value(&self, pos: (usize, usize)) -> Color {
+        let val = self.get(pos);
+        if val < 2 {0} else {val}
+    }
+
+    fn form(&self, labeling: &[usize]) -> Vec<Color> {
+        let n = labeling.len();
+        let mut res = Vec::with_capacity(1 + n + n * (n + 1) / 2);
+        res.push(n as Color);
+        for &i in labeling {res.push(self.nodes[i].color)}
+        for a in 0..n {
+            for b in a..n {
+                res.push(self.edge_value((labeling[a], labeling[b])));
+            }
+        }
+        res
+    }
+
+    /// Refines cells until every node in a cell has the same neighborhood signature.
+    fn refine(&self, cells: &mut [usize]) {
+        let n = cells.len();
+        let mut count = {
+            let mut c = cells.to_vec();
+            c.sort();
+            c.dedup();
+            c.len()
+        };
+        loop {
+            let signatures: Vec<Signature> = (0..n).map(|i| {
+                let mut neighbors: Vec<(Color, usize)> = (0..n)
+                    .filter(|&j| j != i && self.edge_value((i, j)) != 0)
+                    .map(|j| (self.edge_value((i, j)), cells[j])).collect();
+                neighbors.sort();
+                (cells[i], self.edge_value((i, i)), neighbors)
+            }).collect();
+            let mut sorted = signatures.clone();
+            sorted.sort();
+            sorted.dedup();
+            for i in 0..n {
+                cells[i] = sorted.binary_search(&signatures[i]).unwrap();
+            }
+            if sorted.len() == count {break}
+            count = sorted.len();
+        }
+    }
+
+    /// Searches the tree of individualized nodes for the smallest form.
+    ///
+    /// Returns the depth to jump back to when the rest of the branch is equivalent
+    /// to an explored branch, if any.
+    fn canonical_search(
+        &self,
+        mut cells: Vec<usize>,
+        path: &mut Vec<usize>,
+        search: &mut CanonicalSearch
+    ) -> Option<usize> {
+        self.refine(&mut cells);
+        let n = cells.len();
+        let mut sizes = vec![0; n];
+        for &c in &cells {sizes[c] += 1}
+        match (0..n).find(|&c| sizes[c] > 1) {
+            None => {
+                let mut labeling = vec![0; n];
+                for (i, &c) in cells.iter().enumerate() {labeling[c] = i}
+                let form = self.form(&labeling);
+                // Equal forms give an automorphism, which maps the branch of the earlier leaf
+                // to the current branch from their common ancestor.
+                for leaf in search.first.iter().chain(search.best.iter()) {
+                    if leaf.form != form {continue};
+                    let mut gamma = vec![0; n];
+                    for (&a, &b) in leaf.labeling.iter().zip(labeling.iter()) {gamma[a] = b}
+                    let depth = leaf.path.iter().zip(path.iter()).take_while(|(a, b)| a == b).count();
+                    search.automorphisms.push(gamma);
+                    return Some(depth);
+                }
+                let leaf = Leaf {form, labeling, path: path.clone()};
+                if search.first.is_none() {search.first = Some(leaf.clone())};
+                if search.best.as_ref().map(|b| leaf.form < b.form).unwrap_or(true) {
+                    search.best = Some(leaf);
+                }
+                None
+            }
+            Some(cell) => {
+                let depth = path.len();
+                let mut explored = vec![];
+                for i in 0..n {
+                    if cells[i] != cell {continue};
+                    if search.in_explored_orbit(n, path, &explored, i) {continue};
+                    explored.push(i);
+                    // Individualize node by placing it before other nodes in the cell.
+                    let mut new_cells: Vec<usize> = cells.iter().map(|&c| 2 * c + 1).collect();
+                    new_cells[i] = 2 * cell;
+                    path.push(i);
+                    let jump = self.canonical_search(new_cells, path, search);
+                    path.pop();
+                    if let Some(d) = jump {
+                        if d < depth {return Some(d)};
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns a lazy iterator over solutions,
+    /// yielding one representative per isomorphism class.
+    ///
+    /// For more information, see `Graph::canonical_form`.
+    pub fn solve_all_canonical(self, solve_settings: SearchSettings) -> CanonicalSolutions {
+        CanonicalSolutions {
+            solutions: self.solve_all(solve_settings),
+            seen: HashSet::new(),
+        }
+    }
+}
+
+/// Lazy iterator over solutions up to isomorphism.
+pub struct CanonicalSolutions {
+    solutions: Solutions,
+    seen: HashSet<Vec<Color>>,
+}
+
+impl CanonicalSolutions {
+    /// Returns the number of iterations so far.
+    pub fn iterations(&self) -> u64 {self.solutions.iterations()}
+}
+
+impl Iterator for CanonicalSolutions {
+    type Item = Solution<Graph>;
+
+    fn next(&mut self) -> Option<Solution<Graph>> {
+        let seen = &mut self.seen;
+        self.solutions.find(|solution| seen.insert(solution.puzzle.canonical_form()))
+    }
+}
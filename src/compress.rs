@@ -0,0 +1,174 @@
+//! Compression of sparse colors to a dense range.
+
+use super::*;
+
+/// Maps the colors of a compressed graph back to the original colors.
+///
+/// Node colors are compressed to `0..k`, in the original order.
+/// Edge colors are compressed in sign pairs, such that `2` and `3` stay paired,
+/// which preserves the anticommutation of `Graph::commute_quad`.
+/// Edge colors are not compressed when some node has a label,
+/// since labels determine edge colors, see `Graph::labels`,
+/// or when sign pairs are set, see `Graph::sign_pairs`.
+///
+/// Compressed colors are useful for renderers that look up styles by color,
+/// and for storing edge domains as bitsets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorMap {
+    /// The original node color of every compressed node color.
+    node_colors: Vec<Color>,
+    /// The original sign pair of every compressed sign pair, if compressed.
+    edge_pairs: Option<Vec<Color>>,
+}
+
+impl ColorMap {
+    /// Returns the original node color of a compressed node color.
+    pub fn node_color(&self, color: Color) -> Color {
+        self.node_colors[color as usize]
+    }
+
+    /// Returns the original edge value of a compressed edge value.
+    ///
+    /// Empty and no-edge values are unchanged.
+    pub fn edge_color(&self, color: Color) -> Color {
+        match &self.edge_pairs {
+            Some(pairs) if color >= 2 => 2 * pairs[(color / 2 - 1) as usize] + (color & 1),
+            _ => color,
+        }
+    }
+
+    /// Returns the compressed node color of an original node color, if any.
+    pub fn compress_node_color(&self, color: Color) -> Option<Color> {
+        self.node_colors.binary_search(&color).ok().map(|k| k as Color)
+    }
+
+    /// Returns the compressed edge value of an original edge value, if any.
+    pub fn compress_edge_color(&self, color: Color) -> Option<Color> {
+        match &self.edge_pairs {
+            Some(pairs) if color >= 2 => pairs.binary_search(&(color / 2)).ok()
+                .map(|k| 2 * (k as Color + 1) + (color & 1)),
+            _ => Some(color),
+        }
+    }
+
+    /// Returns `true` if the compressed colors are the same as the original colors.
+    pub fn is_identity(&self) -> bool {
+        self.node_colors.iter().enumerate().all(|(k, &c)| k as Color == c) &&
+        self.edge_pairs.as_ref().map(|pairs| pairs.iter().enumerate().all(|(k, &p)| k as Color + 1 == p))
+            .unwrap_or(true)
+    }
+
+    /// Maps a compressed graph, e.g. a solution, back to the original colors.
+    pub fn expand(&self, g: &Graph) -> Graph {
+        g.map_colors(&|c| self.node_color(c), &|c| self.edge_color(c))
+    }
+}
+
+impl Graph {
+    /// Compresses node and edge colors to a dense range.
+    ///
+    /// Returns the compressed graph and the map back to the original colors.
+    /// For more information, see `ColorMap`.
+    pub fn compress_colors(&self) -> (Graph, ColorMap) {
+        let mut node_colors = vec![];
+        let mut edge_colors = vec![];
+        self.collect_colors(&mut node_colors, &mut edge_colors);
+        node_colors.sort();
+        node_colors.dedup();
+        let edge_pairs = if self.labels.iter().any(|label| label.is_some()) ||
+                            !self.sign_pairs.is_empty() {None} else {
+            let mut pairs: Vec<Color> = edge_colors.iter().filter(|&&c| c >= 2).map(|c| c / 2).collect();
+            pairs.sort();
+            pairs.dedup();
+            Some(pairs)
+        };
+        let map = ColorMap {node_colors, edge_pairs};
+        let g = self.map_colors(&|c| map.compress_node_color(c).unwrap(),
+                                &|c| map.compress_edge_color(c).unwrap());
+        (g, map)
+    }
+
+    /// Collects the node and edge colors used anywhere in the specification.
+    fn collect_colors(&self, node_colors: &mut Vec<Color>, edge_colors: &mut Vec<Color>) {
+        for node in &self.nodes {
+            node_colors.push(node.color);
+            for con in &node.edges {
+                if con.node != SELF_LOOP {node_colors.push(con.node)};
+                edge_colors.push(con.edge);
+            }
+        }
+        edge_colors.extend(self.edges.iter().cloned());
+        if let Some((a, b)) = self.bipartition {node_colors.extend([a, b])};
+        edge_colors.extend(self.color_counts.iter().map(|cc| cc.color));
+        edge_colors.extend(self.conditionals.iter().map(|&(color, _)| color));
+        edge_colors.extend(self.quad_rules.iter().flat_map(|r| [r.colors.0, r.colors.1]));
+        edge_colors.extend(self.restrictions.iter().flat_map(|(_, allowed)| allowed.iter().cloned()));
+        edge_colors.extend(self.edit_distances.iter().flat_map(|d| d.edges.iter().map(|&(_, c)| c)));
+        for pattern in &self.required_subgraphs {pattern.collect_colors(node_colors, edge_colors)}
+    }
+
+    /// Returns a copy of the graph with node and edge colors mapped.
+    ///
+    /// The edge map is only applied to colored edges.
+    fn map_colors(&self, node: &dyn Fn(Color) -> Color, edge: &dyn Fn(Color) -> Color) -> Graph {
+        let edge = |c: Color| if c >= 2 {edge(c)} else {c};
+        let mut g = Graph::new();
+        for n in &self.nodes {
+            g.push(Node {
+                color: node(n.color),
+                self_connected: n.self_connected,
+                edges: n.edges.iter().map(|con| Constraint {
+                    edge: edge(con.edge),
+                    node: if con.node == SELF_LOOP {SELF_LOOP} else {node(con.node)},
+                }).collect(),
+            });
+        }
+        let n = self.nodes.len();
+        for i in 0..n {
+            for j in 0..=i {
+                let val = self.get((i, j));
+                if val != 0 {g.set((i, j), edge(val))};
+            }
+        }
+        g.labels = self.labels.clone();
+        g.names = self.names.clone();
+        g.pairs = self.pairs.clone();
+        g.anti_pairs = self.anti_pairs.clone();
+        for (pos, allowed) in &self.restrictions {
+            let allowed: Vec<Color> = allowed.iter().map(|&c| edge(c)).collect();
+            g.restrict(*pos, &allowed);
+        }
+        g.cardinalities = self.cardinalities.clone();
+        g.degrees = self.degrees.clone();
+        g.color_counts = self.color_counts.iter().map(|cc| ColorCount {color: edge(cc.color), ..*cc}).collect();
+        g.no_triangles = self.no_triangles;
+        g.min_girth = self.min_girth;
+        g.planar = self.planar;
+        g.bipartite = self.bipartite;
+        g.meet_quad = self.meet_quad;
+        g.max_local_girth = self.max_local_girth;
+        g.connected = self.connected;
+        g.no_isolated_nodes = self.no_isolated_nodes;
+        g.commute_quad = self.commute_quad;
+        g.sign_pairs = self.sign_pairs.clone();
+        g.quad_nodes = self.quad_nodes.clone();
+        g.quad_rules = self.quad_rules.iter().map(|r| {
+            let (a, b) = (edge(r.colors.0), edge(r.colors.1));
+            QuadRule {colors: (a.min(b), a.max(b)), ..*r}
+        }).collect();
+        g.bipartition = self.bipartition.map(|(a, b)| (node(a), node(b)));
+        g.conditionals = self.conditionals.iter().map(|&(color, flag)| (edge(color), flag)).collect();
+        g.component_constraints = self.component_constraints.clone();
+        g.symmetric_differences = self.symmetric_differences.clone();
+        g.edit_distances = self.edit_distances.iter().map(|d| EditDistance {
+            edges: d.edges.iter().map(|&(e, c)| (e, edge(c))).collect(),
+            max: d.max,
+        }).collect();
+        for pattern in &self.required_subgraphs {g.require_subgraph(&pattern.map_colors(&node, &edge))}
+        g.symmetry_break = self.symmetry_break;
+        g.color_symmetry_break = self.color_symmetry_break;
+        g.priorities = self.priorities.clone();
+        g.provenance = self.provenance.clone();
+        g
+    }
+}
@@ -0,0 +1,127 @@
+//! Detection of edge constraints that can never be satisfied.
+
+use std::fmt;
+
+use super::*;
+
+/// Why an edge constraint can never be satisfied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeadReason {
+    /// No other node has the node color of the edge constraint,
+    /// or the node is not self-connected for a constraint to `SELF_LOOP`.
+    MissingNodeColor,
+    /// No node of the node color has the reverse edge constraint.
+    NoReciprocal,
+    /// Every candidate edge is eliminated, with the explanations.
+    ///
+    /// For more information, see `Graph::why_not`.
+    Eliminated(Vec<String>),
+    /// There are fewer candidate edges than repeated edge constraints.
+    TooFewCandidates {
+        /// The number of candidate edges.
+        candidates: usize,
+        /// The number of repeated edge constraints.
+        required: usize,
+    },
+}
+
+/// An edge constraint of a node that can never be satisfied.
+///
+/// For more information, see `Graph::dead_constraints`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadConstraint {
+    /// The node.
+    pub node: usize,
+    /// The color of the node.
+    pub node_color: Color,
+    /// The index of the edge constraint in the node.
+    pub slot: usize,
+    /// The edge constraint.
+    pub constraint: Constraint,
+    /// Why the edge constraint can never be satisfied.
+    pub reason: DeadReason,
+}
+
+impl DeadConstraint {
+    pub(crate) fn write(&self, w: &mut fmt::Formatter, name: &dyn Fn(usize) -> String) -> fmt::Result {
+        let con = &self.constraint;
+        if con.node == SELF_LOOP {
+            write!(w, "node {} requires self-loop {}, but ", name(self.node), con.edge)?;
+        } else {
+            write!(w, "node {} requires edge {} to node color {}, but ", name(self.node), con.edge, con.node)?;
+        }
+        match &self.reason {
+            DeadReason::MissingNodeColor if con.node == SELF_LOOP => write!(w, "the node is not self-connected"),
+            DeadReason::MissingNodeColor => write!(w, "no other node has this color"),
+            DeadReason::NoReciprocal => write!(w, "no node of this color accepts edge {} to node color {}",
+                                               con.edge, self.node_color),
+            DeadReason::Eliminated(reasons) =>
+                write!(w, "every candidate edge is eliminated: {}", reasons.join("; ")),
+            DeadReason::TooFewCandidates {candidates, required} =>
+                write!(w, "only {} of {} such edges are possible", candidates, required),
+        }
+    }
+}
+
+impl fmt::Display for DeadConstraint {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.write(w, &|i| i.to_string())
+    }
+}
+
+impl Graph {
+    /// Finds edge constraints of nodes that can never be satisfied.
+    ///
+    /// An edge constraint is dead when no node can provide the edge,
+    /// because no node has the node color, no node has the reverse edge constraint,
+    /// or every candidate edge is eliminated in the current state,
+    /// e.g. by pre-set edges, labels, the bipartition or color counts.
+    ///
+    /// A puzzle with dead constraints has no solution.
+    /// This is checked without solving, so dead constraints are a cheap explanation
+    /// of why solving returns `None`.
+    pub fn dead_constraints(&self) -> Vec<DeadConstraint> {
+        let n = self.nodes.len();
+        let mut res = vec![];
+        for (i, node) in self.nodes.iter().enumerate() {
+            for (slot, &con) in node.edges.iter().enumerate() {
+                let dead = |reason| DeadConstraint {node: i, slot, constraint: con, node_color: node.color, reason};
+                let targets: Vec<usize> = (0..n).filter(|&j| self.matches(con, i, j) &&
+                    (j != i || node.self_connected)).collect();
+                if targets.is_empty() {
+                    res.push(dead(DeadReason::MissingNodeColor));
+                    continue;
+                }
+                let targets: Vec<usize> = targets.into_iter().filter(|&j| con.node == SELF_LOOP ||
+                    self.nodes[j].edges.iter().any(|c| c.edge == con.edge && c.node == node.color)).collect();
+                if targets.is_empty() {
+                    res.push(dead(DeadReason::NoReciprocal));
+                    continue;
+                }
+                let mut reasons = vec![];
+                let candidates = targets.iter().filter(|&&j| {
+                    let val = self.get((i, j));
+                    if val == con.edge {return true};
+                    match self.why_not((i, j), con.edge) {
+                        None => true,
+                        Some(reason) => {
+                            if !reasons.contains(&reason) {reasons.push(reason)};
+                            false
+                        }
+                    }
+                }).count();
+                if candidates == 0 {
+                    res.push(dead(DeadReason::Eliminated(reasons)));
+                    continue;
+                }
+                // Repeated edge constraints require different edges.
+                let required = node.edges.iter().filter(|&&c| c == con).count();
+                let before = node.edges[..slot].iter().filter(|&&c| c == con).count();
+                if before >= candidates {
+                    res.push(dead(DeadReason::TooFewCandidates {candidates, required}));
+                }
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,225 @@
+//! Diagnostics of violated constraints in partial solutions.
+
+use std::fmt;
+
+use super::*;
+
+/// A constraint that is violated by the current assignment.
+///
+/// For more information, see `Graph::diagnose`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// A colored edge of a node that no edge constraint of the node allows.
+    ExtraEdge {
+        /// The node.
+        node: usize,
+        /// The edge, using sorted indices.
+        edge: (usize, usize),
+        /// The color of the edge.
+        color: Color,
+    },
+    /// Edge constraints of a node that can not be satisfied by the remaining empty edges.
+    MissingEdges {
+        /// The node.
+        node: usize,
+        /// The unsatisfied edge constraints.
+        missing: Vec<Constraint>,
+    },
+    /// A pair constraint where the edge is no-edge.
+    Pair {
+        /// The pair, using sorted indices.
+        pair: (usize, usize),
+    },
+    /// An anti-pair constraint where the edge is colored.
+    AntiPair {
+        /// The anti-pair, using sorted indices.
+        pair: (usize, usize),
+    },
+    /// A quad of colored edges `a-b-c-d` that does not commute or anticommute.
+    ///
+    /// For more information, see `Graph::commute_quad`.
+    Quad {
+        /// The nodes along the quad.
+        nodes: [usize; 4],
+    },
+    /// A triangle of colored edges when triangles are forbidden.
+    Triangle {
+        /// The nodes of the triangle, in order.
+        nodes: [usize; 3],
+    },
+    /// The graph can not become connected.
+    Disconnected {
+        /// The groups of nodes that are connected along edges that are not no-edge.
+        components: Vec<Vec<usize>>,
+    },
+}
+
+impl Violation {
+    fn write(&self, w: &mut fmt::Formatter, name: &dyn Fn(usize) -> String) -> fmt::Result {
+        let names = |nodes: &[usize]| nodes.iter().map(|&i| name(i)).collect::<Vec<_>>().join(", ");
+        match self {
+            Violation::ExtraEdge {node, edge, color} =>
+                write!(w, "node {} has edge ({}) of color {} that no edge constraint allows",
+                       name(*node), names(&[edge.0, edge.1]), color),
+            Violation::MissingEdges {node, missing} => {
+                write!(w, "node {} can not get edges", name(*node))?;
+                for con in missing {
+                    write!(w, " {}:{}", con.edge, con.node)?;
+                }
+                Ok(())
+            }
+            Violation::Pair {pair} => write!(w, "pair ({}) is no-edge", names(&[pair.0, pair.1])),
+            Violation::AntiPair {pair} => write!(w, "anti-pair ({}) is colored", names(&[pair.0, pair.1])),
+            Violation::Quad {nodes} => write!(w, "quad [{}] violates `commute_quad`", names(nodes)),
+            Violation::Triangle {nodes} => write!(w, "triangle [{}]", names(nodes)),
+            Violation::Disconnected {components} => {
+                let components: Vec<String> = components.iter()
+                    .map(|c| format!("[{}]", names(c))).collect();
+                write!(w, "graph can not become connected, components: [{}]", components.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.write(w, &|i| i.to_string())
+    }
+}
+
+/// Displays a diagnostic using node names instead of indices.
+///
+/// For more information, see `Graph::named`.
+pub struct Named<'a, T> {
+    graph: &'a Graph,
+    value: &'a T,
+}
+
+impl<'a> fmt::Display for Named<'a, Violation> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.value.write(w, &|i| self.graph.node_name(i))
+    }
+}
+
+impl<'a> fmt::Display for Named<'a, DeadConstraint> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.value.write(w, &|i| self.graph.node_name(i))
+    }
+}
+
+impl<'a> fmt::Display for Named<'a, UnsatReason> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.value.write(w, &|i| self.graph.node_name(i))
+    }
+}
+
+impl Graph {
+    /// Displays a diagnostic using node names instead of indices.
+    ///
+    /// This supports `Violation`, `DeadConstraint` and `UnsatReason`.
+    /// Nodes without name are displayed by index, see `Graph::node_name`.
+    pub fn named<'a, T>(&'a self, value: &'a T) -> Named<'a, T> {
+        Named {graph: self, value}
+    }
+
+    /// Reports the constraints that are violated by the current assignment.
+    ///
+    /// The assignment can be partial, in which case only violations that
+    /// can not be repaired by assigning empty edges are reported:
+    ///
+    /// - Colored edges that no edge constraint of a node allows
+    /// - Edge constraints of a node that need more edges than the node has empty edges
+    /// - Pair constraints where the edge is no-edge
+    /// - Anti-pair constraints where the edge is colored
+    /// - Quads that do not commute or anticommute, see `Graph::commute_quad` and `Graph::quad_rules`
+    /// - Triangles, when forbidden by `Graph::no_triangles` or `Graph::min_girth`
+    /// - Disconnection, when `Graph::connected` is set
+    ///
+    /// This is useful to inspect a failed or partial solution,
+    /// instead of reading the matrix printed by `Puzzle::print`.
+    pub fn diagnose(&self) -> Vec<Violation> {
+        let n = self.nodes.len();
+        let mut res = vec![];
+        for i in 0..n {
+            let node = &self.nodes[i];
+            let mut matched = vec![false; node.edges.len()];
+            let mut empty = 0;
+            for j in 0..n {
+                if i == j && !node.self_connected {continue};
+                let color = self.get((i, j));
+                if color == 0 {empty += 1};
+                if color < 2 {continue};
+                match self.match_slot(i, j, color, &matched) {
+                    Some(k) => matched[k] = true,
+                    None => res.push(Violation::ExtraEdge {node: i, edge: (i.min(j), i.max(j)), color}),
+                }
+            }
+            let missing: Vec<Constraint> = (0..node.edges.len())
+                .filter(|&k| !matched[k]).map(|k| node.edges[k]).collect();
+            if missing.len() > empty {
+                res.push(Violation::MissingEdges {node: i, missing});
+            }
+        }
+        for &pair in &self.pairs {
+            if self.get(pair) == 1 {res.push(Violation::Pair {pair})};
+        }
+        for &pair in &self.anti_pairs {
+            if self.get(pair) >= 2 {res.push(Violation::AntiPair {pair})};
+        }
+        if self.commute_quad.is_some() || !self.quad_rules.is_empty() {
+            for a in 0..n {
+                for b in a + 1..n {
+                    if self.get((a, b)) < 2 {continue};
+                    for c in a + 1..n {
+                        if c == b || self.get((b, c)) < 2 {continue};
+                        for d in b + 1..n {
+                            if d == c || self.get((c, d)) < 2 || self.get((d, a)) < 2 {continue};
+                            if ![a, b, c, d].iter().all(|&x| self.in_quad_nodes(x)) {continue};
+                            let x = (self.get((a, b)), self.get((c, d)));
+                            let y = (self.get((b, c)), self.get((d, a)));
+                            if !self.quad_satisfied(x, y, self.commute_quad) {
+                                res.push(Violation::Quad {nodes: [a, b, c, d]});
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if self.no_triangles || self.min_girth.map(|k| k > 3).unwrap_or(false) {
+            for a in 0..n {
+                for b in a + 1..n {
+                    if self.get((a, b)) < 2 {continue};
+                    for c in b + 1..n {
+                        if self.get((b, c)) >= 2 && self.get((a, c)) >= 2 {
+                            res.push(Violation::Triangle {nodes: [a, b, c]});
+                        }
+                    }
+                }
+            }
+        }
+        if self.connected && !self.possibly_connected() {
+            let mut visited = vec![false; n];
+            let mut components = vec![];
+            for i in 0..n {
+                if visited[i] {continue};
+                visited[i] = true;
+                let mut component = vec![i];
+                let mut k = 0;
+                while k < component.len() {
+                    let a = component[k];
+                    for (b, v) in visited.iter_mut().enumerate() {
+                        if !*v && self.get((a, b)) != 1 {
+                            *v = true;
+                            component.push(b);
+                        }
+                    }
+                    k += 1;
+                }
+                component.sort();
+                components.push(component);
+            }
+            res.push(Violation::Disconnected {components});
+        }
+        res
+    }
+}
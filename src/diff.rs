@@ -0,0 +1,116 @@
+//! Comparing two specifications.
+
+use std::fmt;
+
+use super::*;
+
+/// Stores the differences between two specifications.
+///
+/// For more information, see `Graph::spec_diff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpecDiff {
+    /// Readable descriptions of each difference.
+    pub differences: Vec<String>,
+}
+
+impl SpecDiff {
+    /// Returns `true` if the specifications are equal.
+    pub fn is_empty(&self) -> bool {self.differences.is_empty()}
+
+    fn value<T: fmt::Debug + PartialEq>(&mut self, name: &str, a: T, b: T) {
+        if a != b {
+            self.differences.push(format!("{}: {:?} -> {:?}", name, a, b));
+        }
+    }
+
+    fn set<T: fmt::Debug + PartialEq>(&mut self, name: &str, a: &[T], b: &[T]) {
+        for x in a {
+            if !b.contains(x) {self.differences.push(format!("{} removed: {:?}", name, x))};
+        }
+        for x in b {
+            if !a.contains(x) {self.differences.push(format!("{} added: {:?}", name, x))};
+        }
+    }
+}
+
+impl fmt::Display for SpecDiff {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        if self.differences.is_empty() {
+            return writeln!(w, "<no differences>");
+        }
+        for difference in &self.differences {
+            writeln!(w, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Compares the specification with another specification.
+    ///
+    /// Compares node descriptions, pinned edges, pairs, other constraints and flags.
+    /// Edges that are not pinned in either graph are ignored,
+    /// so the comparison is independent of solution state when specifications are unsolved.
+    /// Edge constraints of a node are compared independently of order.
+    pub fn spec_diff(&self, other: &Graph) -> SpecDiff {
+        let mut diff = SpecDiff {differences: vec![]};
+        diff.value("nodes", self.nodes.len(), other.nodes.len());
+        let n = self.nodes.len().min(other.nodes.len());
+        for i in 0..n {
+            let (a, b) = (&self.nodes[i], &other.nodes[i]);
+            diff.value(&format!("node {} color", i), a.color, b.color);
+            diff.value(&format!("node {} self-connected", i), a.self_connected, b.self_connected);
+            let sorted = |node: &Node| {
+                let mut edges: Vec<(Color, Color)> = node.edges.iter().map(|c| (c.edge, c.node)).collect();
+                edges.sort();
+                edges
+            };
+            diff.value(&format!("node {} edges (edge, node)", i), sorted(a), sorted(b));
+            diff.value(&format!("node {} label", i), self.labels[i], other.labels[i]);
+            diff.value(&format!("node {} name", i), &self.names[i], &other.names[i]);
+        }
+        for i in 0..n {
+            for j in i..n {
+                diff.value(&format!("edge ({}, {})", i, j), self.get((i, j)), other.get((i, j)));
+                diff.value(&format!("edge ({}, {}) priority", i, j),
+                    self.priority((i, j)), other.priority((i, j)));
+            }
+        }
+        let sorted_pairs = |g: &Graph| {
+            let mut pairs: Vec<(usize, usize)> = g.pairs.iter().map(|&(i, j)| (i.min(j), i.max(j))).collect();
+            pairs.sort();
+            pairs
+        };
+        diff.set("pair", &sorted_pairs(self), &sorted_pairs(other));
+        diff.set("anti-pair", &self.anti_pairs, &other.anti_pairs);
+        diff.set("restriction", &self.restrictions, &other.restrictions);
+        diff.set("cardinality", &self.cardinalities, &other.cardinalities);
+        diff.set("degree", &self.degrees, &other.degrees);
+        diff.set("color count", &self.color_counts, &other.color_counts);
+        diff.set("conditional", &self.conditionals, &other.conditionals);
+        diff.set("component constraint", &self.component_constraints, &other.component_constraints);
+        diff.set("symmetric difference", &self.symmetric_differences, &other.symmetric_differences);
+        diff.set("edit distance", &self.edit_distances, &other.edit_distances);
+        let forms = |g: &Graph| -> Vec<Vec<Color>> {
+            g.required_subgraphs.iter().map(|pattern| pattern.canonical_form()).collect()
+        };
+        diff.set("required subgraph (canonical form)", &forms(self), &forms(other));
+
+        diff.value("no_triangles", self.no_triangles, other.no_triangles);
+        diff.value("min_girth", self.min_girth, other.min_girth);
+        diff.value("planar", self.planar, other.planar);
+        diff.value("bipartite", self.bipartite, other.bipartite);
+        diff.value("meet_quad", self.meet_quad, other.meet_quad);
+        diff.value("max_local_girth", self.max_local_girth, other.max_local_girth);
+        diff.value("connected", self.connected, other.connected);
+        diff.value("no_isolated_nodes", self.no_isolated_nodes, other.no_isolated_nodes);
+        diff.value("commute_quad", self.commute_quad, other.commute_quad);
+        diff.set("sign pair", &self.sign_pairs, &other.sign_pairs);
+        diff.set("quad rule", &self.quad_rules, &other.quad_rules);
+        diff.value("quad_nodes", &self.quad_nodes, &other.quad_nodes);
+        diff.value("bipartition", self.bipartition, other.bipartition);
+        diff.value("symmetry_break", self.symmetry_break, other.symmetry_break);
+        diff.value("color_symmetry_break", self.color_symmetry_break, other.color_symmetry_break);
+        diff
+    }
+}
@@ -0,0 +1,254 @@
+//! Directed graphs.
+
+use super::*;
+
+/// Stores a description of a node in a directed graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiNode {
+    /// The color of the node.
+    pub color: Color,
+    /// Whether the node can be self-connected.
+    pub self_connected: bool,
+    /// The constraints of edges going into the node.
+    pub in_edges: Vec<Constraint>,
+    /// The constraints of edges going out of the node.
+    pub out_edges: Vec<Constraint>,
+}
+
+/// Stores information about a directed graph.
+///
+/// Unlike `Graph`, the edge matrix is not symmetric:
+/// The edge at `(i, j)` goes from node `i` to node `j`.
+#[derive(Clone, Debug)]
+pub struct Digraph {
+    /// Nodes.
+    pub nodes: Vec<DiNode>,
+    /// Edges.
+    pub edges: Vec<Vec<Color>>,
+    /// Directed pair constraints, using indices.
+    pub pairs: Vec<(usize, usize)>,
+    /// Whether any node can be reached from any other node,
+    /// ignoring the direction of edges.
+    pub connected: bool,
+}
+
+impl Puzzle for Digraph {
+    type Pos = (usize, usize);
+    type Val = Color;
+    fn set(&mut self, (i, j): (usize, usize), val: Color) {
+        self.edges[i][j] = val;
+    }
+    fn get(&self, (i, j): (usize, usize)) -> Color {
+        self.edges[i][j]
+    }
+    fn print(&self) {
+        for i in 0..self.nodes.len() {
+            eprint!("{} ", self.nodes[i].color);
+        }
+        eprintln!("\n========================================");
+        for i in 0..self.nodes.len() {
+            for j in 0..self.nodes.len() {
+                eprint!("{} ", self.get((i, j)));
+            }
+            eprintln!();
+        }
+    }
+    fn solve_simple<F: FnMut(&mut Self, Self::Pos, Self::Val)>(&mut self, mut f: F) {
+        let n = self.nodes.len();
+        for i in 0..n {
+            for j in 0..n {
+                let colors = self.colors((i, j));
+                if colors.len() == 1 {
+                    f(self, (i, j), colors[0]);
+                }
+            }
+        }
+    }
+    fn is_solved(&self) -> bool {
+        self.all_satisfied() &&
+        self.pairs_satisfied() &&
+        if self.connected {self.is_connected()} else {true}
+    }
+    fn remove(&mut self, other: &Digraph) {
+        let n = self.nodes.len();
+        for i in 0..n {
+            for j in 0..n {
+                if other.get((i, j)) != 0 {
+                    self.set((i, j), 0);
+                }
+            }
+        }
+    }
+}
+
+impl Default for Digraph {
+    fn default() -> Digraph {Digraph::new()}
+}
+
+impl Digraph {
+    /// Creates a new directed graph.
+    ///
+    /// Initialized with these default settings:
+    /// - connected: false
+    pub fn new() -> Digraph {
+        Digraph {
+            nodes: vec![],
+            edges: vec![],
+            pairs: vec![],
+            connected: false,
+        }
+    }
+
+    /// Generates a GraphViz dot format.
+    pub fn graphviz(&self, layout: &str, node_colors: &[&str], edge_colors: &[&str]) -> String {
+        use std::fmt::Write;
+
+        let mut s = String::new();
+        writeln!(&mut s, "strict digraph {{").unwrap();
+        writeln!(&mut s, "  layout={}; edge[penwidth=4]", layout).unwrap();
+        for i in 0..self.nodes.len() {
+            writeln!(&mut s, "  {}[regular=true,style=filled,fillcolor={}];", i,
+                   node_colors[self.nodes[i].color as usize % node_colors.len()]).unwrap();
+        }
+        for i in 0..self.nodes.len() {
+            for (j, &ed) in self.edges[i].iter().enumerate() {
+                if ed < 2 {continue};
+                writeln!(&mut s, "  {} -> {}[color={}];", i, j,
+                edge_colors[(ed - 2) as usize % edge_colors.len()]).unwrap();
+            }
+        }
+        writeln!(&mut s, "}}").unwrap();
+        s
+    }
+
+    /// Finds the edge with the least possible colors.
+    pub fn min_colors(&self) -> Option<(usize, usize)> {
+        let mut min: Option<(usize, usize, usize)> = None;
+        let n = self.nodes.len();
+        'outer: for i in 0..n {
+            for j in 0..n {
+                let s = self.colors((i, j)).len();
+                if s == 0 {continue};
+                if min.is_none() || min.unwrap().2 > s {
+                    min = Some((i, j, s));
+                    if s == 1 {break 'outer}
+                }
+            }
+        }
+        min.map(|n| (n.0, n.1))
+    }
+
+    /// Solves the graph puzzle using default strategy.
+    ///
+    /// The default strategy is `Digraph::min_colors, Digraph::colors`.
+    pub fn solve(self, solve_settings: SolveSettings) -> Option<Solution<Digraph>> {
+        #[cfg(feature = "verify-solutions")]
+        let original = self.clone();
+        let solver = BackTrackSolver::new(self, solve_settings);
+        let solution = solver.solve(
+            Digraph::min_colors,
+            Digraph::colors
+        );
+        #[cfg(feature = "verify-solutions")]
+        if let Some(solution) = &solution {reference::verify_digraph(&solution.puzzle, &original)};
+        solution
+    }
+
+    /// Adds a node description.
+    pub fn push(&mut self, node: DiNode) {
+        self.nodes.push(node);
+        let n = self.nodes.len();
+        for row in &mut self.edges {row.push(0)}
+        self.edges.push(vec![0; n]);
+    }
+
+    /// Adds a directed pair constraint from `i` to `j`.
+    pub fn push_pair(&mut self, (i, j): (usize, usize)) {
+        self.pairs.push((i, j));
+    }
+
+    fn unsatisfied<I>(&self, constraints: &[Constraint], edges: I) -> Vec<Constraint>
+        where I: Iterator<Item = (Color, usize)>
+    {
+        let mut m = vec![false; constraints.len()];
+        for (edge, j) in edges {
+            if edge == 0 {continue};
+            for (k, mk) in m.iter_mut().enumerate() {
+                if *mk {continue};
+                let con = &constraints[k];
+                if con.edge == edge && con.node == self.nodes[j].color {
+                    *mk = true;
+                    break;
+                }
+            }
+        }
+        constraints.iter().zip(m.iter()).filter(|(_, &mk)| !mk).map(|(c, _)| *c).collect()
+    }
+
+    /// Returns a list of out-edge constraints that makes a node unsatisfied.
+    pub fn out_satisfied(&self, i: usize) -> Vec<Constraint> {
+        let n = self.nodes.len();
+        self.unsatisfied(&self.nodes[i].out_edges, (0..n).map(|j| (self.get((i, j)), j)))
+    }
+
+    /// Returns a list of in-edge constraints that makes a node unsatisfied.
+    pub fn in_satisfied(&self, i: usize) -> Vec<Constraint> {
+        let n = self.nodes.len();
+        self.unsatisfied(&self.nodes[i].in_edges, (0..n).map(|j| (self.get((j, i)), j)))
+    }
+
+    /// Returns `true` if all nodes are satisfied.
+    pub fn all_satisfied(&self) -> bool {
+        (0..self.nodes.len()).all(|i| {
+            self.out_satisfied(i).is_empty() && self.in_satisfied(i).is_empty()
+        })
+    }
+
+    /// Returns `true` if all pair constraints are satisfied.
+    pub fn pairs_satisfied(&self) -> bool {
+        self.pairs.iter().all(|&pos| self.get(pos) >= 2)
+    }
+
+    /// Returns `true` if all nodes can be reached from any node,
+    /// ignoring the direction of edges.
+    pub fn is_connected(&self) -> bool {
+        let n = self.nodes.len();
+        if n == 0 {return true};
+        let mut reachable = vec![false; n];
+        reachable[0] = true;
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            for (j, r) in reachable.iter_mut().enumerate() {
+                if !*r && (self.get((i, j)) >= 2 || self.get((j, i)) >= 2) {
+                    *r = true;
+                    stack.push(j);
+                }
+            }
+        }
+        reachable.iter().all(|&b| b)
+    }
+
+    /// Returns a list of possible actions for an edge.
+    pub fn colors(&self, (i, j): (usize, usize)) -> Vec<Color> {
+        if self.get((i, j)) != 0 {return vec![]};
+        if !self.nodes[i].self_connected && i == j {return vec![]};
+        let mut res = vec![];
+        let errors = self.out_satisfied(i);
+        let other_errors = self.in_satisfied(j);
+        for err in &errors {
+            if err.node != self.nodes[j].color {continue}
+            for other_err in &other_errors {
+                if err.edge == other_err.edge &&
+                   other_err.node == self.nodes[i].color
+                {
+                    res.push(err.edge);
+                    break;
+                }
+            }
+        }
+        res.push(1);
+        res.sort();
+        res.dedup();
+        res
+    }
+}
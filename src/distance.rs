@@ -0,0 +1,151 @@
+//! Bounds on the distance to reference graphs.
+
+use super::*;
+
+/// Stores the result of repairing a graph.
+///
+/// For more information, see `Graph::repair`.
+#[derive(Clone, Debug)]
+pub struct Repair {
+    /// The solution closest to the reference graph.
+    pub graph: Graph,
+    /// The edges that differ, using sorted indices,
+    /// with the value in the reference graph and the value in the solution.
+    ///
+    /// Empty edges and no-edges are reported as `1`.
+    pub changes: Vec<((usize, usize), Color, Color)>,
+}
+
+impl EditDistance {
+    /// Creates a new bound from the colored edges of a reference graph.
+    ///
+    /// Edges are stored using sorted indices, and values below `2` are left out.
+    pub fn new(edges: &[((usize, usize), Color)], max: usize) -> EditDistance {
+        let mut edges: Vec<((usize, usize), Color)> = edges.iter()
+            .filter(|&&(_, c)| c >= 2)
+            .map(|&((i, j), c)| ((i.min(j), i.max(j)), c)).collect();
+        edges.sort();
+        edges.dedup_by_key(|&mut (e, _)| e);
+        EditDistance {edges, max}
+    }
+
+    /// Returns the edge value of the reference graph, where `1` means no edge.
+    pub fn get(&self, (i, j): (usize, usize)) -> Color {
+        let pos = (i.min(j), i.max(j));
+        match self.edges.binary_search_by_key(&pos, |&(e, _)| e) {
+            Ok(k) => self.edges[k].1,
+            Err(_) => 1,
+        }
+    }
+}
+
+impl Graph {
+    /// Adds a bound on the number of edges where a solution differs from a reference graph.
+    ///
+    /// An edge differs when it has another color, or is colored in only one of the graphs.
+    /// Empty edges and no-edges of the reference graph are treated as no edge.
+    ///
+    /// This is useful for fixing a hand-drawn graph with few changes,
+    /// e.g. by searching with increasing bounds.
+    ///
+    /// Panics if the reference graph has another number of nodes.
+    pub fn push_edit_distance(&mut self, reference: &Graph, max: usize) {
+        let n = self.nodes.len();
+        assert_eq!(reference.nodes.len(), n, "The reference graph must have the same number of nodes");
+        let mut edges = vec![];
+        for i in 0..n {
+            for j in i..n {
+                let val = reference.get((i, j));
+                if val >= 2 {edges.push(((i, j), val))};
+            }
+        }
+        self.edit_distances.push(EditDistance::new(&edges, max));
+    }
+
+    /// Returns the number of decided edges that differ from a reference graph,
+    /// and the number of empty edges.
+    pub fn edit_distance_count(&self, d: &EditDistance) -> (usize, usize) {
+        let n = self.nodes.len();
+        let mut count = 0;
+        let mut empty = 0;
+        for i in 0..n {
+            for j in i..n {
+                let val = self.get((i, j));
+                if val == 0 {
+                    if i != j || self.nodes[i].self_connected {empty += 1};
+                } else if val.max(1) != d.get((i, j)) {
+                    count += 1;
+                }
+            }
+        }
+        (count, empty)
+    }
+
+    /// Returns `true` if all edit distance constraints are satisfied.
+    ///
+    /// Empty edges are treated as no edge.
+    pub fn edit_distances_satisfied(&self) -> bool {
+        self.edit_distances.iter().all(|d| {
+            let n = self.nodes.len();
+            let count = (0..n).flat_map(|i| (i..n).map(move |j| (i, j)))
+                .filter(|&pos| self.get(pos).max(1) != d.get(pos)).count();
+            count <= d.max
+        })
+    }
+
+    /// Returns `true` if all edit distance constraints can still be satisfied.
+    pub fn edit_distances_feasible(&self) -> bool {
+        self.edit_distances.iter().all(|d| self.edit_distance_count(d).0 <= d.max)
+    }
+
+    /// Finds the solution that differs from a reference graph in the fewest edges.
+    ///
+    /// The reference graph can be invalid, e.g. a hand-drawn graph with small errors,
+    /// and must have the same number of nodes.
+    /// Only edges of the reference graph are compared, see `Graph::push_edit_distance`.
+    ///
+    /// Finds any solution first, and then does a binary search on the edit distance bound,
+    /// up to the distance of the closest solution so far.
+    /// Returns `None` if there is no solution, or if a search was stopped,
+    /// e.g. by `SearchSettings::max_iterations`, since the result might not be closest.
+    ///
+    /// The repaired graph is always complete, also when `SearchSettings::difference` is set.
+    ///
+    /// Panics if the reference graph has another number of nodes.
+    pub fn repair(&self, reference: &Graph, solve_settings: SearchSettings) -> Option<Repair> {
+        let n = self.nodes.len();
+        assert_eq!(reference.nodes.len(), n, "The reference graph must have the same number of nodes");
+        let repair = |graph: Graph| -> Repair {
+            let changes = (0..n).flat_map(|i| (i..n).map(move |j| (i, j)))
+                .map(|pos| (pos, reference.get(pos).max(1), graph.get(pos).max(1)))
+                .filter(|&(_, old, new)| old != new).collect();
+            Repair {graph, changes}
+        };
+        let stopped = |solutions: &Solutions| {
+            solutions.cancelled() || solutions.exhausted() || solutions.timed_out()
+        };
+        // The changes are computed from complete graphs.
+        let solve_settings = solve_settings.difference(false);
+
+        let mut best = repair(self.clone().solve_all(solve_settings.clone()).next()?.puzzle);
+        // There is no solution closer than `min` changes.
+        let mut min = 0;
+        while min < best.changes.len() {
+            let max = (min + best.changes.len() - 1) / 2;
+            let mut g = self.clone();
+            g.push_edit_distance(reference, max);
+            let mut solutions = g.solve_all(solve_settings.clone());
+            if let Some(solution) = solutions.next() {
+                let mut graph = solution.puzzle;
+                graph.edit_distances.pop();
+                // The solution can be closer than the bound.
+                best = repair(graph);
+            } else if stopped(&solutions) {
+                return None;
+            } else {
+                min = max + 1;
+            }
+        }
+        Some(best)
+    }
+}
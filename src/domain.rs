@@ -0,0 +1,115 @@
+//! Edge values drawn from user-defined domains.
+//!
+//! The solver stores edge values as colors.
+//! An edge domain assigns colors to richer values, such as tuples or enums,
+//! such that specifications and solutions can be written in terms of the values.
+
+use super::*;
+
+/// Implemented by values of edges.
+///
+/// Values are ordered, which decides the order of their colors.
+pub trait EdgeValue: Clone + Ord {
+    /// Returns the value with opposite sign, if any.
+    ///
+    /// A value and its opposite get a pair of colors `2k` and `2k + 1`,
+    /// which anticommute, see `Graph::commute_quad`.
+    fn flipped(&self) -> Option<Self> {None}
+    /// Renders the value for output.
+    fn render(&self) -> String;
+}
+
+impl EdgeValue for Color {
+    fn flipped(&self) -> Option<Color> {Some(self ^ 1)}
+    fn render(&self) -> String {self.to_string()}
+}
+
+/// Assigns colors to a finite set of edge values.
+///
+/// Colors start at `2`, since `0` is empty and `1` is no-edge.
+/// Values are assigned in order, where opposite values are assigned a pair of colors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdgeDomain<T: EdgeValue> {
+    /// The value of every color, starting at color `2`.
+    slots: Vec<Option<T>>,
+}
+
+impl<T: EdgeValue> EdgeDomain<T> {
+    /// Creates a new edge domain from a set of values.
+    pub fn new<I: IntoIterator<Item = T>>(values: I) -> EdgeDomain<T> {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.sort();
+        values.dedup();
+        let mut slots = vec![];
+        let mut assigned = vec![false; values.len()];
+        for k in 0..values.len() {
+            if assigned[k] {continue};
+            assigned[k] = true;
+            slots.push(Some(values[k].clone()));
+            let opposite = values[k].flipped()
+                .and_then(|w| values.binary_search(&w).ok())
+                .filter(|&l| !assigned[l]);
+            if let Some(l) = opposite {assigned[l] = true};
+            slots.push(opposite.map(|l| values[l].clone()));
+        }
+        EdgeDomain {slots}
+    }
+
+    /// Returns the color of a value, if it is in the domain.
+    pub fn color(&self, value: &T) -> Option<Color> {
+        self.slots.iter().position(|v| v.as_ref() == Some(value)).map(|k| k as Color + 2)
+    }
+
+    /// Returns the value of a color, if any.
+    pub fn value(&self, color: Color) -> Option<&T> {
+        if color < 2 {return None};
+        self.slots.get((color - 2) as usize).and_then(|v| v.as_ref())
+    }
+
+    /// Returns the colors of all values, in order of colors.
+    pub fn colors(&self) -> Vec<Color> {
+        (0..self.slots.len()).filter(|&k| self.slots[k].is_some()).map(|k| k as Color + 2).collect()
+    }
+
+    /// Returns an edge constraint with a value to a node color.
+    ///
+    /// Panics if the value is not in the domain.
+    pub fn constraint(&self, edge: &T, node: Color) -> Constraint {
+        Constraint {edge: self.color(edge).expect("Expected value in domain"), node}
+    }
+
+    /// Returns the value of an edge, if it is colored.
+    pub fn get(&self, g: &Graph, pos: (usize, usize)) -> Option<&T> {
+        self.value(g.get(pos))
+    }
+
+    /// Sets the value of an edge.
+    ///
+    /// Panics if the value is not in the domain.
+    pub fn set(&self, g: &mut Graph, pos: (usize, usize), value: &T) {
+        g.set(pos, self.color(value).expect("Expected value in domain"));
+    }
+
+    /// Returns the colored edges with their values, using sorted indices.
+    pub fn edges<'a>(&'a self, g: &Graph) -> Vec<((usize, usize), &'a T)> {
+        let n = g.nodes.len();
+        let mut res = vec![];
+        for i in 0..n {
+            for j in i..n {
+                if let Some(value) = self.get(g, (i, j)) {res.push(((i, j), value))};
+            }
+        }
+        res
+    }
+
+    /// Renders the value of a color.
+    ///
+    /// Empty edges are rendered as `_`, and no-edges as `-`.
+    pub fn render(&self, color: Color) -> String {
+        match color {
+            0 => "_".into(),
+            1 => "-".into(),
+            _ => self.value(color).map(|v| v.render()).unwrap_or_else(|| format!("#{}", color)),
+        }
+    }
+}
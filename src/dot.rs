@@ -0,0 +1,380 @@
+//! Importing graphs from GraphViz DOT format.
+
+use std::fmt;
+
+use super::*;
+
+/// The version of the DOT format generated by `Graph::graphviz`.
+///
+/// The version is written in a comment `// graph_solver dot <version>` on the first line.
+pub const DOT_VERSION: u32 = 1;
+
+const DOT_HEADER: &str = "// graph_solver dot";
+
+/// The maximum nesting depth of blocks in parsed text.
+///
+/// Deeper nesting is reported as a parse error instead of overflowing the stack.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// Returns the error for nesting deeper than `MAX_DEPTH`.
+pub(crate) fn too_deep(line: usize) -> ParseError {
+    ParseError {line, message: format!("Nesting is deeper than {} levels", MAX_DEPTH)}
+}
+
+/// Stores an error from parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// The error message.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        write!(w, "Line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Id(String),
+    Symbol(&'static str),
+}
+
+fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut res = vec![];
+    let mut line = 1;
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '\n' => {line += 1; chars.next();}
+            c if c.is_whitespace() => {chars.next();}
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' => {
+                chars.next();
+                let sym = match c {
+                    '{' => "{", '}' => "}", '[' => "[", ']' => "]",
+                    '=' => "=", ';' => ";", _ => ",",
+                };
+                res.push((Token::Symbol(sym), line));
+            }
+            '-' => {
+                chars.next();
+                match chars.next() {
+                    Some('-') => res.push((Token::Symbol("--"), line)),
+                    Some('>') => return Err(ParseError {
+                        line, message: "Directed edges are not supported".into()
+                    }),
+                    Some(c) if c.is_ascii_digit() => {
+                        let mut id = format!("-{}", c);
+                        while let Some(&c) = chars.peek() {
+                            if !(c.is_ascii_digit() || c == '.') {break};
+                            id.push(c);
+                            chars.next();
+                        }
+                        res.push((Token::Id(id), line));
+                    }
+                    _ => return Err(ParseError {line, message: "Expected `--`".into()}),
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err(ParseError {line, message: "Unterminated string".into()}),
+                        Some('"') => break,
+                        Some('\\') => if let Some(c) = chars.next() {id.push(c)},
+                        Some(c) => {
+                            if c == '\n' {line += 1};
+                            id.push(c);
+                        }
+                    }
+                }
+                res.push((Token::Id(id), line));
+            }
+            '/' | '#' => {
+                // Skip comments.
+                chars.next();
+                if c == '/' && chars.peek() == Some(&'*') {
+                    chars.next();
+                    let mut prev = ' ';
+                    for c in chars.by_ref() {
+                        if c == '\n' {line += 1};
+                        if prev == '*' && c == '/' {break};
+                        prev = c;
+                    }
+                } else {
+                    for c in chars.by_ref() {
+                        if c == '\n' {line += 1; break};
+                    }
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut id = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.') {break};
+                    id.push(c);
+                    chars.next();
+                }
+                res.push((Token::Id(id), line));
+            }
+            c => return Err(ParseError {line, message: format!("Unexpected character `{}`", c)}),
+        }
+    }
+    Ok(res)
+}
+
+/// Maps color names to colors.
+struct Palette<'a> {
+    names: Vec<String>,
+    fixed: Option<&'a [&'a str]>,
+    offset: Color,
+}
+
+impl<'a> Palette<'a> {
+    fn color(&mut self, name: &str, line: usize) -> Result<Color, ParseError> {
+        if let Some(fixed) = self.fixed {
+            return match fixed.iter().position(|&s| s == name) {
+                Some(k) => Ok(k as Color + self.offset),
+                None => Err(ParseError {line, message: format!("Unknown color `{}`", name)}),
+            };
+        }
+        let k = match self.names.iter().position(|s| s == name) {
+            Some(k) => k,
+            None => {
+                self.names.push(name.into());
+                self.names.len() - 1
+            }
+        };
+        Ok(k as Color + self.offset)
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    ids: Vec<String>,
+    node_colors: Vec<Option<(String, usize)>>,
+    edges: Vec<(usize, usize, Option<String>, usize)>,
+    node_palette: Palette<'a>,
+    edge_palette: Palette<'a>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).or_else(|| self.tokens.last()).map(|t| t.1).unwrap_or(1)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {line: self.line(), message: message.into()})
+    }
+
+    fn peek(&self) -> Option<&Token> {self.tokens.get(self.pos).map(|t| &t.0)}
+
+    fn symbol(&mut self, sym: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == sym) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn id(&mut self) -> Option<String> {
+        if let Some(Token::Id(id)) = self.peek() {
+            let id = id.clone();
+            self.pos += 1;
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    fn expect_id(&mut self) -> Result<String, ParseError> {
+        match self.id() {
+            Some(id) => Ok(id),
+            None => self.error("Expected identifier"),
+        }
+    }
+
+    fn node(&mut self, id: &str) -> usize {
+        match self.ids.iter().position(|s| s == id) {
+            Some(k) => k,
+            None => {
+                self.ids.push(id.into());
+                self.node_colors.push(None);
+                self.ids.len() - 1
+            }
+        }
+    }
+
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, ParseError> {
+        let mut res = vec![];
+        while self.symbol("[") {
+            while !self.symbol("]") {
+                let key = self.expect_id()?;
+                if !self.symbol("=") {return self.error("Expected `=`")};
+                let value = self.expect_id()?;
+                res.push((key, value));
+                if !self.symbol(",") {self.symbol(";");}
+            }
+        }
+        Ok(res)
+    }
+
+    /// Parses the statements of a nested block.
+    fn block(&mut self) -> Result<(), ParseError> {
+        if self.depth >= MAX_DEPTH {return Err(too_deep(self.line()))};
+        self.depth += 1;
+        self.statements()?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn statements(&mut self) -> Result<(), ParseError> {
+        loop {
+            if self.symbol("}") {return Ok(())};
+            if self.symbol(";") {continue};
+            if self.symbol("{") {
+                self.block()?;
+                continue;
+            }
+            let line = self.line();
+            let first = match self.id() {
+                Some(id) => id,
+                None => return self.error("Expected statement or `}`"),
+            };
+            if first == "subgraph" {
+                self.id();
+                if !self.symbol("{") {return self.error("Expected `{`")};
+                self.block()?;
+                continue;
+            }
+            if self.symbol("=") {
+                // Graph attribute.
+                self.expect_id()?;
+                continue;
+            }
+            if first == "graph" || first == "node" || first == "edge" {
+                self.attributes()?;
+                continue;
+            }
+            let mut chain = vec![self.node(&first)];
+            while self.symbol("--") {
+                let id = self.expect_id()?;
+                chain.push(self.node(&id));
+            }
+            let attrs = self.attributes()?;
+            let get = |key: &str| attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+            if chain.len() == 1 {
+                if let Some(color) = get("fillcolor").or_else(|| get("color")) {
+                    self.node_colors[chain[0]] = Some((color, line));
+                }
+            } else {
+                for w in chain.windows(2) {
+                    self.edges.push((w[0], w[1], get("color"), line));
+                }
+            }
+        }
+    }
+}
+
+impl Graph {
+    /// Parses a graph from a subset of the GraphViz DOT format.
+    ///
+    /// Node fill colors and edge colors are mapped to colors by order of first appearance,
+    /// starting at `0` for nodes and `2` for edges.
+    /// Edges that are not listed are undecided,
+    /// such that the graph can be used as a partial solution.
+    /// Nodes have no edge constraints.
+    ///
+    /// When the first line is a version comment, the version is at most `DOT_VERSION`.
+    /// Files without version comment are read as the current version.
+    /// Blocks nested deeper than 64 levels are reported as errors.
+    pub fn from_dot(dot: &str) -> Result<Graph, ParseError> {
+        Graph::parse_dot(dot, None, None)
+    }
+
+    /// Parses a graph from a subset of the GraphViz DOT format, using color palettes.
+    ///
+    /// This is the inverse of `Graph::graphviz` with the same palettes.
+    /// For more information, see `Graph::from_dot`.
+    pub fn from_dot_with_colors(
+        dot: &str,
+        node_colors: &[&str],
+        edge_colors: &[&str]
+    ) -> Result<Graph, ParseError> {
+        Graph::parse_dot(dot, Some(node_colors), Some(edge_colors))
+    }
+
+    fn parse_dot<'a>(
+        dot: &str,
+        node_colors: Option<&'a [&'a str]>,
+        edge_colors: Option<&'a [&'a str]>
+    ) -> Result<Graph, ParseError> {
+        dot_version(dot)?;
+        let mut parser = Parser {
+            tokens: tokenize(dot)?,
+            pos: 0,
+            ids: vec![],
+            node_colors: vec![],
+            edges: vec![],
+            node_palette: Palette {names: vec![], fixed: node_colors, offset: 0},
+            edge_palette: Palette {names: vec![], fixed: edge_colors, offset: 2},
+            depth: 0,
+        };
+        if parser.peek() == Some(&Token::Id("strict".into())) {parser.pos += 1};
+        match parser.id().as_deref() {
+            Some("graph") => {}
+            Some("digraph") => return parser.error("Directed graphs are not supported"),
+            _ => return parser.error("Expected `graph`"),
+        }
+        if parser.peek() != Some(&Token::Symbol("{")) {parser.id();}
+        if !parser.symbol("{") {return parser.error("Expected `{`")};
+        parser.statements()?;
+        if parser.pos < parser.tokens.len() {return parser.error("Expected end of file")};
+
+        let mut g = Graph::new();
+        let n = parser.ids.len();
+        let mut self_connected = vec![false; n];
+        for &(a, b, _, _) in &parser.edges {
+            if a == b {self_connected[a] = true};
+        }
+        for (i, name) in parser.node_colors.clone().iter().enumerate() {
+            let color = match name {
+                Some((name, line)) => parser.node_palette.color(name, *line)?,
+                None => 0,
+            };
+            g.push(Node {color, self_connected: self_connected[i], edges: vec![]});
+        }
+        for (a, b, name, line) in parser.edges.clone() {
+            let color = match name {
+                Some(name) => parser.edge_palette.color(&name, line)?,
+                None => 2,
+            };
+            g.set((a, b), color);
+        }
+        Ok(g)
+    }
+}
+
+/// Returns the version of the DOT format from the comment on the first line.
+///
+/// Files without version comment have version `0`,
+/// which is the same format as version `1`.
+fn dot_version(dot: &str) -> Result<u32, ParseError> {
+    let (line, text) = match dot.lines().enumerate().find(|(_, line)| !line.trim().is_empty()) {
+        Some((k, text)) => (k + 1, text.trim()),
+        None => return Ok(0),
+    };
+    match text.strip_prefix(DOT_HEADER).map(|v| v.trim().parse::<u32>()) {
+        None => Ok(0),
+        Some(Ok(version)) if version <= DOT_VERSION => Ok(version),
+        Some(Ok(version)) => Err(ParseError {line, message: format!("Unsupported format version {}", version)}),
+        Some(Err(_)) => Err(ParseError {line, message: "Expected format version".into()}),
+    }
+}
@@ -0,0 +1,88 @@
+//! Editing nodes of specifications and solved graphs.
+
+use super::*;
+
+impl Graph {
+    /// Adds a copy of a node, returning the index of the new node.
+    ///
+    /// Copies the node description, label, degree constraints and membership of `Graph::quad_nodes`.
+    /// When `edges` is `true`, the assigned edges of the node are copied
+    /// to the same neighbors, where a self-loop is copied to a self-loop of the new node.
+    /// The edge between the node and its copy is left empty.
+    ///
+    /// This is useful for growing a solved graph by one node,
+    /// followed by solving the region around the new node.
+    pub fn duplicate_node(&mut self, i: usize, edges: bool) -> usize {
+        let k = self.nodes.len();
+        self.push(self.nodes[i].clone());
+        self.labels[k] = self.labels[i];
+        let degrees: Vec<DegreeConstraint> = self.degrees.iter()
+            .filter(|d| d.node == i)
+            .map(|d| DegreeConstraint {node: k, ..*d})
+            .collect();
+        self.degrees.extend(degrees);
+        if let Some(nodes) = &mut self.quad_nodes {
+            if nodes.binary_search(&i).is_ok() {nodes.push(k)};
+        }
+        if edges {
+            for j in 0..k {
+                let val = self.get((i, j));
+                if val == 0 {continue};
+                if i == j {self.set((k, k), val)} else {self.set((k, j), val)}
+            }
+        }
+        k
+    }
+
+    /// Replaces the description of a node in place.
+    ///
+    /// Invalidates the caches that depend on the node description,
+    /// and returns the colored edges that became inconsistent, using sorted indices.
+    /// An edge is inconsistent when it does not match an edge constraint
+    /// of either of its nodes.
+    ///
+    /// This supports editing a solved graph and checking the result before re-solving.
+    pub fn replace_node(&mut self, i: usize, node: Node) -> Vec<(usize, usize)> {
+        let color_changed = self.nodes[i].color != node.color;
+        self.nodes[i] = node;
+        self.cache_node_satisfied[i].set(false);
+        let n = self.nodes.len();
+        let neighbors: Vec<usize> = (0..n).filter(|&j| j != i && self.get((i, j)) >= 2).collect();
+        if color_changed {
+            for &j in &neighbors {self.cache_node_satisfied[j].set(false)}
+            self.cache_subgraphs_feasible.set(None);
+        }
+        self.symmetry_groups.clear();
+        self.color_groups.clear();
+        self.initial_domains.clear();
+        self.cache_symmetry_feasible.set(None);
+        self.cache_component_sizes_feasible.set(None);
+        self.cache_isolated_nodes_feasible.set(None);
+
+        let mut res: Vec<(usize, usize)> = self.unmatched_neighbors(i).into_iter()
+            .map(|j| (i.min(j), i.max(j))).collect();
+        if color_changed {
+            for &j in &neighbors {
+                if self.unmatched_neighbors(j).contains(&i) {res.push((i.min(j), i.max(j)))}
+            }
+        }
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    /// Returns the neighbors along colored edges that do not match an edge constraint of a node.
+    fn unmatched_neighbors(&self, i: usize) -> Vec<usize> {
+        let mut res = vec![];
+        let mut m = vec![false; self.nodes[i].edges.len()];
+        for j in 0..self.nodes.len() {
+            let edge = self.get((i, j));
+            if edge < 2 {continue};
+            match self.match_slot(i, j, edge, &m) {
+                Some(k) => m[k] = true,
+                None => res.push(j),
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,178 @@
+//! Explanations of pruned edge colors.
+
+use super::*;
+
+impl Graph {
+    /// Explains why a color is not a possible value of an edge in the current state.
+    ///
+    /// Returns `None` when the color is possible, see `Graph::colors`.
+    /// Otherwise, returns the first constraint or rule that eliminates the color,
+    /// checked in the same order as `Graph::colors`.
+    pub fn why_not(&self, pos: (usize, usize), color: Color) -> Option<String> {
+        self.prune_reason(pos, color).map(|(_, msg)| msg)
+    }
+
+    /// Returns the name of the constraint that eliminates a color, and an explanation.
+    ///
+    /// Names match the fields of `Graph`, where constraints stored in lists
+    /// are named by index, e.g. `cardinalities[0]`.
+    /// Edge constraints of nodes are named `nodes`.
+    pub(crate) fn prune_reason(&self, (i, j): (usize, usize), color: Color) -> Option<(String, String)> {
+        let reason = |name: &str, msg: String| Some((name.to_string(), msg));
+        if color == 0 {return reason("empty", "The empty value can not be assigned".into())};
+        let val = self.get((i, j));
+        if val != 0 {return reason("assigned", format!("The edge is already assigned to {}", val))};
+        if !self.nodes[i].self_connected && i == j {
+            return reason("nodes", format!("Node {} can not be self-connected", self.node_name(i)));
+        }
+        let global = [
+            (self.no_triangles && self.has_triangles(), "no_triangles", "The graph has a triangle"),
+            (!self.min_girth_satisfied(), "min_girth", "The graph has a shorter cycle"),
+            (!self.planar_satisfied(), "planar", "The graph is not planar"),
+            (self.bipartite && self.has_odd_cycle(), "bipartite", "The graph has an odd cycle"),
+            (self.connected && !self.possibly_connected(), "connected", "The graph can no longer be connected"),
+            (self.no_isolated_nodes && !self.isolated_nodes_feasible(),
+                "no_isolated_nodes", "Some node can no longer get an edge"),
+            (!self.quads_satisfied(), "commute_quad", "Some quad violates the commute property"),
+        ];
+        if let Some(&(_, name, msg)) = global.iter().find(|(pruned, _, _)| *pruned) {
+            return reason(name, format!("`{}`: {}", name, msg));
+        }
+        for (k, card) in self.cardinalities.iter().enumerate() {
+            let (colored, empty) = self.cardinality_count(card);
+            if colored > card.max || colored + empty < card.min {
+                return reason(&format!("cardinalities[{}]", k),
+                    "`cardinalities`: Some cardinality can no longer be satisfied".into());
+            }
+        }
+        for (k, deg) in self.degrees.iter().enumerate() {
+            let (colored, empty) = self.degree_count(deg.node);
+            if colored > deg.max || colored + empty < deg.min {
+                return reason(&format!("degrees[{}]", k), "`degrees`: Some degree can no longer be satisfied".into());
+            }
+        }
+        for (k, cc) in self.color_counts.iter().enumerate() {
+            let (count, empty) = self.color_count(cc.color);
+            if count > cc.max || count + empty < cc.min {
+                return reason(&format!("color_counts[{}]", k),
+                    "`color_counts`: Some color count can no longer be satisfied".into());
+            }
+        }
+        let global = [
+            (!self.component_sizes_feasible(),
+                "component_constraints", "Some component size can no longer be satisfied"),
+            (!self.symmetric_differences_feasible(),
+                "symmetric_differences", "Some symmetric difference can no longer be satisfied"),
+            (!self.required_subgraphs_feasible(),
+                "required_subgraphs", "Some required subgraph can no longer be embedded"),
+            (!self.symmetry_break_feasible(), "symmetry_break", "The assignment is not in canonical order"),
+        ];
+        if let Some(&(_, name, msg)) = global.iter().find(|(pruned, _, _)| *pruned) {
+            return reason(name, format!("`{}`: {}", name, msg));
+        }
+        if let Some(allowed) = self.allowed((i, j)) {
+            if !allowed.contains(&color) {
+                return reason("restrictions", format!("`restrictions`: The edge is restricted to {:?}", allowed));
+            }
+        }
+        for (k, d) in self.edit_distances.iter().enumerate() {
+            let count = self.edit_distance_count(d).0;
+            if count > d.max {
+                return reason(&format!("edit_distances[{}]", k),
+                    "`edit_distances`: Too many edges differ from the reference graph".into());
+            } else if count == d.max && d.get((i, j)) != color {
+                return reason(&format!("edit_distances[{}]", k),
+                    format!("`edit_distances`: At most {} edges can differ from the reference graph", d.max));
+            }
+        }
+
+        if color == 1 {
+            for (k, card) in self.cardinalities.iter().enumerate() {
+                if card.edges.binary_search(&(i.min(j), i.max(j))).is_err() {continue};
+                let (colored, empty) = self.cardinality_count(card);
+                if colored < card.max && colored + empty <= card.min {
+                    return reason(&format!("cardinalities[{}]", k),
+                        format!("`cardinalities`: At least {} edges of {:?} must be colored", card.min, card.edges));
+                }
+            }
+            for (k, deg) in self.degrees.iter().enumerate() {
+                if deg.node != i && deg.node != j {continue};
+                let (colored, empty) = self.degree_count(deg.node);
+                if colored < deg.max && colored + empty <= deg.min {
+                    return reason(&format!("degrees[{}]", k),
+                        format!("`degrees`: Node {} must have at least {} colored edges",
+                               self.node_name(deg.node), deg.min));
+                }
+            }
+            for (k, cc) in self.color_counts.iter().enumerate() {
+                let (count, empty) = self.color_count(cc.color);
+                if count < cc.max && count + empty <= cc.min {
+                    return reason(&format!("color_counts[{}]", k),
+                        format!("`color_counts`: Every empty edge must have color {}", cc.color));
+                }
+            }
+            return None;
+        }
+
+        for &(a, b) in &[(i, j), (j, i)] {
+            let target = self.nodes[b].color;
+            if !self.node_satisfied(a).iter().any(|con| con.edge == color && self.matches(*con, a, b)) {
+                return reason("nodes", format!("Node {} has no unsatisfied edge constraint \
+                                                with edge color {} to node color {}",
+                                               self.node_name(a), color, target));
+            }
+        }
+        for (k, card) in self.cardinalities.iter().enumerate() {
+            if card.edges.binary_search(&(i.min(j), i.max(j))).is_err() {continue};
+            if self.cardinality_count(card).0 >= card.max {
+                return reason(&format!("cardinalities[{}]", k),
+                    format!("`cardinalities`: At most {} edges of {:?} can be colored", card.max, card.edges));
+            }
+        }
+        for (k, deg) in self.degrees.iter().enumerate() {
+            if deg.node != i && deg.node != j {continue};
+            if self.degree_count(deg.node).0 >= deg.max {
+                return reason(&format!("degrees[{}]", k),
+                    format!("`degrees`: Node {} can have at most {} colored edges",
+                           self.node_name(deg.node), deg.max));
+            }
+        }
+        for (k, cc) in self.color_counts.iter().enumerate() {
+            let (count, empty) = self.color_count(cc.color);
+            if count >= cc.max && cc.color == color {
+                return reason(&format!("color_counts[{}]", k),
+                    format!("`color_counts`: At most {} edges can have color {}", cc.max, color));
+            } else if count < cc.max && count + empty <= cc.min && cc.color != color {
+                return reason(&format!("color_counts[{}]", k),
+                    format!("`color_counts`: Every empty edge must have color {}", cc.color));
+            }
+        }
+        if self.anti_pairs.contains(&(i.min(j), i.max(j))) {
+            return reason("anti_pairs", "`anti_pairs`: The edge is forbidden".into());
+        }
+        if self.bipartition_forbids((i, j)) {
+            return reason("bipartition", "`bipartition`: The nodes are on the same side".into());
+        }
+        if self.bipartite && self.same_side(i, j) {
+            return reason("bipartite", "`bipartite`: The nodes are on the same side".into());
+        }
+        if !self.labels_allow((i, j), color) {
+            return reason("labels", format!("`labels`: The labels of the nodes do not allow color {}", color));
+        }
+        if let Some((_, max)) = self.component_size_bounds() {
+            if !self.same_component(i, j) &&
+               self.component_size(i) + self.component_size(j) > max
+            {
+                return reason("component_constraints", format!("`component_constraints`: The joined component \
+                                                                would have more than {} nodes", max));
+            }
+        }
+        if let Some(min_girth) = self.min_girth {
+            if i != j && self.path_shorter_than((i, j), min_girth.saturating_sub(1)).is_some() {
+                return reason("min_girth",
+                    format!("`min_girth`: The edge would close a cycle shorter than {}", min_girth));
+            }
+        }
+        None
+    }
+}
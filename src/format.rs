@@ -0,0 +1,58 @@
+//! Loading puzzles from files by detecting the format.
+
+use std::io;
+use std::path::Path;
+
+use super::*;
+
+/// The text formats that puzzles can be loaded from.
+///
+/// Every format is versioned, see `DOT_VERSION`, `JSON_VERSION` and `SPEC_VERSION`.
+/// Every version of the crate reads all earlier versions of every format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// GraphViz DOT format, see `Graph::from_dot`.
+    Dot,
+    /// JSON format, see `Graph::from_json`.
+    Json,
+    /// Canonical text format, see `Graph::from_spec_string`.
+    Spec,
+}
+
+impl Format {
+    /// Detects the format of a text, if any.
+    ///
+    /// Leading empty lines and lines starting with `#` are skipped.
+    pub fn detect(text: &str) -> Option<Format> {
+        let line = text.lines().map(|line| line.trim())
+            .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+        if line.starts_with("graph_solver spec") {Some(Format::Spec)}
+        else if line.starts_with('{') {Some(Format::Json)}
+        else if ["//", "/*", "strict", "graph"].iter().any(|s| line.starts_with(s)) {Some(Format::Dot)}
+        else {None}
+    }
+}
+
+impl Graph {
+    /// Parses a puzzle in any supported format, detecting the format.
+    ///
+    /// Earlier versions of every format can be read.
+    /// For more information, see `Format`.
+    pub fn from_text(text: &str) -> Result<Graph, ParseError> {
+        match Format::detect(text) {
+            Some(Format::Dot) => Graph::from_dot(text),
+            Some(Format::Json) => Graph::from_json(text),
+            Some(Format::Spec) => Graph::from_spec_string(text),
+            None => Err(ParseError {line: 1, message: "Unknown format".into()}),
+        }
+    }
+
+    /// Loads a puzzle from a file in any supported format.
+    ///
+    /// Parse errors are reported as `io::ErrorKind::InvalidData`.
+    /// For more information, see `Graph::from_text`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Graph> {
+        let text = std::fs::read_to_string(path)?;
+        Graph::from_text(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
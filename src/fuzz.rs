@@ -0,0 +1,276 @@
+//! Randomized mutations of specifications for checking solver invariants.
+//!
+//! A `Fuzzer` mutates a specification by perturbing constraints,
+//! toggling flags and pinning edges, solves the mutated specification,
+//! and checks every returned solution independently and against plain backtracking.
+//!
+//! From a cargo-fuzz target, use `Fuzzer::from_bytes`:
+//!
+//! ```ignore
+//! fuzz_target!(|data: &[u8]| {
+//!     Fuzzer::from_bytes(spec(), data).run().unwrap();
+//! });
+//! ```
+
+use std::fmt;
+
+use super::*;
+use crate::restart::Rng;
+
+/// A mutation of a specification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Toggles `no_triangles`.
+    NoTriangles,
+    /// Toggles `meet_quad`.
+    MeetQuad,
+    /// Toggles `connected`.
+    Connected,
+    /// Toggles `no_isolated_nodes`.
+    NoIsolatedNodes,
+    /// Toggles `planar`.
+    Planar,
+    /// Toggles `bipartite`.
+    Bipartite,
+    /// Sets `commute_quad`.
+    CommuteQuad(Option<bool>),
+    /// Pins an edge to a value.
+    Pin((usize, usize), Color),
+    /// Removes an edge constraint of a node.
+    RemoveConstraint {
+        /// The node.
+        node: usize,
+        /// The index of the edge constraint.
+        index: usize,
+    },
+    /// Adds an edge constraint to a node.
+    AddConstraint {
+        /// The node.
+        node: usize,
+        /// The edge constraint.
+        constraint: Constraint,
+    },
+    /// Changes the color of a node.
+    NodeColor {
+        /// The node.
+        node: usize,
+        /// The new color.
+        color: Color,
+    },
+}
+
+impl Mutation {
+    /// Applies the mutation to a specification.
+    pub fn apply(&self, g: &mut Graph) {
+        match *self {
+            Mutation::NoTriangles => g.no_triangles = !g.no_triangles,
+            Mutation::MeetQuad => g.meet_quad = !g.meet_quad,
+            Mutation::Connected => g.connected = !g.connected,
+            Mutation::NoIsolatedNodes => g.no_isolated_nodes = !g.no_isolated_nodes,
+            Mutation::Planar => g.planar = !g.planar,
+            Mutation::Bipartite => g.bipartite = !g.bipartite,
+            Mutation::CommuteQuad(val) => g.commute_quad = val,
+            Mutation::Pin(pos, val) => g.set(pos, val),
+            Mutation::RemoveConstraint {node, index} => {
+                g.nodes[node].edges.remove(index);
+            }
+            Mutation::AddConstraint {node, constraint} => g.nodes[node].edges.push(constraint),
+            Mutation::NodeColor {node, color} => g.nodes[node].color = color,
+        }
+    }
+}
+
+/// Stores a violation of a solver invariant.
+#[derive(Clone, Debug)]
+pub struct FuzzFailure {
+    /// The mutations applied to the original specification.
+    pub mutations: Vec<Mutation>,
+    /// The mutated specification.
+    pub spec: Box<Graph>,
+    /// The offending solution.
+    pub solution: Box<Graph>,
+    /// Describes the violated invariant.
+    pub message: String,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        write!(w, "{} (mutations: {:?})", self.message, self.mutations)
+    }
+}
+
+impl std::error::Error for FuzzFailure {}
+
+/// Generates randomized mutations of a specification and checks solver invariants.
+pub struct Fuzzer {
+    /// The original specification.
+    pub spec: Graph,
+    /// The solve settings used for mutated specifications.
+    ///
+    /// Use `max_iterations` to bound the time spent per specification.
+    pub settings: SearchSettings,
+    /// The maximum number of mutations per specification.
+    pub max_mutations: usize,
+    /// The maximum number of solutions to check per specification.
+    pub max_solutions: usize,
+    rng: Rng,
+}
+
+impl Fuzzer {
+    /// Creates a new fuzzer from a specification and a random seed.
+    ///
+    /// Default settings:
+    ///
+    /// - settings: `SearchSettings::new().max_iterations(10_000)`
+    /// - max_mutations: `3`
+    /// - max_solutions: `10`
+    pub fn new(spec: Graph, seed: u64) -> Fuzzer {
+        Fuzzer {
+            spec,
+            settings: SearchSettings::new().max_iterations(10_000),
+            max_mutations: 3,
+            max_solutions: 10,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Creates a new fuzzer seeded from fuzzing input.
+    pub fn from_bytes(spec: Graph, data: &[u8]) -> Fuzzer {
+        // FNV-1a.
+        let mut seed: u64 = 0xcbf2_9ce4_8422_2325;
+        for &b in data {
+            seed ^= b as u64;
+            seed = seed.wrapping_mul(0x0100_0000_01b3);
+        }
+        Fuzzer::new(spec, seed)
+    }
+
+    fn below(&mut self, n: usize) -> usize {self.rng.below(n)}
+
+    /// Generates a random mutation of a specification.
+    pub fn mutation(&mut self, g: &Graph) -> Mutation {
+        let n = g.nodes.len();
+        // Colors that occur in the specification, plus one new color.
+        let mut node_colors: Vec<Color> = g.nodes.iter().map(|node| node.color).collect();
+        let mut edge_colors: Vec<Color> = g.nodes.iter()
+            .flat_map(|node| node.edges.iter().map(|c| c.edge)).collect();
+        node_colors.push(node_colors.iter().cloned().max().map(|c| c + 1).unwrap_or(0));
+        edge_colors.push(edge_colors.iter().cloned().max().map(|c| c + 1).unwrap_or(2));
+        loop {
+            let kind = self.below(10);
+            if kind >= 6 && n == 0 {continue};
+            return match kind {
+                0 => Mutation::NoTriangles,
+                1 => Mutation::MeetQuad,
+                2 => Mutation::Connected,
+                3 => Mutation::NoIsolatedNodes,
+                4 => [Mutation::Planar, Mutation::Bipartite][self.below(2)].clone(),
+                5 => Mutation::CommuteQuad([None, Some(true), Some(false)][self.below(3)]),
+                6 => {
+                    let (i, j) = (self.below(n), self.below(n));
+                    let val = if self.below(2) == 0 {1}
+                        else {edge_colors[self.below(edge_colors.len())]};
+                    Mutation::Pin((i.min(j), i.max(j)), val)
+                }
+                7 => {
+                    let node = self.below(n);
+                    if g.nodes[node].edges.is_empty() {continue};
+                    Mutation::RemoveConstraint {node, index: self.below(g.nodes[node].edges.len())}
+                }
+                8 => {
+                    let node = self.below(n);
+                    let constraint = Constraint {
+                        edge: edge_colors[self.below(edge_colors.len())],
+                        node: node_colors[self.below(node_colors.len())],
+                    };
+                    Mutation::AddConstraint {node, constraint}
+                }
+                _ => {
+                    let node = self.below(n);
+                    Mutation::NodeColor {node, color: node_colors[self.below(node_colors.len())]}
+                }
+            };
+        }
+    }
+
+    /// Generates a randomly mutated specification.
+    ///
+    /// Returns the specification with the applied mutations.
+    pub fn mutate(&mut self) -> (Graph, Vec<Mutation>) {
+        let mut g = self.spec.clone();
+        let count = 1 + self.below(self.max_mutations.max(1));
+        let mut mutations = vec![];
+        for _ in 0..count {
+            let mutation = self.mutation(&g);
+            mutation.apply(&mut g);
+            mutations.push(mutation);
+        }
+        (g, mutations)
+    }
+
+    /// Mutates the specification once, solves it and checks solver invariants.
+    pub fn run(&mut self) -> Result<(), FuzzFailure> {
+        let (spec, mutations) = self.mutate();
+        check_solutions(&spec, self.settings.clone(), self.max_solutions)
+            .map_err(|(solution, message)| FuzzFailure {mutations, spec: Box::new(spec), solution, message})
+    }
+
+    /// Runs a number of cases, stopping at the first failure.
+    pub fn run_many(&mut self, cases: usize) -> Result<(), FuzzFailure> {
+        for _ in 0..cases {self.run()?}
+        Ok(())
+    }
+}
+
+/// Checks that solutions of a specification are valid.
+///
+/// Every solution must be accepted by `reference::check`, and keep node colors and pinned edges.
+/// When both searches complete, the solver must find the same solutions
+/// as plain backtracking without propagation and simple moves.
+fn check_solutions(
+    spec: &Graph,
+    mut settings: SearchSettings,
+    max_solutions: usize
+) -> Result<(), (Box<Graph>, String)> {
+    settings.difference = false;
+    let n = spec.nodes.len();
+    let mut solutions = spec.clone().solve_all(settings.clone());
+    let mut found = vec![];
+    for solution in solutions.by_ref().take(max_solutions) {
+        let g = Box::new(solution.puzzle);
+        if let Err(constraint) = reference::check(&g) {
+            return Err((g, format!("Solution violates `{}`", constraint)));
+        }
+        if g.nodes.len() != n || (0..n).any(|i| g.nodes[i].color != spec.nodes[i].color) {
+            return Err((g, "Solution changed nodes".into()));
+        }
+        for i in 0..n {
+            for j in i..n {
+                let val = spec.get((i, j));
+                if val != 0 && g.get((i, j)) != val {
+                    return Err((g, format!("Solution changed pinned edge ({}, {})", i, j)));
+                }
+            }
+        }
+        found.push(*g);
+    }
+
+    let plain = settings.propagate(false).solve_simple(false);
+    let mut others = spec.clone().solve_all(plain);
+    let other: Vec<Graph> = others.by_ref().take(max_solutions).map(|s| s.puzzle).collect();
+    let stopped = |s: &Solutions| s.exhausted() || s.timed_out() || s.cancelled();
+    if stopped(&solutions) || stopped(&others) {return Ok(())};
+    // Unless both searches reached the maximum number of solutions, they must agree.
+    if found.len() < max_solutions || other.len() < max_solutions {
+        let missing = |a: &[Graph], b: &[Graph]| {
+            a.iter().find(|g| b.iter().all(|h| h.edges != g.edges)).cloned().map(Box::new)
+        };
+        if let Some(g) = missing(&other, &found) {
+            return Err((g, "Solution is only found without propagation".into()));
+        }
+        if let Some(g) = missing(&found, &other) {
+            return Err((g, "Solution is only found with propagation".into()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! Puzzles for common families of graphs.
+
+use super::*;
+
+impl Graph {
+    /// Creates a puzzle for a hypercube of a dimension.
+    ///
+    /// There are `2^dim` nodes of color `0`, each with `dim` edge constraints.
+    /// The edge constraints cycle through `edge_colors` edge colors starting with `2`,
+    /// e.g. `Graph::hypercube(2, 2)` gives a square with a horizontal and a vertical edge per node.
+    ///
+    /// Sets `no_triangles` and `connected`.
+    /// Other triangle-free connected regular graphs are solutions too,
+    /// so further constraints might be needed for larger dimensions.
+    ///
+    /// Panics if `edge_colors` is zero.
+    pub fn hypercube(dim: u32, edge_colors: usize) -> Graph {
+        assert!(edge_colors > 0, "There must be at least one edge color");
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: (0..dim as usize)
+                .map(|k| Constraint {edge: 2 + (k % edge_colors) as Color, node: 0})
+                .collect(),
+        };
+        let mut g = Graph::new();
+        for _ in 0..1_usize << dim {g.push(a.clone())}
+        g.no_triangles = true;
+        g.connected = true;
+        g
+    }
+
+    /// Creates a puzzle for a grid of `w` times `h` nodes.
+    ///
+    /// Every node has color `0` and one edge constraint of color `2` per neighbor in the grid.
+    /// Nodes are ordered by degree, e.g. corners first.
+    ///
+    /// Sets `no_triangles`, and `meet_quad` when the grid has cycles.
+    /// Neighbors in a grid differ by at most one in degree,
+    /// so edges between other nodes are set to no-edge.
+    pub fn grid(w: usize, h: usize) -> Graph {
+        let mut degrees: Vec<usize> = (0..h).flat_map(|y| (0..w).map(move |x| {
+            [x > 0, x + 1 < w, y > 0, y + 1 < h].iter().filter(|&&b| b).count()
+        })).collect();
+        degrees.sort();
+        let mut g = Graph::new();
+        for &d in &degrees {
+            g.push(Node {
+                color: 0,
+                self_connected: false,
+                edges: vec![Constraint {edge: 2, node: 0}; d],
+            });
+        }
+        for i in 0..degrees.len() {
+            for j in i + 1..degrees.len() {
+                if degrees[j] > degrees[i] + 1 {g.set((i, j), 1)};
+            }
+        }
+        g.no_triangles = true;
+        g.meet_quad = w > 1 && h > 1;
+        g
+    }
+}
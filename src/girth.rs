@@ -0,0 +1,97 @@
+//! Minimum girth constraint.
+
+use std::collections::VecDeque;
+
+use super::*;
+
+impl Graph {
+    /// Returns the length of the shortest path between two nodes along colored edges,
+    /// not using the edge between them, if shorter than a maximum length.
+    pub fn path_shorter_than(&self, (i, j): (usize, usize), max: usize) -> Option<usize> {
+        let n = self.nodes.len();
+        let mut dist = vec![usize::MAX; n];
+        let mut queue = VecDeque::new();
+        dist[i] = 0;
+        queue.push_back(i);
+        while let Some(a) = queue.pop_front() {
+            if dist[a] + 1 >= max {break};
+            for b in 0..n {
+                if b == a || dist[b] != usize::MAX || self.get((a, b)) < 2 {continue};
+                if a == i && b == j {continue};
+                dist[b] = dist[a] + 1;
+                if b == j {return Some(dist[b])};
+                queue.push_back(b);
+            }
+        }
+        None
+    }
+
+    /// Returns the length of the shortest cycle along colored edges, if any.
+    ///
+    /// Self-loops are ignored.
+    pub fn girth(&self) -> Option<usize> {
+        let n = self.nodes.len();
+        let mut min: Option<usize> = None;
+        for i in 0..n {
+            for j in i+1..n {
+                if self.get((i, j)) < 2 {continue};
+                let max = min.unwrap_or(n + 1);
+                if let Some(d) = self.path_shorter_than((i, j), max) {
+                    min = Some(d + 1);
+                }
+            }
+        }
+        min
+    }
+
+    /// Returns the length of the shortest cycle through a node along colored edges, if any.
+    ///
+    /// Self-loops are ignored.
+    pub fn local_girth(&self, i: usize) -> Option<usize> {
+        let n = self.nodes.len();
+        let mut min: Option<usize> = None;
+        for j in 0..n {
+            if i == j || self.get((i, j)) < 2 {continue};
+            let max = min.unwrap_or(n + 1);
+            if let Some(d) = self.path_shorter_than((i, j), max) {
+                min = Some(d + 1);
+            }
+        }
+        min
+    }
+
+    /// Returns `true` if every node lies on a cycle of at most some length.
+    ///
+    /// Only nodes in `Graph::quad_nodes` are checked.
+    pub fn local_girth_satisfied(&self, max: usize) -> bool {
+        (0..self.nodes.len()).filter(|&i| self.in_quad_nodes(i))
+            .all(|i| self.local_girth(i).map(|g| g <= max).unwrap_or(false))
+    }
+
+    /// Returns `true` if there is no cycle shorter than the minimum girth.
+    ///
+    /// This is checked incrementally when colored edges are added.
+    pub fn min_girth_satisfied(&self) -> bool {
+        let min_girth = if let Some(x) = self.min_girth {x} else {return true};
+        if let Some(val) = self.cache_min_girth_satisfied.get() {return val};
+        let val = self.girth().map(|g| g >= min_girth).unwrap_or(true);
+        self.cache_min_girth_satisfied.set(Some(val));
+        val
+    }
+
+    /// Updates the minimum girth cache when the value of an edge changes.
+    pub(crate) fn update_min_girth(&self, (i, j): (usize, usize), old: Color, val: Color) {
+        let min_girth = if let Some(x) = self.min_girth {x} else {return};
+        match self.cache_min_girth_satisfied.get() {
+            // Only a cycle through the new edge can be too short.
+            Some(true) if old < 2 && val >= 2 && i != j &&
+                self.path_shorter_than((i, j), min_girth.saturating_sub(1)).is_some() => {
+                self.cache_min_girth_satisfied.set(Some(false));
+            }
+            Some(false) if old >= 2 && val < 2 => {
+                self.cache_min_girth_satisfied.set(None);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! GraphViz output with options.
+
+use std::fmt::Write;
+
+use super::*;
+
+/// Computes the label of a node, if any.
+pub type NodeLabelFn<'a> = Box<dyn Fn(usize) -> Option<String> + 'a>;
+/// Computes the label of an edge, if any, using sorted indices.
+pub type EdgeLabelFn<'a> = Box<dyn Fn((usize, usize)) -> Option<String> + 'a>;
+/// Computes extra attributes of a node.
+pub type NodeAttributesFn<'a> = Box<dyn Fn(usize) -> Vec<(String, String)> + 'a>;
+/// Computes extra attributes of an edge, using sorted indices.
+pub type EdgeAttributesFn<'a> = Box<dyn Fn((usize, usize)) -> Vec<(String, String)> + 'a>;
+
+/// Stores options for generating GraphViz dot format.
+///
+/// Default settings:
+///
+/// - palette: `None`
+/// - shapes: `[]` (GraphViz default shape)
+/// - rankdir: `None`
+/// - penwidth: `4.0`
+/// - fontname: `None`
+/// - graph_attributes: `[]`
+/// - provenance: `false`
+/// - edge_color_labels: `false`
+/// - edge_color_names: `[]`
+/// - node_label: `None`
+/// - edge_label: `None`
+/// - node_attributes: `None`
+/// - edge_attributes: `None`
+///
+/// Attribute values are quoted, so they can contain any characters.
+pub struct GraphvizOptions<'a> {
+    /// The GraphViz layout engine, e.g. `dot`, `neato` or `sfdp`.
+    pub layout: &'a str,
+    /// The node colors, using GraphViz color names.
+    pub node_colors: &'a [&'a str],
+    /// The edge colors, using GraphViz color names.
+    pub edge_colors: &'a [&'a str],
+    /// The styles of colors, if any.
+    ///
+    /// When set, this is used instead of `node_colors` and `edge_colors`.
+    pub palette: Option<&'a Palette>,
+    /// The node shapes per node color, using GraphViz shape names.
+    ///
+    /// When empty, nodes use the default shape.
+    pub shapes: &'a [&'a str],
+    /// The direction of ranks in the `dot` layout, e.g. `LR`.
+    pub rankdir: Option<&'a str>,
+    /// The width of edges.
+    pub penwidth: f64,
+    /// The font of labels.
+    pub fontname: Option<&'a str>,
+    /// Extra attributes of the graph.
+    pub graph_attributes: Vec<(String, String)>,
+    /// Whether to style edges by provenance, see `Graph::graphviz_provenance`.
+    pub provenance: bool,
+    /// Whether to label edges by their numeric color.
+    ///
+    /// This makes it easier to check e.g. `Graph::commute_quad` on the drawing.
+    pub edge_color_labels: bool,
+    /// The labels of edges per edge color, starting with color `2`.
+    ///
+    /// When not empty, these labels are used instead of numeric colors.
+    pub edge_color_names: &'a [&'a str],
+    /// Computes the label of a node, if any.
+    ///
+    /// This takes precedence over node names, see `Graph::names`.
+    pub node_label: Option<NodeLabelFn<'a>>,
+    /// Computes the label of an edge, if any, using sorted indices.
+    ///
+    /// This takes precedence over labels by edge color.
+    pub edge_label: Option<EdgeLabelFn<'a>>,
+    /// Computes extra attributes of a node.
+    pub node_attributes: Option<NodeAttributesFn<'a>>,
+    /// Computes extra attributes of an edge, using sorted indices.
+    pub edge_attributes: Option<EdgeAttributesFn<'a>>,
+}
+
+impl<'a> GraphvizOptions<'a> {
+    /// Creates new GraphViz options.
+    pub fn new(
+        layout: &'a str,
+        node_colors: &'a [&'a str],
+        edge_colors: &'a [&'a str]
+    ) -> GraphvizOptions<'a> {
+        GraphvizOptions {
+            layout,
+            node_colors,
+            edge_colors,
+            palette: None,
+            shapes: &[],
+            rankdir: None,
+            penwidth: 4.0,
+            fontname: None,
+            graph_attributes: vec![],
+            provenance: false,
+            edge_color_labels: false,
+            edge_color_names: &[],
+            node_label: None,
+            edge_label: None,
+            node_attributes: None,
+            edge_attributes: None,
+        }
+    }
+
+    /// Creates new GraphViz options using a palette.
+    pub fn from_palette(layout: &'a str, palette: &'a Palette) -> GraphvizOptions<'a> {
+        GraphvizOptions::new(layout, &[], &[]).palette(palette)
+    }
+
+    /// The styles of colors.
+    pub fn palette(mut self, val: &'a Palette) -> Self {
+        self.palette = Some(val);
+        self
+    }
+
+    /// The node shapes per node color.
+    pub fn shapes(mut self, val: &'a [&'a str]) -> Self {
+        self.shapes = val;
+        self
+    }
+
+    /// The direction of ranks in the `dot` layout.
+    pub fn rankdir(mut self, val: &'a str) -> Self {
+        self.rankdir = Some(val);
+        self
+    }
+
+    /// The width of edges.
+    pub fn penwidth(mut self, val: f64) -> Self {
+        self.penwidth = val;
+        self
+    }
+
+    /// The font of labels.
+    pub fn fontname(mut self, val: &'a str) -> Self {
+        self.fontname = Some(val);
+        self
+    }
+
+    /// Adds an extra attribute of the graph.
+    pub fn graph_attribute(mut self, key: &str, value: &str) -> Self {
+        self.graph_attributes.push((key.into(), value.into()));
+        self
+    }
+
+    /// Whether to style edges by provenance.
+    pub fn provenance(mut self, val: bool) -> Self {
+        self.provenance = val;
+        self
+    }
+
+    /// Whether to label edges by their numeric color.
+    pub fn edge_color_labels(mut self, val: bool) -> Self {
+        self.edge_color_labels = val;
+        self
+    }
+
+    /// The labels of edges per edge color, starting with color `2`.
+    ///
+    /// This also enables labeling edges by their color.
+    pub fn edge_color_names(mut self, val: &'a [&'a str]) -> Self {
+        self.edge_color_names = val;
+        self.edge_color_labels = true;
+        self
+    }
+
+    /// Computes the label of a node, if any.
+    pub fn node_label<F: Fn(usize) -> Option<String> + 'a>(mut self, f: F) -> Self {
+        self.node_label = Some(Box::new(f));
+        self
+    }
+
+    /// Computes the label of an edge, if any, using sorted indices.
+    pub fn edge_label<F: Fn((usize, usize)) -> Option<String> + 'a>(mut self, f: F) -> Self {
+        self.edge_label = Some(Box::new(f));
+        self
+    }
+
+    /// Computes extra attributes of a node.
+    pub fn node_attributes<F: Fn(usize) -> Vec<(String, String)> + 'a>(mut self, f: F) -> Self {
+        self.node_attributes = Some(Box::new(f));
+        self
+    }
+
+    /// Computes extra attributes of an edge, using sorted indices.
+    pub fn edge_attributes<F: Fn((usize, usize)) -> Vec<(String, String)> + 'a>(mut self, f: F) -> Self {
+        self.edge_attributes = Some(Box::new(f));
+        self
+    }
+}
+
+/// Escapes text in a quoted GraphViz string.
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn write_attributes(s: &mut String, attrs: &[(String, String)]) {
+    for (key, value) in attrs {
+        write!(s, ",{}=\"{}\"", key, escape(value)).unwrap();
+    }
+}
+
+impl Graph {
+    /// Generates a GraphViz dot format using options.
+    ///
+    /// The first line is a comment with the format version, see `DOT_VERSION`.
+    pub fn graphviz_with_options(&self, options: &GraphvizOptions) -> String {
+        let n = self.nodes.len();
+        let mut s = String::new();
+        writeln!(&mut s, "// graph_solver dot {}", dot::DOT_VERSION).unwrap();
+        writeln!(&mut s, "strict graph {{").unwrap();
+        writeln!(&mut s, "  layout={}; edge[penwidth={}]", options.layout, options.penwidth).unwrap();
+        if let Some(rankdir) = options.rankdir {
+            writeln!(&mut s, "  rankdir=\"{}\";", escape(rankdir)).unwrap();
+        }
+        if let Some(font) = options.fontname {
+            writeln!(&mut s, "  graph[fontname=\"{0}\"]; node[fontname=\"{0}\"]; edge[fontname=\"{0}\"]",
+                     escape(font)).unwrap();
+        }
+        for (key, value) in &options.graph_attributes {
+            writeln!(&mut s, "  {}=\"{}\";", key, escape(value)).unwrap();
+        }
+        for i in 0..n {
+            let color = self.nodes[i].color as usize;
+            match options.palette {
+                Some(palette) => {
+                    let style = palette.node(color as Color);
+                    write!(&mut s, "  {}[regular=true,style=filled,fillcolor=\"{}\",color=\"{}\"", i,
+                           escape(&style.fill), escape(&style.pen)).unwrap();
+                }
+                None => write!(&mut s, "  {}[regular=true,style=filled,fillcolor={}", i,
+                               options.node_colors[color % options.node_colors.len()]).unwrap(),
+            }
+            if !options.shapes.is_empty() {
+                write!(&mut s, ",shape={}", options.shapes[color % options.shapes.len()]).unwrap();
+            }
+            let label = options.node_label.as_ref().and_then(|f| f(i)).or_else(|| self.names[i].clone());
+            if let Some(label) = label {
+                write!(&mut s, ",label=\"{}\"", escape(&label)).unwrap();
+            }
+            if let Some(f) = &options.node_attributes {write_attributes(&mut s, &f(i))};
+            writeln!(&mut s, "];").unwrap();
+        }
+        if let Some((a, b)) = self.bipartition {
+            for &color in &[a, b] {
+                write!(&mut s, "  {{rank=same;").unwrap();
+                for i in 0..n {
+                    if self.nodes[i].color == color {write!(&mut s, " {};", i).unwrap()}
+                }
+                writeln!(&mut s, "}}").unwrap();
+            }
+        }
+        for i in 0..n {
+            for (j, &ed) in self.row(i).iter().enumerate() {
+                if ed < 2 {continue};
+                match options.palette {
+                    Some(palette) => {
+                        let style = palette.edge(ed);
+                        write!(&mut s, "  {} -- {}[color=\"{}\"", i, j, escape(&style.pen)).unwrap();
+                        // Provenance styles take precedence over dashed edges.
+                        if style.dashed && !options.provenance {write!(&mut s, ",style=dashed").unwrap()};
+                    }
+                    None => write!(&mut s, "  {} -- {}[color={}", i, j,
+                                   options.edge_colors[(ed - 2) as usize % options.edge_colors.len()]).unwrap(),
+                }
+                if options.provenance {
+                    write!(&mut s, ",style={}", match self.provenance((i, j)) {
+                        Provenance::Branched => "bold",
+                        Provenance::Propagated => "dashed",
+                        Provenance::Pinned | Provenance::Unassigned => "solid",
+                    }).unwrap();
+                }
+                // Labels computed by callback take precedence over color labels.
+                let label = options.edge_label.as_ref().and_then(|f| f((j, i))).or_else(|| {
+                    if !options.edge_color_labels {None}
+                    else if options.edge_color_names.is_empty() {Some(ed.to_string())}
+                    else {
+                        let names = options.edge_color_names;
+                        Some(names[(ed - 2) as usize % names.len()].to_string())
+                    }
+                });
+                if let Some(label) = label {
+                    write!(&mut s, ",label=\"{}\"", escape(&label)).unwrap();
+                }
+                if let Some(f) = &options.edge_attributes {write_attributes(&mut s, &f((j, i)))};
+                writeln!(&mut s, "];").unwrap();
+            }
+        }
+        writeln!(&mut s, "}}").unwrap();
+        s
+    }
+}
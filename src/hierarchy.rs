@@ -0,0 +1,84 @@
+//! Two-level solving of specifications with a block structure.
+//!
+//! A quotient specification has one super-node per block of nodes,
+//! with constraints that describe how blocks connect.
+//! Each solution of the quotient is used as scaffolding for the full specification.
+
+use super::*;
+
+impl Graph {
+    /// Refines a specification using a solution of its quotient.
+    ///
+    /// The `blocks` list contains the block of each node,
+    /// which is the index of the super-node in the quotient.
+    ///
+    /// - When the coarse edge between two blocks is no-edge,
+    ///   the empty edges between the blocks are set to no-edge
+    /// - When the coarse edge between two blocks is colored,
+    ///   at least one edge between the blocks must be colored
+    ///
+    /// Edges within blocks are not restricted.
+    pub fn refine_with(&self, coarse: &Graph, blocks: &[usize]) -> Graph {
+        let mut g = self.clone();
+        let n = self.nodes.len();
+        let m = coarse.nodes.len();
+        let mut between = vec![vec![]; m * m];
+        for i in 0..n {
+            for j in i+1..n {
+                let (a, b) = (blocks[i], blocks[j]);
+                if a == b {continue};
+                match coarse.get((a, b)) {
+                    0 => {}
+                    1 => if g.get((i, j)) == 0 {g.set((i, j), 1)},
+                    _ => between[a.min(b) * m + a.max(b)].push((i, j)),
+                }
+            }
+        }
+        for edges in between.into_iter().filter(|edges| !edges.is_empty()) {
+            let max = edges.len();
+            g.cardinalities.push(Cardinality {edges, min: 1, max});
+        }
+        g
+    }
+
+    /// Solves a specification in two levels, using a quotient specification.
+    ///
+    /// The `blocks` list contains the block of each node,
+    /// which is the index of the super-node in the quotient.
+    /// The quotient should be a relaxation of the specification,
+    /// such that every solution has a corresponding coarse solution.
+    ///
+    /// Enumerates solutions of the quotient and solves the refinement
+    /// of each until a solution is found.
+    /// The number of iterations in the solution includes the iterations of both levels,
+    /// and the maximum number of iterations in the settings applies to both levels together.
+    /// For more information, see `Graph::refine_with`.
+    pub fn solve_hierarchical(
+        self,
+        quotient: Graph,
+        blocks: &[usize],
+        solve_settings: SearchSettings
+    ) -> Option<Solution<Graph>> {
+        let difference = solve_settings.difference;
+        let max = solve_settings.max_iterations;
+        let solve_settings = solve_settings.difference(false);
+        let mut coarse_solutions = quotient.solve_all(solve_settings.clone());
+        let mut fine_iterations = 0;
+        while let Some(coarse) = coarse_solutions.next() {
+            let fine = self.refine_with(&coarse.puzzle, blocks);
+            let remaining = max.map(|max| max.saturating_sub(coarse_solutions.iterations() + fine_iterations));
+            let mut solutions = fine.solve_all(solve_settings.clone().maybe_max_iterations(remaining));
+            let solution = solutions.next();
+            fine_iterations += solutions.iterations();
+            if let Some(mut solution) = solution {
+                // The difference is taken from the specification, not the refinement.
+                if difference {solution.puzzle.remove(&self)};
+                solution.iterations = coarse_solutions.iterations() + fine_iterations;
+                return Some(solution);
+            }
+            // Iterations of the refinements count against the coarse search.
+            coarse_solutions.settings.max_iterations = max.map(|max| max.saturating_sub(fine_iterations));
+        }
+        None
+    }
+}
@@ -0,0 +1,160 @@
+//! Inference of global constraints that are implied by edge constraints.
+
+use std::fmt;
+
+use super::*;
+
+/// A global constraint that is implied by the edge constraints of nodes.
+///
+/// For more information, see `Graph::infer_flags`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImpliedFlag {
+    /// The graph is bipartite, see `Graph::bipartite`.
+    Bipartite,
+    /// Nodes of the same color are never connected, see `Graph::bipartition_by_color`.
+    Bipartition(Color, Color),
+    /// There are no triangles, see `Graph::no_triangles`.
+    NoTriangles,
+    /// Every node has a colored edge, see `Graph::no_isolated_nodes`.
+    NoIsolatedNodes,
+    /// Every node has the same number of colored edges.
+    ///
+    /// This is enabled as a degree constraint per node.
+    Regular(usize),
+    /// A node has a fixed number of colored edges.
+    Degree(DegreeConstraint),
+}
+
+impl fmt::Display for ImpliedFlag {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImpliedFlag::Bipartite => write!(w, "bipartite"),
+            ImpliedFlag::Bipartition(a, b) => write!(w, "bipartition of node colors {} and {}", a, b),
+            ImpliedFlag::NoTriangles => write!(w, "no_triangles"),
+            ImpliedFlag::NoIsolatedNodes => write!(w, "no_isolated_nodes"),
+            ImpliedFlag::Regular(k) => write!(w, "{}-regular", k),
+            ImpliedFlag::Degree(d) => write!(w, "node {} has degree {}", d.node, d.min),
+        }
+    }
+}
+
+impl Graph {
+    /// Infers global constraints that are implied by the edge constraints of nodes,
+    /// but not yet enabled.
+    ///
+    /// Every solution satisfies the implied constraints,
+    /// so enabling them does not change the solutions,
+    /// but can strengthen propagation:
+    ///
+    /// - When the node colors that edge constraints connect form a bipartite graph,
+    ///   the graph is bipartite and has no triangles
+    /// - When there are two node colors and no edge constraint connects a color to itself,
+    ///   the two colors form a bipartition
+    /// - When every node has edge constraints, there are no isolated nodes
+    /// - The number of edge constraints of a node is its degree,
+    ///   so equal numbers make the graph regular
+    ///
+    /// Pre-set colored edges can be in addition to the edge constraints.
+    /// They count as connecting the colors of their nodes,
+    /// and nodes with pre-set colored edges get no degree constraint.
+    ///
+    /// To enable the implied constraints, use `Graph::enable_implied_flags`.
+    pub fn infer_flags(&self) -> Vec<ImpliedFlag> {
+        let n = self.nodes.len();
+        let mut res = vec![];
+        if n == 0 {return res};
+
+        // The node colors that edge constraints connect.
+        let mut colors: Vec<Color> = self.nodes.iter().map(|node| node.color).collect();
+        colors.sort();
+        colors.dedup();
+        let index = |c: Color| colors.binary_search(&c).ok();
+        let mut adj = vec![vec![]; colors.len()];
+        for node in &self.nodes {
+            let a = index(node.color).unwrap();
+            for con in &node.edges {
+                // A self-loop is an odd cycle.
+                if con.node == SELF_LOOP {adj[a].push(a)};
+                if let Some(b) = index(con.node) {adj[a].push(b)};
+            }
+        }
+        let pinned = |i: usize| (0..n).any(|j| self.get((i, j)) >= 2);
+        for i in 0..n {
+            for j in i..n {
+                if self.get((i, j)) < 2 {continue};
+                let (a, b) = (index(self.nodes[i].color).unwrap(), index(self.nodes[j].color).unwrap());
+                adj[a].push(b);
+                adj[b].push(a);
+            }
+        }
+        let mut side: Vec<Option<bool>> = vec![None; colors.len()];
+        let mut two_colorable = true;
+        for start in 0..colors.len() {
+            if side[start].is_some() {continue};
+            side[start] = Some(false);
+            let mut stack = vec![start];
+            while let Some(a) = stack.pop() {
+                let s = side[a].unwrap();
+                for &b in &adj[a] {
+                    match side[b] {
+                        None => {
+                            side[b] = Some(!s);
+                            stack.push(b);
+                        }
+                        Some(t) => if t == s {two_colorable = false},
+                    }
+                }
+            }
+        }
+        if two_colorable {
+            if !self.bipartite {res.push(ImpliedFlag::Bipartite)};
+            if !self.no_triangles && self.min_girth.map(|k| k <= 3).unwrap_or(true) {
+                res.push(ImpliedFlag::NoTriangles);
+            }
+            if colors.len() == 2 && self.bipartition.is_none() {
+                res.push(ImpliedFlag::Bipartition(colors[0], colors[1]));
+            }
+        }
+
+        if !self.no_isolated_nodes && self.nodes.iter().all(|node| !node.edges.is_empty()) {
+            res.push(ImpliedFlag::NoIsolatedNodes);
+        }
+
+        let fixed = |i: usize| {
+            let k = self.nodes[i].edges.len();
+            self.degrees.iter().any(|d| d.node == i && d.min == k && d.max == k)
+        };
+        let k = self.nodes[0].edges.len();
+        if self.nodes.iter().all(|node| node.edges.len() == k) && !(0..n).any(pinned) {
+            if !(0..n).all(fixed) {res.push(ImpliedFlag::Regular(k))};
+        } else {
+            for (i, node) in self.nodes.iter().enumerate() {
+                if fixed(i) || pinned(i) {continue};
+                let k = node.edges.len();
+                res.push(ImpliedFlag::Degree(DegreeConstraint {node: i, min: k, max: k}));
+            }
+        }
+        res
+    }
+
+    /// Enables the global constraints that are implied by the edge constraints of nodes.
+    ///
+    /// Returns the enabled constraints.
+    /// For more information, see `Graph::infer_flags`.
+    pub fn enable_implied_flags(&mut self) -> Vec<ImpliedFlag> {
+        let flags = self.infer_flags();
+        for &flag in &flags {
+            match flag {
+                ImpliedFlag::Bipartite => self.bipartite = true,
+                ImpliedFlag::Bipartition(a, b) => self.bipartition_by_color(a, b),
+                ImpliedFlag::NoTriangles => self.no_triangles = true,
+                ImpliedFlag::NoIsolatedNodes => self.no_isolated_nodes = true,
+                ImpliedFlag::Regular(k) => {
+                    for i in 0..self.nodes.len() {self.push_degree(i, k, k)};
+                }
+                ImpliedFlag::Degree(d) => self.degrees.push(d),
+            }
+        }
+        flags
+    }
+}
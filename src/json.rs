@@ -0,0 +1,753 @@
+//! JSON puzzle specification format.
+//!
+//! For the schema, see `Graph::from_json`.
+
+use std::fmt::Write;
+
+use super::*;
+
+/// The version of the JSON format generated by `Graph::to_json`.
+pub const JSON_VERSION: u32 = 1;
+
+/// A JSON value with the line where it starts.
+struct Json {
+    line: usize,
+    value: Value,
+}
+
+enum Value {
+    Null,
+    Bool(bool),
+    /// Numbers are kept as text to parse colors without loss of precision.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {line: self.line, message: message.into()})
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_whitespace() {break};
+            if c == '\n' {self.line += 1};
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.chars.next() == Some(c) {Ok(())} else {self.error(&format!("Expected `{}`", c))}
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                None => return self.error("Unterminated string"),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let hi = self.hex4()?;
+                        // Characters outside the basic multilingual plane are surrogate pairs.
+                        let c = if (0xd800..0xdc00).contains(&hi) {
+                            if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                                return self.error("Expected low surrogate");
+                            }
+                            let lo = self.hex4()?;
+                            if !(0xdc00..0xe000).contains(&lo) {return self.error("Expected low surrogate")};
+                            char::from_u32(0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00))
+                        } else {
+                            char::from_u32(hi)
+                        };
+                        match c {
+                            Some(c) => s.push(c),
+                            None => return self.error("Invalid unicode escape"),
+                        }
+                    }
+                    Some(c) => return self.error(&format!("Invalid escape `\\{}`", c)),
+                    None => return self.error("Unterminated string"),
+                },
+                Some(c) if (c as u32) < 0x20 => return self.error("Unescaped control character in string"),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    /// Parses the four hexadecimal digits of a unicode escape.
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let mut x = 0;
+        for _ in 0..4 {
+            match self.chars.next().and_then(|c| c.to_digit(16)) {
+                Some(d) => x = x * 16 + d,
+                None => return self.error("Expected four hexadecimal digits after `\\u`"),
+            }
+        }
+        Ok(x)
+    }
+
+    /// Parses comma separated items until a closing character.
+    fn items<F>(&mut self, close: char, mut f: F) -> Result<(), ParseError>
+        where F: FnMut(&mut Parser<'a>) -> Result<(), ParseError>
+    {
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&close) {
+            self.chars.next();
+            return Ok(());
+        }
+        loop {
+            f(self)?;
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => {}
+                Some(c) if c == close => return Ok(()),
+                _ => return self.error(&format!("Expected `,` or `{}`", close)),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, ParseError> {
+        self.skip_whitespace();
+        let line = self.line;
+        if let Some('{') | Some('[') = self.chars.peek() {
+            if self.depth >= dot::MAX_DEPTH {return Err(dot::too_deep(line))};
+            self.depth += 1;
+        }
+        let value = match self.chars.peek() {
+            Some('{') => {
+                self.chars.next();
+                let mut fields = vec![];
+                self.items('}', |p| {
+                    let key = p.string()?;
+                    p.expect(':')?;
+                    fields.push((key, p.value()?));
+                    Ok(())
+                })?;
+                self.depth -= 1;
+                Value::Object(fields)
+            }
+            Some('[') => {
+                self.chars.next();
+                let mut items = vec![];
+                self.items(']', |p| {
+                    items.push(p.value()?);
+                    Ok(())
+                })?;
+                self.depth -= 1;
+                Value::Array(items)
+            }
+            Some('"') => Value::String(self.string()?),
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(&c) = self.chars.peek() {
+                    if !(c.is_alphanumeric() || c == '-' || c == '+' || c == '.') {break};
+                    word.push(c);
+                    self.chars.next();
+                }
+                match &*word {
+                    "null" => Value::Null,
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    _ if word.parse::<f64>().is_ok() => Value::Number(word),
+                    _ => return self.error("Expected value"),
+                }
+            }
+            None => return self.error("Expected value"),
+        };
+        Ok(Json {line, value})
+    }
+}
+
+/// Parses a quoted JSON string, which must be the whole text.
+pub(crate) fn unquote(s: &str) -> Option<String> {
+    let mut parser = Parser {chars: s.chars().peekable(), line: 1, depth: 0};
+    let res = parser.string().ok()?;
+    if parser.chars.next().is_some() {return None};
+    Some(res)
+}
+
+fn parse(s: &str) -> Result<Json, ParseError> {
+    let mut parser = Parser {chars: s.chars().peekable(), line: 1, depth: 0};
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.chars.next().is_some() {return parser.error("Expected end of file")};
+    Ok(value)
+}
+
+impl Json {
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError {line: self.line, message})
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<T, ParseError> {
+        match &self.value {
+            Value::Number(x) => match x.parse() {
+                Ok(x) => Ok(x),
+                Err(_) => self.error(format!("Expected non-negative integer for `{}`", name)),
+            },
+            _ => self.error(format!("Expected number for `{}`", name)),
+        }
+    }
+
+    fn text(&self, name: &str) -> Result<String, ParseError> {
+        match &self.value {
+            Value::String(x) => Ok(x.clone()),
+            _ => self.error(format!("Expected string for `{}`", name)),
+        }
+    }
+
+    fn boolean(&self, name: &str) -> Result<bool, ParseError> {
+        match self.value {
+            Value::Bool(x) => Ok(x),
+            _ => self.error(format!("Expected `true` or `false` for `{}`", name)),
+        }
+    }
+
+    fn array(&self, name: &str) -> Result<&[Json], ParseError> {
+        match &self.value {
+            Value::Array(items) => Ok(items),
+            _ => self.error(format!("Expected array for `{}`", name)),
+        }
+    }
+
+    fn optional<T, F>(&self, f: F) -> Result<Option<T>, ParseError>
+        where F: FnOnce(&Json) -> Result<T, ParseError>
+    {
+        match self.value {
+            Value::Null => Ok(None),
+            _ => f(self).map(Some),
+        }
+    }
+
+    /// Returns the fields of an object, checking that all keys are known.
+    fn object(&self, name: &str, keys: &[&str]) -> Result<Fields<'_>, ParseError> {
+        match &self.value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    if !keys.contains(&&**key) {
+                        return value.error(format!("Unknown key `{}` in `{}`", key, name));
+                    }
+                }
+                Ok(Fields {json: self, fields, name: name.into()})
+            }
+            _ => self.error(format!("Expected object for `{}`", name)),
+        }
+    }
+
+    fn pos(&self, name: &str) -> Result<(usize, usize), ParseError> {
+        match self.array(name)? {
+            [i, j] => Ok((i.number(name)?, j.number(name)?)),
+            _ => self.error(format!("Expected `[i, j]` for `{}`", name)),
+        }
+    }
+}
+
+struct Fields<'a> {
+    json: &'a Json,
+    fields: &'a [(String, Json)],
+    name: String,
+}
+
+impl<'a> Fields<'a> {
+    fn get(&self, key: &str) -> Option<&'a Json> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn required(&self, key: &str) -> Result<&'a Json, ParseError> {
+        match self.get(key) {
+            Some(value) => Ok(value),
+            None => self.json.error(format!("Missing key `{}` in `{}`", key, self.name)),
+        }
+    }
+
+    fn bounds(&self) -> Result<(usize, usize), ParseError> {
+        Ok((self.required("min")?.number("min")?, self.required("max")?.number("max")?))
+    }
+}
+
+pub(crate) fn flag_name(flag: Flag) -> &'static str {
+    match flag {
+        Flag::NoTriangles => "no_triangles",
+        Flag::MeetQuad => "meet_quad",
+        Flag::Connected => "connected",
+        Flag::CommuteQuad(true) => "commute_quad",
+        Flag::CommuteQuad(false) => "anticommute_quad",
+    }
+}
+
+pub(crate) fn flag_by_name(name: &str) -> Option<Flag> {
+    [
+        Flag::NoTriangles, Flag::MeetQuad, Flag::Connected,
+        Flag::CommuteQuad(true), Flag::CommuteQuad(false),
+    ].iter().cloned().find(|&flag| flag_name(flag) == name)
+}
+
+/// Escapes a string for JSON.
+pub(crate) fn escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            '\r' => res.push_str("\\r"),
+            '\u{8}' => res.push_str("\\b"),
+            '\u{c}' => res.push_str("\\f"),
+            c if (c as u32) < 0x20 => write!(res, "\\u{:04x}", c as u32).unwrap(),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+const KEYS: &[&str] = &[
+    "format_version", "nodes", "edges", "pairs", "anti_pairs", "restrictions", "cardinalities", "degrees", "color_counts",
+    "no_triangles", "min_girth", "planar", "bipartite", "meet_quad", "max_local_girth",
+    "connected", "no_isolated_nodes", "commute_quad", "sign_pairs", "quad_rules", "quad_nodes",
+    "labels", "names", "bipartition",
+    "conditionals", "component_constraints", "symmetric_differences", "edit_distances", "required_subgraphs",
+    "symmetry_break", "color_symmetry_break", "priorities",
+];
+
+fn spec(json: &Json) -> Result<Graph, ParseError> {
+    let fields = json.object("spec", KEYS)?;
+    let mut g = Graph::new();
+    for node in fields.required("nodes")?.array("nodes")? {
+        let node_fields = node.object("node", &["color", "self_connected", "edges"])?;
+        let mut edges = vec![];
+        if let Some(constraints) = node_fields.get("edges") {
+            for c in constraints.array("edges")? {
+                let c = c.object("constraint", &["edge", "node"])?;
+                edges.push(Constraint {
+                    edge: c.required("edge")?.number("edge")?,
+                    node: c.required("node")?.number("node")?,
+                });
+            }
+        }
+        g.push(Node {
+            color: node_fields.required("color")?.number("color")?,
+            self_connected: match node_fields.get("self_connected") {
+                Some(x) => x.boolean("self_connected")?,
+                None => false,
+            },
+            edges,
+        });
+    }
+    let n = g.nodes.len();
+    let node = |json: &Json, name: &str| -> Result<usize, ParseError> {
+        let i = json.number(name)?;
+        if i < n {Ok(i)} else {json.error(format!("Node index {} out of range in `{}`", i, name))}
+    };
+    let pos = |json: &Json, name: &str| -> Result<(usize, usize), ParseError> {
+        let (i, j) = json.pos(name)?;
+        if i < n && j < n {Ok((i, j))}
+        else {json.error(format!("Node index out of range in `{}`", name))}
+    };
+
+    for (key, value) in fields.fields {
+        match &**key {
+            "format_version" | "nodes" => {}
+            "edges" => for edge in value.array(key)? {
+                match edge.array(key)? {
+                    [i, j, color] => {
+                        let (i, j) = (node(i, key)?, node(j, key)?);
+                        g.set((i, j), color.number(key)?);
+                    }
+                    _ => return edge.error("Expected `[i, j, color]` for `edges`".into()),
+                }
+            },
+            "pairs" => for pair in value.array(key)? {
+                let p = pos(pair, key)?;
+                g.push_pair(p);
+            },
+            "anti_pairs" => for pair in value.array(key)? {
+                let p = pos(pair, key)?;
+                g.push_anti_pair(p);
+            },
+            "restrictions" => for r in value.array(key)? {
+                let fields = r.object(key, &["edge", "colors"])?;
+                let p = pos(fields.required("edge")?, key)?;
+                let colors = fields.required("colors")?.array("colors")?.iter()
+                    .map(|c| c.number("colors")).collect::<Result<Vec<Color>, _>>()?;
+                g.restrict(p, &colors);
+            },
+            "cardinalities" => for card in value.array(key)? {
+                let fields = card.object(key, &["edges", "min", "max"])?;
+                let mut edges = vec![];
+                for edge in fields.required("edges")?.array("edges")? {
+                    edges.push(pos(edge, key)?);
+                }
+                let (min, max) = fields.bounds()?;
+                g.push_cardinality(&edges, min, max);
+            },
+            "degrees" => for deg in value.array(key)? {
+                let fields = deg.object(key, &["node", "min", "max"])?;
+                let i = node(fields.required("node")?, key)?;
+                let (min, max) = fields.bounds()?;
+                g.push_degree(i, min, max);
+            },
+            "color_counts" => for cc in value.array(key)? {
+                let fields = cc.object(key, &["color", "min", "max"])?;
+                let (min, max) = fields.bounds()?;
+                g.push_color_count(fields.required("color")?.number("color")?, min, max);
+            },
+            "no_triangles" => g.no_triangles = value.boolean(key)?,
+            "min_girth" => g.min_girth = value.optional(|x| x.number(key))?,
+            "planar" => g.planar = value.boolean(key)?,
+            "bipartite" => g.bipartite = value.boolean(key)?,
+            "meet_quad" => g.meet_quad = value.boolean(key)?,
+            "max_local_girth" => g.max_local_girth = value.optional(|x| x.number(key))?,
+            "connected" => g.connected = value.boolean(key)?,
+            "no_isolated_nodes" => g.no_isolated_nodes = value.boolean(key)?,
+            "commute_quad" => g.commute_quad = value.optional(|x| x.boolean(key))?,
+            "quad_rules" => for rule in value.array(key)? {
+                let fields = rule.object(key, &["colors", "commute"])?;
+                let (a, b) = fields.required("colors")?.pos("colors")?;
+                let commute = fields.required("commute")?.optional(|x| x.boolean("commute"))?;
+                g.push_quad_rule(a as Color, b as Color, commute);
+            },
+            "quad_nodes" => {
+                let nodes: Option<Vec<usize>> = value.optional(|x| x.array(key)?.iter().map(|i| node(i, key)).collect())?;
+                if let Some(nodes) = nodes {g.restrict_quads(&nodes)};
+            }
+            "sign_pairs" => for pair in value.array(key)? {
+                let (a, b) = pair.pos(key)?;
+                g.push_sign_pair(a as Color, b as Color);
+            },
+            "labels" => {
+                let labels = value.array(key)?;
+                if labels.len() != n {return value.error(format!("Expected {} labels", n))};
+                for (i, label) in labels.iter().enumerate() {
+                    g.labels[i] = label.optional(|x| x.number(key))?;
+                }
+            }
+            "names" => {
+                let names = value.array(key)?;
+                if names.len() != n {return value.error(format!("Expected {} names", n))};
+                for (i, name) in names.iter().enumerate() {
+                    g.names[i] = name.optional(|x| x.text(key))?;
+                }
+            }
+            "bipartition" => if let Some((a, b)) = value.optional(|x| x.pos(key))? {
+                g.bipartition_by_color(a as Color, b as Color);
+            },
+            "conditionals" => for cond in value.array(key)? {
+                let fields = cond.object(key, &["color", "flag"])?;
+                let color = fields.required("color")?.number("color")?;
+                let flag_json = fields.required("flag")?;
+                let flag = match &flag_json.value {
+                    Value::String(s) => flag_by_name(s),
+                    _ => None,
+                };
+                match flag {
+                    Some(flag) => g.push_conditional(color, flag),
+                    None => return flag_json.error("Unknown flag in `conditionals`".into()),
+                }
+            },
+            "component_constraints" => for c in value.array(key)? {
+                let constraint = match &c.value {
+                    Value::String(s) if s == "regular" => ComponentConstraint::Regular,
+                    Value::String(s) if s == "bipartite" => ComponentConstraint::Bipartite,
+                    Value::Object(_) => {
+                        let (min, max) = c.object(key, &["min", "max"])?.bounds()?;
+                        ComponentConstraint::Size {min, max}
+                    }
+                    _ => return c.error("Expected `\"regular\"`, `\"bipartite\"` or \
+                                         `{\"min\": _, \"max\": _}` in `component_constraints`".into()),
+                };
+                g.push_component_constraint(constraint);
+            },
+            "symmetric_differences" => for d in value.array(key)? {
+                let fields = d.object(key, &["nodes", "size"])?;
+                let p = pos(fields.required("nodes")?, key)?;
+                g.push_symmetric_difference(p, fields.required("size")?.number("size")?);
+            },
+            "edit_distances" => for d in value.array(key)? {
+                let fields = d.object(key, &["edges", "max"])?;
+                let mut edges = vec![];
+                for edge in fields.required("edges")?.array("edges")? {
+                    match edge.array(key)? {
+                        [i, j, color] => edges.push(((node(i, key)?, node(j, key)?), color.number(key)?)),
+                        _ => return edge.error("Expected `[i, j, color]` for `edit_distances`".into()),
+                    }
+                }
+                g.edit_distances.push(EditDistance::new(&edges, fields.required("max")?.number("max")?));
+            },
+            "required_subgraphs" => for pattern in value.array(key)? {
+                g.require_subgraph(&spec(pattern)?);
+            },
+            "priorities" => for edge in value.array(key)? {
+                match edge.array(key)? {
+                    [i, j, p] => {
+                        let (i, j) = (node(i, key)?, node(j, key)?);
+                        g.set_priority((i, j), p.number(key)?);
+                    }
+                    _ => return edge.error("Expected `[i, j, priority]` for `priorities`".into()),
+                }
+            },
+            "symmetry_break" => g.symmetry_break = value.boolean(key)?,
+            "color_symmetry_break" => g.color_symmetry_break = value.boolean(key)?,
+            _ => unreachable!(),
+        }
+    }
+    Ok(g)
+}
+
+/// Checks that the format version of a specification is supported.
+///
+/// Specifications without version were written before the format was versioned,
+/// and have the same schema as version `1`.
+fn check_version(json: &Json) -> Result<(), ParseError> {
+    let value = match &json.value {
+        Value::Object(fields) => fields.iter().find(|(k, _)| k == "format_version").map(|(_, v)| v),
+        _ => None,
+    };
+    match value {
+        None => Ok(()),
+        Some(value) => {
+            let version: u32 = value.number("format_version")?;
+            if version > JSON_VERSION {
+                value.error(format!("Unsupported format version {}", version))
+            } else {Ok(())}
+        }
+    }
+}
+
+fn write_spec(g: &Graph, s: &mut String, indent: &str, version: Option<u32>) {
+    let n = g.nodes.len();
+    let mut fields: Vec<String> = vec![];
+    if let Some(version) = version {fields.push(format!("\"format_version\": {}", version))};
+    let nodes: Vec<String> = g.nodes.iter().map(|node| {
+        let edges: Vec<String> = node.edges.iter()
+            .map(|c| format!("{{\"edge\": {}, \"node\": {}}}", c.edge, c.node)).collect();
+        format!("{}    {{\"color\": {}, \"self_connected\": {}, \"edges\": [{}]}}",
+            indent, node.color, node.self_connected, edges.join(", "))
+    }).collect();
+    fields.push(format!("\"nodes\": [\n{}\n{}  ]", nodes.join(",\n"), indent));
+    let mut edges = vec![];
+    for i in 0..n {
+        for j in i..n {
+            let val = g.get((i, j));
+            if val != 0 {edges.push(format!("[{}, {}, {}]", i, j, val))};
+        }
+    }
+    if !edges.is_empty() {fields.push(format!("\"edges\": [{}]", edges.join(", ")))};
+    let mut priorities = vec![];
+    for i in 0..n {
+        for j in i..n {
+            let p = g.priority((i, j));
+            if p != 0 {priorities.push(format!("[{}, {}, {}]", i, j, p))};
+        }
+    }
+    if !priorities.is_empty() {fields.push(format!("\"priorities\": [{}]", priorities.join(", ")))};
+    if !g.pairs.is_empty() {
+        let pairs: Vec<String> = g.pairs.iter().map(|&(i, j)| format!("[{}, {}]", i, j)).collect();
+        fields.push(format!("\"pairs\": [{}]", pairs.join(", ")));
+    }
+    if !g.anti_pairs.is_empty() {
+        let pairs: Vec<String> = g.anti_pairs.iter().map(|&(i, j)| format!("[{}, {}]", i, j)).collect();
+        fields.push(format!("\"anti_pairs\": [{}]", pairs.join(", ")));
+    }
+    if !g.restrictions.is_empty() {
+        let rs: Vec<String> = g.restrictions.iter().map(|((i, j), allowed)| {
+            let colors: Vec<String> = allowed.iter().map(|c| c.to_string()).collect();
+            format!("{{\"edge\": [{}, {}], \"colors\": [{}]}}", i, j, colors.join(", "))
+        }).collect();
+        fields.push(format!("\"restrictions\": [{}]", rs.join(", ")));
+    }
+    if !g.cardinalities.is_empty() {
+        let cards: Vec<String> = g.cardinalities.iter().map(|c| {
+            let edges: Vec<String> = c.edges.iter().map(|&(i, j)| format!("[{}, {}]", i, j)).collect();
+            format!("{{\"edges\": [{}], \"min\": {}, \"max\": {}}}", edges.join(", "), c.min, c.max)
+        }).collect();
+        fields.push(format!("\"cardinalities\": [{}]", cards.join(", ")));
+    }
+    if !g.degrees.is_empty() {
+        let degs: Vec<String> = g.degrees.iter()
+            .map(|d| format!("{{\"node\": {}, \"min\": {}, \"max\": {}}}", d.node, d.min, d.max)).collect();
+        fields.push(format!("\"degrees\": [{}]", degs.join(", ")));
+    }
+    if !g.color_counts.is_empty() {
+        let ccs: Vec<String> = g.color_counts.iter()
+            .map(|c| format!("{{\"color\": {}, \"min\": {}, \"max\": {}}}", c.color, c.min, c.max)).collect();
+        fields.push(format!("\"color_counts\": [{}]", ccs.join(", ")));
+    }
+    let flags = [
+        ("no_triangles", g.no_triangles),
+        ("planar", g.planar),
+        ("bipartite", g.bipartite),
+        ("meet_quad", g.meet_quad),
+        ("connected", g.connected),
+        ("no_isolated_nodes", g.no_isolated_nodes),
+        ("symmetry_break", g.symmetry_break),
+        ("color_symmetry_break", g.color_symmetry_break),
+    ];
+    for &(name, val) in &flags {
+        if val {fields.push(format!("\"{}\": true", name))};
+    }
+    if let Some(x) = g.min_girth {fields.push(format!("\"min_girth\": {}", x))};
+    if let Some(x) = g.max_local_girth {fields.push(format!("\"max_local_girth\": {}", x))};
+    if let Some(x) = g.commute_quad {fields.push(format!("\"commute_quad\": {}", x))};
+    if !g.sign_pairs.is_empty() {
+        let pairs: Vec<String> = g.sign_pairs.iter().map(|&(a, b)| format!("[{}, {}]", a, b)).collect();
+        fields.push(format!("\"sign_pairs\": [{}]", pairs.join(", ")));
+    }
+    if !g.quad_rules.is_empty() {
+        let rules: Vec<String> = g.quad_rules.iter().map(|r| {
+            let commute = r.commute.map(|x| x.to_string()).unwrap_or_else(|| "null".into());
+            format!("{{\"colors\": [{}, {}], \"commute\": {}}}", r.colors.0, r.colors.1, commute)
+        }).collect();
+        fields.push(format!("\"quad_rules\": [{}]", rules.join(", ")));
+    }
+    if let Some(nodes) = &g.quad_nodes {
+        let nodes: Vec<String> = nodes.iter().map(|i| i.to_string()).collect();
+        fields.push(format!("\"quad_nodes\": [{}]", nodes.join(", ")));
+    }
+    if g.labels.iter().any(|label| label.is_some()) {
+        let labels: Vec<String> = g.labels.iter()
+            .map(|label| label.map(|x| x.to_string()).unwrap_or_else(|| "null".into())).collect();
+        fields.push(format!("\"labels\": [{}]", labels.join(", ")));
+    }
+    if g.names.iter().any(|name| name.is_some()) {
+        let names: Vec<String> = g.names.iter()
+            .map(|name| match name {
+                Some(name) => format!("\"{}\"", escape(name)),
+                None => "null".into(),
+            }).collect();
+        fields.push(format!("\"names\": [{}]", names.join(", ")));
+    }
+    if let Some((a, b)) = g.bipartition {fields.push(format!("\"bipartition\": [{}, {}]", a, b))};
+    if !g.conditionals.is_empty() {
+        let conds: Vec<String> = g.conditionals.iter()
+            .map(|&(color, flag)| format!("{{\"color\": {}, \"flag\": \"{}\"}}", color, flag_name(flag)))
+            .collect();
+        fields.push(format!("\"conditionals\": [{}]", conds.join(", ")));
+    }
+    if !g.component_constraints.is_empty() {
+        let cs: Vec<String> = g.component_constraints.iter().map(|c| match *c {
+            ComponentConstraint::Regular => "\"regular\"".into(),
+            ComponentConstraint::Bipartite => "\"bipartite\"".into(),
+            ComponentConstraint::Size {min, max} => format!("{{\"min\": {}, \"max\": {}}}", min, max),
+        }).collect();
+        fields.push(format!("\"component_constraints\": [{}]", cs.join(", ")));
+    }
+    if !g.symmetric_differences.is_empty() {
+        let ds: Vec<String> = g.symmetric_differences.iter()
+            .map(|d| format!("{{\"nodes\": [{}, {}], \"size\": {}}}", d.nodes.0, d.nodes.1, d.size))
+            .collect();
+        fields.push(format!("\"symmetric_differences\": [{}]", ds.join(", ")));
+    }
+    if !g.edit_distances.is_empty() {
+        let ds: Vec<String> = g.edit_distances.iter().map(|d| {
+            let edges: Vec<String> = d.edges.iter().map(|&((i, j), c)| format!("[{}, {}, {}]", i, j, c)).collect();
+            format!("{{\"edges\": [{}], \"max\": {}}}", edges.join(", "), d.max)
+        }).collect();
+        fields.push(format!("\"edit_distances\": [{}]", ds.join(", ")));
+    }
+    if !g.required_subgraphs.is_empty() {
+        let inner = format!("{}    ", indent);
+        let patterns: Vec<String> = g.required_subgraphs.iter().map(|pattern| {
+            let mut p = String::new();
+            write_spec(pattern, &mut p, &inner, None);
+            format!("{}{}", inner, p)
+        }).collect();
+        fields.push(format!("\"required_subgraphs\": [\n{}\n{}  ]", patterns.join(",\n"), indent));
+    }
+
+    writeln!(s, "{{").unwrap();
+    for (k, field) in fields.iter().enumerate() {
+        write!(s, "{}  {}", indent, field).unwrap();
+        writeln!(s, "{}", if k + 1 < fields.len() {","} else {""}).unwrap();
+    }
+    write!(s, "{}}}", indent).unwrap();
+}
+
+impl Graph {
+    /// Parses a puzzle specification in JSON format.
+    ///
+    /// Only `nodes` is required. Other keys are optional:
+    ///
+    /// ```text
+    /// {
+    ///   "format_version": 1,
+    ///   "nodes": [{"color": 0, "self_connected": false, "edges": [{"edge": 2, "node": 0}]}],
+    ///   "edges": [[i, j, color]],
+    ///   "pairs": [[i, j]],
+    ///   "anti_pairs": [[i, j]],
+    ///   "restrictions": [{"edge": [i, j], "colors": [1, 2]}],
+    ///   "cardinalities": [{"edges": [[i, j]], "min": 0, "max": 1}],
+    ///   "degrees": [{"node": i, "min": 0, "max": 1}],
+    ///   "color_counts": [{"color": 2, "min": 0, "max": 1}],
+    ///   "no_triangles": false,
+    ///   "min_girth": null,
+    ///   "planar": false,
+    ///   "bipartite": false,
+    ///   "meet_quad": false,
+    ///   "max_local_girth": null,
+    ///   "connected": false,
+    ///   "no_isolated_nodes": false,
+    ///   "commute_quad": null,
+    ///   "sign_pairs": [[a, b]],
+    ///   "quad_rules": [{"colors": [a, b], "commute": null}],
+    ///   "quad_nodes": null,
+    ///   "labels": [null],
+    ///   "names": [null],
+    ///   "bipartition": [a, b],
+    ///   "conditionals": [{"color": 2, "flag": "connected"}],
+    ///   "component_constraints": ["regular", "bipartite", {"min": 1, "max": 4}],
+    ///   "symmetric_differences": [{"nodes": [i, j], "size": 0}],
+    ///   "edit_distances": [{"edges": [[i, j, color]], "max": 1}],
+    ///   "required_subgraphs": [{"nodes": [...], "edges": [...]}],
+    ///   "symmetry_break": false,
+    ///   "color_symmetry_break": false,
+    ///   "priorities": [[i, j, priority]]
+    /// }
+    /// ```
+    ///
+    /// The `edges` are pre-set edge values, e.g. `1` for no edge or `2` for a colored edge,
+    /// indexed by nodes in the order of `nodes`.
+    /// The `priorities` are branching priorities of edges, see `Graph::set_priority`.
+    /// Conditional flags are `no_triangles`, `meet_quad`, `connected`,
+    /// `commute_quad` and `anticommute_quad`.
+    /// Unknown keys are reported as errors.
+    /// With the `serde` feature, graphs are serialized with the same schema.
+    ///
+    /// The `format_version` is at most `JSON_VERSION`.
+    /// Specifications without version, written by earlier versions of the crate,
+    /// are read as the current version.
+    /// Arrays and objects nested deeper than 64 levels are reported as errors.
+    pub fn from_json(json: &str) -> Result<Graph, ParseError> {
+        let json = parse(json)?;
+        check_version(&json)?;
+        spec(&json)
+    }
+
+    /// Generates a puzzle specification in JSON format.
+    ///
+    /// Settings with default values are omitted.
+    /// The specification starts with the format version, see `JSON_VERSION`.
+    /// For more information, see `Graph::from_json`.
+    pub fn to_json(&self) -> String {
+        let mut s = String::new();
+        write_spec(self, &mut s, "", Some(JSON_VERSION));
+        s.push('\n');
+        s
+    }
+}
@@ -6,11 +6,11 @@
 //! Brought to you by the [AdvancedResearch](https://github.com/advancedresearch) community!
 //!
 //! - If you are looking for a generic solver that does not remove facts,
-//! see [monotonic_solver](https://github.com/advancedresearch/monotonic_solver)
+//!   see [monotonic_solver](https://github.com/advancedresearch/monotonic_solver)
 //! - If you are looking for a generic solver that can remove facts,
-//! see [linear_solver](https://github.com/advancedresearch/linear_solver)
+//!   see [linear_solver](https://github.com/advancedresearch/linear_solver)
 //! - If you are looking for a brute-force automated theorem prover for classical and path semantical logic,
-//! see [pocket_prover](https://github.com/advancedresearch/pocket_prover)
+//!   see [pocket_prover](https://github.com/advancedresearch/pocket_prover)
 //!
 //! ### Motivation
 //!
@@ -103,6 +103,107 @@
 //! - An edge color `1` means empty
 
 pub use quickbacktrack::*;
+pub use node_color_graph::{NodeColorGraph, Var};
+pub use digraph::{Digraph, DiNode};
+pub use solve::{BranchStrategy, CancelToken, Encoding, SolveOutcome, SolveReport, SearchSettings, Solutions};
+pub use report::{ColorUsage, CompileReport};
+pub use canonical::CanonicalSolutions;
+pub use parallel::Strategy;
+pub use retraction::Retraction;
+pub use backbone::Backbone;
+pub use sink::{DotSink, JsonlSink, Sink};
+pub use diff::SpecDiff;
+pub use dot::{ParseError, DOT_VERSION};
+pub use fuzz::{FuzzFailure, Fuzzer, Mutation};
+pub use tikz::TikzOptions;
+pub use svg::SvgOptions;
+pub use rewrite::RewriteRule;
+pub use stats::{ConstraintStats, Stats};
+pub use restart::RestartPolicy;
+pub use compress::ColorMap;
+pub use domain::{EdgeDomain, EdgeValue};
+pub use payload::{EdgeData, NodeData};
+pub use json::JSON_VERSION;
+pub use spec::SPEC_VERSION;
+pub use unsat::UnsatReason;
+pub use format::Format;
+pub use diagnose::{Named, Violation};
+pub use dead::{DeadConstraint, DeadReason};
+pub use graphviz::{EdgeAttributesFn, EdgeLabelFn, GraphvizOptions, NodeAttributesFn, NodeLabelFn};
+pub use infer::ImpliedFlag;
+pub use palette::{Palette, Style};
+pub use overlay::Overlay;
+pub use lint::Lint;
+pub use distance::Repair;
+pub use auto::{AutoConfig, AutoReport};
+pub use manifest::Manifest;
+#[cfg(feature = "store")]
+pub use store::{Metrics, SolutionStore, StoredSolution};
+#[cfg(feature = "async")]
+pub use async_solve::{SolveFuture, SolveProgress};
+
+pub mod reference;
+pub mod bench;
+pub mod solutions;
+
+mod node_color_graph;
+mod digraph;
+mod solve;
+mod report;
+mod canonical;
+mod parallel;
+mod symmetry;
+mod retraction;
+mod backbone;
+mod girth;
+mod sink;
+mod planar;
+mod bipartite;
+mod diff;
+mod provenance;
+mod subgraph;
+mod progress;
+mod dot;
+mod fuzz;
+mod json;
+mod tikz;
+mod svg;
+mod region;
+mod hierarchy;
+mod adjacency;
+mod rewrite;
+mod edit;
+mod propagate;
+mod explain;
+mod stats;
+mod restart;
+mod compress;
+mod domain;
+mod payload;
+mod spec;
+mod unsat;
+mod format;
+mod diagnose;
+mod dead;
+mod graphviz;
+mod infer;
+mod palette;
+mod auto;
+mod manifest;
+mod overlay;
+mod generators;
+mod adinkra;
+mod spec_view;
+mod lint;
+mod distance;
+#[cfg(feature = "store")]
+mod store;
+#[cfg(feature = "async")]
+mod async_solve;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "petgraph")]
+mod petgraph_interop;
 
 /// The type of color.
 pub type Color = u64;
@@ -116,71 +217,216 @@ pub const DISCONNECTED_EDGE: Color = 1;
 ///
 /// An edge value `0` means no edge.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialization::GraphData", into = "serialization::GraphData"))]
 pub struct Graph {
     /// Nodes.
     pub nodes: Vec<Node>,
-    /// Edges.
-    pub edges: Vec<Vec<Color>>,
+    /// Edges, stored as a flat lower triangle.
+    ///
+    /// Row `i` has `i + 1` values and starts at index `i * (i + 1) / 2`.
+    /// Use `Graph::get`, `Graph::set` and `Graph::row` to access edges.
+    edges: Vec<Color>,
     /// Pair constraints, using indices.
     pub pairs: Vec<(usize, usize)>,
+    /// Anti-pair constraints, using sorted indices.
+    ///
+    /// The edge between the nodes of an anti-pair can not be colored.
+    /// For more information, see `Graph::push_anti_pair`.
+    pub anti_pairs: Vec<(usize, usize)>,
+    /// Allowed values of edges, sorted by sorted indices.
+    ///
+    /// For more information, see `Graph::restrict`.
+    pub restrictions: Vec<((usize, usize), Vec<Color>)>,
+    /// Cardinality constraints over sets of edges.
+    pub cardinalities: Vec<Cardinality>,
+    /// Bounds on the number of colored edges of nodes.
+    pub degrees: Vec<DegreeConstraint>,
+    /// Bounds on the total number of edges of a color.
+    pub color_counts: Vec<ColorCount>,
     /// Whether triangle cycles are allowed.
     pub no_triangles: bool,
+    /// The minimum length of cycles along colored edges, if any.
+    ///
+    /// For example, `Some(4)` forbids triangles and `Some(5)` also forbids quads.
+    /// Self-loops are ignored.
+    pub min_girth: Option<usize>,
+    /// Whether the graph of colored edges must be planar.
+    pub planar: bool,
+    /// Whether the graph of colored edges must be bipartite.
+    ///
+    /// Odd cycles are detected incrementally during search.
+    pub bipartite: bool,
     /// Whether any shortest cycle for any vertex must be 4 or less.
     pub meet_quad: bool,
+    /// The maximum length of the shortest cycle through any vertex, if any.
+    ///
+    /// Every node must lie on a cycle of at most this length.
+    /// This generalizes `meet_quad`, which is the special case `Some(4)`.
+    pub max_local_girth: Option<usize>,
     /// Whether any node can be reached from any other node.
     pub connected: bool,
+    /// Whether every node must have at least one colored edge.
+    pub no_isolated_nodes: bool,
     /// Whether commutativity/anticommutativity is enabled for quads.
     ///
     /// When a quad commutes, the edges along one dimension have same colors.
     /// When a quad anticommutes, the edges along one dimension have same colors,
     /// but with an odd number of positive and negative signs (1+3 or 3+1).
     ///
-    /// It is assumed that even and odd colors for edges
+    /// By default, even and odd colors for edges
     /// above `2` anticommutes, e.g. `2` and `3` anticommutes.
+    /// Other pairs can be set with `Graph::push_sign_pair`.
     ///
     /// - When set to `Some(true)`, every quad commutes.
     /// - When set to `Some(false)`, every quad anticommutes.
     /// - When set to `None`
     pub commute_quad: Option<bool>,
-    cache_has_triangles: std::cell::Cell<bool>,
-    cache_connected: std::cell::Cell<bool>,
+    /// Pairs of edge colors that anticommute, see `Graph::commute_quad`.
+    ///
+    /// When empty, even and odd colors anticommute, e.g. `2` and `3`.
+    /// Otherwise, only these pairs anticommute.
+    pub sign_pairs: Vec<(Color, Color)>,
+    /// Rules for quads spanned by pairs of edge colors.
+    ///
+    /// A quad is spanned by two edge colors when one pair of opposite edges
+    /// has the first color or the color that anticommutes with it,
+    /// and the other pair has the second color or the color that anticommutes with it.
+    /// Rules take precedence over `Graph::commute_quad`, which applies to other quads.
+    pub quad_rules: Vec<QuadRule>,
+    /// The nodes that quad constraints apply to, using sorted indices, or `None` for all nodes.
+    ///
+    /// `Graph::commute_quad` and `Graph::quad_rules` only apply to quads
+    /// whose four nodes are in the set,
+    /// and `Graph::meet_quad` and `Graph::max_local_girth` only apply to nodes in the set.
+    /// Conditional flags are not restricted.
+    /// For more information, see `Graph::restrict_quads`.
+    pub quad_nodes: Option<Vec<usize>>,
+    /// Bitstring labels of nodes.
+    ///
+    /// A colored edge between two labeled nodes must join labels at Hamming distance 1,
+    /// where the edge color is `2 + k` for the differing bit `k`.
+    pub labels: Vec<Option<u64>>,
+    /// Names of nodes.
+    ///
+    /// Names are used instead of indices in GraphViz output, `Puzzle::print` and diagnostics.
+    /// For more information, see `Graph::push_named`.
+    pub names: Vec<Option<String>>,
+    /// Node colors that forms a bipartition.
+    ///
+    /// Nodes of the same color in the bipartition can not be connected.
+    pub bipartition: Option<(Color, Color)>,
+    /// Global constraints guarded by the presence of an edge color.
+    ///
+    /// When any edge has the color, the subgraph of edges with that color
+    /// must satisfy the global constraint.
+    pub conditionals: Vec<(Color, Flag)>,
+    /// Constraints that every connected component must satisfy individually.
+    pub component_constraints: Vec<ComponentConstraint>,
+    /// Constraints on the difference between incident edge colors of two nodes.
+    pub symmetric_differences: Vec<SymmetricDifference>,
+    /// Bounds on the number of edges where a solution differs from reference graphs.
+    ///
+    /// For more information, see `Graph::push_edit_distance`.
+    pub edit_distances: Vec<EditDistance>,
+    /// Colored patterns that every solution must contain as subgraphs.
+    ///
+    /// For more information, see `Graph::require_subgraph`.
+    pub required_subgraphs: Vec<Graph>,
+    /// Whether to break symmetries between interchangeable nodes.
+    ///
+    /// Before solving, groups of interchangeable nodes are detected,
+    /// and their edge rows are required to be in lexicographic order.
+    /// This prunes assignments that are equal up to swapping nodes,
+    /// but keeps at least one solution of every isomorphism class.
+    pub symmetry_break: bool,
+    /// Whether to break symmetries between interchangeable edge colors.
+    ///
+    /// Before solving, groups of interchangeable edge colors are detected,
+    /// and the first occurrence of a lower color must come before
+    /// the first occurrence of a higher color in the same group.
+    pub color_symmetry_break: bool,
+    symmetry_groups: Vec<Vec<usize>>,
+    color_groups: Vec<Vec<Color>>,
+    provenance: Vec<Vec<Provenance>>,
+    initial_domains: Vec<Vec<usize>>,
+    priorities: Vec<Vec<i64>>,
+    /// Neighbors along colored edges of each node, as bitsets.
+    adjacency: Vec<Vec<u64>>,
+    /// The number of triangles along colored edges.
+    triangles: usize,
     cache_upper_triangle_disconnected: std::cell::Cell<bool>,
+    cache_possibly_connected: std::cell::Cell<Option<bool>>,
     cache_commute_quad_satisfied: std::cell::Cell<bool>,
     cache_node_satisfied: Vec<std::cell::Cell<bool>>,
+    cache_components: std::cell::RefCell<Option<Components>>,
+    cache_parity: std::cell::RefCell<Option<Parity>>,
+    cache_component_sizes_feasible: std::cell::Cell<Option<bool>>,
+    cache_isolated_nodes_feasible: std::cell::Cell<Option<bool>>,
+    cache_symmetry_feasible: std::cell::Cell<Option<bool>>,
+    cache_min_girth_satisfied: std::cell::Cell<Option<bool>>,
+    cache_planar: std::cell::Cell<Option<bool>>,
+    cache_subgraphs_feasible: std::cell::Cell<Option<bool>>,
 }
 
 impl Puzzle for Graph {
     type Pos = (usize, usize);
     type Val = Color;
     fn set(&mut self, (i, j): (usize, usize), val: Color) {
-        let old = if j <= i {self.edges[i][j]} else {self.edges[j][i]};
-        if j <= i {self.edges[i][j] = val} else {self.edges[j][i] = val}
+        let ind = Graph::index((i, j));
+        let old = self.edges[ind];
+        self.edges[ind] = val;
+        self.provenance[i.max(j)][i.min(j)] = Provenance::Pinned;
         if old != 0 && val < 2 {
-            self.cache_connected.set(false);
             self.cache_upper_triangle_disconnected.set(false);
         }
+        if (old == 1) != (val == 1) {
+            self.cache_possibly_connected.set(None);
+        }
         if !(old == 0 && val == 1) {
             self.cache_commute_quad_satisfied.set(false);
         }
         if old != 0 {
-            self.cache_has_triangles.set(false);
             self.cache_node_satisfied[i].set(false);
             self.cache_node_satisfied[j].set(false);
         }
+        if old >= 2 && val < 2 {
+            *self.cache_components.get_mut() = None;
+            *self.cache_parity.get_mut() = None;
+        } else if old < 2 && val >= 2 {
+            if let Some(components) = self.cache_components.get_mut() {
+                components.union(i, j);
+            }
+            if let Some(parity) = self.cache_parity.get_mut() {
+                parity.union(i, j);
+            }
+        }
+        self.cache_component_sizes_feasible.set(None);
+        self.cache_isolated_nodes_feasible.set(None);
+        self.cache_symmetry_feasible.set(None);
+        self.update_adjacency((i, j), old, val);
+        self.update_min_girth((i, j), old, val);
+        self.update_planar(old, val);
+        self.cache_subgraphs_feasible.set(None);
     }
-    fn get(&self, (i, j): (usize, usize)) -> Color {
-        if j <= i {self.edges[i][j]} else {self.edges[j][i]}
+    fn get(&self, pos: (usize, usize)) -> Color {
+        self.edges[Graph::index(pos)]
     }
     fn print(&self) {
+        // Rows start with node names when some node has a name.
+        let named = self.names.iter().any(|name| name.is_some());
+        let width = (0..self.nodes.len()).map(|i| self.node_name(i).len()).max().unwrap_or(0);
+        if named {eprint!("{:1$} ", "", width)};
         for i in 0..self.nodes.len() {
             eprint!("{} ", self.nodes[i].color);
         }
         eprintln!("\n========================================");
         for i in 0..self.nodes.len() {
+            if named {eprint!("{:1$} ", self.node_name(i), width)};
             for j in 0..self.nodes.len() {
                 eprint!("{} ", self.get((i, j)));
             }
-            eprintln!("");
+            eprintln!();
         }
     }
     fn solve_simple<F: FnMut(&mut Self, Self::Pos, Self::Val)>(&mut self, mut f: F) {
@@ -197,10 +443,28 @@ impl Puzzle for Graph {
     fn is_solved(&self) -> bool {
         self.all_satisfied() &&
         self.pairs_satisfied() &&
+        self.anti_pairs_satisfied() &&
+        self.restrictions_satisfied() &&
+        self.cardinalities_satisfied() &&
+        self.degrees_satisfied() &&
+        self.color_counts_satisfied() &&
         if self.no_triangles {!self.has_triangles()} else {true} &&
+        self.min_girth_satisfied() &&
+        self.planar_satisfied() &&
+        if self.bipartite {!self.has_odd_cycle()} else {true} &&
         if self.connected {self.is_connected()} else {true} &&
-        if let Some(val) = self.commute_quad {self.commute_quad_satisfied(val)} else {true} &&
-        if self.meet_quad {self.meet_quad_satisfied()} else {true}
+        if self.no_isolated_nodes {!self.has_isolated_nodes()} else {true} &&
+        self.bipartition_satisfied() &&
+        self.labels_satisfied() &&
+        self.quads_satisfied() &&
+        if self.meet_quad {self.meet_quad_satisfied()} else {true} &&
+        if let Some(k) = self.max_local_girth {self.local_girth_satisfied(k)} else {true} &&
+        self.conditionals_satisfied() &&
+        self.component_constraints_satisfied() &&
+        self.symmetric_differences_satisfied() &&
+        self.edit_distances_satisfied() &&
+        self.required_subgraphs_satisfied() &&
+        self.symmetry_break_satisfied()
     }
     fn remove(&mut self, other: &Graph) {
         let n = self.nodes.len();
@@ -225,42 +489,141 @@ impl Graph {
     /// - no-triangles: false
     /// - meet-quad: false
     /// - connected: false
+    /// - no-isolated-nodes: false
+    /// - symmetry-break: false
+    /// - color-symmetry-break: false
     pub fn new() -> Graph {
         Graph {
             nodes: vec![],
             edges: vec![],
             pairs: vec![],
+            anti_pairs: vec![],
+            restrictions: vec![],
+            cardinalities: vec![],
+            degrees: vec![],
+            color_counts: vec![],
             no_triangles: false,
+            min_girth: None,
+            planar: false,
+            bipartite: false,
             meet_quad: false,
+            max_local_girth: None,
             connected: false,
+            no_isolated_nodes: false,
             commute_quad: None,
-            cache_has_triangles: std::cell::Cell::new(false),
-            cache_connected: std::cell::Cell::new(false),
+            sign_pairs: vec![],
+            quad_rules: vec![],
+            quad_nodes: None,
+            labels: vec![],
+            names: vec![],
+            bipartition: None,
+            conditionals: vec![],
+            component_constraints: vec![],
+            symmetric_differences: vec![],
+            edit_distances: vec![],
+            required_subgraphs: vec![],
+            symmetry_break: false,
+            color_symmetry_break: false,
+            symmetry_groups: vec![],
+            color_groups: vec![],
+            provenance: vec![],
+            initial_domains: vec![],
+            priorities: vec![],
+            adjacency: vec![],
+            triangles: 0,
             cache_upper_triangle_disconnected: std::cell::Cell::new(false),
+            cache_possibly_connected: std::cell::Cell::new(None),
             cache_commute_quad_satisfied: std::cell::Cell::new(false),
             cache_node_satisfied: vec![],
+            cache_components: std::cell::RefCell::new(None),
+            cache_parity: std::cell::RefCell::new(None),
+            cache_component_sizes_feasible: std::cell::Cell::new(None),
+            cache_isolated_nodes_feasible: std::cell::Cell::new(None),
+            cache_symmetry_feasible: std::cell::Cell::new(None),
+            cache_min_girth_satisfied: std::cell::Cell::new(None),
+            cache_planar: std::cell::Cell::new(None),
+            cache_subgraphs_feasible: std::cell::Cell::new(None),
         }
     }
 
+    /// Returns the index of an edge in the flat lower triangle.
+    #[inline(always)]
+    fn index((i, j): (usize, usize)) -> usize {
+        let (i, j) = if j <= i {(i, j)} else {(j, i)};
+        i * (i + 1) / 2 + j
+    }
+
+    /// Returns the edges from node `i` to nodes `0..=i`.
+    pub fn row(&self, i: usize) -> &[Color] {
+        let start = i * (i + 1) / 2;
+        &self.edges[start..start + i + 1]
+    }
+
     /// Generates a GraphViz dot format.
+    ///
+    /// The first line is a comment with the format version, see `DOT_VERSION`.
+    /// For more options, see `Graph::graphviz_with_options`.
     pub fn graphviz(&self, layout: &str, node_colors: &[&str], edge_colors: &[&str]) -> String {
+        self.graphviz_with_options(&GraphvizOptions::new(layout, node_colors, edge_colors))
+    }
+
+    /// Generates a GraphML format.
+    ///
+    /// Nodes and edges have a `color` attribute with the color name
+    /// and a `value` attribute with the numeric color.
+    pub fn graphml(&self, node_colors: &[&str], edge_colors: &[&str]) -> String {
+        self.graphml_attributed(node_colors, edge_colors, &|_| None, &|_| None)
+    }
+
+    /// Generates a GraphML format with optional `data` attributes.
+    fn graphml_attributed(
+        &self,
+        node_colors: &[&str],
+        edge_colors: &[&str],
+        node_data: &dyn Fn(usize) -> Option<String>,
+        edge_data: &dyn Fn((usize, usize)) -> Option<String>,
+    ) -> String {
         use std::fmt::Write;
+        use svg::escape_xml as escape;
 
         let mut s = String::new();
-        writeln!(&mut s, "strict graph {{").unwrap();
-        writeln!(&mut s, "  layout={}; edge[penwidth=4]", layout).unwrap();
+        writeln!(&mut s, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+        writeln!(&mut s, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">").unwrap();
+        for (id, domain) in &[("n", "node"), ("e", "edge")] {
+            writeln!(&mut s, "  <key id=\"{}color\" for=\"{}\" attr.name=\"color\" \
+                              attr.type=\"string\"/>", id, domain).unwrap();
+            writeln!(&mut s, "  <key id=\"{}value\" for=\"{}\" attr.name=\"value\" \
+                              attr.type=\"long\"/>", id, domain).unwrap();
+            writeln!(&mut s, "  <key id=\"{}data\" for=\"{}\" attr.name=\"data\" \
+                              attr.type=\"string\"/>", id, domain).unwrap();
+        }
+        writeln!(&mut s, "  <graph id=\"G\" edgedefault=\"undirected\">").unwrap();
         for i in 0..self.nodes.len() {
-            writeln!(&mut s, "  {}[regular=true,style=filled,fillcolor={}];", i,
-                   node_colors[self.nodes[i].color as usize % node_colors.len()]).unwrap();
+            let color = self.nodes[i].color;
+            writeln!(&mut s, "    <node id=\"n{}\">", i).unwrap();
+            writeln!(&mut s, "      <data key=\"ncolor\">{}</data>",
+                escape(node_colors[color as usize % node_colors.len()])).unwrap();
+            writeln!(&mut s, "      <data key=\"nvalue\">{}</data>", color).unwrap();
+            if let Some(data) = node_data(i) {
+                writeln!(&mut s, "      <data key=\"ndata\">{}</data>", escape(&data)).unwrap();
+            }
+            writeln!(&mut s, "    </node>").unwrap();
         }
         for i in 0..self.nodes.len() {
-            for (j, &ed) in self.edges[i].iter().enumerate() {
+            for (j, &ed) in self.row(i).iter().enumerate() {
                 if ed < 2 {continue};
-                writeln!(&mut s, "  {} -- {}[color={}];", i, j,
-                edge_colors[(ed - 2) as usize % edge_colors.len()]).unwrap();
+                writeln!(&mut s, "    <edge source=\"n{}\" target=\"n{}\">", i, j).unwrap();
+                writeln!(&mut s, "      <data key=\"ecolor\">{}</data>",
+                    escape(edge_colors[(ed - 2) as usize % edge_colors.len()])).unwrap();
+                writeln!(&mut s, "      <data key=\"evalue\">{}</data>", ed).unwrap();
+                if let Some(data) = edge_data((j, i)) {
+                    writeln!(&mut s, "      <data key=\"edata\">{}</data>", escape(&data)).unwrap();
+                }
+                writeln!(&mut s, "    </edge>").unwrap();
             }
         }
-        writeln!(&mut s, "}}").unwrap();
+        writeln!(&mut s, "  </graph>").unwrap();
+        writeln!(&mut s, "</graphml>").unwrap();
         s
     }
 
@@ -280,38 +643,147 @@ impl Graph {
     }
 
     /// Finds the edge with the least possible colors.
+    ///
+    /// Ties are broken by picking the edge with highest priority.
+    /// For more information, see `Graph::set_priority`.
     pub fn min_colors(&self) -> Option<(usize, usize)> {
-        let mut min: Option<(usize, usize, usize)> = None;
+        let mut min: Option<(usize, usize, usize, i64)> = None;
         let n = self.nodes.len();
+        // An edge with one color is picked at once when no undecided edge has higher priority.
+        let max_priority = if self.priorities.is_empty() {0} else {
+            (0..n).flat_map(|i| (i..n).map(move |j| (i, j)))
+                .filter(|&pos| self.get(pos) == 0)
+                .map(|pos| self.priority(pos)).max().unwrap_or(0)
+        };
         'outer: for i in 0..n {
             for j in i..n {
                 let s = self.colors((i, j)).len();
                 if s == 0 {continue};
-                if min.is_none() || min.unwrap().2 > s {
-                    min = Some((i, j, s));
-                    if s == 1 {break 'outer}
+                let p = self.priority((i, j));
+                if min.map(|m| m.2 > s || m.2 == s && m.3 < p).unwrap_or(true) {
+                    min = Some((i, j, s, p));
+                    if s == 1 && p == max_priority {break 'outer}
                 }
             }
         }
         min.map(|n| (n.0, n.1))
     }
 
+    /// Sets the branching priority of an edge.
+    ///
+    /// Among edges with the same number of possible colors,
+    /// the solver branches first on the edge with highest priority.
+    /// This biases the search toward structurally important edges
+    /// without pinning them. The default priority is `0`.
+    pub fn set_priority(&mut self, (i, j): (usize, usize), priority: i64) {
+        if self.priorities.is_empty() {
+            self.priorities = (0..self.nodes.len()).map(|i| vec![0; i + 1]).collect();
+        }
+        self.priorities[i.max(j)][i.min(j)] = priority;
+    }
+
+    /// Returns the branching priority of an edge.
+    pub fn priority(&self, (i, j): (usize, usize)) -> i64 {
+        if self.priorities.is_empty() {0} else {self.priorities[i.max(j)][i.min(j)]}
+    }
+
+    /// Returns the neighbors that can satisfy an edge constraint of a node.
+    pub fn slot_candidates(&self, i: usize, con: Constraint) -> Vec<usize> {
+        (0..self.nodes.len()).filter(|&j| {
+            self.matches(con, i, j) &&
+            self.get((i, j)) == 0 &&
+            self.colors((i, j)).contains(&con.edge)
+        }).collect()
+    }
+
+    /// Finds the unsatisfied edge constraint with the least possible neighbors.
+    ///
+    /// Returns the node, the edge constraint and the possible neighbors.
+    pub fn min_slot(&self) -> Option<(usize, Constraint, Vec<usize>)> {
+        let mut min: Option<(usize, Constraint, Vec<usize>)> = None;
+        'outer: for i in 0..self.nodes.len() {
+            let mut errors = self.node_satisfied(i);
+            errors.dedup();
+            for con in errors {
+                let candidates = self.slot_candidates(i, con);
+                if min.is_none() || min.as_ref().unwrap().2.len() > candidates.len() {
+                    let s = candidates.len();
+                    min = Some((i, con, candidates));
+                    if s <= 1 {break 'outer}
+                }
+            }
+        }
+        min
+    }
+
+    /// Channels edge constraints to edge colors.
+    ///
+    /// Returns `None` if some edge constraint has fewer possible neighbors than needed.
+    /// Otherwise, returns the edges that are forced by an edge constraint,
+    /// because the number of possible neighbors equals the number needed.
+    pub fn channel_slots(&self) -> Option<Vec<((usize, usize), Color)>> {
+        for i in 0..self.nodes.len() {
+            let errors = self.node_satisfied(i);
+            let mut k = 0;
+            while k < errors.len() {
+                let con = errors[k];
+                let needed = errors.iter().filter(|&&c| c == con).count();
+                if errors[..k].contains(&con) {
+                    k += 1;
+                    continue;
+                }
+                let candidates = self.slot_candidates(i, con);
+                if candidates.len() < needed {return None};
+                if candidates.len() == needed {
+                    return Some(candidates.into_iter().map(|j| ((i, j), con.edge)).collect());
+                }
+                k += 1;
+            }
+        }
+        Some(vec![])
+    }
+
     /// Solves the graph puzzle using default strategy.
     ///
     /// The default strategy is `Graph::min_colors, Graph::colors`.
-    pub fn solve(self, solve_settings: SolveSettings) -> Option<Solution<Graph>> {
+    /// For propagation, timeouts and other options, see `Graph::solve_all` and `SearchSettings`.
+    pub fn solve(mut self, solve_settings: SolveSettings) -> Option<Solution<Graph>> {
+        self.prepare_symmetry_break();
+        self.record_initial_domains();
+        #[cfg(feature = "verify-solutions")]
+        let original = self.clone();
         let solver = BackTrackSolver::new(self, solve_settings);
-        solver.solve(
+        let solution = solver.solve(
             Graph::min_colors,
             Graph::colors
-        )
+        );
+        #[cfg(feature = "verify-solutions")]
+        if let Some(solution) = &solution {reference::verify(&solution.puzzle, &original)};
+        solution
     }
 
     /// Adds a node description.
     pub fn push(&mut self, node: Node) {
         self.nodes.push(node);
-        self.edges.push(vec![0; self.nodes.len()]);
+        self.labels.push(None);
+        self.names.push(None);
+        self.edges.resize(self.edges.len() + self.nodes.len(), 0);
+        self.push_adjacency();
+        self.provenance.push(vec![Provenance::Pinned; self.nodes.len()]);
+        self.initial_domains.clear();
+        if !self.priorities.is_empty() {self.priorities.push(vec![0; self.nodes.len()])};
         self.cache_node_satisfied.push(std::cell::Cell::new(false));
+        *self.cache_components.get_mut() = None;
+        *self.cache_parity.get_mut() = None;
+        self.cache_component_sizes_feasible.set(None);
+        self.cache_isolated_nodes_feasible.set(None);
+        self.cache_possibly_connected.set(None);
+        self.symmetry_groups.clear();
+        self.color_groups.clear();
+        self.cache_symmetry_feasible.set(None);
+        self.cache_min_girth_satisfied.set(None);
+        self.cache_planar.set(None);
+        self.cache_subgraphs_feasible.set(None);
     }
 
     /// Adds a pair constraint.
@@ -319,240 +791,837 @@ impl Graph {
         self.pairs.push((i.min(j), i.max(j)));
     }
 
-    /// Returns a list of edge constraints that makes a node unsatisfied.
+    /// Adds an anti-pair constraint, which forbids a colored edge between two nodes.
     ///
-    /// If the returned list is empty, then the node is satisfied.
-    pub fn node_satisfied(&self, i: usize) -> Vec<Constraint> {
-        if self.cache_node_satisfied[i].get() {return vec![]};
-        let mut res = vec![];
-        let mut m = vec![false; self.nodes[i].edges.len()];
-        for j in 0..self.nodes.len() {
-            let edge = self.get((i, j));
-            if edge == 0 {continue};
-            for k in 0..m.len() {
-                if m[k] {continue};
-                let con = &self.nodes[i].edges[k];
-                if con.edge == edge &&
-                   con.node == self.nodes[j].color
-                {
-                    m[k] = true;
-                    break;
-                }
-            }
+    /// Unlike setting the edge to no-edge with `Graph::set`,
+    /// this is a constraint of the puzzle, which is kept when the solver backtracks
+    /// and when the edge is cleared.
+    pub fn push_anti_pair(&mut self, (i, j): (usize, usize)) {
+        self.anti_pairs.push((i.min(j), i.max(j)));
+    }
+
+    /// Restricts the values of an edge.
+    ///
+    /// The values are edge colors, where `1` allows no edge,
+    /// e.g. `g.restrict((0, 1), &[1, 3])` means that the edge has color `3` or is absent.
+    /// Restricting an edge again keeps the values that are allowed by both.
+    pub fn restrict(&mut self, (i, j): (usize, usize), allowed: &[Color]) {
+        let pos = (i.min(j), i.max(j));
+        let mut allowed: Vec<Color> = allowed.iter().cloned().filter(|&c| c >= 1).collect();
+        allowed.sort();
+        allowed.dedup();
+        match self.restrictions.binary_search_by_key(&pos, |(p, _)| *p) {
+            Ok(k) => self.restrictions[k].1.retain(|c| allowed.contains(c)),
+            Err(k) => self.restrictions.insert(k, (pos, allowed)),
         }
-        for k in 0..m.len() {
-            if !m[k] {
-                res.push(self.nodes[i].edges[k].clone());
+    }
+
+    /// Returns the allowed values of an edge, if restricted.
+    ///
+    /// For more information, see `Graph::restrict`.
+    pub fn allowed(&self, (i, j): (usize, usize)) -> Option<&[Color]> {
+        let pos = (i.min(j), i.max(j));
+        self.restrictions.binary_search_by_key(&pos, |(p, _)| *p).ok()
+            .map(|k| &*self.restrictions[k].1)
+    }
+
+    /// Returns `true` if all restricted edges have allowed values.
+    ///
+    /// Empty edges are treated as no edge.
+    pub fn restrictions_satisfied(&self) -> bool {
+        self.restrictions.iter().all(|(pos, allowed)| allowed.contains(&self.get(*pos).max(1)))
+    }
+
+    /// Adds a cardinality constraint.
+    ///
+    /// The number of colored edges (`>= 2`) among the given edges
+    /// must be at least `min` and at most `max`.
+    pub fn push_cardinality(&mut self, edges: &[(usize, usize)], min: usize, max: usize) {
+        let mut edges: Vec<(usize, usize)> = edges.iter()
+            .map(|&(i, j)| (i.min(j), i.max(j))).collect();
+        edges.sort();
+        edges.dedup();
+        self.cardinalities.push(Cardinality {edges, min, max});
+    }
+
+    /// Returns the number of colored and empty edges of a cardinality constraint.
+    pub fn cardinality_count(&self, card: &Cardinality) -> (usize, usize) {
+        let mut colored = 0;
+        let mut empty = 0;
+        for &pos in &card.edges {
+            match self.get(pos) {
+                0 => empty += 1,
+                1 => {}
+                _ => colored += 1,
             }
         }
-        if res.len() == 0 {
-            self.cache_node_satisfied[i].set(true);
+        (colored, empty)
+    }
+
+    /// Returns `true` if all cardinality constraints are satisfied.
+    pub fn cardinalities_satisfied(&self) -> bool {
+        for card in &self.cardinalities {
+            let (colored, _) = self.cardinality_count(card);
+            if colored < card.min || colored > card.max {return false}
         }
-        res
+        true
     }
 
-    /// Returns `true` if all nodes are satisfied.
-    pub fn all_satisfied(&self) -> bool {
-        for i in 0..self.nodes.len() {
-            if self.node_satisfied(i).len() != 0 {return false}
+    /// Returns `true` if all cardinality constraints can still be satisfied.
+    pub fn cardinalities_feasible(&self) -> bool {
+        for card in &self.cardinalities {
+            let (colored, empty) = self.cardinality_count(card);
+            if colored > card.max || colored + empty < card.min {return false}
         }
         true
     }
 
-    /// Returns `true` if all pair constraints are satisfied.
-    pub fn pairs_satisfied(&self) -> bool {
-        for &(i, j) in &self.pairs {
-            if self.edges[j][i] < 2 {return false}
+    /// Adds a degree constraint.
+    ///
+    /// The number of colored edges (`>= 2`) of the node,
+    /// of any color, must be at least `min` and at most `max`.
+    pub fn push_degree(&mut self, node: usize, min: usize, max: usize) {
+        self.degrees.push(DegreeConstraint {node, min, max});
+    }
+
+    /// Returns the number of colored and empty edges of a node.
+    pub fn degree_count(&self, i: usize) -> (usize, usize) {
+        let mut colored = 0;
+        let mut empty = 0;
+        for j in 0..self.nodes.len() {
+            match self.get((i, j)) {
+                0 => if i != j || self.nodes[i].self_connected {empty += 1},
+                1 => {}
+                _ => colored += 1,
+            }
+        }
+        (colored, empty)
+    }
+
+    /// Returns `true` if all degree constraints are satisfied.
+    pub fn degrees_satisfied(&self) -> bool {
+        for deg in &self.degrees {
+            let (colored, _) = self.degree_count(deg.node);
+            if colored < deg.min || colored > deg.max {return false}
         }
         true
     }
 
-    /// Returns whether the graph contains triangles.
-    pub fn has_triangles(&self) -> bool {
-        if self.cache_has_triangles.get() {return true};
+    /// Returns `true` if all degree constraints can still be satisfied.
+    pub fn degrees_feasible(&self) -> bool {
+        for deg in &self.degrees {
+            let (colored, empty) = self.degree_count(deg.node);
+            if colored > deg.max || colored + empty < deg.min {return false}
+        }
+        true
+    }
+
+    /// Adds a constraint on the total number of edges of a color.
+    pub fn push_color_count(&mut self, color: Color, min: usize, max: usize) {
+        self.color_counts.push(ColorCount {color, min, max});
+    }
+
+    /// Returns the number of edges of a color and the number of undecided edges.
+    pub fn color_count(&self, color: Color) -> (usize, usize) {
         let n = self.nodes.len();
+        let mut count = 0;
+        let mut empty = 0;
         for i in 0..n {
-            for j in i+1..n {
-                if self.get((i, j)) < 2 {continue};
-                for k in j+1..n {
-                    if self.get((j, k)) >= 2 &&
-                       self.get((i, k)) >= 2
-                    {
-                        self.cache_has_triangles.set(true);
-                        return true
-                    }
-                }
+            for j in i..n {
+                let val = self.get((i, j));
+                if val == color {count += 1}
+                else if val == 0 && (i != j || self.nodes[i].self_connected) {empty += 1}
             }
         }
-        false
+        (count, empty)
     }
 
-    /// Returns `true` when for any node,
-    /// the greatest shortest cycle is either 3 or 4.
-    pub fn meet_quad_satisfied(&self) -> bool {
+    /// Returns `true` if all color count constraints are satisfied.
+    pub fn color_counts_satisfied(&self) -> bool {
+        for cc in &self.color_counts {
+            let (count, _) = self.color_count(cc.color);
+            if count < cc.min || count > cc.max {return false}
+        }
+        true
+    }
+
+    /// Returns `true` if all color count constraints can still be satisfied.
+    pub fn color_counts_feasible(&self) -> bool {
+        for cc in &self.color_counts {
+            let (count, empty) = self.color_count(cc.color);
+            if count > cc.max || count + empty < cc.min {return false}
+        }
+        true
+    }
+
+    /// Sets the bitstring label of a node.
+    ///
+    /// For more information, see `Graph::labels`.
+    pub fn set_label(&mut self, i: usize, label: u64) {
+        self.labels[i] = Some(label);
+    }
+
+    /// Adds a node description with a name, returning the index of the node.
+    ///
+    /// For more information, see `Graph::names`.
+    pub fn push_named(&mut self, name: &str, node: Node) -> usize {
+        self.push(node);
+        let i = self.nodes.len() - 1;
+        self.names[i] = Some(name.into());
+        i
+    }
+
+    /// Returns the name of a node, or its index if the node has no name.
+    pub fn node_name(&self, i: usize) -> String {
+        match &self.names[i] {
+            Some(name) => name.clone(),
+            None => i.to_string(),
+        }
+    }
+
+    /// Returns the index of the node with a name, if any.
+    pub fn find_node(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n.as_deref() == Some(name))
+    }
+
+    /// Returns `false` if an edge color is forbidden by the labels of two nodes.
+    pub fn labels_allow(&self, (i, j): (usize, usize), color: Color) -> bool {
+        if color < 2 {return true};
+        if let (Some(a), Some(b)) = (self.labels[i], self.labels[j]) {
+            let diff = a ^ b;
+            diff.count_ones() == 1 && color == 2 + diff.trailing_zeros() as Color
+        } else {true}
+    }
+
+    /// Returns `true` if all colored edges are allowed by the labels.
+    pub fn labels_satisfied(&self) -> bool {
+        if self.labels.iter().all(|label| label.is_none()) {return true};
         let n = self.nodes.len();
         for i in 0..n {
-            let mut found = false;
-            'outer: for j in 0..n {
-                if i == j {continue};
-                if self.get((i, j)) < 2 {continue};
-                for k in j+1..n {
-                    if k == i {continue};
-                    if self.get((j, k)) < 2 &&
-                       self.get((i, k)) < 2 {continue};
-                    if self.get((j, k)) >= 2 &&
-                       self.get((i, k)) >= 2 {
-                        // Triangle.
-                        found = true;
-                        break 'outer;
-                    }
-                    for k2 in 0..n {
-                        if k2 == i || k2 == j || k2 == k {continue};
-                        if self.get((k, k2)) >= 2 &&
-                           (
-                            self.get((j, k)) >= 2 &&
-                            self.get((i, k2)) >= 2 ||
-                            self.get((i, k)) >= 2 &&
-                            self.get((j, k2)) >= 2
-                           )
-                        {
-                            found = true;
-                            break 'outer;
-                        }
-                    }
-                }
+            for j in i..n {
+                if !self.labels_allow((i, j), self.get((i, j))) {return false}
             }
+        }
+        true
+    }
+
+    /// Sets a bipartition of two node colors.
+    ///
+    /// Nodes with color `a` can not connect to nodes with color `a`,
+    /// and nodes with color `b` can not connect to nodes with color `b`.
+    /// When generating GraphViz output, nodes of each color are placed at the same rank.
+    pub fn bipartition_by_color(&mut self, a: Color, b: Color) {
+        self.bipartition = Some((a, b));
+    }
+
+    /// Returns `true` if an edge between two nodes is forbidden by the bipartition.
+    pub fn bipartition_forbids(&self, (i, j): (usize, usize)) -> bool {
+        if let Some((a, b)) = self.bipartition {
+            let c = self.nodes[i].color;
+            c == self.nodes[j].color && (c == a || c == b)
+        } else {false}
+    }
 
-            if !found {
-                return false
+    /// Returns `true` if no colored edge connects nodes on the same side of the bipartition.
+    pub fn bipartition_satisfied(&self) -> bool {
+        if self.bipartition.is_none() {return true};
+        let n = self.nodes.len();
+        for i in 0..n {
+            for j in i..n {
+                if self.get((i, j)) >= 2 && self.bipartition_forbids((i, j)) {return false}
             }
         }
         true
     }
 
-    /// Returns `true` when for any quad,
-    /// the commute property is satisfied.
+    /// Adds a global constraint guarded by the presence of an edge color.
     ///
-    /// For more information, see `Graph::commute`.
-    pub fn commute_quad_satisfied(&self, commute: bool) -> bool {
-        if self.cache_commute_quad_satisfied.get() {return true};
+    /// For example, `g.push_conditional(ORANGE, Flag::Connected)`
+    /// requires the orange subgraph to be connected when it is non-empty.
+    pub fn push_conditional(&mut self, color: Color, flag: Flag) {
+        self.conditionals.push((color, flag));
+    }
+
+    /// Returns the subgraph of edges with a given color.
+    ///
+    /// Only nodes that have at least one edge of the color are included.
+    pub fn color_subgraph(&self, color: Color) -> Graph {
         let n = self.nodes.len();
+        let mut map = vec![None; n];
+        let mut g = Graph::new();
+        for (i, m) in map.iter_mut().enumerate() {
+            if (0..n).any(|j| self.get((i, j)) == color) {
+                *m = Some(g.nodes.len());
+                g.push(self.nodes[i].clone());
+            }
+        }
         for i in 0..n {
-            for j in 0..n {
-                if i == j {continue};
-                if self.get((i, j)) < 2 {continue};
-                for k in j+1..n {
-                    if k == i {continue};
-                    if self.get((j, k)) < 2 &&
-                       self.get((i, k)) < 2 {continue};
-                    for k2 in 0..n {
-                        if k2 == i || k2 == j || k2 == k {continue};
-                        if self.get((k, k2)) >= 2 &&
-                           self.get((j, k)) >= 2 &&
-                           self.get((i, k2)) >= 2
-                        {
-                            let s = if commute {
-                                self.get((i, j)) == self.get((k, k2)) &&
-                                self.get((i, k2)) == self.get((j, k))
-                            } else {
-                                let ij = self.get((i, j));
-                                let jk = self.get((j, k));
-                                let kk2 = self.get((k, k2));
-                                let ik2 = self.get((i, k2));
-                                let x0 = (ij ^ 1) == kk2;
-                                let x1 = ij == kk2;
-                                let y0 = (jk ^ 1) == ik2;
-                                let y1 = jk == ik2;
-                                if (x0 ^ x1) && (y0 ^ y1) {x0 ^ y0} else {false}
-                            };
-                            if !s {return false}
-                        } else if self.get((k, k2)) >= 2 &&
-                                  self.get((i, k)) >= 2 &&
-                                  self.get((j, k2)) >= 2
-                        {
-                            let s = if commute {
-                                self.get((i, k)) == self.get((j, k2)) &&
-                                self.get((i, j)) == self.get((k, k2))
-                            } else {
-                                let ik = self.get((i, k));
-                                let ij = self.get((i, j));
-                                let jk2 = self.get((j, k2));
-                                let kk2 = self.get((k, k2));
-                                let x0 = (ik ^ 1) == jk2;
-                                let x1 = ik == jk2;
-                                let y0 = (ij ^ 1) == kk2;
-                                let y1 = ij == kk2;
-                                if (x0 ^ x1) && (y0 ^ y1) {x0 ^ y0} else {false}
-                            };
-                            if !s {return false}
-                        }
-                    }
+            for j in i..n {
+                if let (Some(a), Some(b)) = (map[i], map[j]) {
+                    g.set((a, b), if self.get((i, j)) == color {color} else {1});
                 }
             }
         }
-        self.cache_commute_quad_satisfied.set(true);
+        g
+    }
+
+    /// Returns `true` if the graph satisfies a global constraint.
+    pub fn flag_satisfied(&self, flag: Flag) -> bool {
+        match flag {
+            Flag::NoTriangles => !self.has_triangles(),
+            Flag::MeetQuad => self.meet_quad_satisfied(),
+            Flag::Connected => self.is_connected(),
+            Flag::CommuteQuad(val) => self.commute_quad_satisfied(val),
+        }
+    }
+
+    /// Returns `true` if all conditional global constraints are satisfied.
+    pub fn conditionals_satisfied(&self) -> bool {
+        for &(color, flag) in &self.conditionals {
+            let sub = self.color_subgraph(color);
+            if sub.nodes.is_empty() {continue};
+            if !sub.flag_satisfied(flag) {return false}
+        }
         true
     }
 
-    /// Returns `true` if all nodes can be reached from any node.
-    pub fn is_connected(&self) -> bool {
-        if self.cache_connected.get() {return true};
+    /// Adds a constraint that every connected component must satisfy.
+    pub fn push_component_constraint(&mut self, constraint: ComponentConstraint) {
+        self.component_constraints.push(constraint);
+    }
+
+    /// Returns the number of colored edges of a node.
+    pub fn degree(&self, i: usize) -> usize {
+        (0..self.nodes.len()).filter(|&j| self.get((i, j)) >= 2).count()
+    }
+
+    /// Returns the connected components of colored edges.
+    ///
+    /// A node without colored edges is a component by itself.
+    pub fn components(&self) -> Vec<Vec<usize>> {
         let n = self.nodes.len();
-        let mut reachable = vec![false; n];
+        let mut visited = vec![false; n];
+        let mut res = vec![];
         for i in 0..n {
-            if self.get((0, i)) >= 2 {
-                reachable[i] = true;
+            if visited[i] {continue};
+            visited[i] = true;
+            let mut component = vec![i];
+            let mut k = 0;
+            while k < component.len() {
+                let a = component[k];
+                for (b, v) in visited.iter_mut().enumerate() {
+                    if !*v && self.get((a, b)) >= 2 {
+                        *v = true;
+                        component.push(b);
+                    }
+                }
+                k += 1;
             }
+            component.sort();
+            res.push(component);
         }
-        loop {
-            let mut changed = false;
+        res
+    }
+
+    fn with_components<T, F: FnOnce(&mut Components) -> T>(&self, f: F) -> T {
+        let mut cache = self.cache_components.borrow_mut();
+        let components = cache.get_or_insert_with(|| {
+            let n = self.nodes.len();
+            let mut components = Components::new(n);
             for i in 0..n {
-                if !reachable[i] {
-                    for j in 0..n {
-                        if reachable[j] && self.get((i, j)) >= 2 {
-                            reachable[i] = true;
-                            changed = true;
-                            break;
-                        }
-                    }
+                for j in i+1..n {
+                    if self.get((i, j)) >= 2 {components.union(i, j)}
                 }
             }
-            if !changed {break}
+            components
+        });
+        f(components)
+    }
+
+    /// Returns the number of nodes in the connected component of a node.
+    ///
+    /// The components are tracked incrementally when edges are colored,
+    /// and rebuilt when a colored edge is removed.
+    pub fn component_size(&self, i: usize) -> usize {
+        self.with_components(|c| c.size_of(i))
+    }
+
+    /// Returns `true` if two nodes are in the same connected component.
+    pub fn same_component(&self, i: usize, j: usize) -> bool {
+        self.with_components(|c| c.find(i) == c.find(j))
+    }
+
+    /// Returns the combined bounds of component size constraints, if any.
+    pub fn component_size_bounds(&self) -> Option<(usize, usize)> {
+        let mut res: Option<(usize, usize)> = None;
+        for constraint in &self.component_constraints {
+            if let ComponentConstraint::Size {min, max} = *constraint {
+                res = Some(match res {
+                    None => (min, max),
+                    Some((a, b)) => (a.max(min), b.min(max)),
+                });
+            }
         }
+        res
+    }
 
-        let val = reachable.iter().all(|&b| b);
-        if val {self.cache_connected.set(true)};
+    /// Returns `true` if component size constraints can still be satisfied.
+    ///
+    /// Fails when a component is too large,
+    /// or when a component is too small and can not grow any further.
+    pub fn component_sizes_feasible(&self) -> bool {
+        let (min, max) = match self.component_size_bounds() {
+            None => return true,
+            Some(x) => x,
+        };
+        if let Some(val) = self.cache_component_sizes_feasible.get() {return val};
+        let n = self.nodes.len();
+        let val = self.with_components(|c| {
+            let roots: Vec<usize> = (0..n).map(|i| c.find(i)).collect();
+            let mut open = vec![false; n];
+            for i in 0..n {
+                if c.size[roots[i]] > max {return false};
+                for j in i+1..n {
+                    if roots[i] != roots[j] && self.get((i, j)) == 0 {
+                        open[roots[i]] = true;
+                        open[roots[j]] = true;
+                    }
+                }
+            }
+            (0..n).all(|i| roots[i] != i || open[i] || c.size[i] >= min)
+        });
+        self.cache_component_sizes_feasible.set(Some(val));
         val
     }
 
-    /// Returns `true` if no-edges covers the upper right rectangle of the matrix form.
+    /// Returns `true` if some node has no colored edges.
+    pub fn has_isolated_nodes(&self) -> bool {
+        (0..self.nodes.len()).any(|i| self.degree(i) == 0)
+    }
+
+    /// Returns `true` if every node can still get a colored edge.
     ///
-    /// This means that the graph will be disconnected.
-    pub fn is_upper_right_disconnected(&self) -> bool {
-        if self.cache_upper_triangle_disconnected.get() {return true};
+    /// Fails when a node has no colored edges and all its edges are decided.
+    pub fn isolated_nodes_feasible(&self) -> bool {
+        if let Some(val) = self.cache_isolated_nodes_feasible.get() {return val};
         let n = self.nodes.len();
-        if n % 2 != 0 {return false}
-        for i in 0..n/2 {
-            for j in n/2..n {
-                if i == j {continue}
-                if self.get((i, j)) != 1 {return false}
+        let val = (0..n).all(|i| {
+            (0..n).any(|j| {
+                let edge = self.get((i, j));
+                edge >= 2 || edge == 0 && (i != j || self.nodes[i].self_connected)
+            })
+        });
+        self.cache_isolated_nodes_feasible.set(Some(val));
+        val
+    }
+
+    /// Returns `true` if a list of nodes can be 2-colored along colored edges.
+    pub fn is_bipartite_nodes(&self, nodes: &[usize]) -> bool {
+        let n = self.nodes.len();
+        let mut side: Vec<Option<bool>> = vec![None; n];
+        for &start in nodes {
+            if side[start].is_some() {continue};
+            side[start] = Some(false);
+            let mut stack = vec![start];
+            while let Some(a) = stack.pop() {
+                let sa = side[a].unwrap();
+                for (b, sb) in side.iter_mut().enumerate() {
+                    if self.get((a, b)) < 2 {continue};
+                    match *sb {
+                        None => {
+                            *sb = Some(!sa);
+                            stack.push(b);
+                        }
+                        Some(sb) => if sb == sa {return false},
+                    }
+                }
             }
         }
-        self.cache_upper_triangle_disconnected.set(true);
         true
     }
 
-    /// Returns a list of possible actions for a node.
-    pub fn colors(&self, (i, j): (usize, usize)) -> Vec<Color> {
-        if self.get((i, j)) != 0 {return vec![]};
-        if !self.nodes[i].self_connected && i == j {return vec![]};
-        if self.no_triangles && self.has_triangles() {return vec![]};
-        if self.connected && self.is_upper_right_disconnected() {return vec![]};
-        if let Some(val) = self.commute_quad {if !self.commute_quad_satisfied(val) {return vec![]}};
+    /// Returns `true` if every connected component satisfies the component constraints.
+    pub fn component_constraints_satisfied(&self) -> bool {
+        if self.component_constraints.is_empty() {return true};
+        for component in &self.components() {
+            for constraint in &self.component_constraints {
+                let ok = match *constraint {
+                    ComponentConstraint::Regular => {
+                        let d = self.degree(component[0]);
+                        component.iter().all(|&i| self.degree(i) == d)
+                    }
+                    ComponentConstraint::Bipartite => self.is_bipartite_nodes(component),
+                    ComponentConstraint::Size {min, max} =>
+                        component.len() >= min && component.len() <= max,
+                };
+                if !ok {return false}
+            }
+        }
+        true
+    }
+
+    /// Adds a constraint that two nodes have identical incident edge colors.
+    pub fn push_twins(&mut self, (i, j): (usize, usize)) {
+        self.push_symmetric_difference((i, j), 0);
+    }
+
+    /// Adds a constraint on the symmetric difference between incident edge colors of two nodes.
+    ///
+    /// The incident edge colors of a node are counted as a multiset.
+    /// When one node has an extra color, the size of the difference is `1`.
+    /// When one color is replaced by another, the size of the difference is `2`.
+    pub fn push_symmetric_difference(&mut self, (i, j): (usize, usize), size: usize) {
+        self.symmetric_differences.push(SymmetricDifference {nodes: (i.min(j), i.max(j)), size});
+    }
+
+    /// Returns the sorted colors of colored edges of a node.
+    pub fn incident_colors(&self, i: usize) -> Vec<Color> {
+        let mut res: Vec<Color> = (0..self.nodes.len())
+            .map(|j| self.get((i, j)))
+            .filter(|&edge| edge >= 2).collect();
+        res.sort();
+        res
+    }
+
+    /// Returns the size of the symmetric difference between incident edge colors of two nodes.
+    pub fn symmetric_difference(&self, (i, j): (usize, usize)) -> usize {
+        let a = self.incident_colors(i);
+        let b = self.incident_colors(j);
+        let (mut x, mut y, mut res) = (0, 0, 0);
+        while x < a.len() && y < b.len() {
+            if a[x] == b[y] {
+                x += 1;
+                y += 1;
+            } else if a[x] < b[y] {
+                x += 1;
+                res += 1;
+            } else {
+                y += 1;
+                res += 1;
+            }
+        }
+        res + (a.len() - x) + (b.len() - y)
+    }
+
+    /// Returns `true` if all edges of a node are decided.
+    pub fn node_decided(&self, i: usize) -> bool {
+        (0..self.nodes.len()).all(|j| {
+            self.get((i, j)) != 0 || i == j && !self.nodes[i].self_connected
+        })
+    }
+
+    /// Returns `true` if all symmetric difference constraints are satisfied.
+    pub fn symmetric_differences_satisfied(&self) -> bool {
+        self.symmetric_differences.iter()
+            .all(|d| self.symmetric_difference(d.nodes) == d.size)
+    }
+
+    /// Returns `true` if all symmetric difference constraints can still be satisfied.
+    ///
+    /// A constraint is checked when all edges of both nodes are decided.
+    pub fn symmetric_differences_feasible(&self) -> bool {
+        self.symmetric_differences.iter().all(|d| {
+            !self.node_decided(d.nodes.0) ||
+            !self.node_decided(d.nodes.1) ||
+            self.symmetric_difference(d.nodes) == d.size
+        })
+    }
+
+    /// Returns `true` if an edge from node `i` to node `j` has the node color of an edge constraint.
+    ///
+    /// Self-loops match constraints to `SELF_LOOP` and to the color of the node itself.
+    pub fn matches(&self, con: Constraint, i: usize, j: usize) -> bool {
+        con.node == self.nodes[j].color || (i == j && con.node == SELF_LOOP)
+    }
+
+    /// Returns the first unused edge constraint of node `i` that an edge to node `j` satisfies.
+    ///
+    /// Self-loops prefer constraints to `SELF_LOOP`.
+    pub(crate) fn match_slot(&self, i: usize, j: usize, edge: Color, used: &[bool]) -> Option<usize> {
+        let edges = &self.nodes[i].edges;
+        let find = |node: Color| (0..edges.len())
+            .find(|&k| !used[k] && edges[k].edge == edge && edges[k].node == node);
+        if i == j {
+            if let Some(k) = find(SELF_LOOP) {return Some(k)};
+        }
+        find(self.nodes[j].color)
+    }
+
+    /// Returns a list of edge constraints that makes a node unsatisfied.
+    ///
+    /// If the returned list is empty, then the node is satisfied.
+    pub fn node_satisfied(&self, i: usize) -> Vec<Constraint> {
+        if self.cache_node_satisfied[i].get() {return vec![]};
+        let mut res = vec![];
+        let mut m = vec![false; self.nodes[i].edges.len()];
+        for j in 0..self.nodes.len() {
+            let edge = self.get((i, j));
+            if edge == 0 {continue};
+            if let Some(k) = self.match_slot(i, j, edge, &m) {m[k] = true};
+        }
+        for (k, &mk) in m.iter().enumerate() {
+            if !mk {
+                res.push(self.nodes[i].edges[k]);
+            }
+        }
+        if res.is_empty() {
+            self.cache_node_satisfied[i].set(true);
+        }
+        res
+    }
+
+    /// Returns `true` if all nodes are satisfied.
+    pub fn all_satisfied(&self) -> bool {
+        for i in 0..self.nodes.len() {
+            if !self.node_satisfied(i).is_empty() {return false}
+        }
+        true
+    }
+
+    /// Returns `true` if all pair constraints are satisfied.
+    pub fn pairs_satisfied(&self) -> bool {
+        for &(i, j) in &self.pairs {
+            if self.get((i, j)) < 2 {return false}
+        }
+        true
+    }
+
+    /// Returns `true` if all anti-pair constraints are satisfied.
+    pub fn anti_pairs_satisfied(&self) -> bool {
+        self.anti_pairs.iter().all(|&pos| self.get(pos) < 2)
+    }
+
+    /// Returns whether the graph contains triangles.
+    pub fn has_triangles(&self) -> bool {
+        self.triangles > 0
+    }
+
+    /// Returns `true` when for any node,
+    /// the greatest shortest cycle is either 3 or 4.
+    pub fn meet_quad_satisfied(&self) -> bool {
+        self.local_girth_satisfied(4)
+    }
+
+    /// Adds a pair of edge colors that anticommute.
+    ///
+    /// This is useful when colors are not numbered by the even/odd convention.
+    /// Once a pair is added, only added pairs anticommute, see `Graph::sign_pairs`.
+    pub fn push_sign_pair(&mut self, a: Color, b: Color) {
+        self.sign_pairs.push((a.min(b), a.max(b)));
+    }
+
+    /// Returns the edge color that anticommutes with an edge color, if any.
+    ///
+    /// For more information, see `Graph::sign_pairs`.
+    pub fn flipped(&self, color: Color) -> Option<Color> {
+        if self.sign_pairs.is_empty() {return Some(color ^ 1)};
+        self.sign_pairs.iter().find_map(|&(a, b)| {
+            if a == color {Some(b)} else if b == color {Some(a)} else {None}
+        })
+    }
+
+    /// Adds a rule for quads spanned by two edge colors.
+    ///
+    /// For more information, see `Graph::quad_rules`.
+    pub fn push_quad_rule(&mut self, a: Color, b: Color, commute: Option<bool>) {
+        self.quad_rules.push(QuadRule {colors: (a.min(b), a.max(b)), commute});
+    }
+
+    /// Restricts quad constraints to a subset of nodes.
+    ///
+    /// This is useful when only part of the graph should satisfy quad constraints,
+    /// e.g. an Adinkra attached to a scaffold graph.
+    /// For more information, see `Graph::quad_nodes`.
+    pub fn restrict_quads(&mut self, nodes: &[usize]) {
+        let mut nodes = nodes.to_vec();
+        nodes.sort();
+        nodes.dedup();
+        self.quad_nodes = Some(nodes);
+    }
+
+    /// Returns `true` if quad constraints apply to a node.
+    ///
+    /// For more information, see `Graph::quad_nodes`.
+    pub fn in_quad_nodes(&self, i: usize) -> bool {
+        match &self.quad_nodes {
+            None => true,
+            Some(nodes) => nodes.binary_search(&i).is_ok(),
+        }
+    }
+
+    /// Returns the smallest color among an edge color and the color that anticommutes with it.
+    fn sign_class(&self, color: Color) -> Color {
+        self.flipped(color).map(|c| c.min(color)).unwrap_or(color)
+    }
+
+    /// Returns `true` if a quad satisfies the commute property.
+    ///
+    /// The colors `x` and `y` are the pairs of opposite edges.
+    /// Uses the rule in `Graph::quad_rules` for the colors that span the quad, if any,
+    /// and otherwise the default, where `None` means unconstrained.
+    pub(crate) fn quad_satisfied(&self, x: (Color, Color), y: (Color, Color), default: Option<bool>) -> bool {
+        let mut commute = default;
+        let (a, b) = (self.sign_class(x.0), self.sign_class(y.0));
+        if self.sign_class(x.1) == a && self.sign_class(y.1) == b {
+            let rule = self.quad_rules.iter().find(|r| {
+                let (c, d) = (self.sign_class(r.colors.0), self.sign_class(r.colors.1));
+                (c, d) == (a, b) || (c, d) == (b, a)
+            });
+            if let Some(rule) = rule {commute = rule.commute};
+        }
+        match commute {
+            None => true,
+            Some(true) => x.0 == x.1 && y.0 == y.1,
+            Some(false) => {
+                let x0 = self.flipped(x.0) == Some(x.1);
+                let x1 = x.0 == x.1;
+                let y0 = self.flipped(y.0) == Some(y.1);
+                let y1 = y.0 == y.1;
+                if (x0 ^ x1) && (y0 ^ y1) {x0 ^ y0} else {false}
+            }
+        }
+    }
+
+    /// Returns `true` when for any quad,
+    /// the commute property is satisfied.
+    ///
+    /// Rules in `Graph::quad_rules` take precedence.
+    /// For more information, see `Graph::commute_quad`.
+    pub fn commute_quad_satisfied(&self, commute: bool) -> bool {
+        self.quads_satisfied_with(Some(commute))
+    }
+
+    /// Returns `true` when every quad satisfies `Graph::commute_quad` and `Graph::quad_rules`.
+    pub fn quads_satisfied(&self) -> bool {
+        self.quads_satisfied_with(self.commute_quad)
+    }
+
+    fn quads_satisfied_with(&self, default: Option<bool>) -> bool {
+        if default.is_none() && self.quad_rules.is_empty() {return true};
+        if self.cache_commute_quad_satisfied.get() {return true};
+        let n = self.nodes.len();
+        for i in 0..n {
+            if !self.in_quad_nodes(i) {continue};
+            for j in 0..n {
+                if i == j || !self.in_quad_nodes(j) {continue};
+                if self.get((i, j)) < 2 {continue};
+                for k in j+1..n {
+                    if k == i || !self.in_quad_nodes(k) {continue};
+                    if self.get((j, k)) < 2 &&
+                       self.get((i, k)) < 2 {continue};
+                    for k2 in 0..n {
+                        if k2 == i || k2 == j || k2 == k || !self.in_quad_nodes(k2) {continue};
+                        if self.get((k, k2)) >= 2 &&
+                           self.get((j, k)) >= 2 &&
+                           self.get((i, k2)) >= 2
+                        {
+                            let x = (self.get((i, j)), self.get((k, k2)));
+                            let y = (self.get((j, k)), self.get((i, k2)));
+                            if !self.quad_satisfied(x, y, default) {return false}
+                        } else if self.get((k, k2)) >= 2 &&
+                                  self.get((i, k)) >= 2 &&
+                                  self.get((j, k2)) >= 2
+                        {
+                            let x = (self.get((i, k)), self.get((j, k2)));
+                            let y = (self.get((i, j)), self.get((k, k2)));
+                            if !self.quad_satisfied(x, y, default) {return false}
+                        }
+                    }
+                }
+            }
+        }
+        self.cache_commute_quad_satisfied.set(true);
+        true
+    }
+
+    /// Returns `true` if all nodes can be reached from any node.
+    ///
+    /// This uses the incrementally tracked connected components.
+    pub fn is_connected(&self) -> bool {
+        let n = self.nodes.len();
+        n == 0 || self.component_size(0) == n
+    }
+
+    /// Returns `true` if the graph can still become connected.
+    ///
+    /// This is the case when all nodes can be reached from any node
+    /// along edges that are not no-edge.
+    pub fn possibly_connected(&self) -> bool {
+        if let Some(val) = self.cache_possibly_connected.get() {return val};
+        let n = self.nodes.len();
+        let mut visited = vec![false; n];
+        let mut stack = vec![];
+        if n > 0 {
+            visited[0] = true;
+            stack.push(0);
+        }
+        let mut count = stack.len();
+        while let Some(a) = stack.pop() {
+            for (b, v) in visited.iter_mut().enumerate() {
+                if !*v && self.get((a, b)) != 1 {
+                    *v = true;
+                    count += 1;
+                    stack.push(b);
+                }
+            }
+        }
+        let val = count == n;
+        self.cache_possibly_connected.set(Some(val));
+        val
+    }
+
+    /// Returns `true` if no-edges covers the upper right rectangle of the matrix form.
+    ///
+    /// This means that the graph will be disconnected.
+    /// For a general check, see `Graph::possibly_connected`.
+    pub fn is_upper_right_disconnected(&self) -> bool {
+        if self.cache_upper_triangle_disconnected.get() {return true};
+        let n = self.nodes.len();
+        if n % 2 != 0 {return false}
+        for i in 0..n/2 {
+            for j in n/2..n {
+                if i == j {continue}
+                if self.get((i, j)) != 1 {return false}
+            }
+        }
+        self.cache_upper_triangle_disconnected.set(true);
+        true
+    }
+
+    /// Returns `true` if the constraints on the whole graph can still be satisfied.
+    ///
+    /// This does not check the edge constraints of nodes.
+    pub(crate) fn feasible(&self) -> bool {
+        self.min_girth_satisfied() &&
+        if self.no_triangles {!self.has_triangles()} else {true} &&
+        self.planar_satisfied() &&
+        if self.bipartite {!self.has_odd_cycle()} else {true} &&
+        if self.connected {self.possibly_connected()} else {true} &&
+        if self.no_isolated_nodes {self.isolated_nodes_feasible()} else {true} &&
+        self.quads_satisfied() &&
+        self.cardinalities_feasible() &&
+        self.degrees_feasible() &&
+        self.color_counts_feasible() &&
+        self.component_sizes_feasible() &&
+        self.symmetric_differences_feasible() &&
+        self.edit_distances_feasible() &&
+        self.required_subgraphs_feasible() &&
+        self.symmetry_break_feasible()
+    }
+
+    /// Returns a list of possible actions for a node.
+    pub fn colors(&self, (i, j): (usize, usize)) -> Vec<Color> {
+        if self.get((i, j)) != 0 {return vec![]};
+        if !self.nodes[i].self_connected && i == j {return vec![]};
+        if !self.feasible() {return vec![]};
         let mut res = vec![];
         let errors = self.node_satisfied(i);
         let other_errors = self.node_satisfied(j);
         for err in &errors {
+            if i == j && err.node == SELF_LOOP {
+                res.push(err.edge);
+                continue;
+            }
             if err.node != self.nodes[j].color {continue}
             for other_err in &other_errors {
                 if err.edge == other_err.edge &&
@@ -563,30 +1632,301 @@ impl Graph {
                 }
             }
         }
-        res.push(1);
+        // When a cardinality bound is tight, the edge is either forced or forbidden.
+        let mut must_color = false;
+        for card in &self.cardinalities {
+            if card.edges.binary_search(&(i.min(j), i.max(j))).is_err() {continue};
+            let (colored, empty) = self.cardinality_count(card);
+            if colored >= card.max {res.clear()}
+            else if colored + empty <= card.min {must_color = true}
+        }
+        for deg in &self.degrees {
+            if deg.node != i && deg.node != j {continue};
+            let (colored, empty) = self.degree_count(deg.node);
+            if colored >= deg.max {res.clear()}
+            else if colored + empty <= deg.min {must_color = true}
+        }
+        for cc in &self.color_counts {
+            let (count, empty) = self.color_count(cc.color);
+            if count >= cc.max {res.retain(|&c| c != cc.color)}
+            else if count + empty <= cc.min {
+                // Every undecided edge must get the color.
+                res.retain(|&c| c == cc.color);
+                must_color = true;
+            }
+        }
+        if self.anti_pairs.contains(&(i.min(j), i.max(j))) {res.clear()};
+        if self.bipartition_forbids((i, j)) {res.clear()};
+        if self.bipartite && self.same_side(i, j) {res.clear()};
+        res.retain(|&color| self.labels_allow((i, j), color));
+        if let Some((_, max)) = self.component_size_bounds() {
+            if !self.same_component(i, j) &&
+               self.component_size(i) + self.component_size(j) > max
+            {
+                res.clear();
+            }
+        }
+        if let Some(min_girth) = self.min_girth {
+            // A colored edge would close a cycle shorter than the minimum girth.
+            if !res.is_empty() && i != j &&
+               self.path_shorter_than((i, j), min_girth.saturating_sub(1)).is_some()
+            {
+                res.clear();
+            }
+        }
+        if !must_color {res.push(1)};
+        if let Some(allowed) = self.allowed((i, j)) {res.retain(|c| allowed.contains(c))};
+        for d in &self.edit_distances {
+            // When the bound is tight, the edge must agree with the reference graph.
+            if self.edit_distance_count(d).0 >= d.max {
+                let val = d.get((i, j));
+                res.retain(|&c| c == val);
+            }
+        }
         res.sort();
         res.dedup();
         res
     }
 }
 
+/// Describes how an edge got its value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Provenance {
+    /// The edge has no value.
+    Unassigned,
+    /// The edge was set before solving, or outside the solver.
+    Pinned,
+    /// The edge was deduced by propagation, e.g. as a simple move.
+    Propagated,
+    /// The edge was chosen by branching.
+    Branched,
+}
+
+/// The node color of an edge constraint that is satisfied by a self-loop.
+///
+/// A constraint `Constraint {edge, node: SELF_LOOP}` requires a self-loop of color `edge`,
+/// so the node must be self-connected.
+/// It is not satisfied by edges to other nodes, even when they have this color.
+pub const SELF_LOOP: Color = Color::MAX;
+
 /// Stores edge constraint.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Constraint {
     /// The edge color.
     pub edge: Color,
     /// The node color.
+    ///
+    /// Use `SELF_LOOP` to require a self-loop.
     pub node: Color,
 }
 
+impl Constraint {
+    /// Creates an edge constraint that requires a self-loop of an edge color.
+    pub fn self_loop(edge: Color) -> Constraint {
+        Constraint {edge, node: SELF_LOOP}
+    }
+}
+
+/// Global constraints that can be applied to a graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Flag {
+    /// Triangle cycles are not allowed.
+    NoTriangles,
+    /// Any shortest cycle for any vertex must be 4 or less.
+    MeetQuad,
+    /// Any node can be reached from any other node.
+    Connected,
+    /// Every quad commutes (`true`) or anticommutes (`false`).
+    CommuteQuad(bool),
+}
+
+/// Tracks connected components using union-find.
+#[derive(Clone, Debug)]
+struct Components {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl Components {
+    fn new(n: usize) -> Components {
+        Components {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, i: usize, j: usize) {
+        let (mut a, mut b) = (self.find(i), self.find(j));
+        if a == b {return};
+        if self.size[a] < self.size[b] {std::mem::swap(&mut a, &mut b)};
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+
+    fn size_of(&mut self, i: usize) -> usize {
+        let a = self.find(i);
+        self.size[a]
+    }
+}
+
+/// Tracks the sides of nodes in connected components using union-find.
+///
+/// Each node stores its parity relative to the parent.
+#[derive(Clone, Debug)]
+struct Parity {
+    parent: Vec<usize>,
+    parity: Vec<bool>,
+    odd_cycle: bool,
+}
+
+impl Parity {
+    fn new(n: usize) -> Parity {
+        Parity {
+            parent: (0..n).collect(),
+            parity: vec![false; n],
+            odd_cycle: false,
+        }
+    }
+
+    /// Returns the root and the parity of a node relative to the root.
+    fn find(&mut self, i: usize) -> (usize, bool) {
+        let p = self.parent[i];
+        if p == i {return (i, false)};
+        let (root, parity) = self.find(p);
+        self.parent[i] = root;
+        self.parity[i] ^= parity;
+        (root, self.parity[i])
+    }
+
+    fn union(&mut self, i: usize, j: usize) {
+        let (a, pa) = self.find(i);
+        let (b, pb) = self.find(j);
+        if a == b {
+            if pa == pb {self.odd_cycle = true};
+        } else {
+            self.parent[b] = a;
+            self.parity[b] = !(pa ^ pb);
+        }
+    }
+
+    /// Returns `true` if two nodes are connected and on the same side.
+    fn same_side(&mut self, i: usize, j: usize) -> bool {
+        let (a, pa) = self.find(i);
+        let (b, pb) = self.find(j);
+        a == b && pa == pb
+    }
+}
+
+/// Constraints applied to each connected component individually.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComponentConstraint {
+    /// All nodes in the component have the same degree.
+    Regular,
+    /// The component can be 2-colored.
+    Bipartite,
+    /// The number of nodes in the component is within bounds.
+    Size {
+        /// The minimum number of nodes.
+        min: usize,
+        /// The maximum number of nodes.
+        max: usize,
+    },
+}
+
+/// Stores a rule for quads spanned by two edge colors.
+///
+/// For more information, see `Graph::quad_rules`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadRule {
+    /// The edge colors, sorted.
+    pub colors: (Color, Color),
+    /// Whether quads commute or anticommute, or `None` when unconstrained.
+    pub commute: Option<bool>,
+}
+
+/// Stores a constraint on the difference between incident edge colors of two nodes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymmetricDifference {
+    /// The nodes, using sorted indices.
+    pub nodes: (usize, usize),
+    /// The size of the symmetric difference.
+    pub size: usize,
+}
+
+/// Stores a bound on the number of edges where a solution differs from a reference graph.
+///
+/// For more information, see `Graph::push_edit_distance`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EditDistance {
+    /// The colored edges of the reference graph, sorted by sorted indices.
+    pub edges: Vec<((usize, usize), Color)>,
+    /// The maximum number of edges that differ.
+    pub max: usize,
+}
+
+/// Stores a cardinality constraint over a set of edges.
+///
+/// This generalizes pair constraints, e.g. a pair is a single edge with `min = 1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cardinality {
+    /// The edges, using sorted indices.
+    pub edges: Vec<(usize, usize)>,
+    /// The minimum number of colored edges.
+    pub min: usize,
+    /// The maximum number of colored edges.
+    pub max: usize,
+}
+
+/// Stores bounds on the number of colored edges of a node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DegreeConstraint {
+    /// The node index.
+    pub node: usize,
+    /// The minimum number of colored edges.
+    pub min: usize,
+    /// The maximum number of colored edges.
+    pub max: usize,
+}
+
+/// Stores bounds on the total number of edges of a color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorCount {
+    /// The edge color.
+    pub color: Color,
+    /// The minimum number of edges.
+    pub min: usize,
+    /// The maximum number of edges.
+    pub max: usize,
+}
+
 /// Stores a description of a node.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     /// The color of the node.
     pub color: Color,
     /// Whether the node can be self-connected.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub self_connected: bool,
     /// The edges constraints of the node.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub edges: Vec<Constraint>,
 }
 
@@ -620,4 +1960,3085 @@ mod tests {
         g.set((0, 1), 2);
         assert!(g.all_satisfied());
     }
+
+    #[test]
+    fn cardinality() {
+        let mut g = Graph::new();
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        for _ in 0..4 {g.push(a.clone())}
+        g.push_cardinality(&[(1, 0), (0, 2)], 0, 0);
+        let solution = g.solve(SolveSettings::new()).unwrap();
+        assert_eq!(solution.puzzle.get((0, 3)), 2);
+        assert_eq!(solution.puzzle.get((1, 2)), 2);
+
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.push_cardinality(&[(0, 1), (2, 3)], 2, 2);
+        let solution = g.solve(SolveSettings::new()).unwrap();
+        assert_eq!(solution.puzzle.get((0, 1)), 2);
+        assert_eq!(solution.puzzle.get((2, 3)), 2);
+    }
+
+    #[test]
+    fn conditional() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.push_conditional(4, Flag::Connected);
+        assert!(g.clone().solve(SolveSettings::new()).is_some());
+        g.push_conditional(2, Flag::Connected);
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn component_constraints() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.push_component_constraint(ComponentConstraint::Size {min: 2, max: 2});
+        let solution = g.clone().solve(SolveSettings::new()).unwrap();
+        assert_eq!(solution.puzzle.components().len(), 2);
+        g.push_component_constraint(ComponentConstraint::Size {min: 3, max: 4});
+        assert!(g.solve(SolveSettings::new()).is_none());
+
+        let b = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..3 {g.push(b.clone())}
+        g.push_component_constraint(ComponentConstraint::Bipartite);
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn component_size() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.set((0, 1), 2);
+        g.set((1, 2), 2);
+        assert_eq!(g.component_size(0), 3);
+        assert!(g.same_component(0, 2));
+        g.set((1, 2), 1);
+        assert_eq!(g.component_size(0), 2);
+        assert!(!g.same_component(0, 2));
+
+        g.push_component_constraint(ComponentConstraint::Size {min: 3, max: 3});
+        let solution = g.solve(SolveSettings::new()).unwrap();
+        let g = solution.puzzle;
+        assert!((0..6).all(|i| g.component_size(i) == 3));
+    }
+
+    #[test]
+    fn no_isolated_nodes() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let b = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(a);
+        g.push(b);
+        assert!(g.clone().solve(SolveSettings::new()).is_some());
+        g.no_isolated_nodes = true;
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn symmetric_difference() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let b = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(a);
+        g.push(b.clone());
+        g.push(b);
+        g.push_twins((0, 1));
+        assert!(g.clone().solve(SolveSettings::new()).is_some());
+        g.push_symmetric_difference((0, 2), 2);
+        assert!(g.clone().solve(SolveSettings::new()).is_some());
+        g.push_twins((1, 3));
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn node_color_graph() {
+        let boson = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 1},
+                Constraint {edge: 3, node: 1},
+            ],
+        };
+        let fermion = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = NodeColorGraph::new();
+        g.push(boson.clone());
+        for _ in 0..3 {g.push_domain(vec![boson.clone(), fermion.clone()])}
+        let solution = g.solve(SolveSettings::new()).unwrap();
+        let g = solution.puzzle;
+        let bosons = (0..4).filter(|&i| g.node(i).unwrap().color == 0).count();
+        assert_eq!(bosons, 2);
+        assert!(g.graph.is_solved());
+    }
+
+    #[test]
+    fn bipartition() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(a);
+        assert!(g.clone().solve(SolveSettings::new()).is_some());
+        g.bipartition_by_color(0, 1);
+        assert_eq!(g.colors((0, 1)), vec![1]);
+        assert!(g.graphviz("dot", &["black"], &["black"]).contains("{rank=same; 0; 1;}"));
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn digraph() {
+        let mut g = Digraph::new();
+        let a = DiNode {
+            color: 0,
+            self_connected: false,
+            in_edges: vec![Constraint {edge: 2, node: 0}],
+            out_edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        for _ in 0..3 {g.push(a.clone())}
+        g.connected = true;
+        let solution = g.solve(SolveSettings::new()).unwrap();
+        let g = solution.puzzle;
+        for i in 0..3 {
+            assert_eq!((0..3).filter(|&j| g.get((i, j)) == 2).count(), 1);
+            assert_eq!((0..3).filter(|&j| g.get((j, i)) == 2).count(), 1);
+        }
+        assert!(g.get((0, 1)) != g.get((1, 0)));
+    }
+
+    #[test]
+    fn labels() {
+        let mut g = Graph::new();
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: (0..3).map(|k| Constraint {edge: 2 + k, node: 0}).collect(),
+        };
+        for i in 0..8 {
+            g.push(a.clone());
+            g.set_label(i, i as u64);
+        }
+        assert_eq!(g.colors((0, 1)), vec![1, 2]);
+        assert_eq!(g.colors((0, 3)), vec![1]);
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(), 1);
+        let solution = g.solve(SolveSettings::new()).unwrap();
+        let g = solution.puzzle;
+        for i in 0..8 {
+            for k in 0..3 {
+                assert_eq!(g.get((i, i ^ (1 << k))), 2 + k as Color);
+            }
+        }
+    }
+
+    #[test]
+    fn solve_all() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let solutions: Vec<Graph> = g.solve_all(SearchSettings::new())
+            .map(|solution| solution.puzzle).collect();
+        assert_eq!(solutions.len(), 6);
+        for i in 0..solutions.len() {
+            assert!(solutions[i].is_solved());
+            for j in i+1..solutions.len() {
+                assert!(solutions[i].edges != solutions[j].edges);
+            }
+        }
+    }
+
+    #[test]
+    fn compile_report() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.set((0, 1), 2);
+        let report = g.compile_report();
+        assert_eq!(report.nodes, 8);
+        assert_eq!(report.assigned, 1);
+        assert_eq!(report.variables, 27);
+        assert_eq!(report.domain_sizes, vec![0, 0, 27]);
+        assert_eq!(report.constraints, vec!["no triangles".to_string()]);
+        assert!((report.log10_search_space - 27.0 * 2f64.log10()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn slot_matching() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let settings = SearchSettings::new().encoding(Encoding::SlotMatching);
+        let solutions: Vec<Graph> = g.clone().solve_all(settings)
+            .map(|solution| solution.puzzle).collect();
+        assert_eq!(solutions.len(), 6);
+        assert!(solutions.iter().all(|g| g.is_solved()));
+
+        let mut g = Graph::new();
+        for _ in 0..3 {g.push(a.clone())}
+        let settings = SearchSettings::new().encoding(Encoding::SlotMatching);
+        assert!(g.clone().solve_all(settings).next().is_none());
+        let mut edge_colors = g.clone().solve_all(SearchSettings::new());
+        assert!(edge_colors.next().is_none());
+        let settings = SearchSettings::new().encoding(Encoding::Mixed);
+        let mut mixed = g.solve_all(settings);
+        assert!(mixed.next().is_none());
+        assert!(mixed.iterations() <= edge_colors.iterations());
+    }
+
+    #[test]
+    fn mixed() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let forced = g.channel_slots().unwrap();
+        assert!(forced.is_empty());
+        let settings = SearchSettings::new().encoding(Encoding::Mixed);
+        let solutions: Vec<Graph> = g.solve_all(settings)
+            .map(|solution| solution.puzzle).collect();
+        assert_eq!(solutions.len(), 6);
+        assert!(solutions.iter().all(|g| g.is_solved()));
+    }
+
+    #[test]
+    fn solve_parallel() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let strategies = [
+            Strategy::min_colors(),
+            Strategy::fst_empty(),
+            Strategy::min_colors_no_edge_first(),
+        ];
+        let solution = g.clone().solve_parallel(SearchSettings::new(), &strategies).unwrap();
+        assert!(solution.puzzle.is_solved());
+
+        let settings = SearchSettings::new();
+        let solution = Solutions::with_strategy(g.clone(), settings, Strategy::fst_empty()).next().unwrap();
+        assert!(solution.puzzle.is_solved());
+
+        let settings = SearchSettings::new().deterministic(true);
+        let expected = Solutions::with_strategy(g.clone(), settings.clone(), strategies[0])
+            .next().unwrap().puzzle;
+        for _ in 0..3 {
+            let solution = g.clone().solve_parallel(settings.clone(), &strategies).unwrap();
+            assert_eq!(solution.puzzle.edges, expected.edges);
+        }
+    }
+
+    #[test]
+    fn symmetry_break() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        assert_eq!(g.interchangeable_nodes(), vec![vec![0, 1, 2, 3]]);
+        g.symmetry_break = true;
+        let solutions: Vec<Graph> = g.clone().solve_all(SearchSettings::new())
+            .map(|solution| solution.puzzle).collect();
+        assert!(!solutions.is_empty());
+        assert!(solutions.len() < 6);
+        assert!(solutions.iter().all(|g| g.is_solved()));
+
+        g.push_pair((0, 1));
+        assert_eq!(g.interchangeable_nodes(), vec![vec![2, 3]]);
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let plain = g.clone().solve(SolveSettings::new()).unwrap();
+        g.symmetry_break = true;
+        let solution = g.solve(SolveSettings::new()).unwrap();
+        assert!(solution.puzzle.is_solved());
+        // Symmetry breaking also prunes the search of `Graph::solve`.
+        assert!(solution.iterations < plain.iterations);
+    }
+
+    #[test]
+    fn without_edge() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let retraction = solution.without_edge((0, 1));
+        let other = retraction.solve(SearchSettings::new()).unwrap().puzzle;
+        assert!(other.is_solved());
+        assert_ne!(other.get((0, 1)).max(1), solution.get((0, 1)).max(1));
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..2 {g.push(a.clone())}
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(!solution.without_edge((0, 1)).is_still_solvable(SearchSettings::new()));
+    }
+
+    #[test]
+    fn forced_edges() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(b.clone());
+        g.push(a.clone());
+        g.push(b.clone());
+        let backbone = g.forced_edges(SearchSettings::new()).unwrap();
+        assert!(backbone.forced.is_empty());
+        assert_eq!(backbone.free, vec![(0, 1), (0, 3), (1, 2), (2, 3)]);
+        assert!(backbone.unknown.is_empty());
+
+        g.set((0, 1), 2);
+        let backbone = g.forced_edges(SearchSettings::new()).unwrap();
+        assert_eq!(backbone.forced, vec![((0, 1), 2), ((0, 3), 1), ((1, 2), 1), ((2, 3), 2)]);
+        assert!(backbone.free.is_empty());
+    }
+
+    #[test]
+    fn min_girth() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..10 {g.push(a.clone())}
+        g.min_girth = Some(5);
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.is_solved());
+        // The Petersen graph.
+        assert_eq!(solution.girth(), Some(5));
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2]
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.min_girth = Some(6);
+        assert_eq!(g.clone().solve(SolveSettings::new()).unwrap().puzzle.girth(), Some(6));
+        g.min_girth = Some(7);
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn color_symmetry_break() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        assert_eq!(g.interchangeable_colors(), vec![vec![2, 3]]);
+        g.color_symmetry_break = true;
+        let solutions: Vec<Graph> = g.clone().solve_all(SearchSettings::new())
+            .map(|solution| solution.puzzle).collect();
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(|g| g.is_solved()));
+
+        g.commute_quad = Some(false);
+        assert!(g.interchangeable_colors().is_empty());
+        g.commute_quad = None;
+        g.set((0, 1), 2);
+        assert!(g.interchangeable_colors().is_empty());
+
+        // Swapping 2 and 4 without swapping 3 and 5 does not preserve anticommuting quads.
+        let b = Node {edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 4, node: 0}], ..a};
+        let mut h = Graph::new();
+        for _ in 0..4 {h.push(b.clone())}
+        assert_eq!(h.interchangeable_colors(), vec![vec![2, 4]]);
+        h.commute_quad = Some(false);
+        assert!(h.interchangeable_colors().is_empty());
+    }
+
+    #[test]
+    fn max_local_girth() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2]
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.max_local_girth = Some(3);
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert!((0..6).all(|i| solution.local_girth(i) == Some(3)));
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.max_local_girth = Some(4);
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.meet_quad_satisfied());
+    }
+
+    #[test]
+    fn degrees() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        for i in 0..8 {g.push_degree(i, 3, 3)}
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert!((0..8).all(|i| solution.degree(i) == 3));
+
+        g.push_degree(0, 0, 2);
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn planar() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 4]
+        };
+        let mut g = Graph::new();
+        for _ in 0..5 {g.push(a.clone())}
+        let k5 = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(!k5.is_planar());
+        g.planar = true;
+        assert!(g.solve(SolveSettings::new()).is_none());
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}; 3]
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..3 {g.push(a.clone())}
+        for _ in 0..3 {g.push(b.clone())}
+        let k33 = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(!k33.is_planar());
+        g.planar = true;
+        assert!(g.solve(SolveSettings::new()).is_none());
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.planar = true;
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.is_planar());
+        let mut petersen = Graph::new();
+        for _ in 0..10 {petersen.push(a.clone())}
+        for i in 0..5 {
+            petersen.set((i, (i + 1) % 5), 2);
+            petersen.set((i, i + 5), 2);
+            petersen.set((i + 5, (i + 2) % 5 + 5), 2);
+        }
+        assert!(petersen.is_solved());
+        assert!(!petersen.is_planar());
+
+        let mut octahedron = Graph::new();
+        for _ in 0..6 {octahedron.push(a.clone())}
+        for i in 0..6 {
+            for j in i+1..6 {
+                if j != i + 1 || i % 2 == 1 {octahedron.set((i, j), 2)};
+            }
+        }
+        assert!(octahedron.is_planar());
+        octahedron.set((0, 1), 2);
+        assert!(!octahedron.is_planar());
+    }
+
+    #[test]
+    fn bipartite() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2]
+        };
+        let mut g = Graph::new();
+        for _ in 0..3 {g.push(a.clone())}
+        g.bipartite = true;
+        assert!(g.solve(SolveSettings::new()).is_none());
+
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.bipartite = true;
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(!solution.has_odd_cycle());
+        assert!(solution.is_bipartite_nodes(&[0, 1, 2, 3, 4, 5]));
+
+        let mut g = solution.clone();
+        g.bipartite = false;
+        for i in 0..6 {
+            for j in i..6 {g.set((i, j), 0)}
+        }
+        g.set((0, 1), 2);
+        g.set((1, 2), 2);
+        assert!(!g.has_odd_cycle());
+        g.set((0, 2), 2);
+        assert!(g.has_odd_cycle());
+        g.set((0, 2), 0);
+        assert!(!g.has_odd_cycle());
+    }
+
+    #[test]
+    fn spec_diff() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let mut h = g.clone();
+        assert!(g.spec_diff(&h).is_empty());
+        h.nodes[1].edges.reverse();
+        assert!(g.spec_diff(&h).is_empty());
+        h.nodes[2].color = 1;
+        h.push_pair((1, 0));
+        h.set((2, 3), 2);
+        h.no_triangles = true;
+        let diff = g.spec_diff(&h);
+        assert_eq!(diff.differences, vec![
+            "node 2 color: 0 -> 1".to_string(),
+            "edge (2, 3): 0 -> 2".into(),
+            "pair added: (0, 1)".into(),
+            "no_triangles: false -> true".into(),
+        ]);
+        assert_eq!(format!("{}", diff).lines().count(), 4);
+    }
+
+    #[test]
+    fn provenance() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.set((0, 1), 2);
+        assert_eq!(g.provenance((0, 1)), Provenance::Pinned);
+        assert_eq!(g.provenance((0, 2)), Provenance::Unassigned);
+        let solution = g.solve_all(SearchSettings::new()).next().unwrap().puzzle;
+        assert_eq!(solution.provenance((1, 0)), Provenance::Pinned);
+        let count = |p: Provenance| {
+            let mut count = 0;
+            for i in 0..4 {
+                for j in i+1..4 {
+                    if solution.provenance((i, j)) == p {count += 1}
+                }
+            }
+            count
+        };
+        assert!(count(Provenance::Branched) > 0);
+        assert!(count(Provenance::Propagated) > 0);
+        let dot = solution.graphviz_provenance("sfdp", &["black"], &["red", "blue"]);
+        assert!(dot.contains("style=bold"));
+    }
+
+    #[test]
+    fn color_counts() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.push_color_count(2, 2, 2);
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert_eq!(solution.color_count(2).0, 2);
+        g.push_color_count(3, 0, 1);
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn cancel() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let token = CancelToken::new();
+        let mut solutions = g.solve_all(SearchSettings::new().cancel(token.clone()));
+        assert!(solutions.next().is_some());
+        assert!(!solutions.cancelled());
+        token.cancel();
+        assert!(solutions.next().is_none());
+        assert!(solutions.cancelled());
+        assert!(solutions.iterations() > 0);
+    }
+
+    #[test]
+    fn require_subgraph() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2]
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        let mut triangle = Graph::new();
+        for _ in 0..3 {triangle.push(a.clone())}
+        triangle.set((0, 1), 2);
+        triangle.set((1, 2), 2);
+        triangle.set((0, 2), 2);
+        g.no_triangles = true;
+        assert!(g.solve_all(SearchSettings::new())
+            .all(|solution| solution.puzzle.find_subgraph(&triangle).is_none()));
+
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.require_subgraph(&triangle);
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        let map = solution.find_subgraph(&triangle).unwrap();
+        assert_eq!(solution.get((map[0], map[1])), 2);
+        g.no_triangles = true;
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn from_dot() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 1},
+                Constraint {edge: 3, node: 1},
+            ],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(b.clone());
+        g.push(a.clone());
+        g.push(b.clone());
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let node_colors = ["white", "black"];
+        let edge_colors = ["red", "blue"];
+        let dot = solution.graphviz("sfdp", &node_colors, &edge_colors);
+        let h = Graph::from_dot_with_colors(&dot, &node_colors, &edge_colors).unwrap();
+        assert_eq!(h.nodes.len(), 4);
+        for i in 0..4 {
+            assert_eq!(h.nodes[i].color, solution.nodes[i].color);
+            for j in 0..4 {
+                assert_eq!(h.get((i, j)).max(1), solution.get((i, j)).max(1));
+            }
+        }
+
+        let h = Graph::from_dot("graph {\n  a -- b -- c [color=\"green\"];\n  c -- a;\n}").unwrap();
+        assert_eq!(h.nodes.len(), 3);
+        assert_eq!(h.get((0, 1)), 2);
+        assert_eq!(h.get((1, 2)), 2);
+        assert_eq!(h.get((0, 2)), 2);
+        let err = Graph::from_dot("graph {\n  a -- b [color=red]\n  b -> c;\n}").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(Graph::from_dot_with_colors("graph {a -- b [color=red]}", &[], &["blue"]).is_err());
+        // Deep nesting is an error instead of a stack overflow.
+        let dot = format!("graph {{{}", "{".repeat(200_000));
+        assert!(Graph::from_dot(&dot).unwrap_err().message.contains("Nesting"));
+        let dot = format!("graph {{{}a -- b{}}}", "{".repeat(50), "}".repeat(50));
+        assert_eq!(Graph::from_dot(&dot).unwrap().nodes.len(), 2);
+    }
+
+    #[test]
+    fn graphml() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(a.clone());
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let s = solution.graphml(&["black"], &["a<b"]);
+        assert!(s.contains("<node id=\"n1\">"));
+        assert!(s.contains("<edge source=\"n1\" target=\"n0\">"));
+        assert!(s.contains("<data key=\"ecolor\">a&lt;b</data>"));
+        assert!(s.contains("<data key=\"evalue\">2</data>"));
+        assert_eq!(s.matches("<edge ").count(), 1);
+    }
+
+    #[test]
+    fn tikz() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let options = TikzOptions::new(&["black"], &["red", "blue"]);
+        let s = solution.tikz(&options);
+        assert!(s.starts_with("\\documentclass[tikz]{standalone}"));
+        assert!(s.contains("\\node[fill=black] (n0) at (90.00:3cm) {};"));
+        assert_eq!(s.matches("\\draw[red]").count(), 2);
+        assert_eq!(s.matches("\\draw[blue,dashed]").count(), 2);
+        let s = solution.tikz(&options.standalone(false));
+        assert!(s.starts_with("\\begin{tikzpicture}"));
+    }
+
+    #[test]
+    fn svg() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let pos = solution.layout(300);
+        assert_eq!(pos.len(), 8);
+        assert!(pos.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+        // Adjacent nodes are placed closer than the farthest pair.
+        let dist = |i: usize, j: usize| ((pos[i].0 - pos[j].0).powi(2) + (pos[i].1 - pos[j].1).powi(2)).sqrt();
+        let mut max = 0.0_f64;
+        for i in 0..8 {for j in 0..i {max = max.max(dist(i, j))}}
+        for i in 0..8 {
+            for j in 0..i {
+                if solution.get((i, j)) == 2 {assert!(dist(i, j) < max)}
+            }
+        }
+
+        let s = solution.svg(&SvgOptions::new(&["white"], &["black"]));
+        assert!(s.starts_with("<svg "));
+        assert_eq!(s.matches("<line ").count(), 12);
+        assert_eq!(s.matches("fill=\"white\"").count(), 8);
+        assert_eq!(solution.svg(&SvgOptions::new(&["white"], &["black"])), s);
+        // Color names are escaped.
+        let s = solution.svg(&SvgOptions::new(&["a\"<&"], &["black"]));
+        assert_eq!(s.matches("fill=\"a&quot;&lt;&amp;\"").count(), 8);
+    }
+
+    #[test]
+    fn solve_region() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+
+        let region = [0, 1];
+        let part = g.clone().solve_region(&region, SearchSettings::new()).unwrap().puzzle;
+        assert!(part.region_solved(&region));
+        for &i in &region {
+            for j in 0..6 {if i != j {assert_ne!(part.get((i, j)), 0)}}
+        }
+        assert!(!part.is_solved());
+        // Other constraints are checked as far as the assigned edges decide them.
+        let pos = (1..6).map(|j| (0, j)).find(|&pos| part.get(pos) >= 2).unwrap();
+        let mut h = part.clone();
+        h.push_cardinality(&[pos], 0, 0);
+        assert!(!h.region_solved(&region));
+
+        let all: Vec<usize> = (0..6).collect();
+        let solution = part.solve_region(&all, SearchSettings::new()).unwrap().puzzle;
+        assert!(solution.is_solved());
+        assert!(solution.region_solved(&all));
+    }
+
+    #[test]
+    fn hierarchical() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+
+        // Two squares connected by a perfect matching.
+        let blocks = [0, 0, 0, 0, 1, 1, 1, 1];
+        let mut quotient = Graph::new();
+        for _ in 0..2 {
+            quotient.push(Node {
+                color: 0,
+                self_connected: false,
+                edges: vec![Constraint {edge: 2, node: 0}],
+            });
+        }
+        let refined = g.refine_with(&quotient.clone().solve(SolveSettings::new()).unwrap().puzzle, &blocks);
+        assert_eq!(refined.cardinalities.len(), 1);
+        assert_eq!(refined.cardinalities[0].edges.len(), 16);
+
+        let solution = g.clone().solve_hierarchical(quotient.clone(), &blocks, SearchSettings::new()).unwrap();
+        assert!(solution.puzzle.is_solved());
+        let mut coarse = quotient.clone().solve_all(SearchSettings::new());
+        coarse.next();
+        let mut fine = refined.solve_all(SearchSettings::new());
+        fine.next();
+        assert_eq!(solution.iterations, coarse.iterations() + fine.iterations());
+        // The maximum number of iterations applies to both levels together.
+        let settings = SearchSettings::new().max_iterations(fine.iterations());
+        assert!(g.clone().solve_hierarchical(quotient, &blocks, settings).is_none());
+
+        // Three blocks without connections between them have no solution.
+        let blocks = [0, 0, 1, 1, 1, 2, 2, 2];
+        let mut quotient = Graph::new();
+        for _ in 0..3 {quotient.push(Node {color: 0, self_connected: false, edges: vec![]})}
+        assert!(g.solve_hierarchical(quotient, &blocks, SearchSettings::new()).is_none());
+    }
+
+    #[test]
+    fn neighbors() {
+        let a = Node {color: 0, self_connected: true, edges: vec![]};
+        let mut g = Graph::new();
+        for _ in 0..70 {g.push(a.clone())}
+        g.set((0, 65), 2);
+        g.set((65, 69), 3);
+        g.set((69, 69), 2);
+        assert_eq!(g.neighbors(65), &[1, 1 << 5]);
+        assert_eq!(g.neighbors(69), &[0, 1 << 1]);
+        assert!(!g.has_triangles());
+        g.set((69, 0), 2);
+        assert!(g.has_triangles());
+        assert_eq!(g.triangle_count(), 1);
+        g.set((0, 65), 1);
+        assert!(!g.has_triangles());
+        assert_eq!(g.neighbors(65), &[0, 1 << 5]);
+
+        // A complete graph on 4 nodes has 4 triangles.
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        for i in 0..4 {for j in 0..i {g.set((i, j), 2)}}
+        assert_eq!(g.triangle_count(), 4);
+        g.set((1, 0), 3);
+        assert_eq!(g.triangle_count(), 4);
+        g.set((1, 0), 0);
+        assert_eq!(g.triangle_count(), 2);
+    }
+
+    #[test]
+    fn rewrite() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let mut solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+
+        // Normalize edge colors.
+        let b = Node {color: 0, self_connected: false, edges: vec![]};
+        let mut pattern = Graph::new();
+        pattern.push(b.clone());
+        pattern.push(b.clone());
+        let mut replacement = pattern.clone();
+        pattern.set((0, 1), 3);
+        replacement.set((0, 1), 2);
+        let rule = RewriteRule::new(pattern.clone(), replacement);
+        assert_eq!(solution.rewrite(&[rule], 10), 2);
+        assert_eq!((0..4).flat_map(|i| (0..i).map(move |j| (i, j)))
+            .filter(|&pos| solution.get(pos) == 2).count(), 4);
+        assert!(solution.find_subgraph(&pattern).is_none());
+
+        // A replacement containing its own pattern stops at the maximum steps.
+        let rule = RewriteRule::new(pattern.clone(), pattern.clone());
+        let mut h = Graph::new();
+        h.push(b.clone());
+        h.push(b.clone());
+        h.set((0, 1), 3);
+        assert_eq!(h.rewrite(&[rule], 5), 5);
+
+        // An empty replacement removes the pattern edges.
+        let mut empty = Graph::new();
+        empty.push(b.clone());
+        empty.push(b.clone());
+        let rule = RewriteRule::new(pattern.clone(), empty);
+        assert_eq!(h.apply_rule(&rule), Some(vec![0, 1]));
+        assert_eq!(h.get((0, 1)), 1);
+    }
+
+    #[test]
+    fn duplicate_node() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.set_label(0, 7);
+        g.degrees.push(DegreeConstraint {node: 0, min: 2, max: 2});
+        let mut solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+
+        let k = solution.duplicate_node(0, true);
+        assert_eq!(k, 4);
+        assert_eq!(solution.nodes[4], a);
+        assert_eq!(solution.labels[4], Some(7));
+        assert_eq!(solution.degrees[1], DegreeConstraint {node: 4, min: 2, max: 2});
+        for j in 1..4 {assert_eq!(solution.get((4, j)), solution.get((0, j)))}
+        assert_eq!(solution.get((4, 0)), 0);
+
+        let k = solution.duplicate_node(1, false);
+        assert_eq!(k, 5);
+        assert!((0..6).all(|j| solution.get((5, j)) == 0));
+    }
+
+    #[test]
+    fn replace_node() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let mut solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.replace_node(0, a.clone()).is_empty());
+        assert!(solution.is_solved());
+
+        // Allowing only edges with color `2` makes the edge with color `3` inconsistent.
+        let j = (1..4).find(|&j| solution.get((0, j)) == 3).unwrap();
+        let b = Node {edges: vec![Constraint {edge: 2, node: 0}; 2], ..a.clone()};
+        assert_eq!(solution.replace_node(0, b), vec![(0, j)]);
+        assert!(!solution.is_solved());
+
+        // Changing the color makes both edges inconsistent for the neighbors.
+        let c = Node {color: 1, ..a.clone()};
+        assert_eq!(solution.replace_node(0, c).len(), 2);
+        assert!(!solution.is_solved());
+        assert!(solution.replace_node(0, a).is_empty());
+        assert!(solution.is_solved());
+    }
+
+    #[test]
+    fn is_connected() {
+        let a = Node {color: 0, self_connected: false, edges: vec![]};
+        let mut g = Graph::new();
+        assert!(g.is_connected());
+        g.push(a.clone());
+        assert!(g.is_connected());
+        for _ in 0..3 {g.push(a.clone())}
+        assert!(!g.is_connected());
+        g.set((0, 1), 2);
+        g.set((2, 3), 2);
+        assert!(!g.is_connected());
+        g.set((1, 2), 3);
+        assert!(g.is_connected());
+        g.set((1, 2), 1);
+        assert!(!g.is_connected());
+        g.set((3, 0), 2);
+        assert!(g.is_connected());
+    }
+
+    #[test]
+    fn possibly_connected() {
+        let a = Node {color: 0, self_connected: false, edges: vec![]};
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.connected = true;
+        assert!(g.possibly_connected());
+        g.set((0, 1), 1);
+        g.set((0, 2), 1);
+        assert!(g.possibly_connected());
+        g.set((0, 3), 1);
+        assert!(!g.possibly_connected());
+        assert!(g.colors((1, 2)).is_empty());
+        g.set((0, 3), 0);
+        assert!(g.possibly_connected());
+    }
+
+    #[test]
+    fn resolve_around() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+
+        let region = solution.neighborhood(0, 1);
+        assert_eq!(region.len(), 4);
+        assert_eq!(solution.neighborhood(0, 0), vec![0]);
+        assert_eq!(solution.neighborhood(0, 3).len(), 8);
+
+        let resolved = solution.clone().resolve_around(0, 1, SearchSettings::new()).unwrap().puzzle;
+        assert!(resolved.is_solved());
+        for i in 0..8 {
+            for j in i..8 {
+                if !region.contains(&i) || !region.contains(&j) {
+                    assert_eq!(resolved.get((i, j)), solution.get((i, j)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn forward_check() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        assert!(g.forward_check());
+        assert_eq!(g.domains().len(), 6);
+        assert!(g.domains().iter().all(|(_, colors)| colors == &[1, 2]));
+
+        // Node 0 can no longer get three edges.
+        g.set((0, 1), 1);
+        assert!(!g.forward_check());
+
+        let b = Node {edges: vec![Constraint {edge: 2, node: 0}; 2], ..a.clone()};
+        let mut h = Graph::new();
+        for _ in 0..6 {h.push(b.clone())}
+        h.no_triangles = true;
+        let with = h.clone().solve_all(SearchSettings::new()).count();
+        let without = h.solve_all(SearchSettings::new().propagate(false)).count();
+        // Hexagons with labeled nodes.
+        assert_eq!(with, 60);
+        assert_eq!(with, without);
+
+        // Both edges of node 0 are forced alone, but only one can be assigned.
+        let mut g = Graph::new();
+        g.push(Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 1}]});
+        for _ in 0..2 {
+            g.push(Node {color: 1, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]});
+        }
+        g.push_degree(1, 1, 1);
+        g.push_degree(2, 1, 1);
+        assert!(g.clone().solve_all(SearchSettings::new()).next().is_none());
+        assert!(g.solve_all(SearchSettings::new().propagate(false)).next().is_none());
+    }
+
+    #[test]
+    fn learn() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.no_triangles = true;
+        let with = g.clone().solve_all(SearchSettings::new().learn(true)).count();
+        let without = g.clone().solve_all(SearchSettings::new()).count();
+        assert_eq!(with, 60);
+        assert_eq!(with, without);
+        let simple = g.solve_all(SearchSettings::new().learn(true).solve_simple(false)).count();
+        assert_eq!(simple, 60);
+
+        // The sum of degrees is odd.
+        let b = Node {edges: vec![Constraint {edge: 2, node: 0}; 3], ..a};
+        let mut h = Graph::new();
+        for _ in 0..5 {h.push(b.clone())}
+        let mut solutions = h.clone().solve_all(SearchSettings::new().learn(true));
+        assert!(solutions.next().is_none());
+        assert!(!solutions.nogoods().is_empty());
+        let learned = solutions.iterations();
+        let mut solutions = h.solve_all(SearchSettings::new());
+        assert!(solutions.next().is_none());
+        assert!(learned <= solutions.iterations());
+    }
+
+    #[test]
+    fn constraint_stats() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.no_triangles = true;
+        // Never restricts the search.
+        g.push_degree(0, 0, 5);
+        assert_eq!(g.constraint_names(), vec!["nodes", "no_triangles", "degrees[0]"]);
+        let mut solutions = g.clone().solve_all(SearchSettings::new());
+        assert_eq!(solutions.by_ref().count(), 60);
+        assert!(solutions.constraint_stats().is_empty());
+
+        let mut solutions = g.solve_all(SearchSettings::new().constraint_stats(true));
+        assert_eq!(solutions.by_ref().count(), 60);
+        let stats = solutions.constraint_stats();
+        assert_eq!(stats["degrees[0]"], ConstraintStats::default());
+        assert!(stats["nodes"].prunings > 0);
+        assert!(stats["no_triangles"].failures > 0);
+    }
+
+    #[test]
+    fn restart() {
+        let luby: Vec<u64> = (0..15).map(|k| RestartPolicy::Luby {unit: 1}.limit(k)).collect();
+        assert_eq!(luby, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+        let geometric = RestartPolicy::Geometric {first: 10, factor: 1.5};
+        assert_eq!((0..4).map(|k| geometric.limit(k)).collect::<Vec<_>>(), vec![10, 15, 22, 33]);
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.no_triangles = true;
+        for seed in 0..3 {
+            let settings = SearchSettings::new().seed(seed);
+            let solutions: Vec<_> = g.clone().solve_all(settings.clone()).map(|s| s.puzzle).collect();
+            assert_eq!(solutions.len(), 60);
+            let first = g.clone().solve_all(settings).next().unwrap().puzzle;
+            assert!(first.is_solved());
+            assert!(first.edges == solutions[0].edges);
+        }
+
+        let b = Node {edges: vec![Constraint {edge: 2, node: 0}; 3], ..a};
+        let mut h = Graph::new();
+        for _ in 0..8 {h.push(b.clone())}
+        h.no_triangles = true;
+        let settings = SearchSettings::new().seed(1).restart(RestartPolicy::Luby {unit: 1});
+        let mut solutions = h.solve_all(settings);
+        assert!(solutions.next().unwrap().puzzle.is_solved());
+        assert!(solutions.restarts() > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Geometric restart factor must be greater than 1")]
+    fn restart_factor() {
+        SearchSettings::new().restart(RestartPolicy::Geometric {first: 10, factor: 1.0});
+    }
+
+    #[test]
+    fn compress_colors() {
+        let a = Node {
+            color: 10,
+            self_connected: false,
+            edges: vec![Constraint {edge: 100, node: 20}, Constraint {edge: 101, node: 20}],
+        };
+        let b = Node {
+            color: 20,
+            self_connected: false,
+            edges: vec![Constraint {edge: 100, node: 10}, Constraint {edge: 101, node: 10}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..2 {
+            g.push(a.clone());
+            g.push(b.clone());
+        }
+        g.push_color_count(101, 1, 4);
+        g.set((0, 1), 100);
+
+        let (h, map) = g.compress_colors();
+        assert!(!map.is_identity());
+        assert_eq!(h.nodes[0].color, 0);
+        assert_eq!(h.nodes[1].edges, vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}]);
+        assert_eq!(h.get((0, 1)), 2);
+        assert_eq!(h.color_counts[0].color, 3);
+        assert_eq!(map.node_color(1), 20);
+        assert_eq!(map.edge_color(3), 101);
+        assert_eq!(map.compress_edge_color(100), Some(2));
+        assert_eq!(map.compress_edge_color(4), None);
+
+        let count = g.clone().solve_all(SearchSettings::new()).count();
+        let solutions: Vec<Graph> = h.solve_all(SearchSettings::new()).map(|s| map.expand(&s.puzzle)).collect();
+        assert!(count > 0);
+        assert_eq!(solutions.len(), count);
+        assert!(solutions.iter().all(|s| s.is_solved() && s.nodes == g.nodes));
+
+        let (h, map) = solutions[0].compress_colors();
+        assert!(h.compress_colors().1.is_identity());
+        assert_eq!(map.expand(&h).row(3), solutions[0].row(3));
+    }
+
+    #[test]
+    fn edge_domain() {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        enum Arrow {
+            Up(char),
+            Down(char),
+        }
+
+        impl EdgeValue for Arrow {
+            fn flipped(&self) -> Option<Arrow> {
+                Some(match *self {Arrow::Up(c) => Arrow::Down(c), Arrow::Down(c) => Arrow::Up(c)})
+            }
+            fn render(&self) -> String {
+                match self {Arrow::Up(c) => format!("+{}", c), Arrow::Down(c) => format!("-{}", c)}
+            }
+        }
+
+        let domain = EdgeDomain::new(vec![Arrow::Up('a'), Arrow::Up('b'), Arrow::Down('a')]);
+        assert_eq!(domain.color(&Arrow::Up('a')), Some(2));
+        assert_eq!(domain.color(&Arrow::Down('a')), Some(3));
+        assert_eq!(domain.color(&Arrow::Up('b')), Some(4));
+        assert_eq!(domain.colors(), vec![2, 3, 4]);
+        assert_eq!(domain.value(5), None);
+        assert_eq!(domain.render(3), "-a");
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![domain.constraint(&Arrow::Up('a'), 0), domain.constraint(&Arrow::Up('b'), 0)],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        domain.set(&mut g, (0, 1), &Arrow::Up('a'));
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let edges = domain.edges(&solution);
+        assert_eq!(edges.len(), 4);
+        assert_eq!(edges[0], ((0, 1), &Arrow::Up('a')));
+        assert_eq!(domain.get(&solution, (0, 2)), Some(&Arrow::Up('b')));
+
+        let colors = EdgeDomain::new(vec![2, 3, 4]);
+        assert!((2..5).all(|c| colors.color(&c) == Some(c)));
+    }
+
+    #[test]
+    fn solve_with() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Branches on the last empty edge, trying no-edge first.
+        struct LastEmpty {
+            steps: Arc<AtomicUsize>,
+        }
+
+        impl BranchStrategy for LastEmpty {
+            fn choose_pos(&mut self, g: &Graph) -> Option<(usize, usize)> {
+                self.steps.fetch_add(1, Ordering::SeqCst);
+                let n = g.nodes.len();
+                (0..n).flat_map(|i| (i..n).map(move |j| (i, j)))
+                    .rfind(|&pos| !g.colors(pos).is_empty())
+            }
+            fn order_values(&mut self, g: &Graph, pos: (usize, usize)) -> Vec<Color> {
+                let mut colors = g.colors(pos);
+                colors.reverse();
+                colors
+            }
+        }
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.no_triangles = true;
+        let steps = Arc::new(AtomicUsize::new(0));
+        let solution = g.clone().solve_with(Box::new(LastEmpty {steps: steps.clone()}), SearchSettings::new());
+        assert!(solution.unwrap().puzzle.is_solved());
+        assert!(steps.load(Ordering::SeqCst) > 0);
+        let strategy = Box::new(LastEmpty {steps: steps.clone()});
+        assert_eq!(Solutions::with_branch_strategy(g, SearchSettings::new(), strategy).count(), 60);
+    }
+
+    #[test]
+    fn edge_data() {
+        let mut g = Graph::new();
+        for _ in 0..3 {
+            g.push(Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}; 2]});
+        }
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let mut data = EdgeData::from_graph(&solution, |(i, j), _| Some(format!("w{}{}", i, j)));
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.get((1, 0)), Some(&"w01".to_string()));
+        let edges: Vec<(usize, usize)> = data.iter().map(|(pos, _)| pos).collect();
+        assert_eq!(edges, vec![(0, 1), (0, 2), (1, 2)]);
+        assert_eq!(data.to_string(), "0 -- 1: w01\n0 -- 2: w02\n1 -- 2: w12\n");
+
+        let graphml = solution.graphml_with_edge_data(&["white"], &["black"], &data);
+        assert!(graphml.contains("<data key=\"edata\">w12</data>"));
+        let dot = solution.graphviz_with_edge_data("dot", &["white"], &["black"], &data);
+        assert!(dot.contains("1 -- 0[color=black,label=\"w01\"];"));
+
+        let mut h = solution.clone();
+        h.set((1, 2), 1);
+        data.retain_colored(&h);
+        assert_eq!(data.remove((2, 1)), None);
+        assert_eq!(data.set((0, 1), "x".into()), Some("w01".into()));
+        assert_eq!(data.len(), 2);
+        assert!(!data.is_empty());
+    }
+
+    #[test]
+    fn try_solve() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        assert!(g.clone().try_solve(SearchSettings::new()).solution().unwrap().puzzle.is_solved());
+        let outcome = g.clone().try_solve(SearchSettings::new().max_iterations(2));
+        assert!(matches!(outcome, SolveOutcome::Exhausted));
+        assert!(outcome.is_stopped());
+        let outcome = g.clone().try_solve(SearchSettings::new().timeout_ms(0));
+        assert!(matches!(outcome, SolveOutcome::TimedOut));
+
+        // The sum of degrees is odd.
+        let mut h = Graph::new();
+        for _ in 0..5 {h.push(a.clone())}
+        let outcome = h.try_solve(SearchSettings::new());
+        assert!(matches!(outcome, SolveOutcome::NoSolution));
+        assert!(!outcome.is_stopped());
+    }
+
+    #[test]
+    fn node_data() {
+        let mut g = Graph::new();
+        for _ in 0..4 {
+            g.push(Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}; 2]});
+        }
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let mut names = NodeData::from_graph(&solution, |i, _| Some(format!("v{}", i)));
+        names.remove(3);
+        assert_eq!(names.len(), 3);
+        assert_eq!(names.get(1), Some(&"v1".to_string()));
+        assert_eq!(names.find(|name| name.ends_with('2')), vec![2]);
+        let neighbors = names.neighbors(&solution, 0);
+        assert_eq!(neighbors.len(), 2);
+        assert!(neighbors.iter().all(|&(j, name)| name == names.get(j)));
+        assert_eq!(names.set(5, "v5".into()), None);
+        assert_eq!(names.to_string(), "0: v0\n1: v1\n2: v2\n5: v5\n");
+
+        let weights = EdgeData::from_graph(&solution, |(i, j), _| Some(i + j));
+        let graphml = solution.graphml_with_data(&["white"], &["black"], &names, &weights);
+        assert!(graphml.contains("<data key=\"ndata\">v2</data>"));
+        let dot = solution.graphviz_with_data("dot", &["white"], &["black"], &names, &weights);
+        assert!(dot.contains("0[regular=true,style=filled,fillcolor=white,label=\"v0\"];"));
+        let dot = solution.graphviz_with("dot", &["white"], &["black"],
+            &|i| Some(format!("\"{}\"", i)), &|_| None);
+        assert!(dot.contains("label=\"\\\"3\\\"\""));
+        let graphml = solution.graphml_with(&["white"], &["black"], &|_| None, &|(i, j)| Some(format!("{}<{}", i, j)));
+        assert!(graphml.contains("0&lt;1"));
+    }
+
+    #[test]
+    fn on_progress() {
+        use std::sync::{Arc, Mutex};
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let reports = Arc::new(Mutex::new(vec![]));
+        let r = reports.clone();
+        let mut solutions = g.solve_all(SearchSettings::new())
+            .on_progress(3, move |g, steps| r.lock().unwrap().push((steps, g.progress())));
+        assert!(solutions.next().is_some());
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len() as u64, solutions.iterations() / 3);
+        assert!(reports.iter().enumerate().all(|(k, &(steps, _))| steps == 3 * (k as u64 + 1)));
+        assert!(reports.iter().all(|&(_, progress)| (0.0..=1.0).contains(&progress)));
+    }
+
+    #[test]
+    fn spec_string() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}; 2],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: true,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(b.clone());
+        g.push(a.clone());
+        g.push_named(" b \"3\"\n", b.clone());
+        g.set((0, 1), 2);
+        g.no_triangles = true;
+        g.commute_quad = Some(false);
+        g.push_pair((2, 3));
+        g.push_cardinality(&[(0, 3), (1, 2)], 1, 2);
+        g.push_degree(0, 2, 2);
+        g.push_color_count(2, 0, 4);
+        g.set_label(0, 3);
+        g.bipartition_by_color(0, 1);
+        g.push_conditional(2, Flag::Connected);
+        g.push_component_constraint(ComponentConstraint::Size {min: 1, max: 4});
+        g.push_twins((0, 2));
+        g.set_priority((0, 3), -2);
+        let mut pattern = Graph::new();
+        pattern.push(a.clone());
+        pattern.push(b.clone());
+        pattern.set((0, 1), 2);
+        g.require_subgraph(&pattern);
+
+        let text = g.to_spec_string();
+        assert!(text.starts_with(&format!("graph_solver spec {}\n", SPEC_VERSION)));
+        let h = Graph::from_spec_string(&text).unwrap();
+        assert!(g.spec_diff(&h).is_empty(), "{}\n{}", text, g.spec_diff(&h));
+        assert_eq!(h.to_spec_string(), text);
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(),
+                   h.solve_all(SearchSettings::new()).count());
+
+        let h = Graph::from_spec_string("# Comment\ngraph_solver spec 1\n\nnode 0\nnode 0\nedge 1 0 2\n").unwrap();
+        assert_eq!(h.get((0, 1)), 2);
+
+        let err = Graph::from_spec_string("graph_solver spec 2\n").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("version"));
+        assert!(Graph::from_spec_string("node 0\n").is_err());
+        let err = Graph::from_spec_string("graph_solver spec 1\nnode 0\nnode_color 0\n").unwrap_err();
+        assert_eq!(err.line, 3);
+        let err = Graph::from_spec_string("graph_solver spec 1\nnode 0\nedge 0 1 2\n").unwrap_err();
+        assert!(err.message.contains("out of range"));
+        assert!(Graph::from_spec_string("graph_solver spec 1\nsubgraph\nnode 0\n").is_err());
+        assert!(Graph::from_spec_string("graph_solver spec 1\nend\n").is_err());
+        assert!(Graph::from_spec_string("graph_solver spec 1\nnode 0\nname 0 a\n").is_err());
+        let spec = format!("graph_solver spec 1\n{}", "subgraph\n".repeat(200_000));
+        assert!(Graph::from_spec_string(&spec).unwrap_err().message.contains("Nesting"));
+    }
+
+    #[test]
+    fn solve_report() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let report = g.clone().solve_report(SearchSettings::new());
+        let solution = report.outcome.solution().unwrap();
+        let stats = report.stats;
+        assert_eq!(stats.iterations, solution.iterations);
+        assert!(stats.assignments >= 12);
+        assert!(stats.max_depth >= 1);
+        assert!(stats.backtracks >= stats.wipeouts);
+
+        let mut solutions = g.solve_all(SearchSettings::new());
+        let count = solutions.by_ref().count();
+        let stats = solutions.stats();
+        assert!(count > 1);
+        assert!(stats.backtracks > 0);
+        assert!(stats.wipeouts > 0);
+        assert!(stats.elapsed > std::time::Duration::from_secs(0));
+
+        // The sum of degrees is odd.
+        let mut h = Graph::new();
+        for _ in 0..5 {h.push(a.clone())}
+        let report = h.solve_report(SearchSettings::new());
+        assert!(matches!(report.outcome, SolveOutcome::NoSolution));
+        assert!(report.stats.backtracks > 0);
+    }
+
+    #[test]
+    fn explain_unsat() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 3, node: 1}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.push(b.clone());
+        g.push(b.clone());
+        g.push_pair((4, 5));
+        assert_eq!(g.explain_unsat(SearchSettings::new()), None);
+
+        // Two adjacent cubic nodes among four nodes form a triangle.
+        g.no_triangles = true;
+        let reason = g.explain_unsat(SearchSettings::new()).unwrap();
+        assert_eq!(reason.nodes, vec![2, 3]);
+        assert!(reason.pairs.is_empty());
+        assert_eq!(reason.constraints, vec!["no_triangles".to_string()]);
+        assert!(reason.minimal);
+        assert_eq!(reason.to_string(), "no solution:\n  node 2\n  node 3\n  no_triangles\n");
+
+        // The pair can not be colored.
+        let mut h = Graph::new();
+        h.push(b.clone());
+        h.push(a.clone());
+        h.push(b.clone());
+        h.push_pair((0, 1));
+        let reason = h.explain_unsat(SearchSettings::new()).unwrap();
+        assert!(reason.nodes.is_empty());
+        assert_eq!(reason.pairs, vec![(0, 1)]);
+        assert!(reason.constraints.is_empty());
+    }
+
+    #[test]
+    fn load() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.set((0, 1), 2);
+
+        let json = g.to_json();
+        assert!(json.starts_with(&format!("{{\n  \"format_version\": {},", JSON_VERSION)));
+        let spec = g.to_spec_string();
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        let dot = solution.graphviz("neato", &["black"], &["red"]);
+        assert!(dot.starts_with(&format!("// graph_solver dot {}\n", DOT_VERSION)));
+        assert_eq!(Format::detect(&json), Some(Format::Json));
+        assert_eq!(Format::detect(&spec), Some(Format::Spec));
+        assert_eq!(Format::detect(&dot), Some(Format::Dot));
+        assert_eq!(Format::detect("hello"), None);
+        assert!(Graph::from_text(&json).unwrap().spec_diff(&g).is_empty());
+        assert!(Graph::from_text(&spec).unwrap().spec_diff(&g).is_empty());
+        assert_eq!(Graph::from_text(&dot).unwrap().edges.iter().filter(|&&c| c == 2).count(), 4);
+
+        // Specifications written before versioning are migrated.
+        let old = json.replacen(&format!("\"format_version\": {},", JSON_VERSION), "", 1);
+        assert!(Graph::from_json(&old).unwrap().spec_diff(&g).is_empty());
+        let old = dot.lines().skip(1).collect::<Vec<_>>().join("\n");
+        assert!(Graph::from_dot(&old).is_ok());
+        let err = Graph::from_json(r#"{"format_version": 99, "nodes": []}"#).unwrap_err();
+        assert!(err.message.contains("version"));
+        assert!(Graph::from_dot("// graph_solver dot 99\nstrict graph {}").is_err());
+
+        let path = std::env::temp_dir().join(format!("graph_solver_load_{}.txt", std::process::id()));
+        std::fs::write(&path, &spec).unwrap();
+        assert!(Graph::load(&path).unwrap().spec_diff(&g).is_empty());
+        std::fs::write(&path, "hello").unwrap();
+        assert_eq!(Graph::load(&path).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn color_usage() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 1}],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(a.clone());
+        g.push(b.clone());
+        g.push(b.clone());
+        g.set((0, 2), 3);
+        let usage = g.color_usage();
+        assert_eq!(usage.node_colors, vec![0, 1]);
+        assert_eq!(usage.referenced_node_colors, vec![0, 1]);
+        assert_eq!(usage.referenced_edge_colors, vec![2, 3]);
+        assert_eq!(usage.used_edge_colors, vec![3]);
+        assert!(usage.warnings.is_empty());
+
+        let c = Node {
+            color: 2,
+            self_connected: false,
+            edges: vec![Constraint {edge: 4, node: 0}, Constraint {edge: 2, node: 5}],
+        };
+        g.push(c);
+        g.bipartition_by_color(0, 7);
+        g.push_color_count(6, 1, 2);
+        let usage = g.color_usage();
+        assert_eq!(usage.warnings.len(), 4, "{}", usage);
+        assert!(usage.warnings[0].contains("node 4 requires edge 4 to node color 0"));
+        assert!(usage.warnings[0].contains("accepts"));
+        assert!(usage.warnings[1].contains("no other node has this color"));
+        assert!(usage.warnings[2].contains("bipartition uses node color 7"));
+        assert!(usage.warnings[3].contains("color 6"));
+    }
+
+    #[test]
+    fn diagnose() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..5 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.connected = true;
+        g.commute_quad = Some(true);
+        g.push_pair((3, 4));
+        assert!(g.diagnose().is_empty());
+
+        g.set((0, 1), 2);
+        g.set((1, 2), 2);
+        g.set((0, 2), 2);
+        g.set((0, 3), 3);
+        for j in 0..5 {
+            if g.get((3, j)) == 0 && j != 3 {g.set((3, j), 1)};
+        }
+        let violations = g.diagnose();
+        assert_eq!(violations, vec![
+            Violation::ExtraEdge {node: 0, edge: (0, 3), color: 3},
+            Violation::ExtraEdge {node: 3, edge: (0, 3), color: 3},
+            Violation::MissingEdges {node: 3, missing: vec![Constraint {edge: 2, node: 0}; 2]},
+            Violation::Pair {pair: (3, 4)},
+            Violation::Triangle {nodes: [0, 1, 2]},
+        ]);
+        assert_eq!(violations[4].to_string(), "triangle [0, 1, 2]");
+
+        let mut h = Graph::new();
+        for _ in 0..4 {h.push(a.clone())}
+        h.commute_quad = Some(true);
+        h.connected = true;
+        h.set((0, 1), 2);
+        h.set((1, 2), 3);
+        h.set((2, 3), 2);
+        h.set((0, 3), 2);
+        h.set((0, 2), 1);
+        h.set((1, 3), 1);
+        assert!(h.diagnose().contains(&Violation::Quad {nodes: [0, 1, 2, 3]}));
+        h.set((1, 2), 2);
+        assert!(h.diagnose().is_empty());
+
+        let mut h = Graph::new();
+        for _ in 0..4 {
+            h.push(Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]});
+        }
+        h.connected = true;
+        for &(i, j) in &[(0, 2), (0, 3), (1, 2), (1, 3)] {h.set((i, j), 1)}
+        assert_eq!(h.diagnose(), vec![Violation::Disconnected {components: vec![vec![0, 1], vec![2, 3]]}]);
+    }
+
+    #[test]
+    fn dead_constraints() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a);
+        g.push(b.clone());
+        assert!(g.dead_constraints().is_empty());
+        g.set((0, 1), 1);
+        let dead = g.dead_constraints();
+        assert_eq!(dead.len(), 2);
+        assert!(dead.iter().all(|d| matches!(d.reason, DeadReason::Eliminated(_))));
+        assert!(dead[0].to_string().contains("already assigned"), "{}", dead[0]);
+
+        let mut g = Graph::new();
+        g.push(Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}; 2],
+        });
+        g.push(b);
+        g.push(Node {color: 2, self_connected: false, edges: vec![Constraint {edge: 3, node: 0}]});
+        g.push(Node {color: 3, self_connected: false, edges: vec![Constraint {edge: 2, node: 9}]});
+        let dead = g.dead_constraints();
+        assert_eq!(dead.len(), 3);
+        assert_eq!((dead[0].node, dead[0].slot), (0, 1));
+        assert_eq!(dead[0].reason, DeadReason::TooFewCandidates {candidates: 1, required: 2});
+        assert_eq!((dead[1].node, dead[1].reason.clone()), (2, DeadReason::NoReciprocal));
+        assert_eq!((dead[2].node, dead[2].reason.clone()), (3, DeadReason::MissingNodeColor));
+        assert!(g.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn graphviz_options() {
+        let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 1}]};
+        let b = Node {color: 1, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]};
+        let mut g = Graph::new();
+        g.push(a);
+        g.push(b);
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let names = ["a", "b \"quoted\""];
+        let options = GraphvizOptions::new("dot", &["white", "gray"], &["black"])
+            .shapes(&["box", "circle"])
+            .rankdir("LR")
+            .penwidth(2.5)
+            .fontname("Helvetica")
+            .graph_attribute("splines", "true")
+            .node_label(|i| Some(names[i].into()))
+            .edge_label(|(i, j)| Some(format!("{}-{}", i, j)))
+            .node_attributes(|i| vec![("tooltip".into(), format!("node {}", i))])
+            .edge_attributes(|_| vec![("arrowhead".into(), "none".into())]);
+        let dot = solution.graphviz_with_options(&options);
+        assert!(dot.contains("edge[penwidth=2.5]"));
+        assert!(dot.contains("rankdir=\"LR\";"));
+        assert!(dot.contains("node[fontname=\"Helvetica\"]"));
+        assert!(dot.contains("splines=\"true\";"));
+        assert!(dot.contains("0[regular=true,style=filled,fillcolor=white,shape=box,\
+                              label=\"a\",tooltip=\"node 0\"];"), "{}", dot);
+        assert!(dot.contains("label=\"b \\\"quoted\\\"\""), "{}", dot);
+        assert!(dot.contains("1 -- 0[color=black,label=\"0-1\",arrowhead=\"none\"];"), "{}", dot);
+        let h = Graph::from_dot_with_colors(&dot, &["white", "gray"], &["black"]).unwrap();
+        assert_eq!(h.get((0, 1)), 2);
+        assert_eq!(h.nodes[1].color, 1);
+
+        // The default options give the same output as `Graph::graphviz`.
+        let options = GraphvizOptions::new("dot", &["white"], &["black"]);
+        assert_eq!(solution.graphviz_with_options(&options), solution.graphviz("dot", &["white"], &["black"]));
+    }
+
+    #[test]
+    fn graphviz_edge_color_labels() {
+        let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 3, node: 0}]};
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(a);
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let options = GraphvizOptions::new("dot", &["white"], &["red", "blue"]).edge_color_labels(true);
+        assert!(solution.graphviz_with_options(&options).contains("[color=blue,label=\"3\"]"));
+        let options = GraphvizOptions::new("dot", &["white"], &["red", "blue"])
+            .edge_color_names(&["x", "y"]);
+        assert!(solution.graphviz_with_options(&options).contains("[color=blue,label=\"y\"]"));
+        let options = options.edge_label(|_| Some("z".into()));
+        assert!(solution.graphviz_with_options(&options).contains("[color=blue,label=\"z\"]"));
+    }
+
+    #[test]
+    fn infer_flags() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}, Constraint {edge: 3, node: 1}],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(a);
+        g.push(b.clone());
+        g.push(b);
+        assert_eq!(g.infer_flags(), vec![
+            ImpliedFlag::Bipartite,
+            ImpliedFlag::NoTriangles,
+            ImpliedFlag::Bipartition(0, 1),
+            ImpliedFlag::NoIsolatedNodes,
+            ImpliedFlag::Regular(2),
+        ]);
+        let count = Solutions::new(g.clone(), SearchSettings::new()).count();
+        assert_eq!(g.enable_implied_flags().len(), 5);
+        assert!(g.infer_flags().is_empty());
+        assert_eq!(g.degrees.len(), 4);
+        assert_eq!(Solutions::new(g, SearchSettings::new()).count(), count);
+
+        // Odd cycles of node colors are not bipartite.
+        let mut g = Graph::new();
+        for i in 0..3 {
+            g.push(Node {
+                color: i,
+                self_connected: false,
+                edges: vec![Constraint {edge: 2, node: (i + 1) % 3}, Constraint {edge: 2, node: (i + 2) % 3}],
+            });
+        }
+        g.push(Node {color: 3, self_connected: false, edges: vec![]});
+        assert_eq!(g.infer_flags(), vec![
+            ImpliedFlag::Degree(DegreeConstraint {node: 0, min: 2, max: 2}),
+            ImpliedFlag::Degree(DegreeConstraint {node: 1, min: 2, max: 2}),
+            ImpliedFlag::Degree(DegreeConstraint {node: 2, min: 2, max: 2}),
+            ImpliedFlag::Degree(DegreeConstraint {node: 3, min: 0, max: 0}),
+        ]);
+
+        // Pre-set edges can be in addition to the edge constraints.
+        let mut g = Graph::new();
+        g.push(Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 1}]});
+        for _ in 0..2 {
+            g.push(Node {color: 1, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]});
+        }
+        g.set((0, 1), 2);
+        g.set((0, 2), 2);
+        assert!(g.clone().solve_all(SearchSettings::new()).next().is_some());
+        let flags = g.enable_implied_flags();
+        assert!(!flags.iter().any(|flag| matches!(flag, ImpliedFlag::Regular(_) | ImpliedFlag::Degree(_))));
+        assert!(g.solve_all(SearchSettings::new()).next().is_some());
+    }
+
+    #[test]
+    fn bench() {
+        let instances: Vec<_> = bench::instances().into_iter().filter(|i| i.name != "4cube").collect();
+        let settings = SearchSettings::new().max_iterations(1_000_000);
+        let results = bench::run(&instances, &settings, &[0, 1]);
+        assert_eq!(results.len(), 2 * instances.len());
+        for result in &results {
+            assert!(result.correct, "{}", result);
+        }
+        let a = bench::run(&instances[..1], &settings, &[7]);
+        let b = bench::run(&instances[..1], &settings, &[7]);
+        assert_eq!(a[0].stats.iterations, b[0].stats.iterations);
+        assert!(bench::instance("4cube").is_some());
+        assert!(bench::instance("unknown").is_none());
+    }
+
+    #[test]
+    fn named_nodes() {
+        let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]};
+        let mut g = Graph::new();
+        assert_eq!(g.push_named("a1", a.clone()), 0);
+        g.push(a.clone());
+        assert_eq!(g.push_named("c", a.clone()), 2);
+        assert_eq!(g.node_name(0), "a1");
+        assert_eq!(g.node_name(1), "1");
+        assert_eq!(g.find_node("c"), Some(2));
+        assert_eq!(g.find_node("b"), None);
+
+        let violations = g.diagnose();
+        assert!(violations.is_empty());
+        g.set((0, 2), 3);
+        let violations = g.diagnose();
+        assert_eq!(violations[0].to_string(), "node 0 has edge (0, 2) of color 3 that no edge constraint allows");
+        assert_eq!(g.named(&violations[0]).to_string(),
+                   "node a1 has edge (a1, c) of color 3 that no edge constraint allows");
+        assert!(g.why_not((0, 0), 2).unwrap().contains("Node a1"));
+
+        let h = Graph::from_json(&g.to_json()).unwrap();
+        assert_eq!(h.names, g.names);
+        assert!(g.graphviz("dot", &["white"], &["black"]).contains("0[regular=true,style=filled,fillcolor=white,label=\"a1\"];"));
+
+        let mut g = Graph::new();
+        g.push_named("x", a.clone());
+        g.push_named("y", a.clone());
+        g.push_named("z", a);
+        let dead = g.dead_constraints();
+        assert!(dead.is_empty());
+        let reason = g.explain_unsat(SearchSettings::new()).unwrap();
+        assert!(g.named(&reason).to_string().contains("node x"), "{}", g.named(&reason));
+    }
+
+    #[test]
+    fn palette() {
+        let palette = Palette::adinkra();
+        assert_eq!(palette.edge(2), &Style::solid("red"));
+        assert_eq!(palette.edge(3), &Style::dashed("red"));
+        assert_eq!(palette.edge(4).pen, "green");
+        assert_eq!(palette.node(2).fill, "black");
+
+        let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 3, node: 1}]};
+        let b = Node {color: 1, self_connected: false, edges: vec![Constraint {edge: 3, node: 0}]};
+        let mut g = Graph::new();
+        g.push(a);
+        g.push(b);
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        let dot = solution.graphviz_with_options(&GraphvizOptions::from_palette("dot", &palette));
+        assert!(dot.contains("0[regular=true,style=filled,fillcolor=\"black\",color=\"black\"];"), "{}", dot);
+        assert!(dot.contains("1 -- 0[color=\"red\",style=dashed];"), "{}", dot);
+        let h = Graph::from_dot(&dot).unwrap();
+        assert_eq!(h.get((0, 1)), 2);
+        let svg = solution.svg(&SvgOptions::from_palette(&palette));
+        assert!(svg.contains("stroke=\"red\" stroke-width=\"3\" stroke-dasharray=\"6,4\"/>"), "{}", svg);
+        assert!(svg.contains("fill=\"white\" stroke=\"black\""));
+        let tikz = solution.tikz(&TikzOptions::from_palette(&palette));
+        assert!(tikz.contains("\\node[fill=white,draw=black]"), "{}", tikz);
+        assert!(tikz.contains("\\draw[red,dashed]"), "{}", tikz);
+
+        let palette = Palette::from_colors(&["white"], &["black"]);
+        assert_eq!(palette.edge(5), &Style::solid("black"));
+    }
+
+    #[test]
+    fn solve_auto() {
+        let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}; 3]};
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let report = g.clone().solve_auto(SearchSettings::new());
+        assert!(report.outcome.solution().unwrap().puzzle.is_solved());
+        assert!(!report.probes.is_empty());
+
+        // Probes that are too short to complete the search select a configuration.
+        let report = g.clone().solve_auto_with(SearchSettings::new(), &AutoConfig::defaults(), 1);
+        assert_eq!(report.probes.len(), AutoConfig::defaults().len());
+        assert!(AutoConfig::defaults().iter().any(|config| config.name == report.config));
+        assert!(report.outcome.solution().is_some());
+
+        // The probes and the full search share the maximum number of iterations.
+        let settings = SearchSettings::new().max_iterations(4);
+        let report = g.clone().solve_auto_with(settings, &AutoConfig::defaults(), 1);
+        assert_eq!(report.probes.len(), 2);
+        assert!(matches!(report.outcome, SolveOutcome::Exhausted));
+
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.no_triangles = true;
+        let report = g.solve_auto(SearchSettings::new());
+        assert!(matches!(report.outcome, SolveOutcome::NoSolution));
+    }
+
+    #[test]
+    fn manifest() {
+        let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}; 2]};
+        let mut g = Graph::new();
+        for _ in 0..5 {g.push(a.clone())}
+        let settings = SearchSettings::new().seed(3);
+        let (report, manifest) = g.clone().solve_manifest(settings.clone());
+        assert!(report.outcome.solution().is_some());
+        assert_eq!(manifest.result, "solved");
+        assert_eq!(manifest.crate_version, env!("CARGO_PKG_VERSION"));
+        let json = manifest.to_json();
+        assert!(json.contains("\"seed\": 3,"), "{}", json);
+        assert!(json.contains("\"result\": \"solved\""));
+        assert!(json.contains(&format!("\"spec_hash\": \"{:016x}\"", manifest.spec_hash)));
+
+        // Equal runs give equal hashes.
+        let (_, other) = g.clone().solve_manifest(settings);
+        assert_eq!(other.spec_hash, manifest.spec_hash);
+        assert_eq!(other.result_hash, manifest.result_hash);
+
+        g.min_girth = Some(6);
+        let (_, other) = g.solve_manifest(SearchSettings::new());
+        assert_ne!(other.spec_hash, manifest.spec_hash);
+        assert_eq!(other.result_hash, None);
+        assert!(other.to_json().contains("\"result_hash\": null"));
+    }
+
+    #[test]
+    fn self_loops() {
+        let mut g = Graph::new();
+        let a = Node {
+            color: 0,
+            self_connected: true,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint::self_loop(3)],
+        };
+        g.push(a.clone());
+        g.push(a.clone());
+        assert!(g.colors((0, 0)).contains(&3));
+        let solution = g.clone().solve(SolveSettings::new()).unwrap();
+        assert_eq!(solution.puzzle.get((0, 0)), 3);
+        assert_eq!(solution.puzzle.get((1, 1)), 3);
+        assert_eq!(solution.puzzle.get((0, 1)), 2);
+        assert!(solution.puzzle.graphviz("sfdp", &["black"], &["red", "blue"]).contains("  1 -- 1[color=blue"));
+
+        // A loop of the wrong color does not satisfy the constraint.
+        let mut h = g.clone();
+        h.set((0, 1), 2);
+        h.set((0, 0), 2);
+        assert!(!h.node_satisfied(0).is_empty());
+
+        // Without self-connection, the constraint is dead.
+        let mut h = Graph::new();
+        h.push(Node {self_connected: false, ..a.clone()});
+        h.push(a);
+        let dead = h.dead_constraints();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].reason, DeadReason::MissingNodeColor);
+        assert_eq!(format!("{}", dead[0]), "node 0 requires self-loop 3, but the node is not self-connected");
+        assert!(h.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn overlay() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let solutions: Vec<Graph> = g.solve_all(SearchSettings::new()).map(|s| s.puzzle).collect();
+        assert_eq!(solutions.len(), 3);
+
+        let union = solutions[0].overlay(&solutions[1]);
+        assert!(union.conflicts.is_empty());
+        assert_eq!((0..4).flat_map(|i| (i + 1..4).map(move |j| (i, j)))
+            .filter(|&pos| union.graph.get(pos) == 2).count(), 4);
+
+        let common = solutions[0].intersect(&solutions[1]);
+        assert!(common.conflicts.is_empty());
+        assert!((0..4).all(|i| (i + 1..4).all(|j| common.graph.get((i, j)) == 1)));
+        assert_eq!(solutions[0].intersect(&solutions[0]).graph.edges, solutions[0].edges);
+
+        let mut other = solutions[0].clone();
+        let pos = (0..4).flat_map(|i| (i + 1..4).map(move |j| (i, j)))
+            .find(|&pos| solutions[0].get(pos) == 2).unwrap();
+        other.set(pos, 3);
+        let union = solutions[0].overlay(&other);
+        assert_eq!(union.conflicts, vec![(pos, 2, 3)]);
+        assert_eq!(union.graph.get(pos), 2);
+        assert_eq!(solutions[0].intersect(&other).graph.get(pos), 1);
+    }
+
+    #[test]
+    fn generators() {
+        let g = Graph::hypercube(2, 2);
+        assert_eq!(g.nodes.len(), 4);
+        assert_eq!(g.nodes[0].edges, vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}]);
+        assert!(g.solve(SolveSettings::new()).is_some());
+
+        let g = Graph::hypercube(3, 1);
+        assert_eq!(g.nodes.len(), 8);
+        assert!(g.no_triangles && g.connected);
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!((0..8).all(|i| (0..8).filter(|&j| solution.get((i, j)) == 2).count() == 3));
+
+        let g = Graph::grid(3, 3);
+        let degrees: Vec<usize> = g.nodes.iter().map(|node| node.edges.len()).collect();
+        assert_eq!(degrees, vec![2, 2, 2, 2, 3, 3, 3, 3, 4]);
+        assert_eq!(g.get((0, 8)), 1);
+        assert_eq!(g.get((0, 4)), 0);
+        let solution = g.solve_all(SearchSettings::new()).next().unwrap().puzzle;
+        assert_eq!((0..9).map(|i| (0..i).filter(|&j| solution.get((i, j)) == 2).count()).sum::<usize>(), 12);
+
+        let g = Graph::grid(3, 1);
+        assert_eq!(g.nodes.iter().map(|node| node.edges.len()).collect::<Vec<_>>(), vec![1, 1, 2]);
+        assert!(g.solve(SolveSettings::new()).is_some());
+    }
+
+    #[test]
+    fn adinkra() {
+        assert!(Graph::adinkra(2, 1).is_none());
+        assert!(Graph::adinkra(3, 4).is_none());
+
+        let g = Graph::adinkra(3, 3).unwrap();
+        assert_eq!(g.nodes.len(), 8);
+        assert_eq!(g.commute_quad, Some(false));
+        assert_eq!(g.bipartition, Some((0, 1)));
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.commute_quad_satisfied(false));
+
+        // Folded by the code `1111`.
+        let g = Graph::adinkra(4, 3).unwrap();
+        assert_eq!(g.nodes.len(), 8);
+        assert_eq!(g.commute_quad, None);
+        assert!(g.nodes.iter().all(|node| node.edges.len() == 4));
+        assert!(g.solve(SolveSettings::new()).is_some());
+    }
+
+    #[test]
+    fn frequency_graph() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let all: Vec<Graph> = g.solve_all(SearchSettings::new()).map(|s| s.puzzle).collect();
+        let freq = solutions::frequency_graph(&all);
+        assert_eq!(freq.solutions, 3);
+        assert_eq!(freq.counts.len(), 6);
+        assert_eq!(freq.count((1, 0), 2), 1);
+        assert_eq!(freq.count((0, 1), 3), 0);
+        assert!((freq.frequency((0, 1), 2) - 1.0 / 3.0).abs() < 1e-9);
+        let dot = freq.graphviz("sfdp", &["white"], &["#ff0000"]);
+        assert!(dot.contains("  0 -- 1[color=\"#ff000055\",tooltip=\"1/3\"];"));
+
+        let mut other = all[0].clone();
+        let (pos, _, _) = freq.counts.iter().find(|&&(pos, _, _)| other.get(pos) == 2).cloned().unwrap();
+        other.set(pos, 3);
+        let freq = solutions::frequency_graph(&[all[0].clone(), other]);
+        assert_eq!(freq.count(pos, 2), 1);
+        assert_eq!(freq.count(pos, 3), 1);
+        assert!(solutions::frequency_graph(&[]).counts.is_empty());
+    }
+
+    #[test]
+    fn sign_pairs() {
+        // An Adinkra with colors that do not follow the even/odd convention.
+        let mut g = Graph::adinkra(2, 2).unwrap();
+        let map = |c: Color| match c {3 => 7, 5 => 9, c => c};
+        for node in &mut g.nodes {
+            for con in &mut node.edges {con.edge = map(con.edge)}
+        }
+        assert_eq!(g.flipped(7), Some(6));
+        assert!(g.clone().solve(SolveSettings::new()).is_none());
+
+        g.push_sign_pair(7, 2);
+        g.push_sign_pair(4, 9);
+        assert_eq!(g.sign_pairs, vec![(2, 7), (4, 9)]);
+        assert_eq!(g.flipped(7), Some(2));
+        assert_eq!(g.flipped(3), None);
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.commute_quad_satisfied(false));
+        assert!(reference::commute_quad_satisfied(&solution, false));
+        assert!(solution.diagnose().is_empty());
+
+        assert_eq!(Graph::from_json(&g.to_json()).unwrap().sign_pairs, g.sign_pairs);
+        assert_eq!(Graph::from_spec_string(&g.to_spec_string()).unwrap().sign_pairs, g.sign_pairs);
+    }
+
+    #[test]
+    fn spec_view() {
+        let mut g = Graph::new();
+        let a = Node {
+            color: 0,
+            self_connected: true,
+            edges: vec![Constraint {edge: 2, node: 1}, Constraint::self_loop(3)],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        g.push_named("\"a\"", a);
+        g.push(b.clone());
+        g.push(b);
+        g.set((0, 2), 1);
+        g.push_pair((0, 1));
+        g.push_degree(1, 1, 1);
+        g.no_triangles = true;
+
+        let dot = g.spec_graphviz();
+        assert!(dot.contains("  0[label=\"\\\"a\\\": color 0, self\\n2:1 3:self\"];"));
+        assert!(dot.contains("  1[label=\"1: color 1\\n2:0\\ndegree 1..=1\"];"));
+        assert!(dot.contains("  0 -- 2[style=dotted,label=\"no-edge\"];"));
+        assert!(dot.contains("  0 -- 1[style=dashed,label=\"pair\"];"));
+        assert!(dot.contains("constraints[shape=note,label=\"no triangles"));
+
+        let mermaid = g.spec_mermaid();
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("  n0[\"#quot;a#quot;: color 0, self<br/>2:1 3:self\"]"));
+        assert!(mermaid.contains("  n0 -.-|pair| n1"));
+        assert!(mermaid.contains("  constraints[/\"no triangles<br/>pairs: 1<br/>degrees: 1\"/]"));
+    }
+
+    #[test]
+    fn quad_rules() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 4, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+
+        let mut h = g.clone();
+        h.push_quad_rule(4, 2, Some(true));
+        assert_eq!(h.quad_rules, vec![QuadRule {colors: (2, 4), commute: Some(true)}]);
+        let solution = h.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.quads_satisfied());
+        assert!(reference::quads_satisfied(&solution, None));
+        assert!(solution.diagnose().is_empty());
+        assert_eq!(Graph::from_json(&h.to_json()).unwrap().quad_rules, h.quad_rules);
+        assert_eq!(Graph::from_spec_string(&h.to_spec_string()).unwrap().quad_rules, h.quad_rules);
+
+        // Anticommuting quads need a dashed edge.
+        let mut h = g.clone();
+        h.push_quad_rule(2, 4, Some(false));
+        assert!(h.solve(SolveSettings::new()).is_none());
+
+        // Rules take precedence over `commute_quad`.
+        let mut h = g.clone();
+        h.commute_quad = Some(false);
+        assert!(h.clone().solve(SolveSettings::new()).is_none());
+        h.push_quad_rule(3, 5, None);
+        let solution = h.solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(solution.quads_satisfied());
+        assert_eq!(reference::check(&solution), Ok(()));
+    }
+
+    #[test]
+    fn lint() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..2 {g.push(a.clone())}
+        assert!(g.lint().is_empty());
+
+        g.push(Node {color: 1, self_connected: false, edges: vec![Constraint {edge: 2, node: 1}]});
+        g.push(Node {color: 2, self_connected: false, edges: vec![]});
+        g.push_pair((0, 1));
+        g.push_pair((1, 0));
+        g.set((0, 2), 1);
+        g.push_pair((0, 2));
+        g.push_degree(0, 1, 1);
+        g.push_degree(1, 2, 3);
+        g.push_degree(0, 1, 1);
+        g.connected = true;
+        let lints = g.lint();
+        assert_eq!(lints, vec![
+            Lint::DuplicatePair {pair: (0, 1), index: 1, first: 0},
+            Lint::ImpossiblePair {pair: (0, 2)},
+            Lint::DuplicateConstraint {kind: "degrees", index: 2, first: 0},
+            Lint::IsolatedUnderConnected {node: 3},
+            Lint::SelfLoopOnly {node: 2, slot: 0, constraint: Constraint {edge: 2, node: 1}},
+            Lint::DegreeMismatch {index: 1, edges: 1},
+        ]);
+        assert_eq!(format!("{}", lints[2]), "degrees[2] duplicates degrees[0]");
+    }
+
+    #[test]
+    fn shrink_unsat() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 3, node: 1}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.push(b.clone());
+        g.push(b.clone());
+        g.push_pair((4, 5));
+        assert!(g.shrink_unsat(SearchSettings::new()).is_none());
+
+        // Four cubic nodes form a complete graph, which has triangles.
+        g.no_triangles = true;
+        let (h, kept) = g.shrink_unsat(SearchSettings::new()).unwrap();
+        assert_eq!(kept, vec![0, 1, 2, 3]);
+        assert_eq!(h.nodes.len(), 4);
+        assert!(h.pairs.is_empty());
+        assert!(h.no_triangles);
+        assert!(h.solve(SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn quad_nodes() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let b = Node {color: 1, self_connected: false, edges: vec![]};
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        g.push(b.clone());
+        g.meet_quad = true;
+        assert!(g.clone().solve(SolveSettings::new()).is_none());
+        g.restrict_quads(&[3, 1, 0, 2]);
+        assert_eq!(g.quad_nodes, Some(vec![0, 1, 2, 3]));
+        let solution = g.clone().solve(SolveSettings::new()).unwrap();
+        assert_eq!(reference::check(&solution.puzzle), Ok(()));
+        assert_eq!(Graph::from_json(&g.to_json()).unwrap().quad_nodes, g.quad_nodes);
+        assert_eq!(Graph::from_spec_string(&g.to_spec_string()).unwrap().quad_nodes, g.quad_nodes);
+
+        // A quad with one edge of another color does not commute.
+        let mut h = Graph::new();
+        for _ in 0..5 {h.push(a.clone())}
+        h.set((0, 1), 2);
+        h.set((1, 2), 2);
+        h.set((2, 3), 2);
+        h.set((3, 0), 3);
+        h.commute_quad = Some(true);
+        assert!(!h.quads_satisfied());
+        h.restrict_quads(&[0, 1, 2, 4]);
+        assert!(h.quads_satisfied());
+        assert!(reference::quads_satisfied(&h, h.commute_quad));
+        assert!(h.diagnose().iter().all(|v| !matches!(v, Violation::Quad {..})));
+    }
+
+    #[test]
+    fn anti_pairs() {
+        let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]};
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(), 3);
+        g.push_anti_pair((1, 0));
+        assert_eq!(g.anti_pairs, vec![(0, 1)]);
+        assert_eq!(g.colors((0, 1)), vec![1]);
+        assert_eq!(g.why_not((0, 1), 2), Some("`anti_pairs`: The edge is forbidden".into()));
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(), 2);
+        g.push_anti_pair((0, 2));
+        let solution = g.clone().solve(SolveSettings::new()).unwrap();
+        assert_eq!(solution.puzzle.get((0, 3)), 2);
+        assert_eq!(reference::check(&solution.puzzle), Ok(()));
+        assert_eq!(Graph::from_json(&g.to_json()).unwrap().anti_pairs, g.anti_pairs);
+        assert_eq!(Graph::from_spec_string(&g.to_spec_string()).unwrap().anti_pairs, g.anti_pairs);
+
+        let mut h = g.clone();
+        h.set((0, 1), 2);
+        assert!(h.diagnose().contains(&Violation::AntiPair {pair: (0, 1)}));
+        g.push_pair((0, 1));
+        assert!(g.lint().contains(&Lint::ImpossiblePair {pair: (0, 1)}));
+    }
+
+    #[test]
+    fn edit_distance() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut reference = Graph::new();
+        for _ in 0..4 {reference.push(a.clone())}
+        let mut g = reference.clone();
+        for &pos in &[(0, 1), (1, 2), (2, 3), (0, 2)] {reference.set(pos, 2)}
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(), 3);
+        g.push_edit_distance(&reference, 1);
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(), 0);
+        g.edit_distances[0].max = 2;
+        let solutions: Vec<Graph> = g.clone().solve_all(SearchSettings::new()).map(|s| s.puzzle).collect();
+        assert_eq!(solutions.len(), 2);
+        for solution in &solutions {assert_eq!(reference::check(solution), Ok(()))}
+        assert_eq!(Graph::from_json(&g.to_json()).unwrap().edit_distances, g.edit_distances);
+        assert_eq!(Graph::from_spec_string(&g.to_spec_string()).unwrap().edit_distances, g.edit_distances);
+
+        // Two edges already differ, so the other edges must agree with the reference.
+        let mut h = g.clone();
+        h.set((0, 3), 2);
+        h.set((1, 2), 1);
+        assert_eq!(h.colors((0, 1)), vec![2]);
+        assert_eq!(h.why_not((0, 1), 1),
+                   Some("`edit_distances`: At most 2 edges can differ from the reference graph".into()));
+    }
+
+    #[test]
+    fn repair() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let mut reference = g.clone();
+        for &pos in &[(0, 1), (1, 2), (2, 3), (0, 2)] {reference.set(pos, 2)}
+        let repair = g.repair(&reference, SearchSettings::new()).unwrap();
+        assert_eq!(repair.changes.len(), 2);
+        assert!(repair.graph.edit_distances.is_empty());
+        assert_eq!(reference::check(&repair.graph), Ok(()));
+        for &((i, j), old, new) in &repair.changes {
+            assert_eq!(reference.get((i, j)).max(1), old);
+            assert_eq!(repair.graph.get((i, j)).max(1), new);
+        }
+
+        // A valid graph needs no changes.
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert!(g.repair(&solution, SearchSettings::new()).unwrap().changes.is_empty());
+
+        // Pre-set edges are kept when solving for the difference.
+        let mut g = Graph::new();
+        for _ in 0..3 {g.push(a.clone())}
+        g.set((0, 1), 2);
+        let mut reference = g.clone();
+        for &pos in &[(0, 2), (1, 2)] {reference.set(pos, 2)}
+        let repair = g.repair(&reference, SearchSettings::new().difference(true)).unwrap();
+        assert!(repair.changes.is_empty());
+        assert_eq!(repair.graph.get((0, 1)), 2);
+    }
+
+    #[test]
+    fn restrict() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(), 6);
+        // The edge has color 3 or is absent.
+        g.restrict((1, 0), &[1, 3]);
+        assert_eq!(g.allowed((0, 1)), Some(&[1, 3][..]));
+        assert_eq!(g.colors((0, 1)), vec![1, 3]);
+        assert_eq!(g.why_not((0, 1), 2), Some("`restrictions`: The edge is restricted to [1, 3]".into()));
+        let solutions: Vec<Graph> = g.clone().solve_all(SearchSettings::new()).map(|s| s.puzzle).collect();
+        assert_eq!(solutions.len(), 4);
+        for solution in &solutions {assert_eq!(reference::check(solution), Ok(()))}
+        assert_eq!(Graph::from_json(&g.to_json()).unwrap().restrictions, g.restrictions);
+        assert_eq!(Graph::from_spec_string(&g.to_spec_string()).unwrap().restrictions, g.restrictions);
+
+        g.restrict((0, 1), &[3]);
+        assert_eq!(g.allowed((0, 1)), Some(&[3][..]));
+        assert_eq!(g.clone().solve_all(SearchSettings::new()).count(), 2);
+    }
+
+    #[test]
+    fn why_not() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        for _ in 0..6 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.push_cardinality(&[(0, 1), (2, 3)], 1, 1);
+        g.degrees.push(DegreeConstraint {node: 4, min: 2, max: 2});
+        g.color_counts.push(ColorCount {color: 3, min: 0, max: 3});
+        assert!(g.why_not((0, 0), 2).unwrap().contains("self-connected"));
+
+        // Explanations agree with the possible colors in every partial assignment.
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        let mut h = g.clone();
+        for i in 0..6 {
+            for j in i..6 {
+                for k in 0..6 {
+                    for l in k..6 {
+                        let colors = h.colors((k, l));
+                        for c in 1..5 {
+                            assert_eq!(h.why_not((k, l), c).is_none(), colors.contains(&c),
+                                "{:?} {} {:?}", (k, l), c, h.why_not((k, l), c));
+                        }
+                    }
+                }
+                h.set((i, j), solution.get((i, j)));
+            }
+        }
+
+        g.set((0, 1), 2);
+        assert_eq!(g.why_not((2, 3), 2).unwrap(),
+            "`cardinalities`: At most 1 edges of [(0, 1), (2, 3)] can be colored");
+        assert!(g.why_not((0, 2), 2).unwrap().starts_with("Node 0 has no unsatisfied edge constraint"));
+        g.set((0, 2), 3);
+        g.set((1, 2), 3);
+        assert!(g.why_not((3, 4), 2).unwrap().starts_with("`no_triangles`"));
+    }
+
+    #[test]
+    fn fuzz() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+
+        let mut fuzzer = Fuzzer::new(g.clone(), 0);
+        let (h, mutations) = fuzzer.mutate();
+        let mut expected = g.clone();
+        for mutation in &mutations {mutation.apply(&mut expected)}
+        assert!(h.spec_diff(&expected).is_empty());
+        assert!(!h.spec_diff(&g).is_empty() || mutations.len() > 1);
+
+        let mut a = Fuzzer::from_bytes(g.clone(), b"abc");
+        let mut b = Fuzzer::from_bytes(g.clone(), b"abc");
+        assert_eq!(a.mutate().1, b.mutate().1);
+
+        let mut fuzzer = Fuzzer::new(g, 1);
+        fuzzer.settings.set_max_iterations(1000);
+        fuzzer.run_many(50).unwrap();
+    }
+
+    #[test]
+    fn reference() {
+        let mut state: u64 = 7;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..200 {
+            let n = 2 + (next() % 6) as usize;
+            let mut g = Graph::new();
+            for _ in 0..n {
+                g.push(Node {color: 0, self_connected: false, edges: vec![]});
+            }
+            for i in 0..n {
+                for j in i+1..n {
+                    let val = next() % 4;
+                    if val > 0 {g.set((i, j), val)};
+                }
+            }
+            // The cache of `commute_quad_satisfied` assumes a fixed setting.
+            for &commute in &[true, false] {
+                assert_eq!(reference::commute_quad_satisfied(&g, commute),
+                           g.clone().commute_quad_satisfied(commute));
+            }
+            assert_eq!(reference::has_triangles(&g), g.has_triangles());
+            assert_eq!(reference::is_connected(&g), g.is_connected());
+            assert_eq!(reference::has_isolated_nodes(&g), g.has_isolated_nodes());
+            assert_eq!(reference::is_bipartite(&g), !g.has_odd_cycle());
+            assert_eq!(reference::girth(&g), g.girth());
+            for i in 0..n {
+                assert_eq!(reference::local_girth(&g, i), g.local_girth(i));
+            }
+        }
+
+        // Edges that no constraint accounts for are allowed, e.g. pre-set edges.
+        let mut g = Graph::new();
+        g.push(Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 1}]});
+        for _ in 0..2 {
+            g.push(Node {color: 1, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]});
+        }
+        g.set((0, 1), 2);
+        g.set((0, 2), 2);
+        g.set((1, 2), 1);
+        assert!(g.all_satisfied());
+        assert!(reference::nodes_satisfied(&g));
+        // Every constraint needs its own edge.
+        g.nodes[0].edges.push(Constraint {edge: 2, node: 1});
+        assert!(reference::nodes_satisfied(&g));
+        g.nodes[0].edges.push(Constraint {edge: 2, node: 1});
+        assert!(!reference::nodes_satisfied(&g));
+    }
+
+    #[cfg(feature = "verify-solutions")]
+    #[test]
+    fn verify_solutions() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.connected = true;
+        let solution = g.clone().solve(SolveSettings::new()).unwrap().puzzle;
+        assert_eq!(reference::check(&solution), Ok(()));
+
+        let mut h = solution.clone();
+        let n = h.nodes.len();
+        let (i, j) = (0..n).flat_map(|i| (i+1..n).map(move |j| (i, j)))
+            .find(|&pos| solution.get(pos) >= 2).unwrap();
+        h.set((i, j), 1);
+        assert_eq!(reference::check(&h), Err("nodes"));
+
+        // Solving runs the verification, which accepts pre-set edges beyond the edge constraints.
+        let mut g = Graph::new();
+        g.push(Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 1}]});
+        for _ in 0..2 {
+            g.push(Node {color: 1, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]});
+        }
+        g.set((0, 1), 2);
+        g.set((0, 2), 2);
+        assert!(g.clone().solve_all(SearchSettings::new()).next().is_some());
+        assert!(g.clone().solve_all(SearchSettings::new().difference(true)).next().is_some());
+        assert!(g.clone().solve(SolveSettings::new()).is_some());
+        assert!(g.solve(SolveSettings::new().difference(true)).is_some());
+
+        let mut g = Digraph::new();
+        let a = DiNode {
+            color: 0,
+            self_connected: false,
+            in_edges: vec![Constraint {edge: 2, node: 0}],
+            out_edges: vec![Constraint {edge: 2, node: 0}],
+        };
+        for _ in 0..3 {g.push(a.clone())}
+        g.set((0, 1), 2);
+        let solution = g.solve(SolveSettings::new().difference(true)).unwrap().puzzle;
+        assert_eq!(solution.get((0, 1)), 0);
+        assert_eq!(reference::check_digraph(&solution), Err("nodes"));
+    }
+
+    #[cfg(feature = "verify-solutions")]
+    #[test]
+    #[should_panic(expected = "Solution violates `pairs` when verified independently")]
+    fn verify_solutions_panics() {
+        let mut g = Graph::new();
+        for _ in 0..2 {g.push(Node {color: 0, self_connected: false, edges: vec![]})}
+        g.push_pair((0, 1));
+        reference::verify(&g.clone(), &g);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        g.push_pair((0, 1));
+        g.push_conditional(2, Flag::Connected);
+        g.set((2, 3), 1);
+
+        let json = serde_json::to_string(&g).unwrap();
+        let h: Graph = serde_json::from_str(&json).unwrap();
+        assert!(g.spec_diff(&h).is_empty());
+        assert_eq!(h.get((2, 3)), 1);
+
+        let solution = h.solve(SolveSettings::new()).unwrap().puzzle;
+        let json = serde_json::to_string(&solution).unwrap();
+        let h: Graph = serde_json::from_str(&json).unwrap();
+        assert!(h.is_solved());
+        assert_eq!(h.edges, solution.edges);
+
+        assert!(serde_json::from_str::<Graph>(r#"{"nodes":[],"edges":[[0, 0, 2]]}"#).is_err());
+        assert!(json.contains("\"format_version\":1"));
+        assert!(serde_json::from_str::<Graph>(r#"{"format_version":99,"nodes":[],"edges":[]}"#).is_err());
+        let h: Graph = serde_json::from_str(r#"{"nodes":[{"color":0,"self_connected":false,"edges":[]}],
+            "edges":[[0, 0, 1]],"connected":true}"#).unwrap();
+        assert!(h.connected);
+
+        // The serialized form has the same schema as the JSON format.
+        g.push_component_constraint(ComponentConstraint::Size {min: 1, max: 8});
+        g.push_named("a\u{1}\"", a.clone());
+        g.restrict((0, 2), &[1, 2]);
+        let h = Graph::from_json(&serde_json::to_string(&g).unwrap()).unwrap();
+        assert!(g.spec_diff(&h).is_empty());
+        let h: Graph = serde_json::from_str(&g.to_json()).unwrap();
+        assert!(g.spec_diff(&h).is_empty());
+    }
+
+    #[test]
+    fn json() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}; 2],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: true,
+            edges: vec![Constraint {edge: 2, node: 0}; 2],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(b.clone());
+        g.push(a.clone());
+        g.push(b.clone());
+        g.set((0, 1), 2);
+        g.set((1, 1), 1);
+        g.no_triangles = true;
+        g.min_girth = Some(4);
+        g.commute_quad = Some(false);
+        g.push_pair((2, 3));
+        g.push_cardinality(&[(0, 3), (1, 2)], 1, 2);
+        g.push_degree(0, 2, 2);
+        g.push_color_count(2, 0, 4);
+        g.set_label(0, 3);
+        g.bipartition_by_color(0, 1);
+        g.push_conditional(2, Flag::CommuteQuad(false));
+        g.push_component_constraint(ComponentConstraint::Size {min: 1, max: 4});
+        g.push_component_constraint(ComponentConstraint::Regular);
+        g.push_twins((0, 2));
+        g.set_priority((0, 3), -2);
+        let mut pattern = Graph::new();
+        pattern.push(a.clone());
+        pattern.push(b.clone());
+        pattern.set((0, 1), 2);
+        g.require_subgraph(&pattern);
+
+        let json = g.to_json();
+        let h = Graph::from_json(&json).unwrap();
+        assert!(g.spec_diff(&h).is_empty(), "{}\n{}", json, g.spec_diff(&h));
+        assert_eq!(h.to_json(), json);
+
+        let h = Graph::from_json(r#"{"nodes": [{"color": 0}], "connected": true}"#).unwrap();
+        assert_eq!(h.nodes.len(), 1);
+        assert!(h.connected);
+
+        let err = Graph::from_json("{\n  \"nodes\": [],\n  \"no_triangle\": true\n}").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(err.message.contains("no_triangle"));
+        assert!(Graph::from_json(r#"{"nodes": [], "edges": [[0, 1, 2]]}"#).is_err());
+        assert!(Graph::from_json(r#"{"nodes": [{"color": -1}]}"#).is_err());
+        assert!(Graph::from_json(r#"{"nodes": [] "planar": true}"#).is_err());
+
+        // Control characters are escaped.
+        let mut g = Graph::new();
+        g.push_named("a\u{1}\r\u{8}/\"\\", a.clone());
+        let json = g.to_json();
+        assert!(json.contains(r#""a\u0001\r\b/\"\\""#));
+        assert_eq!(Graph::from_json(&json).unwrap().names, g.names);
+        let h = Graph::from_json(r#"{"nodes": [{"color": 0}], "names": ["\u00e5\/\ud83d\ude00\f"]}"#).unwrap();
+        assert_eq!(h.names, vec![Some("\u{e5}/\u{1f600}\u{c}".to_string())]);
+        assert!(Graph::from_json(r#"{"nodes": [{"color": 0}], "names": ["\x"]}"#).is_err());
+        assert!(Graph::from_json("{\"nodes\": [{\"color\": 0}], \"names\": [\"\u{1}\"]}").is_err());
+        // Deep nesting is an error instead of a stack overflow.
+        assert!(Graph::from_json(&"[".repeat(200_000)).unwrap_err().message.contains("Nesting"));
+    }
+
+    #[test]
+    fn progress() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        assert_eq!(g.progress(), 0.0);
+
+        let mut solutions = g.solve_all(SearchSettings::new());
+        assert_eq!(solutions.progress(), 0.0);
+        let solution = solutions.next().unwrap().puzzle;
+        assert_eq!(solution.progress(), 1.0);
+
+        let mut partial = solution.clone();
+        partial.set((0, 1), 0);
+        partial.set((2, 3), 0);
+        let p = partial.progress();
+        assert!(0.0 < p && p < 1.0);
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn petgraph() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 1}, Constraint {edge: 3, node: 1}],
+        };
+        let b = Node {
+            color: 1,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}, Constraint {edge: 3, node: 0}],
+        };
+        let mut g = Graph::new();
+        g.push(a.clone());
+        g.push(b.clone());
+        g.push(a.clone());
+        g.push(b.clone());
+        let solution = g.solve(SolveSettings::new()).unwrap().puzzle;
+
+        let pg: petgraph::graph::UnGraph<Color, Color> = (&solution).into();
+        assert_eq!(pg.node_count(), 4);
+        assert_eq!(pg.edge_count(), 4);
+        assert_eq!(pg.node_weights().cloned().collect::<Vec<_>>(), vec![0, 1, 0, 1]);
+        let h = Graph::from_petgraph(&pg);
+        for i in 0..4 {
+            for j in 0..4 {
+                assert_eq!(h.get((i, j)).max(1), solution.get((i, j)).max(1));
+            }
+        }
+
+        let pg: petgraph::graph::DiGraph<Color, Color> = (&solution).into();
+        assert!(pg.raw_edges().iter().all(|e| e.source() < e.target()));
+    }
+
+    #[test]
+    fn priority() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3],
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        assert_eq!(g.min_colors(), Some((0, 1)));
+        g.set_priority((5, 3), 10);
+        assert_eq!(g.priority((3, 5)), 10);
+        assert_eq!(g.min_colors(), Some((3, 5)));
+        let solution = g.clone().solve_all(SearchSettings::new()).next().unwrap();
+        assert_eq!(solution.puzzle.provenance((3, 5)), Provenance::Branched);
+
+        g.push(a.clone());
+        assert_eq!(g.priority((8, 0)), 0);
+        g.set_priority((0, 8), 20);
+        assert_eq!(g.min_colors(), Some((0, 8)));
+    }
+
+    #[test]
+    fn sinks() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let mut buf: Vec<u8> = vec![];
+        let count = g.clone().solve_all(SearchSettings::new())
+            .write_to(JsonlSink::new(&mut buf)).unwrap();
+        assert_eq!(count, 6);
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.lines().count(), 6);
+        assert!(text.lines().all(|line| line.starts_with("{\"nodes\":[0,0,0,0],\"edges\":[")));
+
+        let mut buf: Vec<u8> = vec![];
+        let mut sink = DotSink::new(&mut buf, "sfdp", &["black"], &["red", "blue"]);
+        let count = g.solve_all(SearchSettings::new()).write_to(&mut sink).unwrap();
+        assert_eq!(count, 6);
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.matches("strict graph").count(), 6);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn solve_async() {
+        use std::future::Future;
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake};
+
+        struct ThreadWaker(std::thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {self.0.unpark()}
+        }
+
+        fn block_on<F: Future>(fut: F) -> F::Output {
+            let mut fut = Box::pin(fut);
+            let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+            let mut cx = Context::from_waker(&waker);
+            loop {
+                match fut.as_mut().poll(&mut cx) {
+                    Poll::Ready(x) => return x,
+                    Poll::Pending => std::thread::park(),
+                }
+            }
+        }
+
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![Constraint {edge: 2, node: 0}; 3]
+        };
+        let mut g = Graph::new();
+        for _ in 0..8 {g.push(a.clone())}
+        g.no_triangles = true;
+        let fut = g.clone().solve_async(SearchSettings::new());
+        let solution = block_on(fut).unwrap();
+        assert!(solution.puzzle.is_solved());
+
+        let token = CancelToken::new();
+        token.cancel();
+        let fut = g.clone().solve_async(SearchSettings::new().cancel(token));
+        assert!(block_on(fut).is_none());
+
+        // There is no solution, since the sum of degrees is odd, but the search takes long.
+        g.push(a.clone());
+        g.push(a.clone());
+        g.push(a.clone());
+        g.push(a.clone());
+        g.push(a.clone());
+        let mut fut = g.solve_async(SearchSettings::new());
+        let progress = fut.take_progress().unwrap();
+        assert!(fut.take_progress().is_none());
+        assert!(progress.recv().unwrap().iterations >= 1000);
+        // Dropping the future cancels the search, which closes the channel.
+        drop(fut);
+        while progress.recv().is_ok() {}
+    }
+
+    #[cfg(feature = "store")]
+    #[test]
+    fn solution_store() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let dir = std::env::temp_dir()
+            .join(format!("graph_solver_store_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut store = SolutionStore::open(&dir).unwrap();
+        let mut inserted = 0;
+        for solution in g.solve_all(SearchSettings::new()) {
+            if store.insert(&solution.puzzle).unwrap() {inserted += 1}
+        }
+        assert_eq!(inserted, 1);
+        let store = SolutionStore::open(&dir).unwrap();
+        assert_eq!(store.len(), 1);
+        let all = store.all().unwrap();
+        assert!(all.iter().all(|s| s.metrics.edges == 4 && s.metrics.girth == Some(4)));
+        assert!(store.contains(&all[0].graph()).unwrap());
+        // The hash is stable across versions of Rust.
+        let bytes: Vec<u8> = all[0].form.iter().flat_map(|c| c.to_le_bytes()).collect();
+        assert_eq!(all[0].hash, manifest::fnv1a(&bytes));
+        assert!(store.query(|m| m.components > 1).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn canonical() {
+        let a = Node {
+            color: 0,
+            self_connected: false,
+            edges: vec![
+                Constraint {edge: 2, node: 0},
+                Constraint {edge: 3, node: 0},
+            ],
+        };
+        let mut g = Graph::new();
+        for _ in 0..4 {g.push(a.clone())}
+        let solutions: Vec<Graph> = g.clone().solve_all(SearchSettings::new())
+            .map(|solution| solution.puzzle).collect();
+        for s in &solutions {assert!(s.is_isomorphic(&solutions[0]))};
+        assert_eq!(g.clone().solve_all_canonical(SearchSettings::new()).count(), 1);
+
+        let mut h = g.clone();
+        h.set((0, 1), 2);
+        h.set((1, 2), 3);
+        g.set((2, 3), 2);
+        g.set((1, 2), 3);
+        assert!(g.is_isomorphic(&h));
+        g.set((1, 2), 2);
+        assert!(!g.is_isomorphic(&h));
+
+        // Automorphisms prune the search, e.g. for perfect matchings.
+        let b = Node {color: 0, self_connected: false, edges: vec![]};
+        let mut g = Graph::new();
+        for _ in 0..16 {g.push(b.clone())}
+        let mut h = g.clone();
+        for k in 0..8 {
+            g.set((2 * k, 2 * k + 1), 2);
+            h.set((k, 15 - k), 2);
+        }
+        assert!(g.is_isomorphic(&h));
+        h.set((0, 15), 3);
+        assert!(!g.is_isomorphic(&h));
+    }
 }
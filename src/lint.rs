@@ -0,0 +1,144 @@
+//! Detection of common mistakes in specifications.
+
+use std::fmt;
+
+use super::*;
+
+/// A likely mistake in a specification.
+///
+/// For more information, see `Graph::lint`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Lint {
+    /// A global constraint is identical to an earlier constraint of the same kind.
+    DuplicateConstraint {
+        /// The kind of constraint, e.g. `degrees`.
+        kind: &'static str,
+        /// The index of the duplicate.
+        index: usize,
+        /// The index of the earlier constraint.
+        first: usize,
+    },
+    /// A pair constraint is identical to an earlier pair constraint.
+    DuplicatePair {
+        /// The edge of the pair.
+        pair: (usize, usize),
+        /// The index of the duplicate.
+        index: usize,
+        /// The index of the earlier pair constraint.
+        first: usize,
+    },
+    /// A pair constraint requires an edge that is pre-set to no-edge or forbidden by an anti-pair,
+    /// or a self-loop of a node that is not self-connected.
+    ImpossiblePair {
+        /// The edge of the pair.
+        pair: (usize, usize),
+    },
+    /// A node without edge constraints can not get edges, but the graph must be connected.
+    IsolatedUnderConnected {
+        /// The node.
+        node: usize,
+    },
+    /// An edge constraint can only be satisfied by a self-loop,
+    /// but the node is not self-connected.
+    SelfLoopOnly {
+        /// The node.
+        node: usize,
+        /// The index of the edge constraint in the node.
+        slot: usize,
+        /// The edge constraint.
+        constraint: Constraint,
+    },
+    /// A degree constraint excludes the number of edge constraints of its node.
+    ///
+    /// Every edge constraint is matched by one edge in a solution,
+    /// so the degree of a node equals its number of edge constraints.
+    DegreeMismatch {
+        /// The index of the degree constraint.
+        index: usize,
+        /// The number of edge constraints of the node.
+        edges: usize,
+    },
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Lint::DuplicateConstraint {kind, index, first} =>
+                write!(w, "{}[{}] duplicates {}[{}]", kind, index, kind, first),
+            Lint::DuplicatePair {pair, index, first} =>
+                write!(w, "pair ({}, {}) at {} duplicates pair at {}", pair.0, pair.1, index, first),
+            Lint::ImpossiblePair {pair} =>
+                write!(w, "pair ({}, {}) can never have an edge", pair.0, pair.1),
+            Lint::IsolatedUnderConnected {node} =>
+                write!(w, "node {} has no edge constraints, but the graph must be connected", node),
+            Lint::SelfLoopOnly {node, slot, constraint} =>
+                write!(w, "edge constraint {} of node {} ({}:{}) needs a self-loop, \
+                           but the node is not self-connected", slot, node, constraint.edge, constraint.node),
+            Lint::DegreeMismatch {index, edges} =>
+                write!(w, "degrees[{}] excludes the {} edge constraints of the node", index, edges),
+        }
+    }
+}
+
+/// Reports every item that is identical to an earlier item.
+fn duplicates<T: PartialEq>(res: &mut Vec<Lint>, kind: &'static str, items: &[T]) {
+    for (index, x) in items.iter().enumerate() {
+        if let Some(first) = items[..index].iter().position(|y| y == x) {
+            res.push(Lint::DuplicateConstraint {kind, index, first});
+        }
+    }
+}
+
+impl Graph {
+    /// Checks the specification for likely mistakes.
+    ///
+    /// Unlike `Graph::dead_constraints`, this does not only report what makes the puzzle unsolvable,
+    /// but also redundant constraints, which are often copy-paste errors.
+    /// An empty list does not mean that the puzzle has a solution.
+    pub fn lint(&self) -> Vec<Lint> {
+        let n = self.nodes.len();
+        let mut res = vec![];
+        for (index, &pair) in self.pairs.iter().enumerate() {
+            if let Some(first) = self.pairs[..index].iter().position(|&p| p == pair) {
+                res.push(Lint::DuplicatePair {pair, index, first});
+            }
+        }
+        for &pair in &self.pairs {
+            if self.get(pair) == 1 || self.anti_pairs.contains(&pair) ||
+               pair.0 == pair.1 && !self.nodes[pair.0].self_connected
+            {
+                res.push(Lint::ImpossiblePair {pair});
+            }
+        }
+        duplicates(&mut res, "anti_pairs", &self.anti_pairs);
+        duplicates(&mut res, "cardinalities", &self.cardinalities);
+        duplicates(&mut res, "degrees", &self.degrees);
+        duplicates(&mut res, "color_counts", &self.color_counts);
+        duplicates(&mut res, "conditionals", &self.conditionals);
+        duplicates(&mut res, "component_constraints", &self.component_constraints);
+        duplicates(&mut res, "symmetric_differences", &self.symmetric_differences);
+        duplicates(&mut res, "edit_distances", &self.edit_distances);
+        duplicates(&mut res, "sign_pairs", &self.sign_pairs);
+        duplicates(&mut res, "quad_rules", &self.quad_rules);
+
+        if self.connected && n > 1 {
+            for (node, x) in self.nodes.iter().enumerate() {
+                if x.edges.is_empty() {res.push(Lint::IsolatedUnderConnected {node})};
+            }
+        }
+        for (node, x) in self.nodes.iter().enumerate() {
+            if x.self_connected {continue};
+            let alone = (0..n).all(|j| j == node || self.nodes[j].color != x.color);
+            for (slot, &constraint) in x.edges.iter().enumerate() {
+                if constraint.node == SELF_LOOP || alone && constraint.node == x.color {
+                    res.push(Lint::SelfLoopOnly {node, slot, constraint});
+                }
+            }
+        }
+        for (index, deg) in self.degrees.iter().enumerate() {
+            let edges = self.nodes[deg.node].edges.len();
+            if edges < deg.min || edges > deg.max {res.push(Lint::DegreeMismatch {index, edges})};
+        }
+        res
+    }
+}
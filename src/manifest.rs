@@ -0,0 +1,129 @@
+//! Machine-readable records of solver runs.
+
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+use super::*;
+
+/// Computes the FNV-1a hash of bytes.
+///
+/// Unlike the hasher of the standard library, this is stable across versions of Rust,
+/// such that hashes in manifests can be compared across machines.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// A record of a solver run, for reproducing experiments.
+///
+/// For more information, see `Graph::solve_manifest`.
+#[derive(Clone, Debug)]
+pub struct Manifest {
+    /// The version of the crate.
+    pub crate_version: &'static str,
+    /// The hash of the canonical text format of the puzzle, see `Graph::to_spec_string`.
+    pub spec_hash: u64,
+    /// The settings of the solver, including the seed.
+    pub settings: SearchSettings,
+    /// Statistics of the search.
+    pub stats: Stats,
+    /// The result: `solved`, `no_solution`, `exhausted`, `timed_out` or `cancelled`.
+    pub result: &'static str,
+    /// The hash of the edges of the solution, if any.
+    pub result_hash: Option<u64>,
+}
+
+impl Manifest {
+    /// Creates a manifest from a puzzle, the settings and the report of solving it.
+    pub fn new(spec: &Graph, solve_settings: &SearchSettings, report: &SolveReport) -> Manifest {
+        let (result, result_hash) = match &report.outcome {
+            SolveOutcome::Solved(solution) => {
+                let g = &solution.puzzle;
+                let edges: Vec<u8> = (0..g.nodes.len())
+                    .flat_map(|i| g.row(i).iter().flat_map(|c| c.to_le_bytes()))
+                    .collect();
+                ("solved", Some(fnv1a(&edges)))
+            }
+            SolveOutcome::NoSolution => ("no_solution", None),
+            SolveOutcome::Exhausted => ("exhausted", None),
+            SolveOutcome::TimedOut => ("timed_out", None),
+            SolveOutcome::Cancelled => ("cancelled", None),
+        };
+        Manifest {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            spec_hash: fnv1a(spec.to_spec_string().as_bytes()),
+            settings: solve_settings.clone(),
+            stats: report.stats,
+            result,
+            result_hash,
+        }
+    }
+
+    /// Generates the manifest in JSON format.
+    ///
+    /// Hashes are written as hexadecimal strings, to avoid loss of precision.
+    /// Settings that can not be recorded, such as cancellation tokens, are left out.
+    pub fn to_json(&self) -> String {
+        let opt = |x: Option<u64>| x.map(|x| x.to_string()).unwrap_or_else(|| "null".into());
+        let settings = &self.settings;
+        let restart = match settings.restart {
+            None => "null".into(),
+            Some(RestartPolicy::Geometric {first, factor}) =>
+                format!("{{\"geometric\": {{\"first\": {}, \"factor\": {}}}}}", first, factor),
+            Some(RestartPolicy::Luby {unit}) => format!("{{\"luby\": {{\"unit\": {}}}}}", unit),
+        };
+        let mut s = String::new();
+        writeln!(s, "{{").unwrap();
+        writeln!(s, "  \"crate_version\": \"{}\",", self.crate_version).unwrap();
+        writeln!(s, "  \"spec_hash\": \"{:016x}\",", self.spec_hash).unwrap();
+        writeln!(s, "  \"settings\": {{").unwrap();
+        writeln!(s, "    \"solve_simple\": {},", settings.solve_simple).unwrap();
+        writeln!(s, "    \"difference\": {},", settings.difference).unwrap();
+        writeln!(s, "    \"max_iterations\": {},", opt(settings.max_iterations)).unwrap();
+        writeln!(s, "    \"encoding\": \"{:?}\",", settings.encoding).unwrap();
+        writeln!(s, "    \"deterministic\": {},", settings.deterministic).unwrap();
+        writeln!(s, "    \"propagate\": {},", settings.propagate).unwrap();
+        writeln!(s, "    \"learn\": {},", settings.learn).unwrap();
+        writeln!(s, "    \"restart\": {},", restart).unwrap();
+        writeln!(s, "    \"seed\": {},", opt(settings.seed)).unwrap();
+        writeln!(s, "    \"timeout_ms\": {}", opt(settings.timeout_ms)).unwrap();
+        writeln!(s, "  }},").unwrap();
+        writeln!(s, "  \"stats\": {{").unwrap();
+        writeln!(s, "    \"iterations\": {},", self.stats.iterations).unwrap();
+        writeln!(s, "    \"assignments\": {},", self.stats.assignments).unwrap();
+        writeln!(s, "    \"backtracks\": {},", self.stats.backtracks).unwrap();
+        writeln!(s, "    \"max_depth\": {},", self.stats.max_depth).unwrap();
+        writeln!(s, "    \"wipeouts\": {},", self.stats.wipeouts).unwrap();
+        writeln!(s, "    \"elapsed_ms\": {:.3}", self.stats.elapsed.as_secs_f64() * 1000.0).unwrap();
+        writeln!(s, "  }},").unwrap();
+        writeln!(s, "  \"result\": \"{}\",", self.result).unwrap();
+        match self.result_hash {
+            Some(hash) => writeln!(s, "  \"result_hash\": \"{:016x}\"", hash).unwrap(),
+            None => writeln!(s, "  \"result_hash\": null").unwrap(),
+        }
+        writeln!(s, "}}").unwrap();
+        s
+    }
+
+    /// Writes the manifest in JSON format to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+impl Graph {
+    /// Solves the graph puzzle, recording the run in a manifest.
+    ///
+    /// For more information, see `Manifest` and `Graph::solve_report`.
+    pub fn solve_manifest(self, solve_settings: SearchSettings) -> (SolveReport, Manifest) {
+        let spec = self.clone();
+        let report = self.solve_report(solve_settings.clone());
+        let manifest = Manifest::new(&spec, &solve_settings, &report);
+        (report, manifest)
+    }
+}
@@ -0,0 +1,177 @@
+//! Solving node colors together with edge colors.
+
+use super::*;
+
+/// The position of a variable in a node color graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Var {
+    /// The choice of node description.
+    Node(usize),
+    /// The edge color between two nodes.
+    Edge(usize, usize),
+}
+
+/// Stores a graph where node descriptions are solved together with edges.
+///
+/// Each node has a domain of possible descriptions.
+/// A node choice `0` means no choice,
+/// otherwise the choice is the index of the description plus one.
+///
+/// Edges can not be assigned before both nodes have a description.
+#[derive(Clone, Debug)]
+pub struct NodeColorGraph {
+    /// The graph.
+    pub graph: Graph,
+    /// The possible node descriptions.
+    pub domains: Vec<Vec<Node>>,
+    /// The node choices.
+    pub choices: Vec<Color>,
+}
+
+impl Puzzle for NodeColorGraph {
+    type Pos = Var;
+    type Val = Color;
+    fn set(&mut self, pos: Var, val: Color) {
+        match pos {
+            Var::Node(i) => {
+                self.choices[i] = val;
+                let node = if val == 0 {0} else {val as usize - 1};
+                self.graph.nodes[i] = self.domains[i][node].clone();
+                // Node satisfaction depends on the colors of adjacent nodes.
+                for cache in &self.graph.cache_node_satisfied {cache.set(false)}
+            }
+            Var::Edge(i, j) => self.graph.set((i, j), val),
+        }
+    }
+    fn get(&self, pos: Var) -> Color {
+        match pos {
+            Var::Node(i) => self.choices[i],
+            Var::Edge(i, j) => self.graph.get((i, j)),
+        }
+    }
+    fn print(&self) {
+        for choice in &self.choices {
+            eprint!("{} ", choice);
+        }
+        eprintln!();
+        self.graph.print();
+    }
+    fn solve_simple<F: FnMut(&mut Self, Var, Color)>(&mut self, mut f: F) {
+        let n = self.choices.len();
+        for i in 0..n {
+            if self.choices[i] == 0 && self.domains[i].len() == 1 {
+                f(self, Var::Node(i), 1);
+            }
+        }
+        for i in 0..n {
+            for j in i+1..n {
+                let colors = self.colors(Var::Edge(i, j));
+                if colors.len() == 1 {
+                    f(self, Var::Edge(i, j), colors[0]);
+                }
+            }
+        }
+    }
+    fn is_solved(&self) -> bool {
+        self.choices.iter().all(|&c| c != 0) && self.graph.is_solved()
+    }
+    fn remove(&mut self, other: &NodeColorGraph) {
+        for i in 0..self.choices.len() {
+            if other.choices[i] != 0 {
+                self.set(Var::Node(i), 0);
+            }
+        }
+        self.graph.remove(&other.graph);
+    }
+}
+
+impl Default for NodeColorGraph {
+    fn default() -> NodeColorGraph {NodeColorGraph::new()}
+}
+
+impl NodeColorGraph {
+    /// Creates a new node color graph.
+    pub fn new() -> NodeColorGraph {
+        NodeColorGraph {
+            graph: Graph::new(),
+            domains: vec![],
+            choices: vec![],
+        }
+    }
+
+    /// Adds a node with a fixed description.
+    pub fn push(&mut self, node: Node) {
+        self.push_domain(vec![node]);
+        let i = self.choices.len() - 1;
+        self.choices[i] = 1;
+    }
+
+    /// Adds a node with a domain of possible descriptions.
+    ///
+    /// Panics if the domain is empty.
+    pub fn push_domain(&mut self, domain: Vec<Node>) {
+        assert!(!domain.is_empty(), "Node domain can not be empty");
+        self.graph.push(domain[0].clone());
+        self.domains.push(domain);
+        self.choices.push(0);
+    }
+
+    /// Returns the chosen description of a node, if any.
+    pub fn node(&self, i: usize) -> Option<&Node> {
+        match self.choices[i] {
+            0 => None,
+            c => Some(&self.domains[i][c as usize - 1]),
+        }
+    }
+
+    /// Finds the node with the smallest domain without a choice,
+    /// or else the edge with the least possible colors.
+    pub fn min_colors(&self) -> Option<Var> {
+        let mut min: Option<(usize, usize)> = None;
+        for (i, domain) in self.domains.iter().enumerate() {
+            if self.choices[i] != 0 {continue};
+            if min.is_none() || min.unwrap().1 > domain.len() {
+                min = Some((i, domain.len()));
+            }
+        }
+        if let Some((i, _)) = min {return Some(Var::Node(i))};
+        self.graph.min_colors().map(|(i, j)| Var::Edge(i, j))
+    }
+
+    /// Returns a list of possible values for a variable.
+    pub fn colors(&self, pos: Var) -> Vec<Color> {
+        match pos {
+            Var::Node(i) => {
+                if self.choices[i] != 0 {return vec![]};
+                (1..=self.domains[i].len() as Color).collect()
+            }
+            Var::Edge(i, j) => {
+                if self.choices[i] == 0 || self.choices[j] == 0 {return vec![]};
+                self.graph.colors((i, j))
+            }
+        }
+    }
+
+    /// Solves the puzzle using default strategy.
+    ///
+    /// The default strategy is `NodeColorGraph::min_colors, NodeColorGraph::colors`.
+    pub fn solve(self, solve_settings: SolveSettings) -> Option<Solution<NodeColorGraph>> {
+        #[cfg(feature = "verify-solutions")]
+        let original = self.clone();
+        let solver = BackTrackSolver::new(self, solve_settings);
+        let solution = solver.solve(
+            NodeColorGraph::min_colors,
+            NodeColorGraph::colors
+        );
+        #[cfg(feature = "verify-solutions")]
+        if let Some(solution) = &solution {
+            // Node choices of the original graph are removed when solving for the difference.
+            let mut g = solution.puzzle.clone();
+            for (i, &choice) in original.choices.iter().enumerate() {
+                if choice != 0 {g.set(Var::Node(i), choice)}
+            }
+            reference::verify(&g.graph, &original.graph);
+        }
+        solution
+    }
+}
@@ -0,0 +1,67 @@
+//! Combining the edges of solutions on the same nodes.
+
+use super::*;
+
+/// Stores the result of combining the edges of two graphs.
+///
+/// For more information, see `Graph::overlay` and `Graph::intersect`.
+#[derive(Clone, Debug)]
+pub struct Overlay {
+    /// The combined graph.
+    pub graph: Graph,
+    /// Edges that are colored differently in the two graphs,
+    /// with the color in the first and second graph.
+    pub conflicts: Vec<((usize, usize), Color, Color)>,
+}
+
+impl Graph {
+    /// Combines the edges of two graphs on the same nodes,
+    /// keeping edges that are colored in either graph.
+    ///
+    /// Node descriptions and constraints are taken from this graph.
+    /// Edges that are colored differently are reported as conflicts
+    /// and keep the color of this graph.
+    /// Other edges are no-edge when either graph has no-edge, and empty otherwise.
+    ///
+    /// This is useful for building the union of enumerated solutions.
+    ///
+    /// Panics if the graphs have different numbers of nodes.
+    pub fn overlay(&self, other: &Graph) -> Overlay {
+        self.combine(other, |a, b| {
+            if a >= 2 {a} else if b >= 2 {b} else {a.max(b)}
+        })
+    }
+
+    /// Combines the edges of two graphs on the same nodes,
+    /// keeping edges that have the same color in both graphs.
+    ///
+    /// Node descriptions and constraints are taken from this graph.
+    /// Edges that are colored differently are reported as conflicts and become no-edge.
+    /// Other edges with different values are empty when either graph has an empty edge,
+    /// and no-edge otherwise.
+    ///
+    /// This is useful for finding the edges that enumerated solutions agree on.
+    ///
+    /// Panics if the graphs have different numbers of nodes.
+    pub fn intersect(&self, other: &Graph) -> Overlay {
+        self.combine(other, |a, b| {
+            if a == b {a} else if a == 0 || b == 0 {0} else {1}
+        })
+    }
+
+    fn combine(&self, other: &Graph, f: impl Fn(Color, Color) -> Color) -> Overlay {
+        let n = self.nodes.len();
+        assert_eq!(n, other.nodes.len(), "Graphs must have the same number of nodes");
+        let mut graph = self.clone();
+        let mut conflicts = vec![];
+        for i in 0..n {
+            for j in i..n {
+                let (a, b) = (self.get((i, j)), other.get((i, j)));
+                if a >= 2 && b >= 2 && a != b {conflicts.push(((i, j), a, b))};
+                let val = f(a, b);
+                if val != a {graph.set((i, j), val)};
+            }
+        }
+        Overlay {graph, conflicts}
+    }
+}
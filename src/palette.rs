@@ -0,0 +1,83 @@
+//! Display styles of colors, shared between output formats.
+
+use super::*;
+
+/// The display style of a node or edge color.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Style {
+    /// The fill color of nodes.
+    pub fill: String,
+    /// The color of edges and node outlines.
+    pub pen: String,
+    /// Whether edges are dashed.
+    pub dashed: bool,
+}
+
+impl Style {
+    /// Creates a solid style with the same fill and pen color.
+    pub fn solid(color: &str) -> Style {
+        Style {fill: color.into(), pen: color.into(), dashed: false}
+    }
+
+    /// Creates a dashed style with the same fill and pen color.
+    pub fn dashed(color: &str) -> Style {
+        Style {fill: color.into(), pen: color.into(), dashed: true}
+    }
+}
+
+/// Maps node and edge colors to display styles.
+///
+/// Styles are used by `Graph::graphviz_with_options`, `Graph::svg` and `Graph::tikz`.
+/// Color names are used by all formats, so they should be names
+/// that GraphViz, SVG and xcolor have in common, e.g. `red` or `black`.
+///
+/// Node color `k` uses node style `k` and edge color `k` uses edge style `k - 2`,
+/// repeating the styles when there are more colors than styles.
+///
+/// The default palette is `Palette::adinkra`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    /// The styles of node colors, starting with color `0`.
+    pub nodes: Vec<Style>,
+    /// The styles of edge colors, starting with color `2`.
+    pub edges: Vec<Style>,
+}
+
+impl Default for Palette {
+    fn default() -> Palette {Palette::adinkra()}
+}
+
+impl Palette {
+    /// Creates a palette of solid styles from color names.
+    pub fn from_colors(node_colors: &[&str], edge_colors: &[&str]) -> Palette {
+        Palette {
+            nodes: node_colors.iter().map(|c| Style::solid(c)).collect(),
+            edges: edge_colors.iter().map(|c| Style::solid(c)).collect(),
+        }
+    }
+
+    /// Creates a palette for Adinkras.
+    ///
+    /// Nodes are black and white, with black outlines.
+    /// Edge colors come in pairs of the same color,
+    /// where the even color is solid and the odd color is dashed,
+    /// e.g. `2` is solid red and `3` is dashed red.
+    /// This matches the anticommuting pairs of `Graph::commute_quad`.
+    pub fn adinkra() -> Palette {
+        let nodes = ["black", "white"].iter()
+            .map(|c| Style {fill: c.to_string(), pen: "black".into(), dashed: false}).collect();
+        let edges = ["red", "green", "blue", "orange", "purple", "brown", "cyan", "magenta"].iter()
+            .flat_map(|c| vec![Style::solid(c), Style::dashed(c)]).collect();
+        Palette {nodes, edges}
+    }
+
+    /// Returns the style of a node color.
+    pub fn node(&self, color: Color) -> &Style {
+        &self.nodes[color as usize % self.nodes.len()]
+    }
+
+    /// Returns the style of an edge color, starting with color `2`.
+    pub fn edge(&self, color: Color) -> &Style {
+        &self.edges[(color - 2) as usize % self.edges.len()]
+    }
+}
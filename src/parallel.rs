@@ -0,0 +1,115 @@
+//! Racing several solving strategies in parallel.
+
+use std::sync::mpsc;
+use std::thread;
+
+use super::*;
+
+/// Stores a variable and value ordering strategy.
+///
+/// The last color returned by `colors` is tried first.
+#[derive(Copy, Clone)]
+pub struct Strategy {
+    /// Picks the next edge to branch on.
+    pub pick: fn(&Graph) -> Option<(usize, usize)>,
+    /// Returns the possible colors of an edge.
+    pub colors: fn(&Graph, (usize, usize)) -> Vec<Color>,
+}
+
+impl Default for Strategy {
+    fn default() -> Strategy {Strategy::min_colors()}
+}
+
+impl Strategy {
+    /// Creates a new strategy.
+    pub fn new(
+        pick: fn(&Graph) -> Option<(usize, usize)>,
+        colors: fn(&Graph, (usize, usize)) -> Vec<Color>
+    ) -> Strategy {
+        Strategy {pick, colors}
+    }
+
+    /// The default strategy `Graph::min_colors, Graph::colors`.
+    pub fn min_colors() -> Strategy {
+        Strategy::new(Graph::min_colors, Graph::colors)
+    }
+
+    /// Branches on the first empty edge, `Graph::fst_empty, Graph::colors`.
+    pub fn fst_empty() -> Strategy {
+        Strategy::new(Graph::fst_empty, Graph::colors)
+    }
+
+    /// Like `Strategy::min_colors`, but tries no-edge before colored edges.
+    pub fn min_colors_no_edge_first() -> Strategy {
+        Strategy::new(Graph::min_colors, |g, pos| {
+            let mut colors = g.colors(pos);
+            colors.reverse();
+            colors
+        })
+    }
+}
+
+impl Graph {
+    /// Races several strategies on separate threads.
+    ///
+    /// Returns the first solution found and cancels the other threads.
+    /// Uses the default strategy when the list of strategies is empty.
+    ///
+    /// When `SearchSettings::deterministic` is enabled, returns the solution
+    /// of the first strategy in the list that finds one.
+    pub fn solve_parallel(
+        self,
+        solve_settings: SearchSettings,
+        strategies: &[Strategy]
+    ) -> Option<Solution<Graph>> {
+        if strategies.is_empty() {
+            return self.solve_all(solve_settings).next();
+        }
+
+        let cancel = CancelToken::new();
+        let (sender, receiver) = mpsc::channel();
+        let handles: Vec<_> = strategies.iter().enumerate().map(|(k, &strategy)| {
+            let mut solutions = Solutions::with_strategy(self.clone(), solve_settings.clone(), strategy);
+            solutions.cancel = Some(cancel.clone());
+            let sender = sender.clone();
+            thread::spawn(move || {
+                // The receiver is dropped after the first solution.
+                let _ = sender.send((k, solutions.next()));
+            })
+        }).collect();
+        drop(sender);
+
+        let mut res = None;
+        if solve_settings.deterministic {
+            // Wait for strategies in order, such that the result does not depend on timing.
+            let mut results: Vec<Option<Option<Solution<Graph>>>> = strategies.iter().map(|_| None).collect();
+            let mut next = 0;
+            for (k, solution) in receiver.iter() {
+                results[k] = Some(solution);
+                while next < results.len() {
+                    match results[next].take() {
+                        None => break,
+                        Some(None) => next += 1,
+                        Some(Some(solution)) => {
+                            res = Some(solution);
+                            break;
+                        }
+                    }
+                }
+                if res.is_some() || next == results.len() {break};
+            }
+        } else {
+            for (_, solution) in receiver.iter() {
+                if solution.is_some() {
+                    res = solution;
+                    break;
+                }
+            }
+        }
+        cancel.cancel();
+        for handle in handles {
+            let _ = handle.join();
+        }
+        res
+    }
+}
@@ -0,0 +1,283 @@
+//! User data attached to nodes and edges of graphs.
+//!
+//! Data is stored in parallel to a graph, using the same edge layout,
+//! such that the graph type stays independent of user data.
+
+use std::fmt::{self, Display};
+
+use super::*;
+
+/// Stores user data of nodes, parallel to a graph.
+///
+/// Node indices are the same as in the graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeData<T> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> Default for NodeData<T> {
+    fn default() -> NodeData<T> {NodeData::new()}
+}
+
+impl<T> NodeData<T> {
+    /// Creates new empty node data.
+    pub fn new() -> NodeData<T> {
+        NodeData {values: vec![]}
+    }
+
+    /// Computes data for every node of a graph.
+    pub fn from_graph<F>(g: &Graph, mut f: F) -> NodeData<T>
+        where F: FnMut(usize, &Node) -> Option<T>
+    {
+        NodeData {values: g.nodes.iter().enumerate().map(|(i, node)| f(i, node)).collect()}
+    }
+
+    /// Returns the data of a node, if any.
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.values.get(i).and_then(|x| x.as_ref())
+    }
+
+    /// Returns mutable data of a node, if any.
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        self.values.get_mut(i).and_then(|x| x.as_mut())
+    }
+
+    /// Sets the data of a node, returning the previous data.
+    pub fn set(&mut self, i: usize, x: T) -> Option<T> {
+        if self.values.len() <= i {self.values.resize_with(i + 1, || None)};
+        self.values[i].replace(x)
+    }
+
+    /// Removes the data of a node, returning it.
+    pub fn remove(&mut self, i: usize) -> Option<T> {
+        self.values.get_mut(i).and_then(|x| x.take())
+    }
+
+    /// Returns the number of nodes with data.
+    pub fn len(&self) -> usize {
+        self.values.iter().filter(|x| x.is_some()).count()
+    }
+
+    /// Returns `true` if no node has data.
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(|x| x.is_none())
+    }
+
+    /// Returns the nodes with data, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        self.values.iter().enumerate().filter_map(|(i, x)| x.as_ref().map(|x| (i, x)))
+    }
+
+    /// Returns the nodes with data that satisfies a predicate.
+    pub fn find<F: Fn(&T) -> bool>(&self, f: F) -> Vec<usize> {
+        self.iter().filter(|(_, x)| f(x)).map(|(i, _)| i).collect()
+    }
+
+    /// Returns the neighbors of a node along colored edges, with their data.
+    pub fn neighbors<'a>(&'a self, g: &Graph, i: usize) -> Vec<(usize, Option<&'a T>)> {
+        (0..g.nodes.len()).filter(|&j| j != i && g.get((i, j)) >= 2).map(|j| (j, self.get(j))).collect()
+    }
+}
+
+/// Stores user data of edges, parallel to a graph.
+///
+/// Edges are undirected, so `(i, j)` and `(j, i)` refer to the same data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeData<T> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> Default for EdgeData<T> {
+    fn default() -> EdgeData<T> {EdgeData::new()}
+}
+
+impl<T> EdgeData<T> {
+    /// Creates new empty edge data.
+    pub fn new() -> EdgeData<T> {
+        EdgeData {values: vec![]}
+    }
+
+    /// Computes data for every colored edge of a graph.
+    ///
+    /// The function is called with the edge, using sorted indices, and its color.
+    pub fn from_graph<F>(g: &Graph, mut f: F) -> EdgeData<T>
+        where F: FnMut((usize, usize), Color) -> Option<T>
+    {
+        let mut data = EdgeData::new();
+        let n = g.nodes.len();
+        for i in 0..n {
+            for j in i..n {
+                let val = g.get((i, j));
+                if val < 2 {continue};
+                if let Some(x) = f((i, j), val) {data.set((i, j), x);}
+            }
+        }
+        data
+    }
+
+    /// Returns the data of an edge, if any.
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.values.get(Graph::index(pos)).and_then(|x| x.as_ref())
+    }
+
+    /// Returns mutable data of an edge, if any.
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        self.values.get_mut(Graph::index(pos)).and_then(|x| x.as_mut())
+    }
+
+    /// Sets the data of an edge, returning the previous data.
+    pub fn set(&mut self, pos: (usize, usize), x: T) -> Option<T> {
+        let ind = Graph::index(pos);
+        if self.values.len() <= ind {self.values.resize_with(ind + 1, || None)};
+        self.values[ind].replace(x)
+    }
+
+    /// Removes the data of an edge, returning it.
+    pub fn remove(&mut self, pos: (usize, usize)) -> Option<T> {
+        self.values.get_mut(Graph::index(pos)).and_then(|x| x.take())
+    }
+
+    /// Returns the number of edges with data.
+    pub fn len(&self) -> usize {
+        self.values.iter().filter(|x| x.is_some()).count()
+    }
+
+    /// Returns `true` if no edge has data.
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(|x| x.is_none())
+    }
+
+    /// Returns the edges with data, using sorted indices, in the order of rows.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        let mut i = 0;
+        let mut start = 0;
+        self.values.iter().enumerate().filter_map(move |(ind, x)| {
+            while ind > start + i {
+                start += i + 1;
+                i += 1;
+            }
+            x.as_ref().map(|x| ((ind - start, i), x))
+        })
+    }
+
+    /// Removes the data of edges that are not colored in a graph.
+    ///
+    /// This keeps the data consistent after edges of the graph are changed.
+    pub fn retain_colored(&mut self, g: &Graph) {
+        for (ind, x) in self.values.iter_mut().enumerate() {
+            if g.edges.get(ind).map(|&val| val < 2).unwrap_or(true) {*x = None};
+        }
+    }
+}
+
+impl Graph {
+    /// Generates a GraphML format with node and edge data.
+    ///
+    /// Nodes and edges with data have a `data` attribute with the rendered data.
+    /// For more information, see `Graph::graphml`.
+    pub fn graphml_with_data<N: Display, E: Display>(
+        &self,
+        node_colors: &[&str],
+        edge_colors: &[&str],
+        node_data: &NodeData<N>,
+        edge_data: &EdgeData<E>
+    ) -> String {
+        self.graphml_attributed(node_colors, edge_colors,
+            &|i| node_data.get(i).map(|x| x.to_string()),
+            &|pos| edge_data.get(pos).map(|x| x.to_string()))
+    }
+
+    /// Generates a GraphML format with `data` attributes computed by callbacks.
+    ///
+    /// The callbacks return the data of nodes and edges, if any.
+    /// Edges are passed using sorted indices.
+    pub fn graphml_with(
+        &self,
+        node_colors: &[&str],
+        edge_colors: &[&str],
+        node_data: &dyn Fn(usize) -> Option<String>,
+        edge_data: &dyn Fn((usize, usize)) -> Option<String>,
+    ) -> String {
+        self.graphml_attributed(node_colors, edge_colors, node_data, edge_data)
+    }
+
+    /// Generates a GraphML format with edge data.
+    ///
+    /// Edges with data have a `data` attribute with the rendered data.
+    /// For more information, see `Graph::graphml`.
+    pub fn graphml_with_edge_data<T: Display>(
+        &self,
+        node_colors: &[&str],
+        edge_colors: &[&str],
+        edge_data: &EdgeData<T>
+    ) -> String {
+        self.graphml_attributed(node_colors, edge_colors, &|_| None,
+            &|pos| edge_data.get(pos).map(|x| x.to_string()))
+    }
+
+    /// Generates a GraphViz dot format with node and edge data as labels.
+    ///
+    /// For more information, see `Graph::graphviz`.
+    pub fn graphviz_with_data<N: Display, E: Display>(
+        &self,
+        layout: &str,
+        node_colors: &[&str],
+        edge_colors: &[&str],
+        node_data: &NodeData<N>,
+        edge_data: &EdgeData<E>
+    ) -> String {
+        self.graphviz_with_options(&GraphvizOptions::new(layout, node_colors, edge_colors)
+            .node_label(|i| node_data.get(i).map(|x| x.to_string()))
+            .edge_label(|pos| edge_data.get(pos).map(|x| x.to_string())))
+    }
+
+    /// Generates a GraphViz dot format with labels computed by callbacks.
+    ///
+    /// The callbacks return the labels of nodes and edges, if any.
+    /// Edges are passed using sorted indices.
+    pub fn graphviz_with(
+        &self,
+        layout: &str,
+        node_colors: &[&str],
+        edge_colors: &[&str],
+        node_label: &dyn Fn(usize) -> Option<String>,
+        edge_label: &dyn Fn((usize, usize)) -> Option<String>,
+    ) -> String {
+        self.graphviz_with_options(&GraphvizOptions::new(layout, node_colors, edge_colors)
+            .node_label(node_label).edge_label(edge_label))
+    }
+
+    /// Generates a GraphViz dot format with edge data as labels.
+    ///
+    /// For more information, see `Graph::graphviz`.
+    pub fn graphviz_with_edge_data<T: Display>(
+        &self,
+        layout: &str,
+        node_colors: &[&str],
+        edge_colors: &[&str],
+        edge_data: &EdgeData<T>
+    ) -> String {
+        self.graphviz_with_options(&GraphvizOptions::new(layout, node_colors, edge_colors)
+            .edge_label(|pos| edge_data.get(pos).map(|x| x.to_string())))
+    }
+}
+
+/// Renders one line per node with data.
+impl<T: Display> Display for NodeData<T> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        for (i, x) in self.iter() {
+            writeln!(w, "{}: {}", i, x)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders one line per edge with data.
+impl<T: Display> Display for EdgeData<T> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        for ((i, j), x) in self.iter() {
+            writeln!(w, "{} -- {}: {}", i, j, x)?;
+        }
+        Ok(())
+    }
+}
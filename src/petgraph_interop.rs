@@ -0,0 +1,44 @@
+//! Conversion to and from petgraph graphs.
+
+use petgraph::EdgeType;
+
+use super::*;
+
+impl<Ty: EdgeType> From<&Graph> for petgraph::Graph<Color, Color, Ty> {
+    /// Converts to a petgraph graph with node colors and colored edges as weights.
+    ///
+    /// Node indices are preserved.
+    /// For directed graphs, each edge goes from the lower to the higher node index.
+    fn from(g: &Graph) -> petgraph::Graph<Color, Color, Ty> {
+        let n = g.nodes.len();
+        let mut res = petgraph::Graph::with_capacity(n, 0);
+        let ids: Vec<_> = g.nodes.iter().map(|node| res.add_node(node.color)).collect();
+        for i in 0..n {
+            for j in i..n {
+                let val = g.get((i, j));
+                if val >= 2 {res.add_edge(ids[i], ids[j], val);}
+            }
+        }
+        res
+    }
+}
+
+impl Graph {
+    /// Creates a graph from a petgraph graph with node colors and edge colors as weights.
+    ///
+    /// Edges are pinned to their weights, and edge direction is ignored.
+    /// Other edges are undecided, such that the graph can be used as a partial solution.
+    /// Nodes have no edge constraints.
+    pub fn from_petgraph<Ty: EdgeType>(pg: &petgraph::Graph<Color, Color, Ty>) -> Graph {
+        let mut g = Graph::new();
+        for &color in pg.node_weights() {
+            g.push(Node {color, self_connected: false, edges: vec![]});
+        }
+        for edge in pg.raw_edges() {
+            let (i, j) = (edge.source().index(), edge.target().index());
+            if i == j {g.nodes[i].self_connected = true};
+            g.set((i, j), edge.weight);
+        }
+        g
+    }
+}
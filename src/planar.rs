@@ -0,0 +1,278 @@
+//! Planarity constraint.
+//!
+//! Uses the algorithm of Demoucron, Malgrange and Pertuiset
+//! on each biconnected component of colored edges.
+
+use super::*;
+
+/// A fragment of the graph relative to the embedded subgraph.
+enum Fragment {
+    /// An edge between two embedded nodes.
+    Chord(usize, usize),
+    /// A connected component of nodes that are not embedded.
+    Component(Vec<bool>),
+}
+
+impl Graph {
+    /// Returns `true` if the graph of colored edges is planar.
+    ///
+    /// Self-loops are ignored.
+    pub fn is_planar(&self) -> bool {
+        let n = self.nodes.len();
+        let mut adj = vec![vec![]; n];
+        let mut m = 0;
+        for i in 0..n {
+            for j in i+1..n {
+                if self.get((i, j)) >= 2 {
+                    adj[i].push(j);
+                    adj[j].push(i);
+                    m += 1;
+                }
+            }
+        }
+        if n >= 3 && m > 3 * n - 6 {return false};
+        // The smallest non-planar graph has 9 edges.
+        blocks(&adj).iter().all(|block| block.len() < 9 || block_is_planar(block))
+    }
+
+    /// Returns `true` if the planarity constraint is satisfied.
+    ///
+    /// Since subgraphs of planar graphs are planar,
+    /// this is also used to reject partial solutions early.
+    pub fn planar_satisfied(&self) -> bool {
+        if !self.planar {return true};
+        if let Some(val) = self.cache_planar.get() {return val};
+        let val = self.is_planar();
+        self.cache_planar.set(Some(val));
+        val
+    }
+
+    /// Updates the planarity cache when the value of an edge changes.
+    pub(crate) fn update_planar(&self, old: Color, val: Color) {
+        match self.cache_planar.get() {
+            Some(true) if old < 2 && val >= 2 => self.cache_planar.set(None),
+            Some(false) if old >= 2 && val < 2 => self.cache_planar.set(None),
+            _ => {}
+        }
+    }
+}
+
+/// Returns the edges of biconnected components.
+fn blocks(adj: &[Vec<usize>]) -> Vec<Vec<(usize, usize)>> {
+    struct State {
+        disc: Vec<usize>,
+        low: Vec<usize>,
+        time: usize,
+        stack: Vec<(usize, usize)>,
+        blocks: Vec<Vec<(usize, usize)>>,
+    }
+
+    fn visit(adj: &[Vec<usize>], u: usize, parent: usize, st: &mut State) {
+        st.disc[u] = st.time;
+        st.low[u] = st.time;
+        st.time += 1;
+        for &v in &adj[u] {
+            if st.disc[v] == usize::MAX {
+                st.stack.push((u, v));
+                visit(adj, v, u, st);
+                st.low[u] = st.low[u].min(st.low[v]);
+                if st.low[v] >= st.disc[u] {
+                    let mut block = vec![];
+                    while let Some(e) = st.stack.pop() {
+                        block.push(e);
+                        if e == (u, v) {break};
+                    }
+                    st.blocks.push(block);
+                }
+            } else if v != parent && st.disc[v] < st.disc[u] {
+                st.stack.push((u, v));
+                st.low[u] = st.low[u].min(st.disc[v]);
+            }
+        }
+    }
+
+    let n = adj.len();
+    let mut st = State {
+        disc: vec![usize::MAX; n],
+        low: vec![0; n],
+        time: 0,
+        stack: vec![],
+        blocks: vec![],
+    };
+    for i in 0..n {
+        if st.disc[i] == usize::MAX {visit(adj, i, usize::MAX, &mut st)};
+    }
+    st.blocks
+}
+
+/// Returns a cycle in a biconnected graph.
+fn find_cycle(adj: &[Vec<bool>]) -> Vec<usize> {
+    fn visit(adj: &[Vec<bool>], u: usize, depth: &mut [usize], parent: &mut [usize]) -> Option<Vec<usize>> {
+        for v in 0..adj.len() {
+            if !adj[u][v] || v == parent[u] {continue};
+            if depth[v] == usize::MAX {
+                depth[v] = depth[u] + 1;
+                parent[v] = u;
+                if let Some(cycle) = visit(adj, v, depth, parent) {return Some(cycle)};
+            } else if depth[v] < depth[u] {
+                // Back edge to an ancestor closes a cycle.
+                let mut cycle = vec![u];
+                let mut k = u;
+                while k != v {
+                    k = parent[k];
+                    cycle.push(k);
+                }
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    let n = adj.len();
+    let mut depth = vec![usize::MAX; n];
+    let mut parent = vec![usize::MAX; n];
+    depth[0] = 0;
+    visit(adj, 0, &mut depth, &mut parent).unwrap_or_default()
+}
+
+/// Returns the nodes of a face from one position to another, inclusive.
+fn face_slice(face: &[usize], from: usize, to: usize) -> Vec<usize> {
+    let mut res = vec![];
+    let mut k = from;
+    loop {
+        res.push(face[k]);
+        if k == to {break};
+        k = (k + 1) % face.len();
+    }
+    res
+}
+
+/// Tests planarity of a biconnected component.
+fn block_is_planar(block: &[(usize, usize)]) -> bool {
+    let mut ids: Vec<usize> = block.iter().flat_map(|&(a, b)| vec![a, b]).collect();
+    ids.sort();
+    ids.dedup();
+    let n = ids.len();
+    if n < 5 {return true};
+    let mut adj = vec![vec![false; n]; n];
+    for &(a, b) in block {
+        let (a, b) = (ids.binary_search(&a).unwrap(), ids.binary_search(&b).unwrap());
+        adj[a][b] = true;
+        adj[b][a] = true;
+    }
+
+    let cycle = find_cycle(&adj);
+    let mut in_h = vec![false; n];
+    let mut h_edge = vec![vec![false; n]; n];
+    for (k, &a) in cycle.iter().enumerate() {
+        let b = cycle[(k + 1) % cycle.len()];
+        in_h[a] = true;
+        h_edge[a][b] = true;
+        h_edge[b][a] = true;
+    }
+    let mut faces = vec![cycle.clone(), cycle];
+
+    loop {
+        // Find fragments with their attachments.
+        let mut fragments: Vec<(Fragment, Vec<usize>)> = vec![];
+        for a in 0..n {
+            for b in a+1..n {
+                if adj[a][b] && !h_edge[a][b] && in_h[a] && in_h[b] {
+                    fragments.push((Fragment::Chord(a, b), vec![a, b]));
+                }
+            }
+        }
+        let mut visited = in_h.clone();
+        for start in 0..n {
+            if visited[start] {continue};
+            let mut members = vec![false; n];
+            let mut attachments = vec![];
+            let mut stack = vec![start];
+            visited[start] = true;
+            while let Some(a) = stack.pop() {
+                members[a] = true;
+                for b in 0..n {
+                    if !adj[a][b] {continue};
+                    if in_h[b] {
+                        if !attachments.contains(&b) {attachments.push(b)};
+                    } else if !visited[b] {
+                        visited[b] = true;
+                        stack.push(b);
+                    }
+                }
+            }
+            fragments.push((Fragment::Component(members), attachments));
+        }
+        if fragments.is_empty() {return true};
+
+        // Pick a fragment with fewest admissible faces.
+        let mut best: Option<(usize, usize, usize)> = None;
+        for (k, (_, attachments)) in fragments.iter().enumerate() {
+            let admissible: Vec<usize> = (0..faces.len())
+                .filter(|&f| attachments.iter().all(|a| faces[f].contains(a))).collect();
+            if admissible.is_empty() {return false};
+            if best.map(|b| admissible.len() < b.2).unwrap_or(true) {
+                best = Some((k, admissible[0], admissible.len()));
+            }
+        }
+        let (k, f, _) = best.unwrap();
+
+        // Find a path through the fragment between two attachments.
+        let path = match &fragments[k] {
+            (Fragment::Chord(a, b), _) => vec![*a, *b],
+            (Fragment::Component(members), attachments) => {
+                let a = attachments[0];
+                let mut prev = vec![usize::MAX; n];
+                let mut queue = std::collections::VecDeque::new();
+                for c in 0..n {
+                    if members[c] && adj[a][c] {
+                        prev[c] = a;
+                        queue.push_back(c);
+                    }
+                }
+                let mut path = vec![];
+                'search: while let Some(c) = queue.pop_front() {
+                    for b in 0..n {
+                        if b != a && in_h[b] && adj[c][b] {
+                            path.push(b);
+                            let mut k = c;
+                            while k != a {
+                                path.push(k);
+                                k = prev[k];
+                            }
+                            path.push(a);
+                            break 'search;
+                        }
+                    }
+                    for d in 0..n {
+                        if members[d] && adj[c][d] && prev[d] == usize::MAX {
+                            prev[d] = c;
+                            queue.push_back(d);
+                        }
+                    }
+                }
+                path.reverse();
+                path
+            }
+        };
+
+        // Split the face along the path.
+        let face = faces.swap_remove(f);
+        let (u, v) = (path[0], path[path.len() - 1]);
+        let iu = face.iter().position(|&x| x == u).unwrap();
+        let iv = face.iter().position(|&x| x == v).unwrap();
+        let interior = &path[1..path.len() - 1];
+        let mut face1 = face_slice(&face, iu, iv);
+        face1.extend(interior.iter().rev());
+        let mut face2 = face_slice(&face, iv, iu);
+        face2.extend(interior.iter());
+        faces.push(face1);
+        faces.push(face2);
+        for w in path.windows(2) {
+            in_h[w[0]] = true;
+            in_h[w[1]] = true;
+            h_edge[w[0]][w[1]] = true;
+            h_edge[w[1]][w[0]] = true;
+        }
+    }
+}
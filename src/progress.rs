@@ -0,0 +1,50 @@
+//! Measuring how far a search has come.
+
+use super::*;
+
+impl Graph {
+    /// Records the domain sizes of undecided edges before solving.
+    ///
+    /// This is called when solving starts.
+    pub(crate) fn record_initial_domains(&mut self) {
+        let n = self.nodes.len();
+        self.initial_domains = (0..n).map(|i| {
+            (0..=i).map(|j| if self.get((i, j)) == 0 {self.colors((i, j)).len()} else {0}).collect()
+        }).collect();
+    }
+
+    /// Returns the fraction of decided edges, weighted by initial domain sizes.
+    ///
+    /// Each edge that was undecided when solving started has weight `ln(d)`,
+    /// where `d` is the number of colors it could take at that time.
+    /// This is the information gained by deciding the edge,
+    /// such that forced edges do not count as progress.
+    ///
+    /// Returns a value from `0.0` (nothing decided) to `1.0` (all decided).
+    /// Before solving, the current domain sizes are used, so the progress is `0.0`
+    /// unless every edge is decided.
+    pub fn progress(&self) -> f64 {
+        let n = self.nodes.len();
+        let mut total = 0.0;
+        let mut decided = 0.0;
+        let mut undecided = false;
+        for i in 0..n {
+            for j in 0..=i {
+                let size = if self.initial_domains.is_empty() {
+                    if self.get((i, j)) == 0 {self.colors((i, j)).len()} else {0}
+                } else {
+                    self.initial_domains[i][j]
+                };
+                if size == 0 {continue};
+                let weight = (size as f64).ln();
+                total += weight;
+                if self.get((i, j)) == 0 {undecided = true} else {decided += weight};
+            }
+        }
+        if total == 0.0 {
+            if undecided {0.0} else {1.0}
+        } else {
+            decided / total
+        }
+    }
+}
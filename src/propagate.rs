@@ -0,0 +1,95 @@
+//! Forward checking of edge color domains.
+
+use std::collections::HashMap;
+
+use super::*;
+
+impl Graph {
+    /// Returns the possible colors of every empty edge.
+    ///
+    /// Edges that can not be assigned, such as self-loops of nodes
+    /// that can not be self-connected, are not included.
+    pub fn domains(&self) -> Vec<((usize, usize), Vec<Color>)> {
+        let n = self.nodes.len();
+        let mut res = vec![];
+        for i in 0..n {
+            for j in i..n {
+                if self.get((i, j)) != 0 || i == j && !self.nodes[i].self_connected {continue};
+                res.push(((i, j), self.colors((i, j))));
+            }
+        }
+        res
+    }
+
+    /// Returns `false` if no completion of the graph can be a solution,
+    /// judged from the possible colors of empty edges.
+    ///
+    /// This is the case when some empty edge has no possible colors,
+    /// or when some node has more unsatisfied edge constraints of an edge color
+    /// and neighbor color than empty edges that can satisfy them.
+    pub fn forward_check(&self) -> bool {
+        self.scan_domains(None).is_some()
+    }
+
+    /// Forward checks the empty edges incident to a region, or all edges.
+    ///
+    /// Returns the edges between different nodes with a single possible color,
+    /// or `None` when the check fails.
+    pub(crate) fn scan_domains(&self, mask: Option<&[bool]>) -> Option<Vec<((usize, usize), Color)>> {
+        let n = self.nodes.len();
+        let in_region = |i: usize| mask.map(|mask| mask[i]).unwrap_or(true);
+        // The number of empty edges that can satisfy a constraint of each node.
+        let mut supply: Vec<HashMap<(Color, Color), usize>> = vec![HashMap::new(); n];
+        let mut forced = vec![];
+        for i in 0..n {
+            for j in i..n {
+                if self.get((i, j)) != 0 || i == j && !self.nodes[i].self_connected {continue};
+                if !in_region(i) && !in_region(j) {continue};
+                let colors = self.colors((i, j));
+                if colors.is_empty() {return None};
+                if colors.len() == 1 && i != j {forced.push(((i, j), colors[0]))};
+                for &edge in colors.iter().filter(|&&c| c >= 2) {
+                    *supply[i].entry((edge, self.nodes[j].color)).or_insert(0) += 1;
+                    if i != j {
+                        *supply[j].entry((edge, self.nodes[i].color)).or_insert(0) += 1;
+                    } else {
+                        *supply[i].entry((edge, SELF_LOOP)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        for (i, supply) in supply.iter_mut().enumerate() {
+            if !in_region(i) {continue};
+            for con in self.node_satisfied(i) {
+                match supply.get_mut(&(con.edge, con.node)) {
+                    Some(count) if *count > 0 => *count -= 1,
+                    _ => return None,
+                }
+            }
+        }
+        Some(forced)
+    }
+
+    /// Assigns edges with a single possible color until a fixed point,
+    /// calling a function to assign each edge.
+    ///
+    /// Returns `false` when forward checking fails.
+    /// When `solve_simple` is `false`, only forward checking is performed.
+    pub(crate) fn propagate_with<F>(&mut self, mask: Option<&[bool]>, solve_simple: bool, mut f: F) -> bool
+        where F: FnMut(&mut Graph, (usize, usize), Color)
+    {
+        loop {
+            let forced = match self.scan_domains(mask) {
+                None => return false,
+                Some(forced) => forced,
+            };
+            if forced.is_empty() || !solve_simple {return true};
+            // Forced edges can compete for the same constraint,
+            // so each edge is checked again after the previous assignments.
+            for (pos, val) in forced {
+                if self.colors(pos) != [val] {break}
+                f(self, pos, val);
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Tracking how edges got their values.
+
+use super::*;
+
+impl Graph {
+    /// Returns how an edge got its value.
+    ///
+    /// Edges set outside the solver are pinned.
+    pub fn provenance(&self, (i, j): (usize, usize)) -> Provenance {
+        if self.get((i, j)) == 0 {return Provenance::Unassigned};
+        self.provenance[i.max(j)][i.min(j)]
+    }
+
+    /// Sets how an edge got its value.
+    ///
+    /// This is called by the solver after setting an edge.
+    pub fn set_provenance(&mut self, (i, j): (usize, usize), provenance: Provenance) {
+        self.provenance[i.max(j)][i.min(j)] = provenance;
+    }
+
+    /// Generates a GraphViz dot format, styling edges by provenance.
+    ///
+    /// Pinned edges are solid, propagated edges are dashed and branched edges are bold.
+    pub fn graphviz_provenance(&self, layout: &str, node_colors: &[&str], edge_colors: &[&str]) -> String {
+        self.graphviz_with_options(&GraphvizOptions::new(layout, node_colors, edge_colors).provenance(true))
+    }
+}
@@ -0,0 +1,520 @@
+//! Straightforward implementations of constraints.
+//!
+//! These do not use cached state of the graph,
+//! and are written for clarity instead of performance.
+//! They are used to verify solutions independently of the solver,
+//! e.g. with the `verify-solutions` feature,
+//! and for differential testing against the optimized methods of `Graph`.
+
+use super::*;
+
+/// Returns the symmetric matrix of edge values.
+fn matrix(g: &Graph) -> Vec<Vec<Color>> {
+    let n = g.nodes.len();
+    (0..n).map(|i| (0..n).map(|j| g.get((i, j))).collect()).collect()
+}
+
+/// Returns the neighbors of every node along colored edges, excluding self-loops.
+fn neighbors(g: &Graph) -> Vec<Vec<usize>> {
+    let m = matrix(g);
+    let n = m.len();
+    (0..n).map(|i| (0..n).filter(|&j| j != i && m[i][j] >= 2).collect()).collect()
+}
+
+/// Returns `true` if quad constraints apply to a node, see `Graph::quad_nodes`.
+fn in_quad_nodes(g: &Graph, i: usize) -> bool {
+    g.quad_nodes.as_ref().map(|nodes| nodes.contains(&i)).unwrap_or(true)
+}
+
+/// Returns the connected components along colored edges.
+fn components(g: &Graph) -> Vec<Vec<usize>> {
+    let adj = neighbors(g);
+    let n = adj.len();
+    let mut visited = vec![false; n];
+    let mut res = vec![];
+    for start in 0..n {
+        if visited[start] {continue};
+        visited[start] = true;
+        let mut component = vec![];
+        let mut stack = vec![start];
+        while let Some(a) = stack.pop() {
+            component.push(a);
+            for &b in &adj[a] {
+                if !visited[b] {
+                    visited[b] = true;
+                    stack.push(b);
+                }
+            }
+        }
+        component.sort();
+        res.push(component);
+    }
+    res
+}
+
+/// Returns the number of colored edges of a node, counting a self-loop once.
+fn degree(g: &Graph, i: usize) -> usize {
+    (0..g.nodes.len()).filter(|&j| g.get((i, j)) >= 2).count()
+}
+
+/// Returns `true` if every edge constraint of every node has its own colored edge.
+///
+/// Like `Graph::all_satisfied`, colored edges that no constraint accounts for are allowed,
+/// since the solver keeps pre-set edges.
+pub fn nodes_satisfied(g: &Graph) -> bool {
+    let m = matrix(g);
+    (0..g.nodes.len()).all(|i| {
+        let edges: Vec<(Color, Color, bool)> = (0..m.len()).filter(|&j| m[i][j] >= 2)
+            .map(|j| (m[i][j], g.nodes[j].color, i == j)).collect();
+        constraints_matched(&g.nodes[i].edges, &edges)
+    })
+}
+
+/// Returns `true` if every constraint can be matched to its own edge.
+///
+/// An edge is given by its color, the color of the other node and whether it is a self-loop.
+fn constraints_matched(cons: &[Constraint], edges: &[(Color, Color, bool)]) -> bool {
+    let fits = |k: usize, e: usize| {
+        let ((edge, node, self_loop), con) = (edges[e], cons[k]);
+        edge == con.edge && (node == con.node || (self_loop && con.node == SELF_LOOP))
+    };
+    let mut owner = vec![None; edges.len()];
+    (0..cons.len()).all(|k| augment(k, &fits, &mut owner, &mut vec![false; edges.len()]))
+}
+
+/// Finds an augmenting path for a constraint in a matching of constraints to edges.
+///
+/// `owner` stores the constraint matched to each edge.
+fn augment(
+    k: usize,
+    fits: &dyn Fn(usize, usize) -> bool,
+    owner: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for e in 0..owner.len() {
+        if seen[e] || !fits(k, e) {continue};
+        seen[e] = true;
+        if owner[e].map(|other| augment(other, fits, owner, seen)).unwrap_or(true) {
+            owner[e] = Some(k);
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns `true` if every pair constraint has a colored edge.
+pub fn pairs_satisfied(g: &Graph) -> bool {
+    g.pairs.iter().all(|&pos| g.get(pos) >= 2)
+}
+
+/// Returns `true` if no anti-pair constraint has a colored edge.
+pub fn anti_pairs_satisfied(g: &Graph) -> bool {
+    g.anti_pairs.iter().all(|&pos| g.get(pos) < 2)
+}
+
+/// Returns `true` if every restricted edge has an allowed value, where empty edges are no edge.
+pub fn restrictions_satisfied(g: &Graph) -> bool {
+    g.restrictions.iter().all(|(pos, allowed)| allowed.contains(&g.get(*pos).max(1)))
+}
+
+/// Returns `true` if all cardinality constraints are satisfied.
+pub fn cardinalities_satisfied(g: &Graph) -> bool {
+    g.cardinalities.iter().all(|card| {
+        let count = card.edges.iter().filter(|&&pos| g.get(pos) >= 2).count();
+        card.min <= count && count <= card.max
+    })
+}
+
+/// Returns `true` if all degree constraints are satisfied.
+pub fn degrees_satisfied(g: &Graph) -> bool {
+    g.degrees.iter().all(|deg| {
+        let d = degree(g, deg.node);
+        deg.min <= d && d <= deg.max
+    })
+}
+
+/// Returns `true` if all color count constraints are satisfied.
+pub fn color_counts_satisfied(g: &Graph) -> bool {
+    let m = matrix(g);
+    g.color_counts.iter().all(|cc| {
+        let count = (0..m.len()).map(|i| (i..m.len()).filter(|&j| m[i][j] == cc.color).count())
+            .sum::<usize>();
+        cc.min <= count && count <= cc.max
+    })
+}
+
+/// Returns `true` if the graph contains a triangle of colored edges.
+pub fn has_triangles(g: &Graph) -> bool {
+    let adj = neighbors(g);
+    let n = adj.len();
+    (0..n).any(|a| adj[a].iter().any(|&b| adj[b].iter().any(|&c| c != a && adj[c].contains(&a))))
+}
+
+/// Returns the length of the shortest cycle through an edge, if any.
+fn shortest_cycle_through(adj: &[Vec<usize>], (i, j): (usize, usize)) -> Option<usize> {
+    // Breadth-first search from `i` to `j` without using the edge itself.
+    let mut dist = vec![None; adj.len()];
+    dist[i] = Some(0);
+    let mut queue = std::collections::VecDeque::from(vec![i]);
+    while let Some(a) = queue.pop_front() {
+        for &b in &adj[a] {
+            if (a, b) == (i, j) || dist[b].is_some() {continue};
+            dist[b] = Some(dist[a].unwrap() + 1);
+            queue.push_back(b);
+        }
+    }
+    dist[j].map(|d| d + 1)
+}
+
+/// Returns the length of the shortest cycle along colored edges, if any.
+///
+/// Self-loops are ignored.
+pub fn girth(g: &Graph) -> Option<usize> {
+    let adj = neighbors(g);
+    (0..adj.len()).flat_map(|i| adj[i].iter().map(move |&j| (i, j)))
+        .filter_map(|e| shortest_cycle_through(&adj, e)).min()
+}
+
+/// Returns the length of the shortest cycle through a node along colored edges, if any.
+///
+/// Self-loops are ignored.
+pub fn local_girth(g: &Graph, i: usize) -> Option<usize> {
+    let adj = neighbors(g);
+    adj[i].iter().filter_map(|&j| shortest_cycle_through(&adj, (i, j))).min()
+}
+
+/// Returns `true` if every node in `Graph::quad_nodes` lies on a cycle of at most some length.
+pub fn local_girth_satisfied(g: &Graph, max: usize) -> bool {
+    (0..g.nodes.len()).filter(|&i| in_quad_nodes(g, i))
+        .all(|i| local_girth(g, i).map(|k| k <= max).unwrap_or(false))
+}
+
+/// Returns `true` if the nodes can be 2-colored along colored edges.
+///
+/// Self-loops are ignored.
+pub fn is_bipartite(g: &Graph) -> bool {
+    let adj = neighbors(g);
+    let mut side = vec![None; adj.len()];
+    for component in components(g) {
+        side[component[0]] = Some(false);
+        let mut stack = vec![component[0]];
+        while let Some(a) = stack.pop() {
+            let sa = side[a].unwrap();
+            for &b in &adj[a] {
+                match side[b] {
+                    None => {
+                        side[b] = Some(!sa);
+                        stack.push(b);
+                    }
+                    Some(sb) => if sb == sa {return false},
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Returns `true` if all nodes are in the same component along colored edges.
+pub fn is_connected(g: &Graph) -> bool {
+    components(g).len() <= 1
+}
+
+/// Returns `true` if some node has no colored edges.
+pub fn has_isolated_nodes(g: &Graph) -> bool {
+    (0..g.nodes.len()).any(|i| degree(g, i) == 0)
+}
+
+/// Returns `true` if no colored edge joins two nodes of the same bipartition color.
+pub fn bipartition_satisfied(g: &Graph) -> bool {
+    let (a, b) = if let Some(x) = g.bipartition {x} else {return true};
+    let m = matrix(g);
+    (0..m.len()).all(|i| (0..m.len()).all(|j| {
+        let c = g.nodes[i].color;
+        m[i][j] < 2 || c != g.nodes[j].color || c != a && c != b
+    }))
+}
+
+/// Returns `true` if every colored edge between labeled nodes
+/// flips the bit corresponding to its color.
+pub fn labels_satisfied(g: &Graph) -> bool {
+    let m = matrix(g);
+    (0..m.len()).all(|i| (0..m.len()).all(|j| {
+        match (g.labels[i], g.labels[j]) {
+            (Some(a), Some(b)) if m[i][j] >= 2 => {
+                let bit = m[i][j] - 2;
+                bit < 64 && a ^ b == 1 << bit
+            }
+            _ => true,
+        }
+    }))
+}
+
+/// Returns `true` if every quad of colored edges commutes or anticommutes.
+///
+/// A quad `a-b-c-d` commutes when opposite edges have the same color.
+/// It anticommutes when opposite edges have the same color or form a sign pair,
+/// with exactly one pair differing.
+/// Sign pairs differ in the lowest bit, unless `Graph::sign_pairs` is set.
+/// Rules in `Graph::quad_rules` take precedence.
+pub fn commute_quad_satisfied(g: &Graph, commute: bool) -> bool {
+    quads_satisfied(g, Some(commute))
+}
+
+/// Returns `true` if every quad satisfies the rule for the colors that span it,
+/// or the default otherwise, where `None` means unconstrained.
+pub fn quads_satisfied(g: &Graph, default: Option<bool>) -> bool {
+    let flipped = |(u, v): (Color, Color)| if g.sign_pairs.is_empty() {
+        u ^ 1 == v
+    } else {
+        g.sign_pairs.contains(&(u.min(v), u.max(v)))
+    };
+    let same = |u: Color, v: Color| u == v || flipped((u, v));
+    let m = matrix(g);
+    let adj = neighbors(g);
+    for a in 0..m.len() {
+        for &b in &adj[a] {
+            for &c in &adj[b] {
+                if c == a {continue};
+                for &d in &adj[c] {
+                    if d == a || d == b || !adj[d].contains(&a) {continue};
+                    if ![a, b, c, d].iter().all(|&x| in_quad_nodes(g, x)) {continue};
+                    let x = (m[a][b], m[c][d]);
+                    let y = (m[b][c], m[d][a]);
+                    let spans = |p: Color, q: Color| same(p, x.0) && same(p, x.1) && same(q, y.0) && same(q, y.1);
+                    let commute = g.quad_rules.iter()
+                        .find(|r| spans(r.colors.0, r.colors.1) || spans(r.colors.1, r.colors.0))
+                        .map(|r| r.commute).unwrap_or(default);
+                    let ok = match commute {
+                        None => true,
+                        Some(true) => x.0 == x.1 && y.0 == y.1,
+                        Some(false) => (x.0 == x.1 || flipped(x)) && (y.0 == y.1 || flipped(y)) &&
+                                       flipped(x) != flipped(y),
+                    };
+                    if !ok {return false};
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Returns `true` if the graph satisfies a global constraint.
+pub fn flag_satisfied(g: &Graph, flag: Flag) -> bool {
+    match flag {
+        Flag::NoTriangles => !has_triangles(g),
+        Flag::MeetQuad => local_girth_satisfied(g, 4),
+        Flag::Connected => is_connected(g),
+        Flag::CommuteQuad(val) => commute_quad_satisfied(g, val),
+    }
+}
+
+/// Returns `true` if all conditional global constraints are satisfied.
+pub fn conditionals_satisfied(g: &Graph) -> bool {
+    g.conditionals.iter().all(|&(color, flag)| {
+        let sub = g.color_subgraph(color);
+        sub.nodes.is_empty() || flag_satisfied(&sub, flag)
+    })
+}
+
+/// Returns `true` if every connected component satisfies the component constraints.
+pub fn component_constraints_satisfied(g: &Graph) -> bool {
+    let adj = neighbors(g);
+    components(g).iter().all(|component| {
+        g.component_constraints.iter().all(|constraint| match *constraint {
+            ComponentConstraint::Regular =>
+                component.iter().all(|&i| degree(g, i) == degree(g, component[0])),
+            ComponentConstraint::Bipartite => {
+                let mut sub = Graph::new();
+                for &i in component {sub.push(g.nodes[i].clone())}
+                for (a, &i) in component.iter().enumerate() {
+                    for (b, &j) in component.iter().enumerate() {
+                        if adj[i].contains(&j) {sub.set((a, b), 2)};
+                    }
+                }
+                is_bipartite(&sub)
+            }
+            ComponentConstraint::Size {min, max} => min <= component.len() && component.len() <= max,
+        })
+    })
+}
+
+/// Returns `true` if every solution differs from its reference graphs in few enough edges.
+pub fn edit_distances_satisfied(g: &Graph) -> bool {
+    let m = matrix(g);
+    g.edit_distances.iter().all(|d| {
+        let mut reference = vec![vec![1; m.len()]; m.len()];
+        for &((i, j), c) in &d.edges {
+            reference[i][j] = c;
+            reference[j][i] = c;
+        }
+        let count = (0..m.len()).flat_map(|i| (i..m.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| m[i][j].max(1) != reference[i][j]).count();
+        count <= d.max
+    })
+}
+
+/// Returns `true` if all symmetric difference constraints are satisfied.
+pub fn symmetric_differences_satisfied(g: &Graph) -> bool {
+    let m = matrix(g);
+    g.symmetric_differences.iter().all(|d| {
+        let (i, j) = d.nodes;
+        let mut colors: Vec<Color> = m[i].iter().chain(m[j].iter()).cloned()
+            .filter(|&c| c >= 2).collect();
+        colors.sort();
+        colors.dedup();
+        let size: usize = colors.iter().map(|&c| {
+            let a = m[i].iter().filter(|&&x| x == c).count();
+            let b = m[j].iter().filter(|&&x| x == c).count();
+            a.max(b) - a.min(b)
+        }).sum();
+        size == d.size
+    })
+}
+
+/// Returns `true` if a colored pattern is embedded as a subgraph.
+pub fn contains_subgraph(g: &Graph, pattern: &Graph) -> bool {
+    fn search(g: &Graph, pattern: &Graph, map: &mut Vec<usize>) -> bool {
+        if map.len() == pattern.nodes.len() {
+            let k = map.len();
+            return (0..k).all(|a| (0..k).all(|b| {
+                let expected = pattern.get((a, b));
+                expected < 2 || g.get((map[a], map[b])) == expected
+            }));
+        }
+        for v in 0..g.nodes.len() {
+            if map.contains(&v) || g.nodes[v].color != pattern.nodes[map.len()].color {continue};
+            map.push(v);
+            if search(g, pattern, map) {return true};
+            map.pop();
+        }
+        false
+    }
+
+    search(g, pattern, &mut vec![])
+}
+
+/// Returns a description of the first constraint that a solution violates, if any.
+///
+/// Symmetry breaking is not checked,
+/// since it only restricts which of several equivalent solutions is returned.
+/// Planarity is checked with `Graph::is_planar`, which does not use cached state.
+pub fn check(g: &Graph) -> Result<(), &'static str> {
+    let checks: [(&'static str, bool); 23] = [
+        ("nodes", nodes_satisfied(g)),
+        ("pairs", pairs_satisfied(g)),
+        ("anti_pairs", anti_pairs_satisfied(g)),
+        ("restrictions", restrictions_satisfied(g)),
+        ("cardinalities", cardinalities_satisfied(g)),
+        ("degrees", degrees_satisfied(g)),
+        ("color_counts", color_counts_satisfied(g)),
+        ("no_triangles", !g.no_triangles || !has_triangles(g)),
+        ("min_girth", g.min_girth.map(|k| girth(g).map(|x| x >= k).unwrap_or(true)).unwrap_or(true)),
+        ("planar", !g.planar || g.is_planar()),
+        ("bipartite", !g.bipartite || is_bipartite(g)),
+        ("connected", !g.connected || is_connected(g)),
+        ("no_isolated_nodes", !g.no_isolated_nodes || !has_isolated_nodes(g)),
+        ("bipartition", bipartition_satisfied(g)),
+        ("labels", labels_satisfied(g)),
+        ("commute_quad", quads_satisfied(g, g.commute_quad)),
+        ("meet_quad", !g.meet_quad || flag_satisfied(g, Flag::MeetQuad)),
+        ("max_local_girth", g.max_local_girth.map(|k| local_girth_satisfied(g, k)).unwrap_or(true)),
+        ("conditionals", conditionals_satisfied(g)),
+        ("component_constraints", component_constraints_satisfied(g)),
+        ("symmetric_differences", symmetric_differences_satisfied(g)),
+        ("edit_distances", edit_distances_satisfied(g)),
+        ("required_subgraphs", g.required_subgraphs.iter().all(|p| contains_subgraph(g, p))),
+    ];
+    match checks.iter().find(|(_, ok)| !ok) {
+        Some(&(name, _)) => Err(name),
+        None => Ok(()),
+    }
+}
+
+/// Returns `true` if a solution satisfies all constraints.
+///
+/// For more information, see `check`.
+pub fn is_solved(g: &Graph) -> bool {
+    check(g).is_ok()
+}
+
+/// Returns the first constraint of a directed graph that a solution violates, if any.
+pub fn check_digraph(g: &Digraph) -> Result<(), &'static str> {
+    let n = g.nodes.len();
+    let nodes = (0..n).all(|i| {
+        let edges = |out: bool| -> Vec<(Color, Color, bool)> {
+            (0..n).map(|j| (if out {g.get((i, j))} else {g.get((j, i))}, j))
+                .filter(|&(edge, _)| edge >= 2)
+                .map(|(edge, j)| (edge, g.nodes[j].color, false)).collect()
+        };
+        constraints_matched(&g.nodes[i].out_edges, &edges(true)) &&
+        constraints_matched(&g.nodes[i].in_edges, &edges(false))
+    });
+    let connected = {
+        let mut reachable = vec![false; n];
+        let mut stack = vec![];
+        if n > 0 {
+            reachable[0] = true;
+            stack.push(0);
+        }
+        while let Some(i) = stack.pop() {
+            for (j, r) in reachable.iter_mut().enumerate() {
+                if !*r && (g.get((i, j)) >= 2 || g.get((j, i)) >= 2) {
+                    *r = true;
+                    stack.push(j);
+                }
+            }
+        }
+        reachable.iter().all(|&b| b)
+    };
+    let checks = [
+        ("nodes", nodes),
+        ("pairs", g.pairs.iter().all(|&pos| g.get(pos) >= 2)),
+        ("connected", !g.connected || connected),
+    ];
+    match checks.iter().find(|(_, ok)| !ok) {
+        Some(&(name, _)) => Err(name),
+        None => Ok(()),
+    }
+}
+
+/// Panics if a solution violates a constraint, with the `verify-solutions` feature.
+///
+/// Edges of the original graph are restored first,
+/// since they are removed from the solution when solving for the difference.
+#[cfg(feature = "verify-solutions")]
+pub(crate) fn verify(solution: &Graph, original: &Graph) {
+    let mut g = solution.clone();
+    restore(&mut g, original);
+    if let Err(constraint) = check(&g) {
+        panic!("Solution violates `{}` when verified independently", constraint);
+    }
+}
+
+/// Panics if a solution of a directed graph violates a constraint,
+/// with the `verify-solutions` feature.
+///
+/// For more information, see `verify`.
+#[cfg(feature = "verify-solutions")]
+pub(crate) fn verify_digraph(solution: &Digraph, original: &Digraph) {
+    let mut g = solution.clone();
+    let n = g.nodes.len();
+    for i in 0..n {
+        for j in 0..n {
+            if original.get((i, j)) != 0 {g.set((i, j), original.get((i, j)))}
+        }
+    }
+    if let Err(constraint) = check_digraph(&g) {
+        panic!("Solution violates `{}` when verified independently", constraint);
+    }
+}
+
+/// Restores the pre-set edges of the original graph in a solution.
+#[cfg(feature = "verify-solutions")]
+pub(crate) fn restore(g: &mut Graph, original: &Graph) {
+    let n = g.nodes.len();
+    for i in 0..n {
+        for j in i..n {
+            if original.get((i, j)) != 0 {g.set((i, j), original.get((i, j)))}
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Solving a part of a graph while the rest is kept fixed.
+
+use super::*;
+
+impl Graph {
+    /// Returns a mask of nodes in a region.
+    pub(crate) fn region_mask(&self, region: &[usize]) -> Vec<bool> {
+        let mut mask = vec![false; self.nodes.len()];
+        for &i in region {mask[i] = true}
+        mask
+    }
+
+    /// Finds the edge incident to a region with the least possible colors.
+    ///
+    /// Ties are broken by picking the edge with highest priority.
+    pub(crate) fn min_colors_in(&self, mask: &[bool]) -> Option<(usize, usize)> {
+        let mut min: Option<(usize, usize, usize, i64)> = None;
+        let n = self.nodes.len();
+        for i in 0..n {
+            for j in i..n {
+                if !mask[i] && !mask[j] {continue};
+                let s = self.colors((i, j)).len();
+                if s == 0 {continue};
+                let p = self.priority((i, j));
+                if min.map(|m| m.2 > s || m.2 == s && m.3 < p).unwrap_or(true) {
+                    min = Some((i, j, s, p));
+                }
+            }
+        }
+        min.map(|n| (n.0, n.1))
+    }
+
+    /// Returns `true` if a region is solved.
+    ///
+    /// A region is solved when every edge incident to the region is assigned,
+    /// the edge constraints of every node in the region are satisfied,
+    /// and the constraints on the whole graph can still be satisfied,
+    /// e.g. there are no triangles when `no_triangles` is set.
+    ///
+    /// Constraints on the whole graph are only checked as far as the assigned edges decide them,
+    /// so a solved region can still violate them once the rest of the graph is solved,
+    /// e.g. when `connected` is set and the region is not yet connected to the rest.
+    ///
+    /// When the region contains all nodes, this is the same as `Puzzle::is_solved`.
+    pub fn region_solved(&self, region: &[usize]) -> bool {
+        self.mask_solved(&self.region_mask(region))
+    }
+
+    pub(crate) fn mask_solved(&self, mask: &[bool]) -> bool {
+        if mask.iter().all(|&b| b) {return self.is_solved()};
+        let n = self.nodes.len();
+        for i in 0..n {
+            if mask[i] && !self.node_satisfied(i).is_empty() {return false}
+            for j in i..n {
+                if (mask[i] || mask[j]) && self.get((i, j)) == 0 &&
+                   (i != j || self.nodes[i].self_connected) {return false}
+            }
+        }
+        self.feasible()
+    }
+
+    /// Solves the edges within or incident to a region of nodes.
+    ///
+    /// The solver only branches on edges with at least one end in the region,
+    /// while other edges are treated as fixed.
+    /// Simple moves might still assign edges outside the region
+    /// when they are forced by constraints.
+    ///
+    /// This can be used to complete a large specification region by region,
+    /// pinning the solution of each region before solving the next.
+    /// For more information, see `Graph::region_solved`.
+    pub fn solve_region(self, region: &[usize], solve_settings: SearchSettings) -> Option<Solution<Graph>> {
+        let mask = self.region_mask(region);
+        let mut solutions = Solutions::new(self, solve_settings);
+        solutions.region = Some(mask);
+        solutions.next()
+    }
+
+    /// Returns the nodes within a hop distance of a node along colored edges.
+    ///
+    /// The nodes are sorted by index.
+    pub fn neighborhood(&self, i: usize, radius: usize) -> Vec<usize> {
+        let n = self.nodes.len();
+        let mut dist = vec![usize::MAX; n];
+        let mut queue = std::collections::VecDeque::new();
+        dist[i] = 0;
+        queue.push_back(i);
+        while let Some(a) = queue.pop_front() {
+            if dist[a] >= radius {continue};
+            for b in 0..n {
+                if dist[b] != usize::MAX || self.get((a, b)) < 2 {continue};
+                dist[b] = dist[a] + 1;
+                queue.push_back(b);
+            }
+        }
+        (0..n).filter(|&j| dist[j] != usize::MAX).collect()
+    }
+
+    /// Clears and re-solves the edges within a hop distance of a node.
+    ///
+    /// Edges between nodes in the neighborhood are cleared,
+    /// while the rest of the graph is kept fixed.
+    /// For more information, see `Graph::neighborhood` and `Graph::solve_region`.
+    pub fn resolve_around(
+        mut self,
+        i: usize,
+        radius: usize,
+        solve_settings: SearchSettings
+    ) -> Option<Solution<Graph>> {
+        let region = self.neighborhood(i, radius);
+        for (k, &a) in region.iter().enumerate() {
+            for &b in &region[k..] {self.set((a, b), 0)}
+        }
+        self.solve_region(&region, solve_settings)
+    }
+}
@@ -0,0 +1,255 @@
+//! Reports about graph puzzles before solving.
+
+use std::fmt;
+
+use super::*;
+
+/// Summarizes a graph puzzle before solving.
+///
+/// This is used to compare formulations of the same problem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompileReport {
+    /// The number of nodes.
+    pub nodes: usize,
+    /// The number of edges without a value that can be assigned.
+    pub variables: usize,
+    /// The number of edges with a value.
+    pub assigned: usize,
+    /// The number of variables by domain size, indexed by domain size.
+    pub domain_sizes: Vec<usize>,
+    /// The active global constraints.
+    pub constraints: Vec<String>,
+    /// The estimated search space size, as a base 10 logarithm.
+    ///
+    /// This is the product of initial domain sizes.
+    pub log10_search_space: f64,
+}
+
+impl fmt::Display for CompileReport {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(w, "nodes: {}", self.nodes)?;
+        writeln!(w, "variables: {}", self.variables)?;
+        writeln!(w, "assigned: {}", self.assigned)?;
+        writeln!(w, "domain sizes:")?;
+        for (size, &count) in self.domain_sizes.iter().enumerate() {
+            if count == 0 {continue};
+            writeln!(w, "  {}: {}", size, count)?;
+        }
+        writeln!(w, "constraints:")?;
+        for constraint in &self.constraints {
+            writeln!(w, "  {}", constraint)?;
+        }
+        writeln!(w, "search space: 10^{:.2}", self.log10_search_space)
+    }
+}
+
+/// Lists the colors that are referenced by constraints and used in the graph.
+///
+/// For more information, see `Graph::color_usage`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorUsage {
+    /// The colors of nodes.
+    pub node_colors: Vec<Color>,
+    /// The node colors referenced by edge constraints and the bipartition.
+    pub referenced_node_colors: Vec<Color>,
+    /// The edge colors referenced by edge constraints, color counts and conditionals.
+    pub referenced_edge_colors: Vec<Color>,
+    /// The edge colors of colored edges in the assignment.
+    pub used_edge_colors: Vec<Color>,
+    /// Constraints that reference colors which no node can provide.
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for ColorUsage {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        fn list(colors: &[Color]) -> String {
+            colors.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+        }
+
+        writeln!(w, "node colors: {}", list(&self.node_colors))?;
+        writeln!(w, "referenced node colors: {}", list(&self.referenced_node_colors))?;
+        writeln!(w, "referenced edge colors: {}", list(&self.referenced_edge_colors))?;
+        writeln!(w, "used edge colors: {}", list(&self.used_edge_colors))?;
+        for warning in &self.warnings {
+            writeln!(w, "warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Returns which colors are referenced by constraints and used in the graph,
+    /// with warnings about constraints that reference colors no node can provide.
+    ///
+    /// Such constraints are a common cause of puzzles without solution:
+    ///
+    /// - An edge constraint to a node color that no other node has
+    /// - An edge constraint that no node of the target color accepts in return
+    /// - A bipartition with a color that no node has
+    /// - A color count with a positive minimum for an edge color that no edge constraint allows
+    pub fn color_usage(&self) -> ColorUsage {
+        fn sorted(mut colors: Vec<Color>) -> Vec<Color> {
+            colors.sort();
+            colors.dedup();
+            colors
+        }
+
+        let n = self.nodes.len();
+        let node_colors = sorted(self.nodes.iter().map(|node| node.color).collect());
+        let mut referenced_node_colors = vec![];
+        let mut referenced_edge_colors = vec![];
+        for node in &self.nodes {
+            for con in &node.edges {
+                if con.node != SELF_LOOP {referenced_node_colors.push(con.node)};
+                referenced_edge_colors.push(con.edge);
+            }
+        }
+        if let Some((a, b)) = self.bipartition {referenced_node_colors.extend([a, b])};
+        referenced_edge_colors.extend(self.color_counts.iter().map(|cc| cc.color));
+        referenced_edge_colors.extend(self.conditionals.iter().map(|&(color, _)| color));
+        let mut used_edge_colors = vec![];
+        for i in 0..n {
+            used_edge_colors.extend(self.row(i).iter().cloned().filter(|&c| c >= 2));
+        }
+
+        let mut warnings = vec![];
+        warnings.extend(self.dead_constraints().into_iter()
+            .filter(|dead| matches!(dead.reason, DeadReason::MissingNodeColor | DeadReason::NoReciprocal))
+            .map(|dead| dead.to_string()));
+        if let Some((a, b)) = self.bipartition {
+            for color in sorted(vec![a, b]) {
+                if !node_colors.contains(&color) {
+                    warnings.push(format!("bipartition uses node color {}, but no node has this color", color));
+                }
+            }
+        }
+        for cc in &self.color_counts {
+            if cc.min > 0 && !self.nodes.iter().any(|node| node.edges.iter().any(|con| con.edge == cc.color)) {
+                warnings.push(format!("color count requires at least {} edges of color {}, \
+                    but no edge constraint has this color", cc.min, cc.color));
+            }
+        }
+        // Repeated edge constraints give the same warning.
+        let mut seen = std::collections::HashSet::new();
+        warnings.retain(|w| seen.insert(w.clone()));
+
+        ColorUsage {
+            node_colors,
+            referenced_node_colors: sorted(referenced_node_colors),
+            referenced_edge_colors: sorted(referenced_edge_colors),
+            used_edge_colors: sorted(used_edge_colors),
+            warnings,
+        }
+    }
+
+    /// Returns a summary of the puzzle before solving.
+    pub fn compile_report(&self) -> CompileReport {
+        let n = self.nodes.len();
+        let mut variables = 0;
+        let mut assigned = 0;
+        let mut domain_sizes = vec![];
+        let mut log10_search_space = 0.0;
+        for i in 0..n {
+            for j in i..n {
+                if self.get((i, j)) != 0 {
+                    assigned += 1;
+                    continue;
+                }
+                let size = self.colors((i, j)).len();
+                if size == 0 {continue};
+                variables += 1;
+                if domain_sizes.len() <= size {domain_sizes.resize(size + 1, 0)};
+                domain_sizes[size] += 1;
+                log10_search_space += (size as f64).log10();
+            }
+        }
+
+        let constraints = self.constraint_summary();
+
+        CompileReport {
+            nodes: n,
+            variables,
+            assigned,
+            domain_sizes,
+            constraints,
+            log10_search_space,
+        }
+    }
+
+    /// Returns readable descriptions of the global constraints.
+    pub(crate) fn constraint_summary(&self) -> Vec<String> {
+        let mut constraints = vec![];
+        if self.no_triangles {constraints.push("no triangles".into())};
+        if let Some(min_girth) = self.min_girth {
+            constraints.push(format!("min girth {}", min_girth));
+        }
+        if self.planar {constraints.push("planar".into())};
+        if self.bipartite {constraints.push("bipartite".into())};
+        if self.meet_quad {constraints.push("meet quad".into())};
+        if let Some(k) = self.max_local_girth {
+            constraints.push(format!("max local girth {}", k));
+        }
+        if self.connected {constraints.push("connected".into())};
+        if self.no_isolated_nodes {constraints.push("no isolated nodes".into())};
+        if self.symmetry_break {constraints.push("symmetry break".into())};
+        if self.color_symmetry_break {constraints.push("color symmetry break".into())};
+        match self.commute_quad {
+            Some(true) => constraints.push("commute quad".into()),
+            Some(false) => constraints.push("anticommute quad".into()),
+            None => {}
+        }
+        if !self.sign_pairs.is_empty() {
+            constraints.push(format!("sign pairs: {}", self.sign_pairs.len()));
+        }
+        for rule in &self.quad_rules {
+            let (a, b) = rule.colors;
+            constraints.push(match rule.commute {
+                Some(true) => format!("commute quad {} {}", a, b),
+                Some(false) => format!("anticommute quad {} {}", a, b),
+                None => format!("free quad {} {}", a, b),
+            });
+        }
+        if let Some(nodes) = &self.quad_nodes {
+            constraints.push(format!("quad nodes: {}", nodes.len()));
+        }
+        if let Some((a, b)) = self.bipartition {
+            constraints.push(format!("bipartition {} {}", a, b));
+        }
+        let labels = self.labels.iter().filter(|label| label.is_some()).count();
+        if labels > 0 {constraints.push(format!("labels: {}", labels))};
+        if !self.pairs.is_empty() {
+            constraints.push(format!("pairs: {}", self.pairs.len()));
+        }
+        if !self.anti_pairs.is_empty() {
+            constraints.push(format!("anti-pairs: {}", self.anti_pairs.len()));
+        }
+        if !self.restrictions.is_empty() {
+            constraints.push(format!("restricted edges: {}", self.restrictions.len()));
+        }
+        if !self.cardinalities.is_empty() {
+            constraints.push(format!("cardinalities: {}", self.cardinalities.len()));
+        }
+        if !self.degrees.is_empty() {
+            constraints.push(format!("degrees: {}", self.degrees.len()));
+        }
+        for cc in &self.color_counts {
+            constraints.push(format!("color {} count {}..={}", cc.color, cc.min, cc.max));
+        }
+        for &(color, flag) in &self.conditionals {
+            constraints.push(format!("if {} then {:?}", color, flag));
+        }
+        for constraint in &self.component_constraints {
+            constraints.push(format!("component {:?}", constraint));
+        }
+        if !self.required_subgraphs.is_empty() {
+            constraints.push(format!("required subgraphs: {}", self.required_subgraphs.len()));
+        }
+        if !self.symmetric_differences.is_empty() {
+            constraints.push(format!("symmetric differences: {}", self.symmetric_differences.len()));
+        }
+        for d in &self.edit_distances {
+            constraints.push(format!("edit distance <= {}", d.max));
+        }
+        constraints
+    }
+}
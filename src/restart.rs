@@ -0,0 +1,125 @@
+//! Restarts and randomized branching.
+//!
+//! Backtracking search often has heavy-tailed runtimes,
+//! where an early bad choice is explored for a long time.
+//! Restarting with a growing iteration limit and a randomized order
+//! avoids getting stuck, while remaining complete.
+
+use super::*;
+
+/// Decides when the solver restarts the search from the initial graph.
+///
+/// The search restarts when the number of iterations since the last restart
+/// reaches the limit of the run, until the first solution is found.
+/// Limits grow without bound, so the search is still complete.
+///
+/// Restarts require a seed or learning to explore different choices,
+/// see `SearchSettings::seed` and `SearchSettings::learn`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RestartPolicy {
+    /// Multiplies the iteration limit by a factor after every restart.
+    Geometric {
+        /// The iteration limit of the first run.
+        first: u64,
+        /// The factor to multiply the limit with, greater than `1`.
+        ///
+        /// This is checked by `SearchSettings::set_restart`.
+        factor: f64,
+    },
+    /// Uses the Luby sequence `1, 1, 2, 1, 1, 2, 4, 1, ...` times a unit of iterations.
+    ///
+    /// This is within a logarithmic factor of the optimal restart strategy
+    /// when the runtime distribution is unknown.
+    Luby {
+        /// The number of iterations per unit of the sequence.
+        unit: u64,
+    },
+}
+
+impl RestartPolicy {
+    /// Returns the iteration limit of a run, starting with `0` for the first run.
+    pub fn limit(&self, run: u64) -> u64 {
+        match *self {
+            RestartPolicy::Geometric {first, factor} => {
+                let limit = first as f64 * factor.powi(run.min(i32::MAX as u64) as i32);
+                if limit >= u64::MAX as f64 {u64::MAX} else {(limit as u64).max(1)}
+            }
+            RestartPolicy::Luby {unit} => unit.saturating_mul(luby(run + 1)).max(1),
+        }
+    }
+}
+
+/// Returns element `k` of the Luby sequence, starting with `1`.
+fn luby(mut k: u64) -> u64 {
+    loop {
+        // Find the smallest `2^m - 1 >= k`.
+        let mut size: u64 = 1;
+        while size < k {size = 2 * size + 1}
+        if size == k {return size / 2 + 1};
+        k -= size / 2;
+    }
+}
+
+/// A xorshift random number generator, used for branching order and fuzzing.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    /// Creates a new random number generator from a seed.
+    pub fn new(seed: u64) -> Rng {
+        // Xorshift requires a non-zero state.
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+    }
+
+    fn next(&mut self) -> u64 {
+        if self.0 == 0 {self.0 = 1};
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a random number below `n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {0} else {(self.next() % n as u64) as usize}
+    }
+
+    /// Shuffles a list.
+    pub fn shuffle<T>(&mut self, list: &mut [T]) {
+        for k in (1..list.len()).rev() {
+            list.swap(k, self.below(k + 1));
+        }
+    }
+}
+
+impl Graph {
+    /// Finds the edge with the least possible colors, breaking ties randomly.
+    ///
+    /// Edges with higher priority are preferred among edges with the same number of colors,
+    /// see `Graph::min_colors`.
+    pub(crate) fn min_colors_random(&self, rng: &mut Rng) -> Option<(usize, usize)> {
+        let mut min: Option<(usize, usize, usize, i64)> = None;
+        let mut ties = 0;
+        let n = self.nodes.len();
+        for i in 0..n {
+            for j in i..n {
+                let s = self.colors((i, j)).len();
+                if s == 0 {continue};
+                let p = self.priority((i, j));
+                match min {
+                    Some(m) if m.2 == s && m.3 == p => {
+                        // Reservoir sampling picks every tie with same probability.
+                        ties += 1;
+                        if rng.below(ties) == 0 {min = Some((i, j, s, p))}
+                    }
+                    Some(m) if m.2 < s || m.2 == s && m.3 > p => {}
+                    _ => {
+                        min = Some((i, j, s, p));
+                        ties = 1;
+                    }
+                }
+            }
+        }
+        min.map(|n| (n.0, n.1))
+    }
+}
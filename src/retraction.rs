@@ -0,0 +1,116 @@
+//! Re-checking solvability after retracting an edge from a solution.
+
+use super::*;
+
+/// Stores a solution with one edge retracted.
+///
+/// The retracted edge is required to get a different value than before.
+/// For more information, see `Graph::without_edge`.
+#[derive(Clone, Debug)]
+pub struct Retraction {
+    /// The solution before retracting the edge.
+    pub graph: Graph,
+    /// The retracted edge.
+    pub edge: (usize, usize),
+    /// The value of the edge before retracting it.
+    pub old: Color,
+}
+
+impl Graph {
+    /// Retracts an edge from a solution.
+    ///
+    /// Use `Retraction::is_still_solvable` to check whether
+    /// some solution has a different value for the edge.
+    /// If not, the edge is forced by the specification.
+    pub fn without_edge(&self, edge: (usize, usize)) -> Retraction {
+        Retraction {
+            graph: self.clone(),
+            edge,
+            old: self.get(edge).max(1),
+        }
+    }
+
+    /// Returns the nodes within some distance of a set of nodes, following colored edges.
+    pub fn nodes_within(&self, start: &[usize], radius: usize) -> Vec<bool> {
+        let n = self.nodes.len();
+        let mut inside = vec![false; n];
+        let mut front: Vec<usize> = start.to_vec();
+        for &i in start {inside[i] = true};
+        for _ in 0..radius {
+            let mut next = vec![];
+            for &i in &front {
+                for (j, inside_j) in inside.iter_mut().enumerate() {
+                    if !*inside_j && self.get((i, j)) >= 2 {
+                        *inside_j = true;
+                        next.push(j);
+                    }
+                }
+            }
+            if next.is_empty() {break};
+            front = next;
+        }
+        inside
+    }
+}
+
+impl Retraction {
+    /// Returns the values the retracted edge can have instead of the old value.
+    pub fn candidates(&self) -> Vec<Color> {
+        let (i, j) = self.edge;
+        let g = &self.graph;
+        let mut res = vec![1];
+        for a in &g.nodes[i].edges {
+            if i == j && a.node == SELF_LOOP {
+                res.push(a.edge);
+                continue;
+            }
+            if a.node != g.nodes[j].color {continue};
+            if g.nodes[j].edges.iter().any(|b| b.edge == a.edge && b.node == g.nodes[i].color) {
+                res.push(a.edge);
+            }
+        }
+        res.sort();
+        res.dedup();
+        res.retain(|&c| c != self.old);
+        res
+    }
+
+    /// Solves with a different value for the retracted edge.
+    ///
+    /// Starts by re-solving only edges close to the retracted edge,
+    /// re-using the rest of the previous solution.
+    /// The radius grows until every edge is re-solved,
+    /// so assignments of the original specification are not kept.
+    pub fn solve(&self, solve_settings: SearchSettings) -> Option<Solution<Graph>> {
+        let (i, j) = self.edge;
+        let n = self.graph.nodes.len();
+        let candidates = self.candidates();
+        let mut radius = 0;
+        loop {
+            let inside = self.graph.nodes_within(&[i, j], radius);
+            let all = inside.iter().all(|&b| b);
+            let mut g = self.graph.clone();
+            for a in 0..n {
+                for b in a..n {
+                    if all || inside[a] || inside[b] {g.set((a, b), 0)};
+                }
+            }
+            for &c in &candidates {
+                let mut g = g.clone();
+                g.set(self.edge, c);
+                if let Some(solution) = g.solve_all(solve_settings.clone()).next() {
+                    return Some(solution);
+                }
+            }
+            if all {return None};
+            let grown = self.graph.nodes_within(&[i, j], radius + 1);
+            // Jump to re-solving every edge when the region stops growing.
+            radius = if grown == inside {n} else {radius + 1};
+        }
+    }
+
+    /// Returns `true` if some solution has a different value for the retracted edge.
+    pub fn is_still_solvable(&self, solve_settings: SearchSettings) -> bool {
+        self.solve(solve_settings).is_some()
+    }
+}
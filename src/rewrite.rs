@@ -0,0 +1,75 @@
+//! Rewriting occurrences of colored patterns.
+//!
+//! Rewrite rules can normalize solutions, or simplify specifications before solving
+//! by rewriting pinned edges.
+
+use super::*;
+
+/// Stores a rule that replaces occurrences of a colored pattern.
+///
+/// The pattern and the replacement have the same number of nodes,
+/// where node `k` of the replacement corresponds to node `k` of the pattern.
+/// Occurrences are found with `Graph::find_subgraph`.
+///
+/// For every pair of pattern nodes:
+///
+/// - When the replacement edge is assigned, the edge is set to the replacement value
+/// - When the replacement edge is empty, a colored pattern edge is set to no-edge
+/// - Otherwise, the edge is kept
+///
+/// Node colors and edge constraints of the replacement are ignored.
+#[derive(Clone, Debug)]
+pub struct RewriteRule {
+    /// The pattern to find.
+    pub pattern: Graph,
+    /// The replacement edges.
+    pub replacement: Graph,
+}
+
+impl RewriteRule {
+    /// Creates a new rewrite rule.
+    ///
+    /// Panics if the pattern and replacement have different number of nodes.
+    pub fn new(pattern: Graph, replacement: Graph) -> RewriteRule {
+        assert_eq!(pattern.nodes.len(), replacement.nodes.len(),
+            "Expected same number of nodes in pattern and replacement");
+        RewriteRule {pattern, replacement}
+    }
+}
+
+impl Graph {
+    /// Rewrites a single occurrence of the pattern of a rule.
+    ///
+    /// Returns the nodes of the rewritten occurrence, if any.
+    pub fn apply_rule(&mut self, rule: &RewriteRule) -> Option<Vec<usize>> {
+        let map = self.find_subgraph(&rule.pattern)?;
+        let n = map.len();
+        for k in 0..n {
+            for l in k..n {
+                let val = rule.replacement.get((k, l));
+                if val != 0 {
+                    self.set((map[k], map[l]), val);
+                } else if rule.pattern.get((k, l)) >= 2 {
+                    self.set((map[k], map[l]), 1);
+                }
+            }
+        }
+        Some(map)
+    }
+
+    /// Applies rewrite rules until no pattern occurs or the maximum number of steps is reached.
+    ///
+    /// Rules are tried in order, starting from the first rule after every step.
+    /// The maximum number of steps prevents rules from rewriting forever,
+    /// e.g. when a replacement contains its own pattern.
+    ///
+    /// Returns the number of rewrite steps.
+    pub fn rewrite(&mut self, rules: &[RewriteRule], max_steps: usize) -> usize {
+        let mut steps = 0;
+        while steps < max_steps {
+            if !rules.iter().any(|rule| self.apply_rule(rule).is_some()) {break};
+            steps += 1;
+        }
+        steps
+    }
+}
@@ -0,0 +1,226 @@
+//! Serde support for checkpointing puzzles and solutions.
+//!
+//! A graph is serialized through `GraphData`, which has the settings and edges
+//! but no cached state. Caches are rebuilt when deserializing.
+//! The serialized form has the same schema as the JSON format, see `Graph::from_json`,
+//! such that JSON written by `serde_json` can be read by `Graph::from_json` and the reverse.
+//!
+//! The serialized form has a format version, such that checkpoints written
+//! by earlier versions of the crate can still be read.
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// The version of the serialized form.
+///
+/// Data without version was written before the form was versioned,
+/// and has version `0`, which is the same form as version `1`.
+const DATA_VERSION: u32 = 1;
+
+/// The serialized form of a graph.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct GraphData {
+    format_version: u32,
+    nodes: Vec<Node>,
+    /// Edges that are not empty, using sorted indices.
+    edges: Vec<(usize, usize, Color)>,
+    pairs: Vec<(usize, usize)>,
+    anti_pairs: Vec<(usize, usize)>,
+    restrictions: Vec<RestrictionData>,
+    cardinalities: Vec<Cardinality>,
+    degrees: Vec<DegreeConstraint>,
+    color_counts: Vec<ColorCount>,
+    no_triangles: bool,
+    min_girth: Option<usize>,
+    planar: bool,
+    bipartite: bool,
+    meet_quad: bool,
+    max_local_girth: Option<usize>,
+    connected: bool,
+    no_isolated_nodes: bool,
+    commute_quad: Option<bool>,
+    sign_pairs: Vec<(Color, Color)>,
+    quad_rules: Vec<QuadRule>,
+    quad_nodes: Option<Vec<usize>>,
+    labels: Vec<Option<u64>>,
+    names: Vec<Option<String>>,
+    bipartition: Option<(Color, Color)>,
+    conditionals: Vec<ConditionalData>,
+    component_constraints: Vec<ComponentData>,
+    symmetric_differences: Vec<SymmetricDifference>,
+    edit_distances: Vec<EditDistanceData>,
+    required_subgraphs: Vec<Graph>,
+    symmetry_break: bool,
+    color_symmetry_break: bool,
+    /// Branching priorities of edges that are not `0`.
+    priorities: Vec<(usize, usize, i64)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RestrictionData {
+    edge: (usize, usize),
+    colors: Vec<Color>,
+}
+
+/// A conditional, where the flag is named as in `Graph::from_json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConditionalData {
+    color: Color,
+    flag: String,
+}
+
+/// A component constraint, which is `"regular"`, `"bipartite"` or `{"min": _, "max": _}`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ComponentData {
+    Named(String),
+    Size {min: usize, max: usize},
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EditDistanceData {
+    edges: Vec<(usize, usize, Color)>,
+    max: usize,
+}
+
+impl From<Graph> for GraphData {
+    fn from(g: Graph) -> GraphData {
+        let n = g.nodes.len();
+        let priorities = (0..n).flat_map(|i| (i..n).map(move |j| (i, j)))
+            .map(|(i, j)| (i, j, g.priority((i, j))))
+            .filter(|&(_, _, p)| p != 0).collect();
+        let edges = (0..n).flat_map(|i| (i..n).map(move |j| (i, j)))
+            .map(|(i, j)| (i, j, g.get((i, j))))
+            .filter(|&(_, _, val)| val != 0).collect();
+        GraphData {
+            format_version: DATA_VERSION,
+            nodes: g.nodes,
+            edges,
+            pairs: g.pairs,
+            anti_pairs: g.anti_pairs,
+            restrictions: g.restrictions.into_iter()
+                .map(|(edge, colors)| RestrictionData {edge, colors}).collect(),
+            cardinalities: g.cardinalities,
+            degrees: g.degrees,
+            color_counts: g.color_counts,
+            no_triangles: g.no_triangles,
+            min_girth: g.min_girth,
+            planar: g.planar,
+            bipartite: g.bipartite,
+            meet_quad: g.meet_quad,
+            max_local_girth: g.max_local_girth,
+            connected: g.connected,
+            no_isolated_nodes: g.no_isolated_nodes,
+            commute_quad: g.commute_quad,
+            sign_pairs: g.sign_pairs,
+            quad_rules: g.quad_rules,
+            quad_nodes: g.quad_nodes,
+            labels: g.labels,
+            names: g.names,
+            bipartition: g.bipartition,
+            conditionals: g.conditionals.iter()
+                .map(|&(color, flag)| ConditionalData {color, flag: json::flag_name(flag).into()}).collect(),
+            component_constraints: g.component_constraints.iter().map(|c| match *c {
+                ComponentConstraint::Regular => ComponentData::Named("regular".into()),
+                ComponentConstraint::Bipartite => ComponentData::Named("bipartite".into()),
+                ComponentConstraint::Size {min, max} => ComponentData::Size {min, max},
+            }).collect(),
+            symmetric_differences: g.symmetric_differences,
+            edit_distances: g.edit_distances.into_iter().map(|d| EditDistanceData {
+                edges: d.edges.iter().map(|&((i, j), c)| (i, j, c)).collect(),
+                max: d.max,
+            }).collect(),
+            required_subgraphs: g.required_subgraphs,
+            symmetry_break: g.symmetry_break,
+            color_symmetry_break: g.color_symmetry_break,
+            priorities,
+        }
+    }
+}
+
+impl TryFrom<GraphData> for Graph {
+    type Error = String;
+
+    fn try_from(data: GraphData) -> Result<Graph, String> {
+        if data.format_version > DATA_VERSION {
+            return Err(format!("Unsupported format version {}", data.format_version));
+        }
+        let n = data.nodes.len();
+        if !data.labels.is_empty() && data.labels.len() != n {
+            return Err(format!("Expected {} labels", n));
+        }
+        if !data.names.is_empty() && data.names.len() != n {
+            return Err(format!("Expected {} names", n));
+        }
+        let in_range = |(i, j): (usize, usize)| i < n && j < n;
+        if !data.edges.iter().all(|&(i, j, _)| in_range((i, j))) ||
+           !data.pairs.iter().all(|&pos| in_range(pos)) ||
+           !data.anti_pairs.iter().all(|&pos| in_range(pos)) ||
+           !data.restrictions.iter().all(|r| in_range(r.edge)) ||
+           !data.cardinalities.iter().all(|c| c.edges.iter().all(|&pos| in_range(pos))) ||
+           !data.degrees.iter().all(|d| d.node < n) ||
+           !data.quad_nodes.iter().flatten().all(|&i| i < n) ||
+           !data.symmetric_differences.iter().all(|d| in_range(d.nodes)) ||
+           !data.edit_distances.iter().all(|d| d.edges.iter().all(|&(i, j, _)| in_range((i, j)))) ||
+           !data.priorities.iter().all(|&(i, j, _)| in_range((i, j)))
+        {
+            return Err("Node index out of range".into());
+        }
+
+        let mut g = Graph::new();
+        for node in data.nodes {g.push(node)}
+        for (i, j, val) in data.edges {g.set((i, j), val)}
+        if !data.labels.is_empty() {g.labels = data.labels};
+        if !data.names.is_empty() {g.names = data.names};
+        g.pairs = data.pairs;
+        for pair in data.anti_pairs {g.push_anti_pair(pair)}
+        for r in data.restrictions {g.restrict(r.edge, &r.colors)}
+        g.cardinalities = data.cardinalities;
+        g.degrees = data.degrees;
+        g.color_counts = data.color_counts;
+        g.no_triangles = data.no_triangles;
+        g.min_girth = data.min_girth;
+        g.planar = data.planar;
+        g.bipartite = data.bipartite;
+        g.meet_quad = data.meet_quad;
+        g.max_local_girth = data.max_local_girth;
+        g.connected = data.connected;
+        g.no_isolated_nodes = data.no_isolated_nodes;
+        g.commute_quad = data.commute_quad;
+        g.sign_pairs = data.sign_pairs;
+        g.quad_rules = data.quad_rules;
+        if let Some(nodes) = data.quad_nodes {g.restrict_quads(&nodes)};
+        g.bipartition = data.bipartition;
+        for c in data.conditionals {
+            match json::flag_by_name(&c.flag) {
+                Some(flag) => g.push_conditional(c.color, flag),
+                None => return Err(format!("Unknown flag `{}` in `conditionals`", c.flag)),
+            }
+        }
+        for c in data.component_constraints {
+            g.push_component_constraint(match c {
+                ComponentData::Named(name) if name == "regular" => ComponentConstraint::Regular,
+                ComponentData::Named(name) if name == "bipartite" => ComponentConstraint::Bipartite,
+                ComponentData::Named(name) => return Err(format!("Unknown component constraint `{}`", name)),
+                ComponentData::Size {min, max} => ComponentConstraint::Size {min, max},
+            });
+        }
+        g.symmetric_differences = data.symmetric_differences;
+        for d in data.edit_distances {
+            let edges: Vec<_> = d.edges.iter().map(|&(i, j, c)| ((i, j), c)).collect();
+            g.edit_distances.push(EditDistance::new(&edges, d.max));
+        }
+        for pattern in &data.required_subgraphs {g.require_subgraph(pattern)}
+        g.symmetry_break = data.symmetry_break;
+        g.color_symmetry_break = data.color_symmetry_break;
+        for (i, j, p) in data.priorities {g.set_priority((i, j), p)}
+        Ok(g)
+    }
+}
@@ -0,0 +1,96 @@
+//! Writing solutions immediately while enumerating.
+
+use std::io::{self, Write};
+
+use super::*;
+
+/// Receives solutions while enumerating.
+pub trait Sink {
+    /// Writes a solution.
+    fn write(&mut self, g: &Graph) -> io::Result<()>;
+}
+
+/// Writes solutions as a bundle of GraphViz graphs.
+///
+/// The output can be split into separate files with e.g. `csplit`,
+/// or rendered directly with `dot -O`.
+pub struct DotSink<'a, W: Write> {
+    /// The writer.
+    pub writer: W,
+    /// The GraphViz layout.
+    pub layout: &'a str,
+    /// The node colors.
+    pub node_colors: &'a [&'a str],
+    /// The edge colors.
+    pub edge_colors: &'a [&'a str],
+}
+
+impl<'a, W: Write> DotSink<'a, W> {
+    /// Creates a new DOT sink.
+    pub fn new(
+        writer: W,
+        layout: &'a str,
+        node_colors: &'a [&'a str],
+        edge_colors: &'a [&'a str]
+    ) -> DotSink<'a, W> {
+        DotSink {writer, layout, node_colors, edge_colors}
+    }
+}
+
+impl<'a, W: Write> Sink for DotSink<'a, W> {
+    fn write(&mut self, g: &Graph) -> io::Result<()> {
+        write!(self.writer, "{}", g.graphviz(self.layout, self.node_colors, self.edge_colors))?;
+        self.writer.flush()
+    }
+}
+
+/// Writes solutions as JSON, one solution per line.
+///
+/// Each line has the form `{"nodes":[...],"edges":[[i,j,color],...]}`,
+/// where `nodes` are the node colors and `edges` are the colored edges.
+pub struct JsonlSink<W: Write> {
+    /// The writer.
+    pub writer: W,
+}
+
+impl<W: Write> JsonlSink<W> {
+    /// Creates a new JSONL sink.
+    pub fn new(writer: W) -> JsonlSink<W> {
+        JsonlSink {writer}
+    }
+}
+
+impl<W: Write> Sink for JsonlSink<W> {
+    fn write(&mut self, g: &Graph) -> io::Result<()> {
+        let n = g.nodes.len();
+        let nodes: Vec<String> = g.nodes.iter().map(|node| node.color.to_string()).collect();
+        let mut edges = vec![];
+        for i in 0..n {
+            for j in i..n {
+                let val = g.get((i, j));
+                if val >= 2 {edges.push(format!("[{},{},{}]", i, j, val))};
+            }
+        }
+        writeln!(self.writer, "{{\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))?;
+        self.writer.flush()
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    fn write(&mut self, g: &Graph) -> io::Result<()> {(**self).write(g)}
+}
+
+impl Solutions {
+    /// Writes each solution to a sink as soon as it is found.
+    ///
+    /// Returns the number of solutions written.
+    /// Solutions written before an interruption are kept by the sink.
+    pub fn write_to<S: Sink>(self, mut sink: S) -> io::Result<usize> {
+        let mut count = 0;
+        for solution in self {
+            sink.write(&solution.puzzle)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
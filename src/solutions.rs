@@ -0,0 +1,107 @@
+//! Analysis of collections of solutions.
+//!
+//! This is used to visualize how often edges appear among enumerated solutions:
+//!
+//! ```
+//! use graph_solver::*;
+//!
+//! let mut g = Graph::new();
+//! let a = Node {color: 0, self_connected: false, edges: vec![Constraint {edge: 2, node: 0}]};
+//! for _ in 0..4 {g.push(a.clone())}
+//! let all: Vec<Graph> = g.solve_all(SearchSettings::new()).map(|s| s.puzzle).collect();
+//! let freq = solutions::frequency_graph(&all);
+//! assert_eq!(freq.count((0, 1), 2), 1);
+//! println!("{}", freq.graphviz("sfdp", &["white"], &["#ff0000"]));
+//! ```
+
+use std::fmt::Write;
+
+use super::*;
+
+/// Stores how often colored edges appear in a collection of solutions.
+///
+/// For more information, see `frequency_graph`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrequencyGraph {
+    /// The number of solutions.
+    pub solutions: usize,
+    /// The nodes of the first solution.
+    pub nodes: Vec<Node>,
+    /// The number of solutions per edge and color, sorted by edge and color.
+    ///
+    /// Edges are stored as `(i, j)` with `i <= j`.
+    /// Only colored edges that appear in some solution are included.
+    pub counts: Vec<((usize, usize), Color, usize)>,
+}
+
+impl FrequencyGraph {
+    /// Returns the number of solutions where an edge has a color.
+    pub fn count(&self, (i, j): (usize, usize), color: Color) -> usize {
+        let edge = (i.min(j), i.max(j));
+        match self.counts.binary_search_by_key(&(edge, color), |&(e, c, _)| (e, c)) {
+            Ok(k) => self.counts[k].2,
+            Err(_) => 0,
+        }
+    }
+
+    /// Returns the fraction of solutions where an edge has a color.
+    pub fn frequency(&self, edge: (usize, usize), color: Color) -> f64 {
+        if self.solutions == 0 {return 0.0};
+        self.count(edge, color) as f64 / self.solutions as f64
+    }
+
+    /// Generates a GraphViz dot format with edge opacity proportional to frequency.
+    ///
+    /// Edge colors must be hexadecimal RGB colors, e.g. `#ff0000`,
+    /// since opacity is added as an alpha channel.
+    /// An edge with several colors among the solutions is drawn once per color,
+    /// so the output is not a strict graph.
+    /// Every edge has a tooltip with the number of solutions.
+    pub fn graphviz(&self, layout: &str, node_colors: &[&str], edge_colors: &[&str]) -> String {
+        let mut s = String::new();
+        writeln!(&mut s, "// graph_solver dot {}", dot::DOT_VERSION).unwrap();
+        writeln!(&mut s, "graph {{").unwrap();
+        writeln!(&mut s, "  layout={}; edge[penwidth=4]", layout).unwrap();
+        for (i, node) in self.nodes.iter().enumerate() {
+            writeln!(&mut s, "  {}[regular=true,style=filled,fillcolor={}];", i,
+                     node_colors[node.color as usize % node_colors.len()]).unwrap();
+        }
+        for &((i, j), color, count) in &self.counts {
+            let alpha = (255 * count + self.solutions - 1) / self.solutions;
+            writeln!(&mut s, "  {} -- {}[color=\"{}{:02x}\",tooltip=\"{}/{}\"];", i, j,
+                     edge_colors[(color - 2) as usize % edge_colors.len()], alpha,
+                     count, self.solutions).unwrap();
+        }
+        writeln!(&mut s, "}}").unwrap();
+        s
+    }
+}
+
+/// Counts how often each colored edge appears in a collection of solutions.
+///
+/// The solutions must have the same nodes, e.g. from `Graph::solve_all`.
+///
+/// Panics if the solutions have different numbers of nodes.
+pub fn frequency_graph(solutions: &[Graph]) -> FrequencyGraph {
+    let nodes = solutions.first().map(|g| g.nodes.clone()).unwrap_or_default();
+    let n = nodes.len();
+    let mut counts = vec![];
+    for i in 0..n {
+        for j in i..n {
+            let mut colors = vec![];
+            for g in solutions {
+                assert_eq!(g.nodes.len(), n, "Solutions must have the same number of nodes");
+                let color = g.get((i, j));
+                if color >= 2 {colors.push(color)};
+            }
+            colors.sort();
+            for (k, &color) in colors.iter().enumerate() {
+                if k == 0 || colors[k - 1] != color {
+                    let count = colors[k..].iter().take_while(|&&c| c == color).count();
+                    counts.push(((i, j), color, count));
+                }
+            }
+        }
+    }
+    FrequencyGraph {solutions: solutions.len(), nodes, counts}
+}
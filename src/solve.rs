@@ -0,0 +1,1005 @@
+//! Solving and enumerating graph puzzles.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::*;
+use crate::restart::Rng;
+
+/// Stores settings for searching solutions with `Graph::solve_all` and related methods.
+///
+/// This extends `SolveSettings` of the
+/// [quickbacktrack](https://crates.io/crates/quickbacktrack) library,
+/// which is used by `Graph::solve`, with options that can be read back when searching.
+///
+/// Default settings:
+///
+/// - solve_simple: `true`
+/// - debug: `false`
+/// - difference: `false`
+/// - sleep_ms: `None`
+/// - max_iterations: `None`
+/// - encoding: `Encoding::EdgeColors`
+/// - deterministic: `false`
+/// - cancel: `None`
+/// - propagate: `true`
+/// - learn: `false`
+/// - constraint_stats: `false`
+/// - restart: `None`
+/// - seed: `None`
+/// - timeout_ms: `None`
+#[derive(Clone, Debug)]
+pub struct SearchSettings {
+    /// Whether to solve simple moves between each step.
+    pub solve_simple: bool,
+    /// Whether to debug by printing out to standard error.
+    pub debug: bool,
+    /// Whether to return the difference from initial puzzle.
+    pub difference: bool,
+    /// How many milliseconds to sleep between each step when debugging, if any.
+    pub sleep_ms: Option<u64>,
+    /// The maximum number of iterations before giving up, if any.
+    pub max_iterations: Option<u64>,
+    /// The variables that the solver branches on.
+    pub encoding: Encoding,
+    /// Whether to guarantee identical solutions across runs and platforms.
+    ///
+    /// This disables sleeping between steps,
+    /// and makes parallel solving return the solution of the first strategy
+    /// in the list that finds one, instead of the fastest strategy.
+    pub deterministic: bool,
+    /// A token for cancelling the search from another thread, if any.
+    pub cancel: Option<CancelToken>,
+    /// Whether to backtrack when some empty edge has no possible colors,
+    /// propagating simple moves to a fixed point when `solve_simple` is enabled.
+    ///
+    /// For more information, see `Graph::forward_check`.
+    pub propagate: bool,
+    /// Whether to record sets of branching moves that lead to dead ends,
+    /// and backtrack when a recorded set is repeated.
+    ///
+    /// Dead ends found by forward checking are minimized before they are recorded,
+    /// which requires `propagate`.
+    pub learn: bool,
+    /// Whether to count how often each constraint restricts the search.
+    ///
+    /// For more information, see `Solutions::constraint_stats`.
+    pub constraint_stats: bool,
+    /// When to restart the search before the first solution is found, if ever.
+    ///
+    /// For more information, see `RestartPolicy`.
+    pub restart: Option<RestartPolicy>,
+    /// A seed for randomizing the order of edges with the same number of possible colors,
+    /// and the order of colors, if any.
+    ///
+    /// The same seed gives the same order.
+    /// Edges are only randomized when branching on edge colors without a strategy.
+    pub seed: Option<u64>,
+    /// The maximum number of milliseconds of solving before giving up, if any.
+    ///
+    /// The time is measured from the creation of the solution iterator.
+    pub timeout_ms: Option<u64>,
+}
+
+impl Default for SearchSettings {
+    fn default() -> SearchSettings {SearchSettings::new()}
+}
+
+impl SearchSettings {
+    /// Creates new solve settings.
+    pub fn new() -> SearchSettings {
+        SearchSettings {
+            solve_simple: true,
+            debug: false,
+            difference: false,
+            sleep_ms: None,
+            max_iterations: None,
+            encoding: Encoding::EdgeColors,
+            deterministic: false,
+            cancel: None,
+            propagate: true,
+            learn: false,
+            constraint_stats: false,
+            restart: None,
+            seed: None,
+            timeout_ms: None,
+        }
+    }
+
+    /// Sets whether to solve simple moves between each step.
+    pub fn set_solve_simple(&mut self, val: bool) {
+        self.solve_simple = val;
+    }
+
+    /// Whether to solve simple moves between each step.
+    pub fn solve_simple(mut self, val: bool) -> Self {
+        self.set_solve_simple(val);
+        self
+    }
+
+    /// Sets whether to debug by printing out to standard error.
+    pub fn set_debug(&mut self, val: bool) {
+        self.debug = val;
+    }
+
+    /// Whether to debug by printing out to standard error.
+    pub fn debug(mut self, val: bool) -> Self {
+        self.set_debug(val);
+        self
+    }
+
+    /// Sets whether to return the difference from initial puzzle.
+    pub fn set_difference(&mut self, val: bool) {
+        self.difference = val;
+    }
+
+    /// Whether to return the difference from initial puzzle.
+    pub fn difference(mut self, val: bool) -> Self {
+        self.set_difference(val);
+        self
+    }
+
+    /// Sets how many milliseconds to sleep between each step, if any.
+    pub fn set_maybe_sleep_ms(&mut self, val: Option<u64>) {
+        self.sleep_ms = val;
+    }
+
+    /// Sets how many milliseconds to sleep between each step, if any.
+    pub fn maybe_sleep_ms(mut self, val: Option<u64>) -> Self {
+        self.set_maybe_sleep_ms(val);
+        self
+    }
+
+    /// Sets how many milliseconds to sleep between each step.
+    pub fn set_sleep_ms(&mut self, val: u64) {
+        self.sleep_ms = Some(val);
+    }
+
+    /// How many milliseconds to sleep between each step.
+    pub fn sleep_ms(mut self, val: u64) -> Self {
+        self.set_sleep_ms(val);
+        self
+    }
+
+    /// Sets the maximum number of iterations before giving up.
+    pub fn set_maybe_max_iterations(&mut self, val: Option<u64>) {
+        self.max_iterations = val;
+    }
+
+    /// The maximum number of iterations before giving up.
+    pub fn maybe_max_iterations(mut self, val: Option<u64>) -> Self {
+        self.set_maybe_max_iterations(val);
+        self
+    }
+
+    /// Sets the maximum number of iterations before giving up.
+    pub fn set_max_iterations(&mut self, val: u64) {
+        self.max_iterations = Some(val);
+    }
+
+    /// The maximum number of iterations before giving up.
+    pub fn max_iterations(mut self, val: u64) -> Self {
+        self.set_max_iterations(val);
+        self
+    }
+
+    /// Sets the variables that the solver branches on.
+    pub fn set_encoding(&mut self, val: Encoding) {
+        self.encoding = val;
+    }
+
+    /// The variables that the solver branches on.
+    pub fn encoding(mut self, val: Encoding) -> Self {
+        self.set_encoding(val);
+        self
+    }
+
+    /// Sets whether to guarantee identical solutions across runs and platforms.
+    pub fn set_deterministic(&mut self, val: bool) {
+        self.deterministic = val;
+    }
+
+    /// Whether to guarantee identical solutions across runs and platforms.
+    pub fn deterministic(mut self, val: bool) -> Self {
+        self.set_deterministic(val);
+        self
+    }
+
+    /// Sets a token for cancelling the search from another thread.
+    pub fn set_cancel(&mut self, val: CancelToken) {
+        self.cancel = Some(val);
+    }
+
+    /// A token for cancelling the search from another thread.
+    pub fn cancel(mut self, val: CancelToken) -> Self {
+        self.set_cancel(val);
+        self
+    }
+
+    /// Sets whether to propagate and forward check after each step.
+    pub fn set_propagate(&mut self, val: bool) {
+        self.propagate = val;
+    }
+
+    /// Whether to propagate and forward check after each step.
+    pub fn propagate(mut self, val: bool) -> Self {
+        self.set_propagate(val);
+        self
+    }
+
+    /// Sets whether to record and avoid dead ends.
+    pub fn set_learn(&mut self, val: bool) {
+        self.learn = val;
+    }
+
+    /// Whether to record and avoid dead ends.
+    pub fn learn(mut self, val: bool) -> Self {
+        self.set_learn(val);
+        self
+    }
+
+    /// Sets whether to count how often each constraint restricts the search.
+    pub fn set_constraint_stats(&mut self, val: bool) {
+        self.constraint_stats = val;
+    }
+
+    /// Whether to count how often each constraint restricts the search.
+    pub fn constraint_stats(mut self, val: bool) -> Self {
+        self.set_constraint_stats(val);
+        self
+    }
+
+    /// Sets when to restart the search.
+    ///
+    /// Panics if the factor of a geometric policy is not greater than `1`,
+    /// since the iteration limit would not grow and the search would not be complete.
+    pub fn set_restart(&mut self, val: RestartPolicy) {
+        if let RestartPolicy::Geometric {factor, ..} = val {
+            assert!(factor > 1.0, "Geometric restart factor must be greater than 1");
+        }
+        self.restart = Some(val);
+    }
+
+    /// When to restart the search.
+    pub fn restart(mut self, val: RestartPolicy) -> Self {
+        self.set_restart(val);
+        self
+    }
+
+    /// Sets a seed for randomizing the branching order.
+    pub fn set_seed(&mut self, val: u64) {
+        self.seed = Some(val);
+    }
+
+    /// A seed for randomizing the branching order.
+    pub fn seed(mut self, val: u64) -> Self {
+        self.set_seed(val);
+        self
+    }
+
+    /// Sets the maximum number of milliseconds of solving before giving up.
+    pub fn set_timeout_ms(&mut self, val: u64) {
+        self.timeout_ms = Some(val);
+    }
+
+    /// The maximum number of milliseconds of solving before giving up.
+    pub fn timeout_ms(mut self, val: u64) -> Self {
+        self.set_timeout_ms(val);
+        self
+    }
+}
+
+/// A token for cooperative cancellation of a search.
+///
+/// Clones of the token share the same state.
+/// The solver checks the token at every iteration.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Creates a new token that is not cancelled.
+    pub fn new() -> CancelToken {CancelToken::default()}
+
+    /// Cancels the search.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the search is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Chooses the edges and colors that the solver branches on.
+///
+/// This is implemented by `Strategy` for pairs of functions,
+/// and can be implemented by strategies that keep state between steps.
+pub trait BranchStrategy {
+    /// Picks the next edge to branch on.
+    ///
+    /// Returns `None` when no edge can be assigned.
+    fn choose_pos(&mut self, g: &Graph) -> Option<(usize, usize)>;
+    /// Returns the colors to try for an edge.
+    ///
+    /// The last color is tried first.
+    /// Colors that are not possible, see `Graph::colors`, lead to dead ends.
+    fn order_values(&mut self, g: &Graph, pos: (usize, usize)) -> Vec<Color>;
+}
+
+impl BranchStrategy for Strategy {
+    fn choose_pos(&mut self, g: &Graph) -> Option<(usize, usize)> {(self.pick)(g)}
+    fn order_values(&mut self, g: &Graph, pos: (usize, usize)) -> Vec<Color> {(self.colors)(g, pos)}
+}
+
+/// The variables that the solver branches on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Branch on the color of the edge with least possible colors.
+    ///
+    /// This uses the strategy `Graph::min_colors, Graph::colors`.
+    EdgeColors,
+    /// Branch on which neighbor satisfies the edge constraint
+    /// with least possible neighbors.
+    ///
+    /// This uses `Graph::min_slot` and fails early
+    /// when an edge constraint can not be satisfied.
+    /// Propagates better than edge colors for densely constrained graphs.
+    SlotMatching,
+    /// Branch on edge colors, with channeling to edge constraints.
+    ///
+    /// Edges that are the only possible neighbors for an edge constraint are assigned,
+    /// and the solver fails early when an edge constraint can not be satisfied.
+    /// For more information, see `Graph::channel_slots`.
+    Mixed,
+}
+
+impl From<SearchSettings> for quickbacktrack::SolveSettings {
+    fn from(settings: SearchSettings) -> quickbacktrack::SolveSettings {
+        quickbacktrack::SolveSettings::new()
+            .solve_simple(settings.solve_simple)
+            .debug(settings.debug)
+            .difference(settings.difference)
+            .maybe_sleep_ms(if settings.deterministic {None} else {settings.sleep_ms})
+            .maybe_max_iterations(settings.max_iterations)
+    }
+}
+
+/// The result of solving, with the reason when there is no solution.
+///
+/// For more information, see `Graph::try_solve`.
+pub enum SolveOutcome {
+    /// A solution was found.
+    Solved(Box<Solution<Graph>>),
+    /// The search completed without finding a solution.
+    NoSolution,
+    /// The maximum number of iterations was reached.
+    Exhausted,
+    /// The timeout was reached.
+    TimedOut,
+    /// The search was cancelled.
+    Cancelled,
+}
+
+impl SolveOutcome {
+    /// Returns the solution, if any.
+    pub fn solution(self) -> Option<Solution<Graph>> {
+        if let SolveOutcome::Solved(solution) = self {Some(*solution)} else {None}
+    }
+
+    /// Returns `true` if the search stopped before it was completed.
+    ///
+    /// Puzzles that stopped might have a solution.
+    pub fn is_stopped(&self) -> bool {
+        matches!(self, SolveOutcome::Exhausted | SolveOutcome::TimedOut | SolveOutcome::Cancelled)
+    }
+}
+
+/// The outcome of solving, with statistics of the search.
+///
+/// For more information, see `Graph::solve_report`.
+pub struct SolveReport {
+    /// The solution, or why there is no solution.
+    pub outcome: SolveOutcome,
+    /// Statistics of the search.
+    pub stats: Stats,
+}
+
+/// Called with the current partial assignment and the number of iterations.
+type ProgressCallback = (u64, Box<dyn FnMut(&Graph, u64) + Send>);
+
+/// A move sets the value of an edge.
+type Move = ((usize, usize), Color);
+
+/// Counts the colors of a palette that are not possible for an edge,
+/// by the constraint that removes them.
+fn count_prunings(
+    state: &Graph,
+    palette: &[Color],
+    stats: &mut BTreeMap<String, ConstraintStats>,
+    pos: (usize, usize),
+    possible: &[Color],
+) {
+    for color in palette {
+        if possible.contains(color) {continue};
+        if let Some((name, _)) = state.prune_reason(pos, *color) {
+            stats.entry(name).or_default().prunings += 1;
+        }
+    }
+}
+
+/// Lazy iterator over all solutions of a graph puzzle.
+///
+/// Continues back tracking after each solution,
+/// such that every graph satisfying the constraints is found once.
+pub struct Solutions {
+    original: Graph,
+    state: Graph,
+    prevs: Vec<((usize, usize), Color, bool)>,
+    choice: Vec<Vec<Move>>,
+    // The branching moves that explain the dead ends below each choice,
+    // or `None` when some dead end is not explained.
+    conflicts: Vec<Option<Vec<Move>>>,
+    nogoods: Vec<Vec<Move>>,
+    constraint_stats: BTreeMap<String, ConstraintStats>,
+    // The edge values that are counted as prunings.
+    palette: Vec<Color>,
+    rng: Option<Rng>,
+    // The number of restarts and the iteration of the last restart.
+    restarts: u64,
+    run_start: u64,
+    found: bool,
+    pub(crate) settings: SearchSettings,
+    iterations: u64,
+    resume: bool,
+    done: bool,
+    seen: std::collections::HashSet<Vec<Color>>,
+    strategy: Option<Box<dyn BranchStrategy + Send>>,
+    pub(crate) cancel: Option<CancelToken>,
+    pub(crate) counter: Option<Arc<std::sync::atomic::AtomicU64>>,
+    on_progress: Option<ProgressCallback>,
+    pub(crate) region: Option<Vec<bool>>,
+    cancelled: bool,
+    exhausted: bool,
+    timed_out: bool,
+    start: Instant,
+    stats: Stats,
+}
+
+impl Solutions {
+    /// Creates a new iterator over solutions.
+    pub fn new(mut graph: Graph, settings: SearchSettings) -> Solutions {
+        graph.prepare_symmetry_break();
+        graph.record_initial_domains();
+        if settings.debug {
+            for dead in graph.dead_constraints() {
+                eprintln!("Dead constraint: {}", dead);
+            }
+        }
+        let mut constraint_stats = BTreeMap::new();
+        let mut palette = vec![];
+        if settings.constraint_stats {
+            for name in graph.constraint_names() {
+                constraint_stats.insert(name, ConstraintStats::default());
+            }
+            palette.push(1);
+            palette.extend(graph.nodes.iter().flat_map(|node| node.edges.iter().map(|con| con.edge)));
+            palette.sort();
+            palette.dedup();
+        }
+        Solutions {
+            original: graph.clone(),
+            state: graph,
+            prevs: vec![],
+            choice: vec![],
+            conflicts: vec![],
+            nogoods: vec![],
+            constraint_stats,
+            palette,
+            rng: settings.seed.map(Rng::new),
+            restarts: 0,
+            run_start: 0,
+            found: false,
+            settings,
+            iterations: 0,
+            resume: false,
+            done: false,
+            seen: std::collections::HashSet::new(),
+            strategy: None,
+            cancel: None,
+            counter: None,
+            on_progress: None,
+            region: None,
+            cancelled: false,
+            exhausted: false,
+            timed_out: false,
+            start: Instant::now(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// Creates a new iterator over solutions using a strategy.
+    ///
+    /// The strategy overrides the branching variables of the encoding.
+    pub fn with_strategy(graph: Graph, settings: SearchSettings, strategy: Strategy) -> Solutions {
+        Solutions::with_branch_strategy(graph, settings, Box::new(strategy))
+    }
+
+    /// Creates a new iterator over solutions using a branching strategy.
+    ///
+    /// The strategy overrides the branching variables of the encoding.
+    pub fn with_branch_strategy(
+        graph: Graph,
+        settings: SearchSettings,
+        strategy: Box<dyn BranchStrategy + Send>
+    ) -> Solutions {
+        let mut solutions = Solutions::new(graph, settings);
+        solutions.strategy = Some(strategy);
+        solutions
+    }
+
+    /// Calls a function with the current partial assignment and the number of iterations,
+    /// every `every` iterations.
+    ///
+    /// This is useful for displaying live status, e.g. with `Graph::progress`,
+    /// instead of printing with `SearchSettings::debug`.
+    pub fn on_progress<F>(mut self, every: u64, f: F) -> Solutions
+        where F: FnMut(&Graph, u64) + Send + 'static
+    {
+        self.on_progress = Some((every.max(1), Box::new(f)));
+        self
+    }
+
+    /// Returns the number of iterations so far.
+    pub fn iterations(&self) -> u64 {self.iterations}
+
+    /// Returns statistics of the search so far.
+    ///
+    /// The elapsed time only includes time spent searching for solutions.
+    pub fn stats(&self) -> Stats {
+        Stats {iterations: self.iterations, ..self.stats}
+    }
+
+    /// Returns the number of restarts so far.
+    ///
+    /// For more information, see `SearchSettings::restart`.
+    pub fn restarts(&self) -> u64 {self.restarts}
+
+    /// Returns `true` if the search was stopped by cancellation.
+    pub fn cancelled(&self) -> bool {self.cancelled}
+
+    /// Returns `true` if the search was stopped by the maximum number of iterations.
+    pub fn exhausted(&self) -> bool {self.exhausted}
+
+    /// Returns `true` if the search was stopped by the timeout.
+    pub fn timed_out(&self) -> bool {self.timed_out}
+
+    /// Returns the next solution, or why there are no more solutions.
+    pub fn next_outcome(&mut self) -> SolveOutcome {
+        match self.next() {
+            Some(solution) => SolveOutcome::Solved(Box::new(solution)),
+            None if self.cancelled => SolveOutcome::Cancelled,
+            None if self.timed_out => SolveOutcome::TimedOut,
+            None if self.exhausted => SolveOutcome::Exhausted,
+            None => SolveOutcome::NoSolution,
+        }
+    }
+
+    /// Returns the progress of the current partial assignment.
+    ///
+    /// For more information, see `Graph::progress`.
+    pub fn progress(&self) -> f64 {self.state.progress()}
+
+    /// Returns the recorded sets of moves that lead to dead ends.
+    ///
+    /// Nogoods are only recorded when learning is enabled, see `SearchSettings::learn`.
+    pub fn nogoods(&self) -> &[Vec<((usize, usize), Color)>] {&self.nogoods}
+
+    /// Returns how often each constraint restricted the search so far, by name.
+    ///
+    /// Every active constraint is included, such that constraints that never
+    /// restricted the search can be found.
+    /// Dead ends caused by recorded nogoods are counted as `nogoods`.
+    /// Statistics are only counted when enabled, see `SearchSettings::constraint_stats`.
+    /// For names of constraints, see `Graph::constraint_names`.
+    pub fn constraint_stats(&self) -> &BTreeMap<String, ConstraintStats> {&self.constraint_stats}
+
+    /// Undoes moves until a new choice is made.
+    ///
+    /// Returns `false` when there are no more possible choices.
+    fn backtrack(&mut self, failed: Option<(usize, usize)>, mut explanation: Option<Vec<Move>>) -> bool {
+        loop {
+            let mut possible = match self.choice.pop() {
+                None => {
+                    if self.settings.debug {
+                        eprintln!("No more possible choices");
+                    }
+                    return false;
+                }
+                Some(x) => x,
+            };
+            // The dead end is explained by the branching moves above this choice.
+            let decision = self.prevs.iter().rev().find(|p| !p.2).map(|p| (p.0, self.state.get(p.0)));
+            let conflict = match (self.conflicts.pop().unwrap_or(None), explanation.take()) {
+                (Some(mut conflict), Some(explanation)) => {
+                    for m in explanation {
+                        if Some(m) != decision && !conflict.contains(&m) {conflict.push(m)}
+                    }
+                    Some(conflict)
+                }
+                _ => None,
+            };
+            if let Some((pos, new_val)) = possible.pop() {
+                // Try next choice.
+                while let Some((old_pos, old_val, simple)) = self.prevs.pop() {
+                    self.state.set(old_pos, old_val);
+                    if !simple {break}
+                }
+                self.prevs.push((pos, self.state.get(pos), false));
+                self.state.set(pos, new_val);
+                self.state.set_provenance(pos, Provenance::Branched);
+                self.stats.assignments += 1;
+                self.choice.push(possible);
+                self.conflicts.push(conflict);
+                if self.settings.debug {
+                    eprintln!("Try   {:?}, {:?} depth ch: {}, prev: {} (failed at {:?})",
+                        pos, new_val, self.choice.len(), self.prevs.len(), failed);
+                }
+                return true;
+            } else {
+                let mut undo = false;
+                while let Some((old_pos, old_val, simple)) = self.prevs.pop() {
+                    self.state.set(old_pos, old_val);
+                    undo = true;
+                    if !simple {break}
+                }
+                // Every choice failed, so the moves above it lead to a dead end.
+                if let Some(conflict) = &conflict {
+                    if !self.nogoods.contains(conflict) {self.nogoods.push(conflict.clone())}
+                }
+                explanation = conflict;
+                if !undo {return false}
+            }
+        }
+    }
+}
+
+impl Solutions {
+    /// Assigns edges with a single possible color as simple moves until a fixed point,
+    /// when solving simple moves is enabled.
+    ///
+    /// Returns `false` when some empty edge has no possible colors.
+    /// With a region, only edges incident to the region are checked.
+    fn propagate(&mut self) -> bool {
+        let prevs = &mut self.prevs;
+        let stats = &mut self.constraint_stats;
+        let assignments = &mut self.stats.assignments;
+        let palette = &self.palette;
+        let count = self.settings.constraint_stats;
+        self.state.propagate_with(self.region.as_deref(), self.settings.solve_simple, |state, pos, val| {
+            if count {count_prunings(state, palette, stats, pos, &[val])};
+            *assignments += 1;
+            prevs.push((pos, state.get(pos), true));
+            state.set(pos, val);
+            state.set_provenance(pos, Provenance::Propagated);
+        })
+    }
+
+    /// Restarts the search from the initial graph.
+    ///
+    /// Recorded nogoods are kept, since they hold for every completion of the initial graph.
+    fn restart(&mut self) {
+        if self.settings.debug {
+            eprintln!("Restart after {} iterations", self.iterations - self.run_start);
+        }
+        while let Some((pos, old, _)) = self.prevs.pop() {
+            self.state.set(pos, old);
+        }
+        self.choice.clear();
+        self.conflicts.clear();
+        self.restarts += 1;
+        self.run_start = self.iterations;
+    }
+
+    /// Returns the branching moves of the current state.
+    fn decisions(&self) -> Vec<Move> {
+        self.prevs.iter().filter(|p| !p.2).map(|p| (p.0, self.state.get(p.0))).collect()
+    }
+
+    /// Returns a recorded nogood that is contained in the current state, if any.
+    fn violated_nogood(&self) -> Option<Vec<Move>> {
+        self.nogoods.iter().find(|nogood| nogood.iter().all(|&(pos, val)| self.state.get(pos) == val)).cloned()
+    }
+
+    /// Returns a minimal subset of the branching moves for which forward checking fails.
+    ///
+    /// Moves are removed one at a time while the remaining moves still fail,
+    /// replaying them on the original graph.
+    fn minimize_conflict(&self) -> Vec<Move> {
+        let mask = self.region.as_deref();
+        let solve_simple = self.settings.solve_simple;
+        let fails = |moves: &[Move]| {
+            let mut g = self.original.clone();
+            for &(pos, val) in moves {
+                match g.get(pos) {
+                    0 => g.set(pos, val),
+                    old if old != val => return true,
+                    _ => {}
+                }
+            }
+            !g.propagate_with(mask, solve_simple, |g, pos, val| g.set(pos, val))
+        };
+        let mut conflict = self.decisions();
+        if !fails(&conflict) {return conflict};
+        let mut k = 0;
+        while k < conflict.len() {
+            let mut smaller = conflict.clone();
+            smaller.remove(k);
+            if fails(&smaller) {conflict = smaller} else {k += 1}
+        }
+        conflict
+    }
+
+    /// Assigns edges forced by edge constraints as simple moves.
+    ///
+    /// Returns `false` when some edge constraint can not be satisfied.
+    fn channel(&mut self) -> bool {
+        loop {
+            match self.state.channel_slots() {
+                None => return false,
+                Some(forced) => {
+                    if forced.is_empty() {return true};
+                    for (pos, val) in forced {
+                        self.stats.assignments += 1;
+                        self.prevs.push((pos, self.state.get(pos), true));
+                        self.state.set(pos, val);
+                        self.state.set_provenance(pos, Provenance::Propagated);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the failed position, if any, and a list of alternative moves.
+    ///
+    /// The last move in the list has highest priority.
+    fn alternatives(&mut self) -> (Option<(usize, usize)>, Vec<Move>) {
+        let (empty, mut possible) = self.ordered_alternatives();
+        if let Some(rng) = &mut self.rng {rng.shuffle(&mut possible)};
+        (empty, possible)
+    }
+
+    /// Returns the failed position, if any, and a list of alternative moves,
+    /// before randomizing the order of moves.
+    fn ordered_alternatives(&mut self) -> (Option<(usize, usize)>, Vec<Move>) {
+        if let Some(mask) = &self.region {
+            let empty = self.state.min_colors_in(mask);
+            return match empty {
+                None => (None, vec![]),
+                Some(x) => (empty, self.state.colors(x).into_iter().map(|c| (x, c)).collect()),
+            };
+        }
+        if let Some(strategy) = &mut self.strategy {
+            let empty = strategy.choose_pos(&self.state);
+            return match empty {
+                None => (None, vec![]),
+                Some(x) => (empty, strategy.order_values(&self.state, x).into_iter().map(|c| (x, c)).collect()),
+            };
+        }
+        match self.settings.encoding {
+            Encoding::EdgeColors | Encoding::Mixed => {
+                let empty = match &mut self.rng {
+                    None => self.state.min_colors(),
+                    Some(rng) => self.state.min_colors_random(rng),
+                };
+                match empty {
+                    None => (None, vec![]),
+                    Some(x) => (empty, self.state.colors(x).into_iter().map(|c| (x, c)).collect()),
+                }
+            }
+            Encoding::SlotMatching => {
+                match self.state.min_slot() {
+                    None => (None, vec![]),
+                    Some((i, con, candidates)) => {
+                        (None, candidates.into_iter().rev().map(|j| ((i, j), con.edge)).collect())
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Solutions {
+    /// Searches for the next solution.
+    fn search(&mut self) -> Option<Solution<Graph>> {
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        if self.done {return None};
+        if self.resume {
+            self.resume = false;
+            if !self.backtrack(None, None) {
+                self.done = true;
+                return None;
+            }
+        }
+        loop {
+            if let Some(policy) = self.settings.restart {
+                if !self.found && self.iterations - self.run_start >= policy.limit(self.restarts) {
+                    self.restart();
+                }
+            }
+            if self.settings.debug && !self.settings.deterministic {
+                if let Some(ms) = self.settings.sleep_ms {
+                    sleep(Duration::from_millis(ms));
+                }
+            }
+            let consistent = if self.settings.propagate {
+                self.propagate()
+            } else {
+                if self.settings.solve_simple {
+                    let prevs = &mut self.prevs;
+                    let assignments = &mut self.stats.assignments;
+                    self.state.solve_simple(|state, pos, val| {
+                        *assignments += 1;
+                        prevs.push((pos, state.get(pos), true));
+                        state.set(pos, val);
+                        state.set_provenance(pos, Provenance::Propagated);
+                    });
+                }
+                true
+            };
+            let violated = if self.settings.learn && consistent {self.violated_nogood()} else {None};
+            let channeled = consistent && violated.is_none() &&
+                (self.settings.encoding != Encoding::Mixed || self.channel());
+            if self.settings.debug {
+                self.state.print();
+            }
+            self.iterations += 1;
+            if let Some(counter) = &self.counter {
+                counter.store(self.iterations, Ordering::SeqCst);
+            }
+            if let Some((every, f)) = &mut self.on_progress {
+                if self.iterations % *every == 0 {f(&self.state, self.iterations)};
+            }
+            if let Some(max_iterations) = self.settings.max_iterations {
+                if self.iterations > max_iterations {
+                    self.exhausted = true;
+                    self.done = true;
+                    return None;
+                }
+            }
+            if let Some(ms) = self.settings.timeout_ms {
+                if self.start.elapsed() >= Duration::from_millis(ms) {
+                    if self.settings.debug {
+                        eprintln!("Timed out! Iterations: {}", self.iterations);
+                    }
+                    self.timed_out = true;
+                    self.done = true;
+                    return None;
+                }
+            }
+            if self.cancel.iter().chain(self.settings.cancel.iter()).any(|c| c.is_cancelled()) {
+                if self.settings.debug {
+                    eprintln!("Cancelled! Iterations: {}", self.iterations);
+                }
+                self.cancelled = true;
+                self.done = true;
+                return None;
+            }
+            let solved = channeled && match &self.region {
+                None => self.state.is_solved(),
+                Some(mask) => self.state.mask_solved(mask),
+            };
+            // Different slot choices can lead to the same graph.
+            let duplicate = solved && self.settings.encoding == Encoding::SlotMatching &&
+                !self.seen.insert(self.state.edges.clone());
+            if solved && !duplicate {
+                if self.settings.debug {
+                    eprintln!("Solved! Iterations: {}", self.iterations);
+                }
+                #[cfg(feature = "verify-solutions")]
+                if self.region.as_ref().map(|mask| mask.iter().all(|&b| b)).unwrap_or(true) {
+                    reference::verify(&self.state, &self.original);
+                }
+                let mut puzzle = self.state.clone();
+                if self.settings.difference {
+                    puzzle.remove(&self.original);
+                }
+                self.resume = true;
+                self.found = true;
+                return Some(Solution {puzzle, iterations: self.iterations, strategy: None});
+            }
+
+            let (empty, mut possible) = if channeled {self.alternatives()} else {(None, vec![])};
+            if self.settings.constraint_stats {
+                if let Some(pos) = empty {
+                    let colors: Vec<Color> = possible.iter().map(|&(_, c)| c).collect();
+                    count_prunings(&self.state, &self.palette, &mut self.constraint_stats, pos, &colors);
+                }
+            }
+            if let Some((empty, v)) = possible.pop() {
+                // Put in the first guess.
+                self.prevs.push((empty, self.state.get(empty), false));
+                self.state.set(empty, v);
+                self.state.set_provenance(empty, Provenance::Branched);
+                self.stats.assignments += 1;
+                self.choice.push(possible);
+                self.stats.max_depth = self.stats.max_depth.max(self.choice.len());
+                self.conflicts.push(if self.settings.learn {Some(vec![])} else {None});
+                if self.settings.debug {
+                    eprintln!("Guess {:?}, {:?} depth {} {}",
+                        empty, v, self.choice.len(), self.prevs.len());
+                }
+            } else {
+                self.stats.backtracks += 1;
+                if !consistent {self.stats.wipeouts += 1};
+                if self.settings.constraint_stats && !duplicate {
+                    let name = if violated.is_some() {"nogoods".into()}
+                        else if consistent && !channeled {"nodes".into()}
+                        else {self.state.failure_reason(self.region.as_deref())};
+                    self.constraint_stats.entry(name).or_default().failures += 1;
+                }
+                let explanation = if !self.settings.learn || duplicate {None}
+                    else if violated.is_some() {violated}
+                    else if !consistent {Some(self.minimize_conflict())}
+                    else {Some(self.decisions())};
+                if !self.backtrack(empty, explanation) {
+                    self.done = true;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for Solutions {
+    type Item = Solution<Graph>;
+
+    fn next(&mut self) -> Option<Solution<Graph>> {
+        let start = Instant::now();
+        let res = self.search();
+        self.stats.elapsed += start.elapsed();
+        res
+    }
+}
+
+impl Graph {
+    /// Solves the graph puzzle using default strategy,
+    /// reporting why there is no solution.
+    ///
+    /// Unlike `Graph::solve`, this distinguishes puzzles without solution
+    /// from puzzles where the search stopped, e.g. by `SearchSettings::max_iterations`
+    /// or `SearchSettings::timeout_ms`.
+    pub fn try_solve(self, solve_settings: SearchSettings) -> SolveOutcome {
+        self.solve_all(solve_settings).next_outcome()
+    }
+
+    /// Solves the graph puzzle using default strategy,
+    /// reporting why there is no solution together with statistics of the search.
+    ///
+    /// For more information, see `Graph::try_solve` and `Stats`.
+    pub fn solve_report(self, solve_settings: SearchSettings) -> SolveReport {
+        let mut solutions = self.solve_all(solve_settings);
+        let outcome = solutions.next_outcome();
+        SolveReport {outcome, stats: solutions.stats()}
+    }
+
+    /// Solves the graph puzzle using a branching strategy.
+    pub fn solve_with(
+        self,
+        strategy: Box<dyn BranchStrategy + Send>,
+        solve_settings: SearchSettings
+    ) -> Option<Solution<Graph>> {
+        Solutions::with_branch_strategy(self, solve_settings, strategy).next()
+    }
+
+    /// Returns a lazy iterator over all solutions, using default strategy.
+    ///
+    /// The default strategy is `Graph::min_colors, Graph::colors`.
+    pub fn solve_all(self, solve_settings: SearchSettings) -> Solutions {
+        Solutions::new(self, solve_settings)
+    }
+}
@@ -0,0 +1,402 @@
+//! Canonical, versioned text format of puzzles.
+
+use super::*;
+
+/// The version of the text format generated by `Graph::to_spec_string`.
+pub const SPEC_VERSION: u32 = 1;
+
+const HEADER: &str = "graph_solver spec";
+
+impl Graph {
+    /// Generates the canonical text format of the puzzle.
+    ///
+    /// The text starts with a header with the format version, see `SPEC_VERSION`.
+    /// Equal puzzles give equal text.
+    /// For more information, see `Graph::from_spec_string`.
+    pub fn to_spec_string(&self) -> String {
+        let mut s = format!("{} {}\n", HEADER, SPEC_VERSION);
+        write_spec(self, &mut s, "");
+        s
+    }
+
+    /// Parses the text format of a puzzle.
+    ///
+    /// The format is line based, where every line is a keyword followed by numbers:
+    ///
+    /// ```text
+    /// graph_solver spec 1
+    /// node 0 2:0 2:0
+    /// node 0 self 2:0 2:0
+    /// edge 0 1 2
+    /// pair 0 1
+    /// anti_pair 0 2
+    /// restrict 0 1 1 3
+    /// cardinality 1 2 0,1 0,2
+    /// degree 0 1 2
+    /// color_count 2 0 3
+    /// no_triangles
+    /// min_girth 5
+    /// commute_quad
+    /// sign_pair 2 5
+    /// quad_rule 2 4 anticommute
+    /// quad_nodes 0 1 2 3
+    /// label 0 5
+    /// name 0 "a \"b\""
+    /// bipartition 0 1
+    /// conditional 2 connected
+    /// component size 1 4
+    /// symmetric_difference 0 1 2
+    /// edit_distance 1 0,1,2 1,2,2
+    /// priority 0 1 3
+    /// subgraph
+    ///   node 0
+    /// end
+    /// ```
+    ///
+    /// Nodes are `node <color> [self] <edge>:<node>...`, where `self` allows self-loops.
+    /// Edges are pre-set edge values using sorted indices, and empty edges are omitted.
+    /// Flags are `no_triangles`, `planar`, `bipartite`, `meet_quad`, `connected`,
+    /// `no_isolated_nodes`, `commute_quad`, `anticommute_quad`, `symmetry_break`
+    /// and `color_symmetry_break`.
+    /// Component constraints are `component regular`, `component bipartite`
+    /// and `component size <min> <max>`.
+    /// Names are quoted strings with the escapes of JSON.
+    /// A required subgraph is a nested specification between `subgraph` and `end`.
+    /// Empty lines and lines starting with `#` are ignored.
+    ///
+    /// Text generated by `Graph::to_spec_string` is canonical:
+    /// equal puzzles give equal text, with lines in the order above.
+    ///
+    /// The text must start with the header `graph_solver spec <version>`,
+    /// where the version is at most `SPEC_VERSION`.
+    /// Text generated by any earlier version of the crate can be read,
+    /// such that archived puzzles stay loadable.
+    /// Subgraphs nested deeper than 64 levels are reported as errors.
+    pub fn from_spec_string(text: &str) -> Result<Graph, ParseError> {
+        let mut lines = text.lines().enumerate()
+            .map(|(k, line)| (k + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        match lines.next() {
+            Some((line, header)) => {
+                let version = header.strip_prefix(HEADER).map(|v| v.trim().parse::<u32>());
+                match version {
+                    Some(Ok(version)) if (1..=SPEC_VERSION).contains(&version) => {}
+                    Some(Ok(version)) => return Err(ParseError {
+                        line, message: format!("Unsupported format version {}", version)
+                    }),
+                    _ => return Err(ParseError {line, message: format!("Expected `{} <version>`", HEADER)}),
+                }
+            }
+            None => return Err(ParseError {line: 1, message: format!("Expected `{} <version>`", HEADER)}),
+        }
+        let (g, end) = read_spec(&mut lines, 0)?;
+        if let Some(line) = end {
+            return Err(ParseError {line, message: "Unexpected `end`".into()});
+        }
+        Ok(g)
+    }
+}
+
+fn write_spec(g: &Graph, s: &mut String, indent: &str) {
+    use std::fmt::Write;
+
+    let n = g.nodes.len();
+    for node in &g.nodes {
+        write!(s, "{}node {}", indent, node.color).unwrap();
+        if node.self_connected {s.push_str(" self")};
+        for con in &node.edges {write!(s, " {}:{}", con.edge, con.node).unwrap()};
+        s.push('\n');
+    }
+    for i in 0..n {
+        for j in i..n {
+            let val = g.get((i, j));
+            if val != 0 {writeln!(s, "{}edge {} {} {}", indent, i, j, val).unwrap()};
+        }
+    }
+    for &(i, j) in &g.pairs {writeln!(s, "{}pair {} {}", indent, i, j).unwrap()};
+    for &(i, j) in &g.anti_pairs {writeln!(s, "{}anti_pair {} {}", indent, i, j).unwrap()};
+    for ((i, j), allowed) in &g.restrictions {
+        write!(s, "{}restrict {} {}", indent, i, j).unwrap();
+        for c in allowed {write!(s, " {}", c).unwrap()};
+        s.push('\n');
+    }
+    for card in &g.cardinalities {
+        write!(s, "{}cardinality {} {}", indent, card.min, card.max).unwrap();
+        for &(i, j) in &card.edges {write!(s, " {},{}", i, j).unwrap()};
+        s.push('\n');
+    }
+    for deg in &g.degrees {writeln!(s, "{}degree {} {} {}", indent, deg.node, deg.min, deg.max).unwrap()};
+    for cc in &g.color_counts {writeln!(s, "{}color_count {} {} {}", indent, cc.color, cc.min, cc.max).unwrap()};
+    let flags = [
+        ("no_triangles", g.no_triangles),
+        ("planar", g.planar),
+        ("bipartite", g.bipartite),
+        ("meet_quad", g.meet_quad),
+        ("connected", g.connected),
+        ("no_isolated_nodes", g.no_isolated_nodes),
+        ("commute_quad", g.commute_quad == Some(true)),
+        ("anticommute_quad", g.commute_quad == Some(false)),
+        ("symmetry_break", g.symmetry_break),
+        ("color_symmetry_break", g.color_symmetry_break),
+    ];
+    for &(name, val) in &flags {
+        if val {writeln!(s, "{}{}", indent, name).unwrap()};
+    }
+    for &(a, b) in &g.sign_pairs {writeln!(s, "{}sign_pair {} {}", indent, a, b).unwrap()};
+    for rule in &g.quad_rules {
+        let kind = match rule.commute {
+            Some(true) => "commute",
+            Some(false) => "anticommute",
+            None => "free",
+        };
+        writeln!(s, "{}quad_rule {} {} {}", indent, rule.colors.0, rule.colors.1, kind).unwrap();
+    }
+    if let Some(nodes) = &g.quad_nodes {
+        write!(s, "{}quad_nodes", indent).unwrap();
+        for i in nodes {write!(s, " {}", i).unwrap()};
+        s.push('\n');
+    }
+    if let Some(x) = g.min_girth {writeln!(s, "{}min_girth {}", indent, x).unwrap()};
+    if let Some(x) = g.max_local_girth {writeln!(s, "{}max_local_girth {}", indent, x).unwrap()};
+    for (i, label) in g.labels.iter().enumerate() {
+        if let Some(x) = label {writeln!(s, "{}label {} {}", indent, i, x).unwrap()};
+    }
+    for (i, name) in g.names.iter().enumerate() {
+        if let Some(x) = name {writeln!(s, "{}name {} \"{}\"", indent, i, json::escape(x)).unwrap()};
+    }
+    if let Some((a, b)) = g.bipartition {writeln!(s, "{}bipartition {} {}", indent, a, b).unwrap()};
+    for &(color, flag) in &g.conditionals {
+        writeln!(s, "{}conditional {} {}", indent, color, json::flag_name(flag)).unwrap();
+    }
+    for c in &g.component_constraints {
+        match *c {
+            ComponentConstraint::Regular => writeln!(s, "{}component regular", indent).unwrap(),
+            ComponentConstraint::Bipartite => writeln!(s, "{}component bipartite", indent).unwrap(),
+            ComponentConstraint::Size {min, max} =>
+                writeln!(s, "{}component size {} {}", indent, min, max).unwrap(),
+        }
+    }
+    for d in &g.symmetric_differences {
+        writeln!(s, "{}symmetric_difference {} {} {}", indent, d.nodes.0, d.nodes.1, d.size).unwrap();
+    }
+    for d in &g.edit_distances {
+        write!(s, "{}edit_distance {}", indent, d.max).unwrap();
+        for &((i, j), c) in &d.edges {write!(s, " {},{},{}", i, j, c).unwrap()};
+        s.push('\n');
+    }
+    for pattern in &g.required_subgraphs {
+        writeln!(s, "{}subgraph", indent).unwrap();
+        write_spec(pattern, s, &format!("{}  ", indent));
+        writeln!(s, "{}end", indent).unwrap();
+    }
+    for i in 0..n {
+        for j in i..n {
+            let p = g.priority((i, j));
+            if p != 0 {writeln!(s, "{}priority {} {} {}", indent, i, j, p).unwrap()};
+        }
+    }
+}
+
+/// Reads a specification until the end of the text or an `end` line.
+///
+/// Returns the line of `end`, if any.
+fn read_spec<'a, I>(lines: &mut I, depth: usize) -> Result<(Graph, Option<usize>), ParseError>
+    where I: Iterator<Item = (usize, &'a str)>
+{
+    let mut g = Graph::new();
+    while let Some((line, text)) = lines.next() {
+        let err = |message: &str| Err(ParseError {line, message: message.into()});
+        let mut words = text.split_whitespace();
+        let keyword = words.next().unwrap_or("");
+        let args: Vec<&str> = words.collect();
+        let numbers = |count: usize| -> Result<Vec<u64>, ParseError> {
+            if args.len() != count {
+                return Err(ParseError {line, message: format!("Expected {} numbers after `{}`", count, keyword)});
+            }
+            args.iter().map(|x| x.parse::<u64>().map_err(|_| ParseError {
+                line, message: format!("Expected number, found `{}`", x)
+            })).collect()
+        };
+        let n = g.nodes.len();
+        let node = |i: u64| -> Result<usize, ParseError> {
+            if (i as usize) < n {Ok(i as usize)}
+            else {Err(ParseError {line, message: format!("Node index {} out of range", i)})}
+        };
+        match keyword {
+            "node" => {
+                let mut args = args.iter();
+                let color = match args.next().map(|x| x.parse::<Color>()) {
+                    Some(Ok(color)) => color,
+                    _ => return err("Expected node color"),
+                };
+                let mut args = args.peekable();
+                let self_connected = args.peek() == Some(&&"self");
+                if self_connected {args.next();}
+                let mut edges = vec![];
+                for arg in args {
+                    let con = arg.split_once(':')
+                        .and_then(|(edge, node)| Some(Constraint {edge: edge.parse().ok()?, node: node.parse().ok()?}));
+                    match con {
+                        Some(con) => edges.push(con),
+                        None => return err(&format!("Expected `<edge>:<node>`, found `{}`", arg)),
+                    }
+                }
+                g.push(Node {color, self_connected, edges});
+            }
+            "edge" => {
+                let x = numbers(3)?;
+                g.set((node(x[0])?, node(x[1])?), x[2]);
+            }
+            "pair" => {
+                let x = numbers(2)?;
+                g.push_pair((node(x[0])?, node(x[1])?));
+            }
+            "anti_pair" => {
+                let x = numbers(2)?;
+                g.push_anti_pair((node(x[0])?, node(x[1])?));
+            }
+            "restrict" => {
+                if args.len() < 2 {return err("Expected edge after `restrict`")};
+                let x = numbers(args.len())?;
+                g.restrict((node(x[0])?, node(x[1])?), &x[2..]);
+            }
+            "cardinality" => {
+                if args.len() < 2 {return err("Expected minimum and maximum after `cardinality`")};
+                let (min, max) = match (args[0].parse(), args[1].parse()) {
+                    (Ok(min), Ok(max)) => (min, max),
+                    _ => return err("Expected minimum and maximum after `cardinality`"),
+                };
+                let mut edges = vec![];
+                for arg in &args[2..] {
+                    let pos = arg.split_once(',')
+                        .and_then(|(i, j)| Some((i.parse::<u64>().ok()?, j.parse::<u64>().ok()?)));
+                    match pos {
+                        Some((i, j)) => edges.push((node(i)?, node(j)?)),
+                        None => return err(&format!("Expected `<i>,<j>`, found `{}`", arg)),
+                    }
+                }
+                g.push_cardinality(&edges, min, max);
+            }
+            "degree" => {
+                let x = numbers(3)?;
+                g.push_degree(node(x[0])?, x[1] as usize, x[2] as usize);
+            }
+            "color_count" => {
+                let x = numbers(3)?;
+                g.push_color_count(x[0], x[1] as usize, x[2] as usize);
+            }
+            "no_triangles" => {numbers(0)?; g.no_triangles = true}
+            "planar" => {numbers(0)?; g.planar = true}
+            "bipartite" => {numbers(0)?; g.bipartite = true}
+            "meet_quad" => {numbers(0)?; g.meet_quad = true}
+            "connected" => {numbers(0)?; g.connected = true}
+            "no_isolated_nodes" => {numbers(0)?; g.no_isolated_nodes = true}
+            "commute_quad" => {numbers(0)?; g.commute_quad = Some(true)}
+            "anticommute_quad" => {numbers(0)?; g.commute_quad = Some(false)}
+            "symmetry_break" => {numbers(0)?; g.symmetry_break = true}
+            "color_symmetry_break" => {numbers(0)?; g.color_symmetry_break = true}
+            "min_girth" => g.min_girth = Some(numbers(1)?[0] as usize),
+            "max_local_girth" => g.max_local_girth = Some(numbers(1)?[0] as usize),
+            "label" => {
+                let x = numbers(2)?;
+                g.set_label(node(x[0])?, x[1]);
+            }
+            "name" => {
+                let rest = text[keyword.len()..].trim_start();
+                let (i, name) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                match (i.parse(), json::unquote(name.trim_start())) {
+                    (Ok(i), Some(name)) => g.names[node(i)?] = Some(name),
+                    _ => return err("Expected `name <i> \"<name>\"`"),
+                }
+            }
+            "quad_rule" => {
+                let commute = match args.get(2) {
+                    Some(&"commute") => Some(true),
+                    Some(&"anticommute") => Some(false),
+                    Some(&"free") => None,
+                    _ => return err("Expected `quad_rule <color> <color> commute|anticommute|free`"),
+                };
+                match (args.len(), args[0].parse::<Color>(), args[1].parse::<Color>()) {
+                    (3, Ok(a), Ok(b)) => g.push_quad_rule(a, b, commute),
+                    _ => return err("Expected `quad_rule <color> <color> commute|anticommute|free`"),
+                }
+            }
+            "quad_nodes" => {
+                let nodes = numbers(args.len())?.into_iter().map(node).collect::<Result<Vec<_>, _>>()?;
+                g.restrict_quads(&nodes);
+            }
+            "sign_pair" => {
+                let x = numbers(2)?;
+                g.push_sign_pair(x[0], x[1]);
+            }
+            "bipartition" => {
+                let x = numbers(2)?;
+                g.bipartition_by_color(x[0], x[1]);
+            }
+            "conditional" => {
+                let flag = args.get(1).and_then(|name| json::flag_by_name(name));
+                match (args.len(), args[..].first().map(|x| x.parse::<Color>()), flag) {
+                    (2, Some(Ok(color)), Some(flag)) => g.push_conditional(color, flag),
+                    _ => return err("Expected `conditional <color> <flag>`"),
+                }
+            }
+            "component" => {
+                let constraint = match args[..] {
+                    ["regular"] => ComponentConstraint::Regular,
+                    ["bipartite"] => ComponentConstraint::Bipartite,
+                    ["size", min, max] => match (min.parse(), max.parse()) {
+                        (Ok(min), Ok(max)) => ComponentConstraint::Size {min, max},
+                        _ => return err("Expected `component size <min> <max>`"),
+                    },
+                    _ => return err("Expected `regular`, `bipartite` or `size` after `component`"),
+                };
+                g.push_component_constraint(constraint);
+            }
+            "symmetric_difference" => {
+                let x = numbers(3)?;
+                let (i, j) = (node(x[0])?, node(x[1])?);
+                g.push_symmetric_difference((i.min(j), i.max(j)), x[2] as usize);
+            }
+            "edit_distance" => {
+                let max = match args.first().map(|x| x.parse()) {
+                    Some(Ok(max)) => max,
+                    _ => return err("Expected maximum after `edit_distance`"),
+                };
+                let mut edges = vec![];
+                for arg in &args[1..] {
+                    let edge = arg.split(',').map(|x| x.parse::<u64>().ok()).collect::<Option<Vec<_>>>();
+                    match edge.as_deref() {
+                        Some(&[i, j, c]) => edges.push(((node(i)?, node(j)?), c)),
+                        _ => return err(&format!("Expected `<i>,<j>,<color>`, found `{}`", arg)),
+                    }
+                }
+                g.edit_distances.push(EditDistance::new(&edges, max));
+            }
+            "subgraph" => {
+                numbers(0)?;
+                if depth >= dot::MAX_DEPTH {return Err(dot::too_deep(line))};
+                match read_spec(lines, depth + 1)? {
+                    (pattern, Some(_)) => g.require_subgraph(&pattern),
+                    (_, None) => return err("Expected `end` of `subgraph`"),
+                }
+            }
+            "priority" => {
+                if args.len() != 3 {return err("Expected `priority <i> <j> <priority>`")};
+                let (i, j) = match (args[0].parse(), args[1].parse()) {
+                    (Ok(i), Ok(j)) => (node(i)?, node(j)?),
+                    _ => return err("Expected `priority <i> <j> <priority>`"),
+                };
+                match args[2].parse() {
+                    Ok(p) => g.set_priority((i, j), p),
+                    Err(_) => return err("Expected `priority <i> <j> <priority>`"),
+                }
+            }
+            "end" => {
+                numbers(0)?;
+                return Ok((g, Some(line)));
+            }
+            _ => return err(&format!("Unknown keyword `{}`", keyword)),
+        }
+    }
+    Ok((g, None))
+}
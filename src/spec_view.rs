@@ -0,0 +1,108 @@
+//! Rendering of specifications for review before solving.
+
+use std::fmt::Write;
+
+use super::*;
+
+/// Escapes text in a quoted Mermaid label.
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;")
+}
+
+impl Graph {
+    /// Returns the lines that describe a node in a specification.
+    fn spec_node_lines(&self, i: usize) -> Vec<String> {
+        let node = &self.nodes[i];
+        let mut lines = vec![format!("{}: color {}{}", self.node_name(i), node.color,
+                                     if node.self_connected {", self"} else {""})];
+        if !node.edges.is_empty() {
+            let edges: Vec<String> = node.edges.iter().map(|con| {
+                if con.node == SELF_LOOP {format!("{}:self", con.edge)}
+                else {format!("{}:{}", con.edge, con.node)}
+            }).collect();
+            lines.push(edges.join(" "));
+        }
+        for deg in self.degrees.iter().filter(|d| d.node == i) {
+            lines.push(format!("degree {}..={}", deg.min, deg.max));
+        }
+        if let Some(label) = self.labels[i] {lines.push(format!("label {}", label))};
+        lines
+    }
+
+    /// Returns the pre-set edges, pairs and anti-pairs of a specification,
+    /// with a description of each.
+    fn spec_edges(&self) -> Vec<((usize, usize), String)> {
+        let n = self.nodes.len();
+        let mut res = vec![];
+        for i in 0..n {
+            for j in i..n {
+                match self.get((i, j)) {
+                    0 => {}
+                    1 => res.push(((i, j), "no-edge".into())),
+                    c => res.push(((i, j), c.to_string())),
+                }
+            }
+        }
+        for &pair in &self.pairs {res.push((pair, "pair".into()))};
+        for &pair in &self.anti_pairs {res.push((pair, "anti-pair".into()))};
+        res
+    }
+
+    /// Generates a GraphViz dot format of the specification itself.
+    ///
+    /// Every node lists its color, edge constraints, degree constraints and label.
+    /// Pre-set colored edges are bold, pre-set no-edges and anti-pairs are dotted and pairs are dashed.
+    /// Global constraints are listed in a note.
+    ///
+    /// This is useful for reviewing a puzzle before solving it.
+    pub fn spec_graphviz(&self) -> String {
+        let mut s = String::new();
+        writeln!(&mut s, "graph {{").unwrap();
+        writeln!(&mut s, "  node[shape=box]").unwrap();
+        for i in 0..self.nodes.len() {
+            let lines: Vec<String> = self.spec_node_lines(i).iter().map(|x| graphviz::escape(x)).collect();
+            writeln!(&mut s, "  {}[label=\"{}\"];", i, lines.join("\\n")).unwrap();
+        }
+        for ((i, j), desc) in self.spec_edges() {
+            let style = match &*desc {
+                "no-edge" | "anti-pair" => "dotted",
+                "pair" => "dashed",
+                _ => "bold",
+            };
+            writeln!(&mut s, "  {} -- {}[style={},label=\"{}\"];", i, j, style, desc).unwrap();
+        }
+        let constraints = self.constraint_summary();
+        if !constraints.is_empty() {
+            let lines: Vec<String> = constraints.iter().map(|x| graphviz::escape(x)).collect();
+            writeln!(&mut s, "  constraints[shape=note,label=\"{}\"];", lines.join("\\n")).unwrap();
+        }
+        writeln!(&mut s, "}}").unwrap();
+        s
+    }
+
+    /// Generates a Mermaid flowchart of the specification itself.
+    ///
+    /// Shows the same information as `Graph::spec_graphviz`.
+    /// Pre-set colored edges are thick, pre-set no-edges, pairs and anti-pairs are dotted.
+    pub fn spec_mermaid(&self) -> String {
+        let mut s = String::new();
+        writeln!(&mut s, "graph LR").unwrap();
+        for i in 0..self.nodes.len() {
+            let lines: Vec<String> = self.spec_node_lines(i).iter().map(|x| escape_mermaid(x)).collect();
+            writeln!(&mut s, "  n{}[\"{}\"]", i, lines.join("<br/>")).unwrap();
+        }
+        for ((i, j), desc) in self.spec_edges() {
+            let arrow = match &*desc {
+                "no-edge" | "pair" | "anti-pair" => "-.-",
+                _ => "===",
+            };
+            writeln!(&mut s, "  n{} {}|{}| n{}", i, arrow, desc, j).unwrap();
+        }
+        let constraints = self.constraint_summary();
+        if !constraints.is_empty() {
+            let lines: Vec<String> = constraints.iter().map(|x| escape_mermaid(x)).collect();
+            writeln!(&mut s, "  constraints[/\"{}\"/]", lines.join("<br/>")).unwrap();
+        }
+        s
+    }
+}
@@ -0,0 +1,103 @@
+//! Statistics of the search and of how constraints restrict it.
+
+use std::time::Duration;
+
+use super::*;
+
+/// Statistics of the search, useful for tuning strategies and settings.
+///
+/// For more information, see `Solutions::stats`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of iterations.
+    pub iterations: u64,
+    /// The number of edges assigned, by branching or by simple moves.
+    pub assignments: u64,
+    /// The number of dead ends that the search backtracked from.
+    pub backtracks: u64,
+    /// The maximum number of nested branching choices.
+    pub max_depth: usize,
+    /// The number of dead ends where some empty edge had no possible colors.
+    ///
+    /// Wipeouts are only detected when propagating, see `SearchSettings::propagate`.
+    pub wipeouts: u64,
+    /// The time spent searching.
+    pub elapsed: Duration,
+}
+
+/// Counts how often a constraint restricted the search.
+///
+/// For more information, see `SearchSettings::constraint_stats`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintStats {
+    /// The number of colors removed from edges that the solver branched on or propagated.
+    pub prunings: u64,
+    /// The number of dead ends caused by the constraint.
+    pub failures: u64,
+}
+
+impl Graph {
+    /// Returns the names of the active constraints, as used in constraint statistics.
+    ///
+    /// Names match the fields of `Graph`, where `cardinalities`, `degrees`, `color_counts`
+    /// and `edit_distances` are named by index, e.g. `cardinalities[0]`.
+    /// Other constraints stored in lists are named by the whole list, e.g. `conditionals`.
+    /// Edge constraints of nodes are named `nodes`.
+    pub fn constraint_names(&self) -> Vec<String> {
+        let mut res = vec!["nodes".to_string()];
+        let flags = [
+            (self.no_triangles, "no_triangles"),
+            (self.min_girth.is_some(), "min_girth"),
+            (self.planar, "planar"),
+            (self.bipartite, "bipartite"),
+            (self.connected, "connected"),
+            (self.no_isolated_nodes, "no_isolated_nodes"),
+            (self.commute_quad.is_some(), "commute_quad"),
+            (!self.quad_rules.is_empty(), "quad_rules"),
+            (self.meet_quad, "meet_quad"),
+            (self.max_local_girth.is_some(), "max_local_girth"),
+            (!self.pairs.is_empty(), "pairs"),
+            (!self.anti_pairs.is_empty(), "anti_pairs"),
+            (!self.restrictions.is_empty(), "restrictions"),
+            (!self.conditionals.is_empty(), "conditionals"),
+            (self.bipartition.is_some(), "bipartition"),
+            (self.labels.iter().any(|label| label.is_some()), "labels"),
+            (!self.component_constraints.is_empty(), "component_constraints"),
+            (!self.symmetric_differences.is_empty(), "symmetric_differences"),
+            (!self.required_subgraphs.is_empty(), "required_subgraphs"),
+            (self.symmetry_break || self.color_symmetry_break, "symmetry_break"),
+        ];
+        res.extend(flags.iter().filter(|(active, _)| *active).map(|(_, name)| name.to_string()));
+        res.extend((0..self.cardinalities.len()).map(|k| format!("cardinalities[{}]", k)));
+        res.extend((0..self.degrees.len()).map(|k| format!("degrees[{}]", k)));
+        res.extend((0..self.color_counts.len()).map(|k| format!("color_counts[{}]", k)));
+        res.extend((0..self.edit_distances.len()).map(|k| format!("edit_distances[{}]", k)));
+        res
+    }
+
+    /// Returns the name of the constraint that caused a dead end.
+    ///
+    /// This is the constraint that removed the last color of some empty edge.
+    /// When every empty edge has a possible color, the dead end is caused by edge constraints,
+    /// or by the first violated constraint when there are no empty edges.
+    pub(crate) fn failure_reason(&self, mask: Option<&[bool]>) -> String {
+        let n = self.nodes.len();
+        let in_region = |i: usize| mask.map(|mask| mask[i]).unwrap_or(true);
+        let mut complete = true;
+        for i in 0..n {
+            for j in i..n {
+                if self.get((i, j)) != 0 || i == j && !self.nodes[i].self_connected {continue};
+                if !in_region(i) && !in_region(j) {continue};
+                complete = false;
+                if self.colors((i, j)).is_empty() {
+                    // No-edge is removed by the same constraint as the last color.
+                    if let Some((name, _)) = self.prune_reason((i, j), 1) {return name};
+                }
+            }
+        }
+        if complete {
+            if let Err(name) = reference::check(self) {return name.to_string()};
+        }
+        "nodes".to_string()
+    }
+}
@@ -0,0 +1,277 @@
+//! Persistent storage of solutions.
+//!
+//! Solutions are stored in canonical form, one JSON object per line,
+//! in the file `solutions.jsonl`.
+//! The file `index.txt` maps hashes of canonical forms to line offsets,
+//! such that duplicates are detected without reading all solutions.
+//! Hashes are FNV-1a of the little-endian bytes of canonical forms.
+//!
+//! Every line has a format version, such that stores written
+//! by earlier versions of the crate can still be read.
+//! Lines without version have version `0`, which is the same format as version `1`.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::*;
+
+/// The version of lines in `solutions.jsonl`.
+const STORE_VERSION: u64 = 1;
+
+/// Summary metrics of a stored solution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Metrics {
+    /// The number of nodes.
+    pub nodes: usize,
+    /// The number of colored edges.
+    pub edges: usize,
+    /// The smallest number of colored edges of a node.
+    pub min_degree: usize,
+    /// The largest number of colored edges of a node.
+    pub max_degree: usize,
+    /// The length of the shortest cycle, if any.
+    pub girth: Option<usize>,
+    /// The number of connected components.
+    pub components: usize,
+}
+
+impl Metrics {
+    /// Computes metrics of a graph.
+    pub fn new(g: &Graph) -> Metrics {
+        let n = g.nodes.len();
+        let degrees: Vec<usize> = (0..n).map(|i| g.degree(i)).collect();
+        let mut edges = 0;
+        for i in 0..n {
+            for j in i..n {
+                if g.get((i, j)) >= 2 {edges += 1}
+            }
+        }
+        Metrics {
+            nodes: n,
+            edges,
+            min_degree: degrees.iter().cloned().min().unwrap_or(0),
+            max_degree: degrees.iter().cloned().max().unwrap_or(0),
+            girth: g.girth(),
+            components: g.components().len(),
+        }
+    }
+}
+
+/// Stores a solution in canonical form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredSolution {
+    /// The hash of the canonical form.
+    pub hash: u64,
+    /// The canonical form.
+    ///
+    /// For more information, see `Graph::canonical_form`.
+    pub form: Vec<Color>,
+    /// The summary metrics.
+    pub metrics: Metrics,
+}
+
+impl StoredSolution {
+    /// Reconstructs the graph from the canonical form.
+    ///
+    /// Nodes have no edge constraints.
+    pub fn graph(&self) -> Graph {
+        let n = self.form[0] as usize;
+        let mut g = Graph::new();
+        for k in 0..n {
+            g.push(Node {color: self.form[1 + k], self_connected: false, edges: vec![]});
+        }
+        let mut k = 1 + n;
+        for a in 0..n {
+            for b in a..n {
+                let val = self.form[k];
+                k += 1;
+                g.set((a, b), if val == 0 {1} else {val});
+            }
+        }
+        g
+    }
+
+    fn to_json(&self) -> String {
+        let form: Vec<String> = self.form.iter().map(|c| c.to_string()).collect();
+        let m = &self.metrics;
+        format!("{{\"version\":{},\"hash\":{},\"form\":[{}],\"nodes\":{},\"edges\":{},\
+                 \"min_degree\":{},\"max_degree\":{},\"girth\":{},\"components\":{}}}",
+            STORE_VERSION, self.hash, form.join(","), m.nodes, m.edges, m.min_degree, m.max_degree,
+            m.girth.map(|g| g.to_string()).unwrap_or_else(|| "null".into()), m.components)
+    }
+
+    fn from_json(line: &str) -> io::Result<StoredSolution> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid line: {}", line));
+        let line = line.trim();
+        let body = line.strip_prefix('{').and_then(|s| s.strip_suffix('}')).ok_or_else(invalid)?;
+        let mut hash = None;
+        let mut form = None;
+        let mut numbers: HashMap<&str, Option<u64>> = HashMap::new();
+        let mut rest = body;
+        while !rest.is_empty() {
+            let rest2 = rest.strip_prefix('"').ok_or_else(invalid)?;
+            let end = rest2.find('"').ok_or_else(invalid)?;
+            let key = &rest2[..end];
+            let rest3 = rest2[end + 1..].strip_prefix(':').ok_or_else(invalid)?;
+            let (value, rest4) = if let Some(arr) = rest3.strip_prefix('[') {
+                let end = arr.find(']').ok_or_else(invalid)?;
+                (&arr[..end], &arr[end + 1..])
+            } else {
+                let end = rest3.find(',').unwrap_or(rest3.len());
+                (&rest3[..end], &rest3[end..])
+            };
+            match key {
+                "form" => {
+                    let values: Result<Vec<Color>, _> = value.split(',')
+                        .filter(|s| !s.is_empty()).map(|s| s.parse()).collect();
+                    form = Some(values.map_err(|_| invalid())?);
+                }
+                "hash" => hash = Some(value.parse().map_err(|_| invalid())?),
+                _ => {
+                    let number = if value == "null" {None}
+                        else {Some(value.parse().map_err(|_| invalid())?)};
+                    numbers.insert(key, number);
+                }
+            }
+            rest = rest4.strip_prefix(',').unwrap_or(rest4);
+        }
+        let version = numbers.get("version").cloned().flatten().unwrap_or(0);
+        if version > STORE_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                format!("Unsupported format version {}: {}", version, line)));
+        }
+        let number = |key: &str| -> io::Result<usize> {
+            numbers.get(key).cloned().flatten().map(|x| x as usize).ok_or_else(invalid)
+        };
+        Ok(StoredSolution {
+            hash: hash.ok_or_else(invalid)?,
+            form: form.ok_or_else(invalid)?,
+            metrics: Metrics {
+                nodes: number("nodes")?,
+                edges: number("edges")?,
+                min_degree: number("min_degree")?,
+                max_degree: number("max_degree")?,
+                girth: numbers.get("girth").cloned().ok_or_else(invalid)?.map(|x| x as usize),
+                components: number("components")?,
+            },
+        })
+    }
+}
+
+/// Stores canonicalized solutions on disk.
+///
+/// Isomorphic solutions are only stored once.
+pub struct SolutionStore {
+    dir: PathBuf,
+    index: HashMap<u64, Vec<u64>>,
+    len: usize,
+}
+
+impl SolutionStore {
+    /// Opens a store in a directory, creating the directory if needed.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<SolutionStore> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut store = SolutionStore {dir, index: HashMap::new(), len: 0};
+        let index_file = store.dir.join("index.txt");
+        if index_file.exists() {
+            for line in BufReader::new(File::open(index_file)?).lines() {
+                let line = line?;
+                let mut parts = line.split_whitespace();
+                let parse = |s: Option<&str>| -> io::Result<u64> {
+                    s.and_then(|s| s.parse().ok()).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("Invalid index: {}", line))
+                    })
+                };
+                let hash = parse(parts.next())?;
+                let offset = parse(parts.next())?;
+                store.index.entry(hash).or_default().push(offset);
+                store.len += 1;
+            }
+        }
+        Ok(store)
+    }
+
+    /// Returns the number of stored solutions.
+    pub fn len(&self) -> usize {self.len}
+
+    /// Returns `true` if there are no stored solutions.
+    pub fn is_empty(&self) -> bool {self.len == 0}
+
+    /// Hashes a canonical form with a hash that is stable across versions of Rust,
+    /// since hashes are stored in the index.
+    fn hash(form: &[Color]) -> u64 {
+        let bytes: Vec<u8> = form.iter().flat_map(|c| c.to_le_bytes()).collect();
+        crate::manifest::fnv1a(&bytes)
+    }
+
+    fn read_at(&self, offset: u64) -> io::Result<StoredSolution> {
+        let mut file = File::open(self.dir.join("solutions.jsonl"))?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut line = String::new();
+        BufReader::new(file).read_line(&mut line)?;
+        StoredSolution::from_json(&line)
+    }
+
+    fn find(&self, form: &[Color]) -> io::Result<bool> {
+        if let Some(offsets) = self.index.get(&SolutionStore::hash(form)) {
+            for &offset in offsets {
+                if self.read_at(offset)?.form == form {return Ok(true)};
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns `true` if an isomorphic graph is stored.
+    pub fn contains(&self, g: &Graph) -> io::Result<bool> {
+        self.find(&g.canonical_form())
+    }
+
+    /// Stores a graph unless an isomorphic graph is already stored.
+    ///
+    /// Returns `true` if the graph was stored.
+    pub fn insert(&mut self, g: &Graph) -> io::Result<bool> {
+        let form = g.canonical_form();
+        if self.find(&form)? {return Ok(false)};
+        let hash = SolutionStore::hash(&form);
+        let solution = StoredSolution {hash, form, metrics: Metrics::new(g)};
+        let mut file = OpenOptions::new().create(true).append(true)
+            .open(self.dir.join("solutions.jsonl"))?;
+        let offset = file.seek(SeekFrom::End(0))?;
+        writeln!(file, "{}", solution.to_json())?;
+        let mut index = OpenOptions::new().create(true).append(true)
+            .open(self.dir.join("index.txt"))?;
+        writeln!(index, "{} {}", hash, offset)?;
+        self.index.entry(hash).or_default().push(offset);
+        self.len += 1;
+        Ok(true)
+    }
+
+    /// Returns all stored solutions.
+    pub fn all(&self) -> io::Result<Vec<StoredSolution>> {
+        self.query(|_| true)
+    }
+
+    /// Returns stored solutions whose metrics satisfy a predicate.
+    pub fn query<F: Fn(&Metrics) -> bool>(&self, f: F) -> io::Result<Vec<StoredSolution>> {
+        let path = self.dir.join("solutions.jsonl");
+        if !path.exists() {return Ok(vec![])};
+        let mut data = String::new();
+        File::open(path)?.read_to_string(&mut data)?;
+        let mut res = vec![];
+        for line in data.lines() {
+            if line.trim().is_empty() {continue};
+            let solution = StoredSolution::from_json(line)?;
+            if f(&solution.metrics) {res.push(solution)};
+        }
+        Ok(res)
+    }
+}
+
+impl Sink for SolutionStore {
+    fn write(&mut self, g: &Graph) -> io::Result<()> {
+        self.insert(g).map(|_| ())
+    }
+}
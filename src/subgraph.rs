@@ -0,0 +1,69 @@
+//! Required subgraph constraints.
+
+use super::*;
+
+impl Graph {
+    /// Requires every solution to contain a colored pattern as a subgraph.
+    ///
+    /// The pattern is embedded when its nodes can be mapped to distinct nodes
+    /// of the same color, such that every colored edge of the pattern
+    /// maps to an edge with the same color.
+    /// Edge constraints of the pattern nodes are ignored.
+    pub fn require_subgraph(&mut self, pattern: &Graph) {
+        self.required_subgraphs.push(pattern.clone());
+        self.cache_subgraphs_feasible.set(None);
+    }
+
+    /// Finds an embedding of a colored pattern as a subgraph.
+    ///
+    /// Returns the node in the graph of every pattern node.
+    pub fn find_subgraph(&self, pattern: &Graph) -> Option<Vec<usize>> {
+        self.embed(pattern, false)
+    }
+
+    fn embed(&self, pattern: &Graph, optimistic: bool) -> Option<Vec<usize>> {
+        fn search(
+            g: &Graph,
+            pattern: &Graph,
+            optimistic: bool,
+            map: &mut Vec<usize>,
+            used: &mut [bool]
+        ) -> bool {
+            let k = map.len();
+            if k == pattern.nodes.len() {return true};
+            let matches = |expected: Color, actual: Color| {
+                expected < 2 || actual == expected || optimistic && actual == 0
+            };
+            for v in 0..g.nodes.len() {
+                if used[v] || g.nodes[v].color != pattern.nodes[k].color {continue};
+                if !matches(pattern.get((k, k)), g.get((v, v))) {continue};
+                if !(0..k).all(|l| matches(pattern.get((k, l)), g.get((v, map[l])))) {continue};
+                map.push(v);
+                used[v] = true;
+                if search(g, pattern, optimistic, map, used) {return true};
+                used[v] = false;
+                map.pop();
+            }
+            false
+        }
+
+        let mut map = vec![];
+        let mut used = vec![false; self.nodes.len()];
+        if search(self, pattern, optimistic, &mut map, &mut used) {Some(map)} else {None}
+    }
+
+    /// Returns `true` if all required subgraphs are embedded.
+    pub fn required_subgraphs_satisfied(&self) -> bool {
+        self.required_subgraphs.iter().all(|pattern| self.embed(pattern, false).is_some())
+    }
+
+    /// Returns `true` if all required subgraphs can still be embedded,
+    /// treating undecided edges as any color.
+    pub fn required_subgraphs_feasible(&self) -> bool {
+        if self.required_subgraphs.is_empty() {return true};
+        if let Some(val) = self.cache_subgraphs_feasible.get() {return val};
+        let val = self.required_subgraphs.iter().all(|pattern| self.embed(pattern, true).is_some());
+        self.cache_subgraphs_feasible.set(Some(val));
+        val
+    }
+}
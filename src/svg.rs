@@ -0,0 +1,212 @@
+//! SVG output with a built-in force-directed layout.
+//!
+//! This does not require GraphViz, which is useful in WASM and CI environments.
+
+use super::*;
+
+/// Stores options for generating SVG pictures.
+///
+/// Default settings:
+///
+/// - width: `400.0`
+/// - height: `400.0`
+/// - node_radius: `8.0`
+/// - iterations: `300`
+/// - palette: `None`
+#[derive(Clone, Debug)]
+pub struct SvgOptions<'a> {
+    /// The node colors, using SVG color names.
+    pub node_colors: &'a [&'a str],
+    /// The edge colors, using SVG color names.
+    pub edge_colors: &'a [&'a str],
+    /// The width of the picture.
+    pub width: f64,
+    /// The height of the picture.
+    pub height: f64,
+    /// The radius of nodes.
+    pub node_radius: f64,
+    /// The number of layout iterations.
+    pub iterations: usize,
+    /// The styles of colors, if any.
+    ///
+    /// When set, this is used instead of `node_colors` and `edge_colors`.
+    pub palette: Option<&'a Palette>,
+}
+
+impl<'a> SvgOptions<'a> {
+    /// Creates new SVG options.
+    pub fn new(node_colors: &'a [&'a str], edge_colors: &'a [&'a str]) -> SvgOptions<'a> {
+        SvgOptions {
+            node_colors,
+            edge_colors,
+            width: 400.0,
+            height: 400.0,
+            node_radius: 8.0,
+            iterations: 300,
+            palette: None,
+        }
+    }
+
+    /// Creates new SVG options using a palette.
+    pub fn from_palette(palette: &'a Palette) -> SvgOptions<'a> {
+        SvgOptions::new(&[], &[]).palette(palette)
+    }
+
+    /// The styles of colors.
+    pub fn palette(mut self, val: &'a Palette) -> Self {
+        self.palette = Some(val);
+        self
+    }
+
+    /// The size of the picture.
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// The radius of nodes.
+    pub fn node_radius(mut self, val: f64) -> Self {
+        self.node_radius = val;
+        self
+    }
+
+    /// The number of layout iterations.
+    pub fn iterations(mut self, val: usize) -> Self {
+        self.iterations = val;
+        self
+    }
+}
+
+/// Escapes text in a quoted XML attribute or element content.
+pub(crate) fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl Graph {
+    /// Computes node positions using a force-directed layout.
+    ///
+    /// Uses the Fruchterman-Reingold algorithm starting from nodes on a circle,
+    /// so the layout is deterministic.
+    /// Positions are in the unit square.
+    pub fn layout(&self, iterations: usize) -> Vec<(f64, f64)> {
+        use std::f64::consts::PI;
+
+        let n = self.nodes.len();
+        let mut pos: Vec<(f64, f64)> = (0..n).map(|i| {
+            // Perturb the radius slightly to break symmetric equilibria.
+            let angle = 2.0 * PI * i as f64 / n as f64;
+            let r = 0.4 + 0.01 * (i % 3) as f64;
+            (0.5 + r * angle.cos(), 0.5 + r * angle.sin())
+        }).collect();
+        if n < 2 {return pos};
+
+        let mut adjacent = vec![];
+        for i in 0..n {
+            for (j, &ed) in self.row(i).iter().enumerate() {
+                if ed >= 2 && i != j {adjacent.push((i, j))}
+            }
+        }
+
+        let k = (1.0 / n as f64).sqrt();
+        let mut temperature = 0.1;
+        let cooling = temperature / (iterations as f64 + 1.0);
+        for _ in 0..iterations {
+            let mut disp = vec![(0.0, 0.0); n];
+            for i in 0..n {
+                for j in 0..i {
+                    let (dx, dy) = (pos[i].0 - pos[j].0, pos[i].1 - pos[j].1);
+                    let d = (dx * dx + dy * dy).sqrt().max(1e-6);
+                    let f = k * k / d;
+                    disp[i].0 += dx / d * f;
+                    disp[i].1 += dy / d * f;
+                    disp[j].0 -= dx / d * f;
+                    disp[j].1 -= dy / d * f;
+                }
+            }
+            for &(i, j) in &adjacent {
+                let (dx, dy) = (pos[i].0 - pos[j].0, pos[i].1 - pos[j].1);
+                let d = (dx * dx + dy * dy).sqrt().max(1e-6);
+                let f = d * d / k;
+                disp[i].0 -= dx / d * f;
+                disp[i].1 -= dy / d * f;
+                disp[j].0 += dx / d * f;
+                disp[j].1 += dy / d * f;
+            }
+            for (p, d) in pos.iter_mut().zip(&disp) {
+                let len = (d.0 * d.0 + d.1 * d.1).sqrt().max(1e-6);
+                let step = len.min(temperature);
+                p.0 = (p.0 + d.0 / len * step).clamp(0.0, 1.0);
+                p.1 = (p.1 + d.1 / len * step).clamp(0.0, 1.0);
+            }
+            temperature -= cooling;
+        }
+
+        // Scale to fill the unit square.
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in &pos {
+            min_x = min_x.min(p.0);
+            min_y = min_y.min(p.1);
+            max_x = max_x.max(p.0);
+            max_y = max_y.max(p.1);
+        }
+        let (w, h) = ((max_x - min_x).max(1e-6), (max_y - min_y).max(1e-6));
+        for p in &mut pos {
+            p.0 = (p.0 - min_x) / w;
+            p.1 = (p.1 - min_y) / h;
+        }
+        pos
+    }
+
+    /// Generates an SVG picture.
+    ///
+    /// The node positions are computed with `Graph::layout`.
+    pub fn svg(&self, options: &SvgOptions) -> String {
+        use std::fmt::Write;
+
+        let margin = options.node_radius * 2.0;
+        let pos: Vec<(f64, f64)> = self.layout(options.iterations).into_iter()
+            .map(|(x, y)| (
+                margin + x * (options.width - 2.0 * margin),
+                margin + y * (options.height - 2.0 * margin),
+            )).collect();
+
+        let mut s = String::new();
+        writeln!(&mut s, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+                          viewBox=\"0 0 {} {}\">", options.width, options.height,
+                          options.width, options.height).unwrap();
+        for i in 0..self.nodes.len() {
+            for (j, &ed) in self.row(i).iter().enumerate() {
+                if ed < 2 {continue};
+                let (color, dash) = match options.palette {
+                    Some(palette) => {
+                        let style = palette.edge(ed);
+                        (escape_xml(&style.pen), if style.dashed {" stroke-dasharray=\"6,4\""} else {""})
+                    }
+                    None => (escape_xml(options.edge_colors[(ed - 2) as usize % options.edge_colors.len()]), ""),
+                };
+                if i == j {
+                    writeln!(&mut s, "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"none\" \
+                                      stroke=\"{}\" stroke-width=\"3\"{}/>", pos[i].0,
+                                      pos[i].1 - options.node_radius, options.node_radius, color, dash).unwrap();
+                } else {
+                    writeln!(&mut s, "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" \
+                                      stroke=\"{}\" stroke-width=\"3\"{}/>",
+                                      pos[i].0, pos[i].1, pos[j].0, pos[j].1, color, dash).unwrap();
+                }
+            }
+        }
+        for (i, p) in pos.iter().enumerate() {
+            let color = self.nodes[i].color;
+            let (fill, pen) = match options.palette {
+                Some(palette) => (escape_xml(&palette.node(color).fill), escape_xml(&palette.node(color).pen)),
+                None => (escape_xml(options.node_colors[color as usize % options.node_colors.len()]), "black".into()),
+            };
+            writeln!(&mut s, "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+                p.0, p.1, options.node_radius, fill, pen).unwrap();
+        }
+        writeln!(&mut s, "</svg>").unwrap();
+        s
+    }
+}
@@ -0,0 +1,231 @@
+//! Symmetry breaking for interchangeable nodes and colors.
+
+use super::*;
+
+impl Graph {
+    /// Returns groups of interchangeable nodes.
+    ///
+    /// Nodes are interchangeable when they have the same description and label,
+    /// are both in or both outside `Graph::quad_nodes`,
+    /// are not referred to by index in pairs, anti-pairs, restrictions, cardinalities, degrees, symmetric differences
+    /// or edges of edit distances,
+    /// and have the same edges to every other node.
+    /// Only groups with two or more nodes are returned.
+    pub fn interchangeable_nodes(&self) -> Vec<Vec<usize>> {
+        let n = self.nodes.len();
+        let mut referred = vec![false; n];
+        let restricted = self.restrictions.iter().map(|(pos, _)| pos);
+        for &(i, j) in self.pairs.iter().chain(&self.anti_pairs).chain(restricted) {
+            referred[i] = true;
+            referred[j] = true;
+        }
+        for card in &self.cardinalities {
+            for &(i, j) in &card.edges {
+                referred[i] = true;
+                referred[j] = true;
+            }
+        }
+        for deg in &self.degrees {
+            referred[deg.node] = true;
+        }
+        for d in &self.edit_distances {
+            for &((i, j), _) in &d.edges {
+                referred[i] = true;
+                referred[j] = true;
+            }
+        }
+        for sym in &self.symmetric_differences {
+            referred[sym.nodes.0] = true;
+            referred[sym.nodes.1] = true;
+        }
+        // Edge constraints are compared independently of order.
+        let patterns: Vec<Node> = self.nodes.iter().map(|node| {
+            let mut node = node.clone();
+            node.edges.sort_by_key(|c| (c.edge, c.node));
+            node
+        }).collect();
+
+        let mut groups: Vec<Vec<usize>> = vec![];
+        'node: for i in 0..n {
+            if referred[i] {continue};
+            for group in &mut groups {
+                let j = group[0];
+                if patterns[i] == patterns[j] &&
+                   self.labels[i] == self.labels[j] &&
+                   self.in_quad_nodes(i) == self.in_quad_nodes(j) &&
+                   self.get((i, i)) == self.get((j, j)) &&
+                   (0..n).all(|k| k == i || k == j || self.get((i, k)) == self.get((j, k)))
+                {
+                    group.push(i);
+                    continue 'node;
+                }
+            }
+            groups.push(vec![i]);
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// Returns groups of interchangeable edge colors.
+    ///
+    /// Two colors are interchangeable when swapping them in all edge constraints,
+    /// conditionals and color counts gives the same specification.
+    /// Colors of assigned edges, restrictions, required subgraphs or edit distances are not interchangeable,
+    /// and no colors are interchangeable when nodes have labels.
+    /// With commuting or anticommuting quads, colors that anticommute with some color are not interchangeable,
+    /// see `Graph::flipped`.
+    /// Only groups with two or more colors are returned.
+    pub fn interchangeable_colors(&self) -> Vec<Vec<Color>> {
+        if self.labels.iter().any(|label| label.is_some()) {return vec![]};
+        let n = self.nodes.len();
+        let mut colors: Vec<Color> = self.nodes.iter()
+            .flat_map(|node| node.edges.iter().map(|c| c.edge))
+            .filter(|&c| c >= 2).collect();
+        colors.sort();
+        colors.dedup();
+        for i in 0..n {
+            for j in i..n {
+                let val = self.get((i, j));
+                colors.retain(|&c| c != val);
+            }
+        }
+        for pattern in &self.required_subgraphs {
+            colors.retain(|c| !pattern.edges.contains(c));
+        }
+        for d in &self.edit_distances {
+            colors.retain(|&c| d.edges.iter().all(|&(_, x)| x != c));
+        }
+        for (_, allowed) in &self.restrictions {
+            colors.retain(|c| !allowed.contains(c));
+        }
+
+        let swap = |c: Color, a: Color, b: Color| if c == a {b} else if c == b {a} else {c};
+        let invariant = |a: Color, b: Color| -> bool {
+            // Swapping must preserve quad rules.
+            let in_rule = |c: Color| self.quad_rules.iter().any(|r| {
+                self.sign_class(r.colors.0) == self.sign_class(c) ||
+                self.sign_class(r.colors.1) == self.sign_class(c)
+            });
+            if in_rule(a) || in_rule(b) {return false};
+            // Swapping a color without its opposite color does not preserve anticommuting quads.
+            if self.commute_quad.is_some() && (self.flipped(a).is_some() || self.flipped(b).is_some()) {
+                return false;
+            }
+            for node in &self.nodes {
+                let mut edges: Vec<(Color, Color)> = node.edges.iter()
+                    .map(|c| (c.edge, c.node)).collect();
+                let mut swapped: Vec<(Color, Color)> = node.edges.iter()
+                    .map(|c| (swap(c.edge, a, b), c.node)).collect();
+                edges.sort();
+                swapped.sort();
+                if edges != swapped {return false};
+            }
+            let count = |x: (Color, Flag)| self.conditionals.iter().filter(|&&y| y == x).count();
+            let color_count = |x: ColorCount| self.color_counts.iter().filter(|&&y| y == x).count();
+            self.conditionals.iter().all(|&(c, flag)| count((c, flag)) == count((swap(c, a, b), flag))) &&
+            self.color_counts.iter().all(|&cc| {
+                color_count(cc) == color_count(ColorCount {color: swap(cc.color, a, b), ..cc})
+            })
+        };
+
+        let mut groups: Vec<Vec<Color>> = vec![];
+        'color: for &c in &colors {
+            for group in &mut groups {
+                if invariant(group[0], c) {
+                    group.push(c);
+                    continue 'color;
+                }
+            }
+            groups.push(vec![c]);
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// Detects interchangeable nodes and colors when symmetry breaking is enabled.
+    ///
+    /// This is called before solving, such that the ordering constraints
+    /// refer to the interchangeable nodes and colors of the initial graph.
+    pub(crate) fn prepare_symmetry_break(&mut self) {
+        self.symmetry_groups = if self.symmetry_break {self.interchangeable_nodes()} else {vec![]};
+        self.color_groups = if self.color_symmetry_break {self.interchangeable_colors()} else {vec![]};
+        self.cache_symmetry_feasible.set(None);
+    }
+
+    /// Returns edges in the order used for symmetry breaking.
+    ///
+    /// This is the row order of the upper triangle, including self-loops.
+    fn edge_order(&self) -> impl Iterator<Item = (usize, usize)> {
+        let n = self.nodes.len();
+        (0..n).flat_map(move |i| (i..n).map(move |j| (i, j)))
+    }
+
+    /// Returns `false` if the first occurrence of `b` is before the first occurrence of `a`.
+    ///
+    /// When `solved` is `false`, the order is not decided after an undecided edge.
+    fn precedes(&self, a: Color, b: Color, solved: bool) -> bool {
+        for pos in self.edge_order() {
+            let val = self.get(pos);
+            if val == 0 && !solved {return true};
+            if val == a {return true};
+            if val == b {return false};
+        }
+        true
+    }
+
+    /// Compares edge rows of two nodes, skipping the columns of both nodes.
+    ///
+    /// Returns `None` when the order is not yet decided.
+    /// When `solved` is `true`, undecided edges are treated as no edge.
+    fn row_order(&self, i: usize, j: usize, solved: bool) -> Option<std::cmp::Ordering> {
+        use std::cmp::Ordering::*;
+
+        for k in 0..self.nodes.len() {
+            if k == i || k == j {continue};
+            let (mut a, mut b) = (self.get((i, k)), self.get((j, k)));
+            if solved {
+                a = a.max(1);
+                b = b.max(1);
+            } else if a == 0 || b == 0 {
+                return None;
+            }
+            if a != b {return Some(a.cmp(&b))};
+        }
+        Some(Equal)
+    }
+
+    fn symmetry_ordered(&self, solved: bool) -> bool {
+        for group in &self.symmetry_groups {
+            for (k, &i) in group.iter().enumerate() {
+                for &j in &group[k + 1..] {
+                    if self.row_order(i, j, solved) == Some(std::cmp::Ordering::Greater) {
+                        return false;
+                    }
+                }
+            }
+        }
+        for group in &self.color_groups {
+            for w in group.windows(2) {
+                if !self.precedes(w[0], w[1], solved) {return false};
+            }
+        }
+        true
+    }
+
+    /// Returns `true` if edge rows of interchangeable nodes are ordered lexicographically,
+    /// and first occurrences of interchangeable colors are ordered.
+    ///
+    /// For more information, see `Graph::symmetry_break` and `Graph::color_symmetry_break`.
+    pub fn symmetry_break_satisfied(&self) -> bool {
+        self.symmetry_ordered(true)
+    }
+
+    /// Returns `false` if decided edges violate the symmetry breaking orderings.
+    pub fn symmetry_break_feasible(&self) -> bool {
+        if self.symmetry_groups.is_empty() && self.color_groups.is_empty() {return true};
+        if let Some(val) = self.cache_symmetry_feasible.get() {return val};
+        let val = self.symmetry_ordered(false);
+        self.cache_symmetry_feasible.set(Some(val));
+        val
+    }
+}
@@ -0,0 +1,124 @@
+//! TikZ output for including diagrams in LaTeX documents.
+
+use super::*;
+
+/// Stores options for generating TikZ pictures.
+///
+/// Default settings:
+///
+/// - radius: `3.0`
+/// - node_size: `0.3`
+/// - standalone: `true`
+/// - palette: `None`
+#[derive(Clone, Debug)]
+pub struct TikzOptions<'a> {
+    /// The node colors, using xcolor names.
+    pub node_colors: &'a [&'a str],
+    /// The edge colors, using xcolor names.
+    pub edge_colors: &'a [&'a str],
+    /// The radius of the circle that nodes are placed on, in centimeters.
+    pub radius: f64,
+    /// The minimum size of nodes, in centimeters.
+    pub node_size: f64,
+    /// Whether to wrap the picture in a standalone LaTeX document.
+    pub standalone: bool,
+    /// The styles of colors, if any.
+    ///
+    /// When set, this is used instead of `node_colors` and `edge_colors`.
+    pub palette: Option<&'a Palette>,
+}
+
+impl<'a> TikzOptions<'a> {
+    /// Creates new TikZ options.
+    pub fn new(node_colors: &'a [&'a str], edge_colors: &'a [&'a str]) -> TikzOptions<'a> {
+        TikzOptions {
+            node_colors,
+            edge_colors,
+            radius: 3.0,
+            node_size: 0.3,
+            standalone: true,
+            palette: None,
+        }
+    }
+
+    /// Creates new TikZ options using a palette.
+    pub fn from_palette(palette: &'a Palette) -> TikzOptions<'a> {
+        TikzOptions::new(&[], &[]).palette(palette)
+    }
+
+    /// The styles of colors.
+    pub fn palette(mut self, val: &'a Palette) -> Self {
+        self.palette = Some(val);
+        self
+    }
+
+    /// The radius of the circle that nodes are placed on, in centimeters.
+    pub fn radius(mut self, val: f64) -> Self {
+        self.radius = val;
+        self
+    }
+
+    /// The minimum size of nodes, in centimeters.
+    pub fn node_size(mut self, val: f64) -> Self {
+        self.node_size = val;
+        self
+    }
+
+    /// Whether to wrap the picture in a standalone LaTeX document.
+    pub fn standalone(mut self, val: bool) -> Self {
+        self.standalone = val;
+        self
+    }
+}
+
+impl Graph {
+    /// Generates a TikZ picture.
+    ///
+    /// Nodes are placed on a circle and filled with their color.
+    /// Edges with odd colors are dashed, such that the picture
+    /// can be read when printed in black and white.
+    /// When the options have a palette, edges are dashed by the palette instead.
+    pub fn tikz(&self, options: &TikzOptions) -> String {
+        use std::fmt::Write;
+
+        let mut s = String::new();
+        if options.standalone {
+            writeln!(&mut s, "\\documentclass[tikz]{{standalone}}").unwrap();
+            writeln!(&mut s, "\\begin{{document}}").unwrap();
+        }
+        writeln!(&mut s, "\\begin{{tikzpicture}}[every node/.style={{circle,draw,\
+                          minimum size={}cm,inner sep=0pt}},line width=1.5pt]", options.node_size).unwrap();
+        let n = self.nodes.len();
+        for i in 0..n {
+            let angle = 90.0 - 360.0 * i as f64 / n as f64;
+            let color = self.nodes[i].color;
+            let style = match options.palette {
+                Some(palette) => format!("fill={},draw={}", palette.node(color).fill, palette.node(color).pen),
+                None => format!("fill={}", options.node_colors[color as usize % options.node_colors.len()]),
+            };
+            writeln!(&mut s, "  \\node[{}] (n{}) at ({:.2}:{}cm) {{}};",
+                style, i, angle, options.radius).unwrap();
+        }
+        for i in 0..n {
+            for (j, &ed) in self.row(i).iter().enumerate() {
+                if ed < 2 {continue};
+                let (color, dashed) = match options.palette {
+                    Some(palette) => (&*palette.edge(ed).pen, palette.edge(ed).dashed),
+                    None => (options.edge_colors[(ed - 2) as usize % options.edge_colors.len()], ed % 2 == 1),
+                };
+                let style = if dashed {",dashed"} else {""};
+                if i == j {
+                    writeln!(&mut s, "  \\draw[{}{}] (n{}) to[loop above] (n{});",
+                        color, style, i, j).unwrap();
+                } else {
+                    writeln!(&mut s, "  \\draw[{}{}] (n{}) -- (n{});", color, style, i, j).unwrap();
+                }
+            }
+        }
+        writeln!(&mut s, "\\end{{tikzpicture}}").unwrap();
+        if options.standalone {
+            writeln!(&mut s, "\\end{{document}}").unwrap();
+        }
+        s
+    }
+}
@@ -0,0 +1,390 @@
+//! Explaining why a graph puzzle has no solution.
+
+use std::fmt;
+
+use super::*;
+
+/// A minimal set of constraints that has no solution together.
+///
+/// For more information, see `Graph::explain_unsat`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatReason {
+    /// The nodes whose edge constraints are in the conflict.
+    pub nodes: Vec<usize>,
+    /// The pair constraints in the conflict.
+    pub pairs: Vec<(usize, usize)>,
+    /// The other constraints in the conflict, by name.
+    ///
+    /// Names match the fields of `Graph`, where constraints stored in lists
+    /// are named by index, e.g. `conditionals[0]`.
+    /// Unlike `Graph::constraint_names`, this indexes every list,
+    /// so `conditionals[0]` is counted as `conditionals` in constraint statistics.
+    pub constraints: Vec<String>,
+    /// Whether every constraint was shown to be necessary.
+    ///
+    /// This is `false` when some check was stopped,
+    /// e.g. by `SearchSettings::max_iterations`.
+    pub minimal: bool,
+}
+
+impl UnsatReason {
+    pub(crate) fn write(&self, w: &mut fmt::Formatter, name: &dyn Fn(usize) -> String) -> fmt::Result {
+        writeln!(w, "no solution{}:", if self.minimal {""} else {" (not minimal)"})?;
+        for &i in &self.nodes {
+            writeln!(w, "  node {}", name(i))?;
+        }
+        for &(i, j) in &self.pairs {
+            writeln!(w, "  pair {} {}", name(i), name(j))?;
+        }
+        for constraint in &self.constraints {
+            writeln!(w, "  {}", constraint)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for UnsatReason {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.write(w, &|i| i.to_string())
+    }
+}
+
+/// A part of the specification that can be relaxed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Part {
+    Node(usize),
+    Pair(usize),
+    AntiPair(usize),
+    Restriction(usize),
+    Flag(&'static str),
+    Cardinality(usize),
+    Degree(usize),
+    ColorCount(usize),
+    Conditional(usize),
+    Component(usize),
+    SymmetricDifference(usize),
+    EditDistance(usize),
+    Subgraph(usize),
+}
+
+impl Graph {
+    /// Finds a minimal set of constraints that has no solution together.
+    ///
+    /// The constraints are edge constraints of nodes, pair constraints
+    /// and other constraints, such as global flags.
+    /// When the edge constraints of a node are left out,
+    /// the node accepts any edges that other nodes require.
+    /// Pre-set edges are kept.
+    ///
+    /// Starting with all constraints, every constraint is left out in turn,
+    /// and kept only when the rest has a solution.
+    /// This requires solving once per constraint, each bounded by the settings.
+    ///
+    /// Returns `None` if the puzzle has a solution, or if the search was stopped.
+    pub fn explain_unsat(&self, solve_settings: SearchSettings) -> Option<UnsatReason> {
+        let mut parts = self.parts();
+        if self.relaxation_unsat(&parts, &solve_settings) != Some(true) {return None};
+        let mut minimal = true;
+        let mut k = 0;
+        while k < parts.len() {
+            let mut smaller = parts.clone();
+            smaller.remove(k);
+            match self.relaxation_unsat(&smaller, &solve_settings) {
+                Some(true) => parts = smaller,
+                Some(false) => k += 1,
+                None => {
+                    minimal = false;
+                    k += 1;
+                }
+            }
+        }
+
+        let mut reason = UnsatReason {nodes: vec![], pairs: vec![], constraints: vec![], minimal};
+        for part in parts {
+            match part {
+                Part::Node(i) => reason.nodes.push(i),
+                Part::Pair(k) => reason.pairs.push(self.pairs[k]),
+                Part::AntiPair(k) => reason.constraints.push(format!("anti_pairs[{}]", k)),
+                Part::Restriction(k) => reason.constraints.push(format!("restrictions[{}]", k)),
+                Part::Flag(name) => reason.constraints.push(name.into()),
+                Part::Cardinality(k) => reason.constraints.push(format!("cardinalities[{}]", k)),
+                Part::Degree(k) => reason.constraints.push(format!("degrees[{}]", k)),
+                Part::ColorCount(k) => reason.constraints.push(format!("color_counts[{}]", k)),
+                Part::Conditional(k) => reason.constraints.push(format!("conditionals[{}]", k)),
+                Part::Component(k) => reason.constraints.push(format!("component_constraints[{}]", k)),
+                Part::SymmetricDifference(k) =>
+                    reason.constraints.push(format!("symmetric_differences[{}]", k)),
+                Part::EditDistance(k) => reason.constraints.push(format!("edit_distances[{}]", k)),
+                Part::Subgraph(k) => reason.constraints.push(format!("required_subgraphs[{}]", k)),
+            }
+        }
+        Some(reason)
+    }
+
+    /// Shrinks an unsolvable puzzle to a smaller puzzle that still has no solution.
+    ///
+    /// Nodes are removed in chunks of decreasing size, delta-debugging style,
+    /// keeping every removal after which the puzzle still has no solution.
+    /// Then other constraints are left out one at a time in the same way.
+    /// A removal is undone when its check is stopped, e.g. by `SearchSettings::max_iterations`.
+    ///
+    /// Unlike `Graph::explain_unsat`, removed nodes are gone from the result,
+    /// together with the constraints that refer to them.
+    /// Edge constraints of the remaining nodes are kept unchanged,
+    /// so removing nodes might leave other nodes without possible neighbors.
+    /// To keep the reason of the failure, when the edge constraints alone have a solution,
+    /// a removal is only kept when this is still the case.
+    ///
+    /// Returns the result and the original indices of its nodes,
+    /// or `None` if the puzzle has a solution, or if the search was stopped.
+    pub fn shrink_unsat(&self, solve_settings: SearchSettings) -> Option<(Graph, Vec<usize>)> {
+        let nodes_unsat = |g: &Graph| {
+            let parts: Vec<Part> = (0..g.nodes.len()).map(Part::Node).collect();
+            g.relaxation_unsat(&parts, &solve_settings) != Some(false)
+        };
+        if self.relaxation_unsat(&self.parts(), &solve_settings) != Some(true) {return None};
+        let local = nodes_unsat(self);
+        let unsat = |g: &Graph| {
+            g.relaxation_unsat(&g.parts(), &solve_settings) == Some(true) && (local || !nodes_unsat(g))
+        };
+        let mut kept: Vec<usize> = (0..self.nodes.len()).collect();
+        let mut chunk = ((kept.len() + 1) / 2).max(1);
+        while !kept.is_empty() {
+            let mut removed = false;
+            let mut start = 0;
+            while start < kept.len() {
+                let end = (start + chunk).min(kept.len());
+                let smaller: Vec<usize> = kept[..start].iter().chain(&kept[end..]).cloned().collect();
+                if unsat(&self.with_nodes(&smaller)) {
+                    kept = smaller;
+                    removed = true;
+                } else {
+                    start = end;
+                }
+            }
+            if !removed {
+                if chunk == 1 {break};
+                chunk = (chunk + 1) / 2;
+            }
+        }
+
+        let g = self.with_nodes(&kept);
+        let mut parts: Vec<Part> = g.parts();
+        let mut k = 0;
+        while k < parts.len() {
+            if let Part::Node(_) = parts[k] {
+                k += 1;
+                continue;
+            }
+            let mut smaller = parts.clone();
+            smaller.remove(k);
+            if g.relaxation_unsat(&smaller, &solve_settings) == Some(true) {parts = smaller}
+            else {k += 1}
+        }
+        let (mut res, _) = g.relax(&parts);
+        res.names = g.names.clone();
+        res.symmetry_break = g.symmetry_break;
+        res.color_symmetry_break = g.color_symmetry_break;
+        Some((res, kept))
+    }
+
+    /// Returns a copy of the puzzle with only some nodes, by original indices.
+    ///
+    /// Constraints that refer to other nodes are left out.
+    fn with_nodes(&self, kept: &[usize]) -> Graph {
+        let mut map = vec![None; self.nodes.len()];
+        for (k, &i) in kept.iter().enumerate() {map[i] = Some(k)}
+        let edge = |(i, j): (usize, usize)| -> Option<(usize, usize)> {
+            Some((map[i]?, map[j]?))
+        };
+        let mut g = Graph::new();
+        for &i in kept {
+            g.push(self.nodes[i].clone());
+            let k = g.nodes.len() - 1;
+            g.labels[k] = self.labels[i];
+            g.names[k] = self.names[i].clone();
+        }
+        for (a, &i) in kept.iter().enumerate() {
+            for (b, &j) in kept[..=a].iter().enumerate() {
+                let val = self.get((i, j));
+                if val != 0 {g.set((a, b), val)};
+            }
+        }
+        g.pairs = self.pairs.iter().filter_map(|&p| edge(p)).collect();
+        g.anti_pairs = self.anti_pairs.iter().filter_map(|&p| edge(p)).collect();
+        for (pos, allowed) in &self.restrictions {
+            if let Some(pos) = edge(*pos) {g.restrict(pos, allowed)};
+        }
+        g.cardinalities = self.cardinalities.iter().filter_map(|c| {
+            let edges = c.edges.iter().map(|&e| edge(e)).collect::<Option<Vec<_>>>()?;
+            Some(Cardinality {edges, ..c.clone()})
+        }).collect();
+        g.degrees = self.degrees.iter()
+            .filter_map(|d| Some(DegreeConstraint {node: map[d.node]?, ..*d})).collect();
+        g.symmetric_differences = self.symmetric_differences.iter()
+            .filter_map(|d| Some(SymmetricDifference {nodes: edge(d.nodes)?, ..*d})).collect();
+        g.edit_distances = self.edit_distances.iter().map(|d| EditDistance {
+            edges: d.edges.iter().filter_map(|&(e, c)| Some((edge(e)?, c))).collect(),
+            max: d.max,
+        }).collect();
+        g.color_counts = self.color_counts.clone();
+        g.no_triangles = self.no_triangles;
+        g.min_girth = self.min_girth;
+        g.planar = self.planar;
+        g.bipartite = self.bipartite;
+        g.connected = self.connected;
+        g.no_isolated_nodes = self.no_isolated_nodes;
+        g.commute_quad = self.commute_quad;
+        g.sign_pairs = self.sign_pairs.clone();
+        g.quad_rules = self.quad_rules.clone();
+        if let Some(nodes) = &self.quad_nodes {
+            g.quad_nodes = Some(nodes.iter().filter_map(|&i| map[i]).collect());
+        }
+        g.meet_quad = self.meet_quad;
+        g.max_local_girth = self.max_local_girth;
+        g.bipartition = self.bipartition;
+        g.conditionals = self.conditionals.clone();
+        g.component_constraints = self.component_constraints.clone();
+        g.required_subgraphs = self.required_subgraphs.clone();
+        g.symmetry_break = self.symmetry_break;
+        g.color_symmetry_break = self.color_symmetry_break;
+        g
+    }
+
+    /// Returns the parts of the specification that can be relaxed,
+    /// in the order they are tried to be left out.
+    fn parts(&self) -> Vec<Part> {
+        let mut parts: Vec<Part> = (0..self.nodes.len()).map(Part::Node).collect();
+        parts.extend((0..self.pairs.len()).map(Part::Pair));
+        parts.extend((0..self.anti_pairs.len()).map(Part::AntiPair));
+        parts.extend((0..self.restrictions.len()).map(Part::Restriction));
+        let flags = [
+            (self.no_triangles, "no_triangles"),
+            (self.min_girth.is_some(), "min_girth"),
+            (self.planar, "planar"),
+            (self.bipartite, "bipartite"),
+            (self.connected, "connected"),
+            (self.no_isolated_nodes, "no_isolated_nodes"),
+            (self.commute_quad.is_some(), "commute_quad"),
+            (!self.quad_rules.is_empty(), "quad_rules"),
+            (self.meet_quad, "meet_quad"),
+            (self.max_local_girth.is_some(), "max_local_girth"),
+            (self.bipartition.is_some(), "bipartition"),
+            (self.labels.iter().any(|label| label.is_some()), "labels"),
+        ];
+        parts.extend(flags.iter().filter(|(active, _)| *active).map(|&(_, name)| Part::Flag(name)));
+        parts.extend((0..self.cardinalities.len()).map(Part::Cardinality));
+        parts.extend((0..self.degrees.len()).map(Part::Degree));
+        parts.extend((0..self.color_counts.len()).map(Part::ColorCount));
+        parts.extend((0..self.conditionals.len()).map(Part::Conditional));
+        parts.extend((0..self.component_constraints.len()).map(Part::Component));
+        parts.extend((0..self.symmetric_differences.len()).map(Part::SymmetricDifference));
+        parts.extend((0..self.edit_distances.len()).map(Part::EditDistance));
+        parts.extend((0..self.required_subgraphs.len()).map(Part::Subgraph));
+        parts
+    }
+
+    /// Returns a copy of the puzzle with only some parts kept,
+    /// and a mask of the nodes that keep their edge constraints.
+    ///
+    /// Symmetry breaking is left out, since it does not change whether there is a solution.
+    fn relax(&self, parts: &[Part]) -> (Graph, Vec<bool>) {
+        let keep = |part: Part| parts.contains(&part);
+        let n = self.nodes.len();
+        let mask: Vec<bool> = (0..n).map(|i| keep(Part::Node(i))).collect();
+        let mut g = Graph::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if mask[i] {
+                g.push(node.clone());
+                continue;
+            }
+            // Accept any edge that other nodes require.
+            let edges = self.nodes.iter().enumerate()
+                .filter(|&(j, _)| j != i)
+                .flat_map(|(_, other)| other.edges.iter()
+                    .filter(|con| con.node == node.color)
+                    .map(move |con| Constraint {edge: con.edge, node: other.color}))
+                .collect();
+            g.push(Node {color: node.color, self_connected: false, edges});
+        }
+        for i in 0..n {
+            for j in i..n {
+                let val = self.get((i, j));
+                if val != 0 {g.set((i, j), val)};
+            }
+        }
+        g.pairs = self.pairs.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::Pair(k))).map(|(_, &p)| p).collect();
+        g.anti_pairs = self.anti_pairs.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::AntiPair(k))).map(|(_, &p)| p).collect();
+        g.restrictions = self.restrictions.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::Restriction(k))).map(|(_, r)| r.clone()).collect();
+        if keep(Part::Flag("no_triangles")) {g.no_triangles = self.no_triangles};
+        if keep(Part::Flag("min_girth")) {g.min_girth = self.min_girth};
+        if keep(Part::Flag("planar")) {g.planar = self.planar};
+        if keep(Part::Flag("bipartite")) {g.bipartite = self.bipartite};
+        if keep(Part::Flag("connected")) {g.connected = self.connected};
+        if keep(Part::Flag("no_isolated_nodes")) {g.no_isolated_nodes = self.no_isolated_nodes};
+        if keep(Part::Flag("commute_quad")) {g.commute_quad = self.commute_quad};
+        g.sign_pairs = self.sign_pairs.clone();
+        g.quad_nodes = self.quad_nodes.clone();
+        if keep(Part::Flag("quad_rules")) {g.quad_rules = self.quad_rules.clone()};
+        if keep(Part::Flag("meet_quad")) {g.meet_quad = self.meet_quad};
+        if keep(Part::Flag("max_local_girth")) {g.max_local_girth = self.max_local_girth};
+        if keep(Part::Flag("bipartition")) {g.bipartition = self.bipartition};
+        if keep(Part::Flag("labels")) {g.labels = self.labels.clone()};
+        g.cardinalities = self.cardinalities.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::Cardinality(k))).map(|(_, c)| c.clone()).collect();
+        g.degrees = self.degrees.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::Degree(k))).map(|(_, &d)| d).collect();
+        g.color_counts = self.color_counts.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::ColorCount(k))).map(|(_, &c)| c).collect();
+        g.conditionals = self.conditionals.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::Conditional(k))).map(|(_, &c)| c).collect();
+        g.component_constraints = self.component_constraints.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::Component(k))).map(|(_, &c)| c).collect();
+        g.symmetric_differences = self.symmetric_differences.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::SymmetricDifference(k))).map(|(_, &d)| d).collect();
+        g.edit_distances = self.edit_distances.iter().enumerate()
+            .filter(|&(k, _)| keep(Part::EditDistance(k))).map(|(_, d)| d.clone()).collect();
+        for (k, pattern) in self.required_subgraphs.iter().enumerate() {
+            if keep(Part::Subgraph(k)) {g.require_subgraph(pattern)};
+        }
+        (g, mask)
+    }
+
+    /// Returns `Some(true)` if the puzzle with only some parts kept has no solution,
+    /// or `None` if the search was stopped.
+    fn relaxation_unsat(&self, parts: &[Part], solve_settings: &SearchSettings) -> Option<bool> {
+        let (g, mask) = self.relax(parts);
+        let all = mask.iter().all(|&b| b);
+        let mut solutions = Solutions::new(g, solve_settings.clone().difference(false));
+        if !all {solutions.region = Some(mask.clone())};
+        // Solutions of a region only check the constraints of nodes in the region.
+        let found = solutions.by_ref().any(|solution| all || relaxed_solved(solution.puzzle, &mask));
+        if found {Some(false)}
+        else if solutions.cancelled() || solutions.exhausted() || solutions.timed_out() {None}
+        else {Some(true)}
+    }
+}
+
+/// Returns `true` if a solution of a region satisfies the other constraints,
+/// when nodes outside the region accept their edges.
+///
+/// Empty edges outside the region are treated as no-edges.
+fn relaxed_solved(mut g: Graph, mask: &[bool]) -> bool {
+    let n = g.nodes.len();
+    for (i, &kept) in mask.iter().enumerate() {
+        if kept {continue};
+        let edges = (0..n).filter(|&j| g.get((i, j)) >= 2)
+            .map(|j| Constraint {edge: g.get((i, j)), node: g.nodes[j].color}).collect();
+        g.nodes[i].self_connected = g.get((i, i)) >= 2;
+        g.nodes[i].edges = edges;
+    }
+    for i in 0..n {
+        for j in i..n {
+            if g.get((i, j)) == 0 && (i != j || g.nodes[i].self_connected) {g.set((i, j), 1)};
+        }
+    }
+    g.is_solved()
+}